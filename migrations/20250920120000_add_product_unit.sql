@@ -0,0 +1,12 @@
+-- Quantities are stored with 3 fixed decimal places (1 UN = 1000)
+ALTER TABLE tb_product
+    ADD COLUMN unit TEXT NOT NULL DEFAULT 'UN';
+
+UPDATE tb_product
+SET quantity = quantity * 1000;
+
+UPDATE tb_product_sale
+SET quantity = quantity * 1000;
+
+UPDATE tb_product_purchase
+SET quantity = quantity * 1000;
@@ -0,0 +1,32 @@
+-- Blank barcodes are stored as NULL, which the unique index allows repeatedly
+UPDATE tb_product
+SET ean = NULL
+WHERE TRIM(ean) = '';
+
+-- Products whose barcode was removed because another product had it, so the
+-- store can review them and print new labels
+CREATE TABLE tb_product_ean_conflict
+(
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id      INTEGER  NOT NULL,
+    kept_product_id INTEGER  NOT NULL,
+    ean             TEXT     NOT NULL,
+    created_at      DATETIME NOT NULL
+);
+
+INSERT INTO tb_product_ean_conflict (product_id, kept_product_id, ean, created_at)
+SELECT id,
+       (SELECT MIN(p.id) FROM tb_product p WHERE p.ean = tb_product.ean),
+       ean,
+       CURRENT_TIMESTAMP
+FROM tb_product
+WHERE ean IS NOT NULL
+  AND id > (SELECT MIN(p.id) FROM tb_product p WHERE p.ean = tb_product.ean);
+
+-- Keep a duplicated barcode only on the oldest product
+UPDATE tb_product
+SET ean = NULL
+WHERE ean IS NOT NULL
+  AND id > (SELECT MIN(p.id) FROM tb_product p WHERE p.ean = tb_product.ean);
+
+CREATE UNIQUE INDEX ux_tb_product_ean ON tb_product (ean);
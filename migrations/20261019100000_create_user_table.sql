@@ -0,0 +1,9 @@
+-- Operators allowed to use the system, created from the command line
+CREATE TABLE tb_user
+(
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    username      TEXT     NOT NULL UNIQUE,
+    password_hash TEXT     NOT NULL,
+    created_at    DATETIME NOT NULL,
+    updated_at    DATETIME
+);
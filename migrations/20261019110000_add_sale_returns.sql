@@ -0,0 +1,18 @@
+-- Voided sales stay recorded, with every item back in stock
+ALTER TABLE tb_sale
+    ADD COLUMN voided_at DATETIME;
+
+-- Items brought back by the customer, part or all of a sold line
+CREATE TABLE tb_product_sale_return
+(
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_sale_id INTEGER  NOT NULL,
+    quantity        INTEGER  NOT NULL,
+    total           INTEGER  NOT NULL,
+    created_at      DATETIME NOT NULL,
+    FOREIGN KEY (product_sale_id) REFERENCES tb_product_sale (id)
+);
+
+CREATE INDEX ix_tb_product_sale_return_product_sale ON tb_product_sale_return (product_sale_id);
+CREATE INDEX ix_tb_product_sale_sale ON tb_product_sale (sale_id);
+CREATE INDEX ix_tb_sale_created_at ON tb_sale (created_at);
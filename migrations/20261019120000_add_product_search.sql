@@ -0,0 +1,45 @@
+ALTER TABLE tb_product
+    ADD COLUMN category TEXT;
+
+-- Full-text index of the products. The unicode61 tokenizer folds case and
+-- accents, so "CAFÉ" and "cafe" are the same term, and the prefix indexes
+-- keep searches while typing fast.
+CREATE VIRTUAL TABLE tb_product_search USING fts5
+(
+    name,
+    ean,
+    category,
+    content = 'tb_product',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE TRIGGER tg_tb_product_search_insert
+    AFTER INSERT
+    ON tb_product
+BEGIN
+    INSERT INTO tb_product_search (rowid, name, ean, category)
+    VALUES (new.id, new.name, new.ean, new.category);
+END;
+
+CREATE TRIGGER tg_tb_product_search_delete
+    AFTER DELETE
+    ON tb_product
+BEGIN
+    INSERT INTO tb_product_search (tb_product_search, rowid, name, ean, category)
+    VALUES ('delete', old.id, old.name, old.ean, old.category);
+END;
+
+CREATE TRIGGER tg_tb_product_search_update
+    AFTER UPDATE OF name, ean, category
+    ON tb_product
+BEGIN
+    INSERT INTO tb_product_search (tb_product_search, rowid, name, ean, category)
+    VALUES ('delete', old.id, old.name, old.ean, old.category);
+    INSERT INTO tb_product_search (rowid, name, ean, category)
+    VALUES (new.id, new.name, new.ean, new.category);
+END;
+
+INSERT INTO tb_product_search (tb_product_search)
+VALUES ('rebuild');
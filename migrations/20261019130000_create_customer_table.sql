@@ -0,0 +1,19 @@
+-- Customers of the store. CPF and phone are stored as digits only
+CREATE TABLE tb_customer
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    name       TEXT     NOT NULL,
+    cpf        TEXT UNIQUE,
+    phone      TEXT,
+    email      TEXT,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME
+);
+
+CREATE INDEX ix_tb_customer_phone ON tb_customer (phone);
+
+-- Sales may name the customer who bought
+ALTER TABLE tb_sale
+    ADD COLUMN customer_id INTEGER REFERENCES tb_customer (id);
+
+CREATE INDEX ix_tb_sale_customer ON tb_sale (customer_id);
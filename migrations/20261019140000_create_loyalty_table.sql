@@ -0,0 +1,19 @@
+-- Loyalty points ledger. Earned and refunded points are positive, redeemed
+-- and reversed points negative; the balance is their sum minus what expired
+CREATE TABLE tb_loyalty_entry
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    customer_id INTEGER  NOT NULL REFERENCES tb_customer (id),
+    sale_id     INTEGER REFERENCES tb_sale (id),
+    kind        TEXT     NOT NULL,
+    points      INTEGER  NOT NULL,
+    expires_at  DATETIME,
+    created_at  DATETIME NOT NULL
+);
+
+CREATE INDEX ix_tb_loyalty_entry_customer ON tb_loyalty_entry (customer_id);
+CREATE INDEX ix_tb_loyalty_entry_sale ON tb_loyalty_entry (sale_id);
+
+-- Part of the sale paid with points, in centavos
+ALTER TABLE tb_sale
+    ADD COLUMN loyalty_discount INTEGER NOT NULL DEFAULT 0;
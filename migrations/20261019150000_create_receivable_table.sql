@@ -0,0 +1,23 @@
+-- How the sale was paid
+ALTER TABLE tb_sale
+    ADD COLUMN payment_method TEXT NOT NULL DEFAULT 'CASH';
+
+-- Most a customer may owe on store credit, in centavos
+ALTER TABLE tb_customer
+    ADD COLUMN credit_limit INTEGER NOT NULL DEFAULT 0;
+
+-- Customer accounts receivable. Store credit sales are charged (positive),
+-- payments and returns credited (negative); the balance is their sum
+CREATE TABLE tb_receivable_entry
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    customer_id INTEGER  NOT NULL REFERENCES tb_customer (id),
+    sale_id     INTEGER REFERENCES tb_sale (id),
+    kind        TEXT     NOT NULL,
+    amount      INTEGER  NOT NULL,
+    due_date    DATE,
+    created_at  DATETIME NOT NULL
+);
+
+CREATE INDEX ix_tb_receivable_entry_customer ON tb_receivable_entry (customer_id);
+CREATE INDEX ix_tb_receivable_entry_sale ON tb_receivable_entry (sale_id);
@@ -0,0 +1,18 @@
+-- Accounts payable: supplier titles, one per installment of a purchase
+CREATE TABLE tb_payable
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    purchase_id    INTEGER REFERENCES tb_purchase (id),
+    supplier       TEXT     NOT NULL,
+    document       TEXT,
+    installment    INTEGER  NOT NULL,
+    installments   INTEGER  NOT NULL,
+    amount         INTEGER  NOT NULL,
+    due_date       DATE     NOT NULL,
+    paid_at        DATETIME,
+    payment_method TEXT,
+    created_at     DATETIME NOT NULL
+);
+
+CREATE INDEX ix_tb_payable_due_date ON tb_payable (due_date);
+CREATE INDEX ix_tb_payable_purchase ON tb_payable (purchase_id);
@@ -0,0 +1,15 @@
+-- Operating expenses: rent, utilities, salaries and the like
+CREATE TABLE tb_expense
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    category       TEXT     NOT NULL,
+    description    TEXT     NOT NULL,
+    amount         INTEGER  NOT NULL,
+    paid_on        DATE     NOT NULL,
+    payment_method TEXT     NOT NULL,
+    created_at     DATETIME NOT NULL
+);
+
+CREATE INDEX ix_tb_expense_paid_on ON tb_expense (paid_on);
+CREATE INDEX ix_tb_payable_paid_at ON tb_payable (paid_at);
+CREATE INDEX ix_tb_purchase_created_at ON tb_purchase (created_at);
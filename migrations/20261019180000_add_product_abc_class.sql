@@ -0,0 +1,3 @@
+-- ABC class of the product from the last curve saved: A, B or C
+ALTER TABLE tb_product
+    ADD COLUMN abc_class TEXT;
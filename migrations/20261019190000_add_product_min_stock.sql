@@ -0,0 +1,7 @@
+-- Stock below which the product must be bought again, 3 fixed decimals
+ALTER TABLE tb_product
+    ADD COLUMN min_stock INTEGER NOT NULL DEFAULT 0;
+
+-- Supplier of the last purchase, used to filter purchase suggestions
+ALTER TABLE tb_product
+    ADD COLUMN supplier TEXT;
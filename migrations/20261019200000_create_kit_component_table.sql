@@ -0,0 +1,56 @@
+-- Kits are sold as one item but their stock is made of other products
+ALTER TABLE tb_product
+    ADD COLUMN is_kit INTEGER NOT NULL DEFAULT 0;
+
+-- Products in a kit, with the quantity of each one, 3 fixed decimals
+CREATE TABLE tb_kit_component
+(
+    kit_id       INTEGER NOT NULL,
+    component_id INTEGER NOT NULL,
+    quantity     INTEGER NOT NULL,
+    PRIMARY KEY (kit_id, component_id),
+    FOREIGN KEY (kit_id) REFERENCES tb_product (id),
+    FOREIGN KEY (component_id) REFERENCES tb_product (id)
+);
+
+CREATE INDEX ix_tb_kit_component_component ON tb_kit_component (component_id);
+
+-- The stock of a kit is the number of whole kits its components make. It is
+-- kept in tb_product.quantity so searches and listings read it as any other
+-- product.
+CREATE TRIGGER tg_tb_product_kit_stock
+    AFTER UPDATE OF quantity
+    ON tb_product
+    WHEN new.is_kit = 0
+BEGIN
+    UPDATE tb_product
+    SET quantity = COALESCE((SELECT MIN(MAX(c.quantity, 0) / k.quantity)
+                             FROM tb_kit_component k
+                                      JOIN tb_product c ON c.id = k.component_id
+                             WHERE k.kit_id = tb_product.id), 0) * 1000
+    WHERE id IN (SELECT kit_id FROM tb_kit_component WHERE component_id = new.id);
+END;
+
+CREATE TRIGGER tg_tb_kit_component_insert
+    AFTER INSERT
+    ON tb_kit_component
+BEGIN
+    UPDATE tb_product
+    SET quantity = COALESCE((SELECT MIN(MAX(c.quantity, 0) / k.quantity)
+                             FROM tb_kit_component k
+                                      JOIN tb_product c ON c.id = k.component_id
+                             WHERE k.kit_id = tb_product.id), 0) * 1000
+    WHERE id = new.kit_id;
+END;
+
+CREATE TRIGGER tg_tb_kit_component_delete
+    AFTER DELETE
+    ON tb_kit_component
+BEGIN
+    UPDATE tb_product
+    SET quantity = COALESCE((SELECT MIN(MAX(c.quantity, 0) / k.quantity)
+                             FROM tb_kit_component k
+                                      JOIN tb_product c ON c.id = k.component_id
+                             WHERE k.kit_id = tb_product.id), 0) * 1000
+    WHERE id = old.kit_id;
+END;
@@ -0,0 +1,3 @@
+-- Terms of the product search index, one row per term and column. Typo
+-- tolerant searches pick the indexed words close to what was typed.
+CREATE VIRTUAL TABLE tb_product_search_vocab USING fts5vocab(tb_product_search, col);
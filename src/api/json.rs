@@ -0,0 +1,270 @@
+//! Request and response bodies. Amounts and quantities travel as strings
+//! with a `.` before the decimals, such as `"25.90"` and `"1.250"`, so no
+//! value goes through floating point. Input also accepts a `,`.
+
+use crate::entities::product::Product;
+use crate::entities::product_sale::ProductSaleLine;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use crate::helpers::{format_quantity, to_local};
+use crate::services::product_sale_service::{DailyReport, ProductSummary};
+use crate::services::product_service::BarcodeMatch;
+use chrono::{NaiveDate, NaiveDateTime, SubsecRound};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct ProductJson {
+    pub id: i64,
+    pub ean: Option<String>,
+    pub name: String,
+    pub category: Option<String>,
+    pub unit: &'static str,
+    pub stock: String,
+    pub price_purchase: String,
+    pub price_sale: String,
+    /// Last change to the product, in local time.
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl From<&Product> for ProductJson {
+    fn from(product: &Product) -> Self {
+        Self {
+            id: product.id,
+            ean: product.ean.clone(),
+            name: product.name.clone(),
+            category: product.category.clone(),
+            unit: product.unit.as_str(),
+            stock: quantity(product.quantity, product.unit),
+            price_purchase: money(product.price_purchase),
+            price_sale: money(product.price_sale),
+            updated_at: product
+                .updated_at
+                .map(|updated_at| to_local(updated_at).trunc_subsecs(0)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockJson {
+    pub id: i64,
+    pub name: String,
+    pub unit: &'static str,
+    pub stock: String,
+}
+
+impl From<&Product> for StockJson {
+    fn from(product: &Product) -> Self {
+        Self {
+            id: product.id,
+            name: product.name.clone(),
+            unit: product.unit.as_str(),
+            stock: quantity(product.quantity, product.unit),
+        }
+    }
+}
+
+/// A product found by barcode, with the quantity of a scale label and the
+/// total of a price label.
+#[derive(Debug, Serialize)]
+pub struct BarcodeJson {
+    pub product: ProductJson,
+    pub quantity: Option<String>,
+    pub total: Option<String>,
+}
+
+impl From<&BarcodeMatch> for BarcodeJson {
+    fn from(found: &BarcodeMatch) -> Self {
+        Self {
+            product: ProductJson::from(&found.product),
+            quantity: found
+                .quantity
+                .map(|value| quantity(value, found.product.unit)),
+            total: found.total.map(money),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductQuery {
+    pub name: Option<String>,
+    pub ean: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaleRequest {
+    pub items: Vec<SaleItem>,
+    pub customer_id: Option<i64>,
+    /// Loyalty points of the customer used to pay part of the sale.
+    #[serde(default)]
+    pub points: i64,
+    /// `CASH`, `CARD`, `PIX` or `STORE_CREDIT`, cash when left out.
+    pub payment_method: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaleItem {
+    pub product_id: i64,
+    pub quantity: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SaleCreated {
+    pub id: i64,
+}
+
+/// A purchase. With `terms`, a due date (`DD/MM/AAAA`) or days such as
+/// `30/60/90`, the total is owed to `supplier` in installments.
+#[derive(Debug, Deserialize)]
+pub struct PurchaseRequest {
+    pub items: Vec<PurchaseItem>,
+    pub supplier: Option<String>,
+    pub document: Option<String>,
+    pub terms: Option<String>,
+}
+
+/// A purchased line. Without `product_id` a new product is registered, and
+/// then `name` and `price_sale` are required.
+#[derive(Debug, Deserialize)]
+pub struct PurchaseItem {
+    pub product_id: Option<i64>,
+    pub ean: Option<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub unit: Option<String>,
+    pub quantity: String,
+    pub price_purchase: String,
+    pub price_sale: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurchaseCreated {
+    pub products: Vec<ReceivedJson>,
+}
+
+/// A purchased line with the final id and EAN of the product.
+#[derive(Debug, Serialize)]
+pub struct ReceivedJson {
+    pub product_id: i64,
+    pub ean: Option<String>,
+    pub name: String,
+    pub unit: &'static str,
+    pub quantity: String,
+    pub price_purchase: String,
+    pub price_sale: String,
+}
+
+impl From<&Product> for ReceivedJson {
+    fn from(product: &Product) -> Self {
+        Self {
+            product_id: product.id,
+            ean: product.ean.clone(),
+            name: product.name.clone(),
+            unit: product.unit.as_str(),
+            quantity: quantity(product.quantity, product.unit),
+            price_purchase: money(product.price_purchase),
+            price_sale: money(product.price_sale),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyQuery {
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeriodQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyReportJson {
+    pub date: NaiveDate,
+    pub sales: usize,
+    pub total: String,
+    pub products: Vec<ProductSummaryJson>,
+}
+
+impl From<&DailyReport> for DailyReportJson {
+    fn from(report: &DailyReport) -> Self {
+        Self {
+            date: report.date,
+            sales: report.sales,
+            total: money(report.total),
+            products: report
+                .products
+                .iter()
+                .map(ProductSummaryJson::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductSummaryJson {
+    pub product_id: i64,
+    pub name: String,
+    pub unit: &'static str,
+    pub quantity: String,
+    pub total: String,
+}
+
+impl From<&ProductSummary> for ProductSummaryJson {
+    fn from(summary: &ProductSummary) -> Self {
+        Self {
+            product_id: summary.product_id,
+            name: summary.name.clone(),
+            unit: summary.unit.as_str(),
+            quantity: quantity(summary.quantity, summary.unit),
+            total: money(summary.total),
+        }
+    }
+}
+
+/// A sold line, with the sale time in local time.
+#[derive(Debug, Serialize)]
+pub struct SaleLineJson {
+    pub sale_id: i64,
+    pub sold_at: NaiveDateTime,
+    pub product_id: i64,
+    pub name: String,
+    pub unit: &'static str,
+    pub price: String,
+    pub quantity: String,
+    pub total: String,
+}
+
+impl From<&ProductSaleLine> for SaleLineJson {
+    fn from(line: &ProductSaleLine) -> Self {
+        Self {
+            sale_id: line.sale_id,
+            sold_at: to_local(line.sold_at).trunc_subsecs(0),
+            product_id: line.product_id,
+            name: line.name.clone(),
+            unit: line.unit.as_str(),
+            price: money(line.price),
+            quantity: quantity(line.quantity, line.unit),
+            total: money(line.total),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorJson {
+    pub error: String,
+}
+
+fn money(value: Money) -> String {
+    let cents = value.cents();
+    format!(
+        "{}{}.{:02}",
+        if cents < 0 { "-" } else { "" },
+        (cents / 100).abs(),
+        (cents % 100).abs()
+    )
+}
+
+fn quantity(value: i64, unit: Unit) -> String {
+    format_quantity(value, unit).replace(',', ".")
+}
@@ -0,0 +1,532 @@
+//! Local HTTP API with JSON bodies, for other tools of the store and for
+//! end-to-end scripts. It goes through the same services as the screens, so
+//! sales and purchases follow the same rules.
+//!
+//! | Method | Path                         | Body / query                     |
+//! |--------|------------------------------|----------------------------------|
+//! | GET    | /api/products                | `?name=` or `?ean=`              |
+//! | GET    | /api/products/{id}           |                                  |
+//! | GET    | /api/stock                   |                                  |
+//! | POST   | /api/sales                   | [`json::SaleRequest`]            |
+//! | POST   | /api/purchases               | [`json::PurchaseRequest`]        |
+//! | GET    | /api/reports/daily           | `?date=AAAA-MM-DD`, today if not |
+//! | GET    | /api/reports/sales           | `?from=AAAA-MM-DD&to=AAAA-MM-DD` |
+
+mod json;
+
+use crate::config::ApiConfig;
+use crate::entities::payment_method::PaymentMethod;
+use crate::entities::product::Product;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use crate::helpers::{parse_quantity, payable};
+use crate::services::product_purchase_service::{PaymentTerms, ProductPurchaseService};
+use crate::services::product_sale_service::ProductSaleService;
+use crate::services::product_service::ProductService;
+use anyhow::{anyhow, Result};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{Local, Utc};
+use json::*;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, PoisonError, RwLock};
+use tokio::net::TcpListener;
+
+/// Services shared with the screens.
+#[derive(Debug, Clone)]
+pub struct Services {
+    pub product_service: Arc<dyn ProductService>,
+    pub product_sale_service: Arc<dyn ProductSaleService>,
+    pub product_purchase_service: Arc<dyn ProductPurchaseService>,
+}
+
+/// The services of a running API. The application replaces them when the
+/// settings are saved, and the next request already goes through the new
+/// ones.
+#[derive(Debug, Clone)]
+pub struct SharedServices(Arc<RwLock<Services>>);
+
+impl SharedServices {
+    pub fn new(services: Services) -> Self {
+        Self(Arc::new(RwLock::new(services)))
+    }
+
+    pub fn replace(&self, services: Services) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = services;
+    }
+
+    fn get(&self) -> Services {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ApiState {
+    services: SharedServices,
+    token: Arc<str>,
+}
+
+/// Listens on `127.0.0.1` until the server fails.
+pub async fn serve(config: ApiConfig, services: SharedServices) -> Result<()> {
+    config.validate_token()?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, config.port));
+    let listener = TcpListener::bind(address)
+        .await
+        .map_err(|e| anyhow!("Não foi possível abrir a porta {}: {}", config.port, e))?;
+    let state = ApiState {
+        services,
+        token: Arc::from(config.token.trim()),
+    };
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/api/products", get(search_products))
+        .route("/api/products/{id}", get(find_product))
+        .route("/api/stock", get(list_stock))
+        .route("/api/sales", post(add_sale))
+        .route("/api/purchases", post(add_purchase))
+        .route("/api/reports/daily", get(daily_report))
+        .route("/api/reports/sales", get(sale_lines))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+async fn authorize(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), state.token.as_bytes()));
+    if !authorized {
+        return ApiError::new(StatusCode::UNAUTHORIZED, "Token de acesso inválido").into_response();
+    }
+    next.run(request).await
+}
+
+/// Compares without stopping at the first difference, so the response time
+/// does not reveal how much of the token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn search_products(
+    State(state): State<ApiState>,
+    Query(query): Query<ProductQuery>,
+) -> Result<Response, ApiError> {
+    let product_service = state.services.get().product_service;
+    if let Some(ean) = query.ean {
+        return match product_service.search_by_barcode(ean.trim()).await? {
+            Some(found) => Ok(Json(BarcodeJson::from(&found)).into_response()),
+            None => Err(ApiError::not_found(format!(
+                "Produto não encontrado para o código {}",
+                ean.trim()
+            ))),
+        };
+    }
+    let name = query.name.unwrap_or_default().trim().to_uppercase();
+    let products = product_service.list_products(&name).await?;
+    Ok(Json(products.iter().map(ProductJson::from).collect::<Vec<_>>()).into_response())
+}
+
+async fn find_product(
+    State(state): State<ApiState>,
+    Path(id): Path<i64>,
+) -> Result<Json<ProductJson>, ApiError> {
+    let product = state
+        .services
+        .get()
+        .product_service
+        .find_product(id)
+        .await?;
+    product
+        .map(|product| Json(ProductJson::from(&product)))
+        .ok_or_else(|| ApiError::not_found(format!("Produto {} não encontrado", id)))
+}
+
+async fn list_stock(State(state): State<ApiState>) -> Result<Json<Vec<StockJson>>, ApiError> {
+    let products = state.services.get().product_service.list_catalog().await?;
+    Ok(Json(products.iter().map(StockJson::from).collect()))
+}
+
+async fn add_sale(
+    State(state): State<ApiState>,
+    Json(request): Json<SaleRequest>,
+) -> Result<(StatusCode, Json<SaleCreated>), ApiError> {
+    if request.items.is_empty() {
+        return Err(ApiError::bad_request("Informe ao menos um item"));
+    }
+    let mut products = Vec::with_capacity(request.items.len());
+    for item in request.items {
+        let mut product = existing_product(&state, item.product_id).await?;
+        product.quantity = positive_quantity(&item.quantity, product.unit)?;
+        products.push(product.into());
+    }
+    let payment_method = match request.payment_method.as_deref() {
+        Some(value) => PaymentMethod::parse(value).ok_or_else(|| {
+            ApiError::bad_request(format!("Forma de pagamento \"{}\" inválida", value))
+        })?,
+        None => PaymentMethod::default(),
+    };
+    let id = state
+        .services
+        .get()
+        .product_sale_service
+        .add_sale(
+            products,
+            request.customer_id,
+            request.points,
+            payment_method,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(SaleCreated { id })))
+}
+
+async fn add_purchase(
+    State(state): State<ApiState>,
+    Json(request): Json<PurchaseRequest>,
+) -> Result<(StatusCode, Json<PurchaseCreated>), ApiError> {
+    if request.items.is_empty() {
+        return Err(ApiError::bad_request("Informe ao menos um item"));
+    }
+    let terms = match request.terms.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(terms) => Some(PaymentTerms {
+            supplier: request.supplier.clone().unwrap_or_default(),
+            document: request.document.clone(),
+            due_dates: payable::parse_terms(terms, Local::now().date_naive()).ok_or_else(|| {
+                ApiError::bad_request(format!("Prazo de pagamento \"{}\" inválido", terms))
+            })?,
+        }),
+    };
+    let mut products = Vec::with_capacity(request.items.len());
+    for item in request.items {
+        let mut product = match item.product_id {
+            Some(id) => existing_product(&state, id).await?,
+            None => new_product(&item)?,
+        };
+        if let Some(ean) = &item.ean {
+            product.ean = Some(ean.trim().to_string());
+        }
+        if let Some(category) = &item.category {
+            product.category = Some(category.trim().to_uppercase());
+        }
+        product.quantity = positive_quantity(&item.quantity, product.unit)?;
+        product.price_purchase = price("price_purchase", &item.price_purchase)?;
+        if let Some(price_sale) = &item.price_sale {
+            product.price_sale = price("price_sale", price_sale)?;
+        }
+        products.push(product);
+    }
+    let received = state
+        .services
+        .get()
+        .product_purchase_service
+        .add_purchase(products, terms)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(PurchaseCreated {
+            products: received.iter().map(ReceivedJson::from).collect(),
+        }),
+    ))
+}
+
+async fn daily_report(
+    State(state): State<ApiState>,
+    Query(query): Query<DailyQuery>,
+) -> Result<Json<DailyReportJson>, ApiError> {
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    let report = state
+        .services
+        .get()
+        .product_sale_service
+        .daily_report(date)
+        .await?;
+    Ok(Json(DailyReportJson::from(&report)))
+}
+
+async fn sale_lines(
+    State(state): State<ApiState>,
+    Query(query): Query<PeriodQuery>,
+) -> Result<Json<Vec<SaleLineJson>>, ApiError> {
+    let lines = state
+        .services
+        .get()
+        .product_sale_service
+        .list_lines(query.from, query.to)
+        .await?;
+    Ok(Json(lines.iter().map(SaleLineJson::from).collect()))
+}
+
+async fn existing_product(state: &ApiState, id: i64) -> Result<Product, ApiError> {
+    state
+        .services
+        .get()
+        .product_service
+        .find_product(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Produto {} não encontrado", id)))
+}
+
+fn new_product(item: &PurchaseItem) -> Result<Product, ApiError> {
+    let name = item
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| ApiError::bad_request("Informe product_id ou o nome do novo produto"))?;
+    let unit = match item.unit.as_deref() {
+        Some(unit) => Unit::parse(unit)
+            .ok_or_else(|| ApiError::bad_request(format!("Unidade \"{}\" inválida", unit)))?,
+        None => Unit::default(),
+    };
+    if item.price_sale.is_none() {
+        return Err(ApiError::bad_request(format!(
+            "Informe price_sale do novo produto {}",
+            name
+        )));
+    }
+    Ok(Product::new(
+        0,
+        name.to_uppercase(),
+        Money::ZERO,
+        Money::ZERO,
+        0,
+        unit,
+        None,
+        Utc::now().naive_local(),
+    ))
+}
+
+fn positive_quantity(value: &str, unit: Unit) -> Result<i64, ApiError> {
+    parse_quantity(value.trim(), unit)
+        .filter(|quantity| *quantity > 0)
+        .ok_or_else(|| {
+            ApiError::bad_request(format!("Quantidade \"{}\" inválida para {}", value, unit))
+        })
+}
+
+fn price(field: &str, value: &str) -> Result<Money, ApiError> {
+    Money::parse(value)
+        .filter(|price| !price.is_negative())
+        .ok_or_else(|| ApiError::bad_request(format!("{} \"{}\" inválido", field, value)))
+}
+
+/// Error answered as `{"error": "..."}`. Errors of the services are rules of
+/// the domain, such as insufficient stock, and answer 422, except for
+/// database and file failures, which answer 500.
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let infrastructure = error
+            .chain()
+            .any(|cause| cause.is::<sqlx::Error>() || cause.is::<std::io::Error>());
+        let status = if infrastructure {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+        Self::new(status, format!("{:#}", error))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(ErrorJson {
+                error: self.message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, stock, units};
+    use crate::services::product_purchase_service::SqliteProductPurchaseService;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Method;
+    use sqlx::SqlitePool;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "segredo";
+
+    fn services(pool: &SqlitePool) -> Services {
+        Services {
+            product_service: Arc::new(testing::product_service(pool)),
+            product_sale_service: Arc::new(testing::sale_service(pool)),
+            product_purchase_service: Arc::new(SqliteProductPurchaseService::new(pool.clone())),
+        }
+    }
+
+    fn shared_router(services: &SharedServices) -> Router {
+        router(ApiState {
+            services: services.clone(),
+            token: Arc::from(TOKEN),
+        })
+    }
+
+    fn test_router(pool: &SqlitePool) -> Router {
+        shared_router(&SharedServices::new(services(pool)))
+    }
+
+    /// Sends the request with the token, when given, and returns the status
+    /// and the JSON body.
+    async fn send(
+        router: Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn requests_without_the_token_are_unauthorized() {
+        let pool = testing::pool().await;
+        for token in [None, Some("errado")] {
+            let (status, body) =
+                send(test_router(&pool), Method::GET, "/api/stock", token, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(body["error"], "Token de acesso inválido");
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_products_are_not_found() {
+        let pool = testing::pool().await;
+        let (status, body) = send(
+            test_router(&pool),
+            Method::GET,
+            "/api/products/42",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Produto 42 não encontrado");
+    }
+
+    #[tokio::test]
+    async fn replaced_services_answer_the_next_request() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let shared = SharedServices::new(services(&testing::pool().await));
+        let uri = format!("/api/products/{}", rice.id);
+
+        let (status, _) = send(shared_router(&shared), Method::GET, &uri, Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        shared.replace(services(&pool));
+        let (status, body) =
+            send(shared_router(&shared), Method::GET, &uri, Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "ARROZ");
+        assert!(body["updated_at"].is_null());
+    }
+
+    #[tokio::test]
+    async fn sales_take_the_stock_and_answer_the_id() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let request = format!(
+            r#"{{"items": [{{"product_id": {}, "quantity": "3"}}], "payment_method": "PIX"}}"#,
+            rice.id
+        );
+        let (status, body) = send(
+            test_router(&pool),
+            Method::POST,
+            "/api/sales",
+            Some(TOKEN),
+            Some(&request),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(body["id"].as_i64().is_some_and(|id| id > 0));
+        assert_eq!(stock(&pool, rice.id).await, units(7));
+        let uri = format!("/api/products/{}", rice.id);
+        let (_, product) = send(test_router(&pool), Method::GET, &uri, Some(TOKEN), None).await;
+        assert!(product["updated_at"].is_string());
+
+        // Breaking a rule of the sale is the client's fault
+        let request = format!(
+            r#"{{"items": [{{"product_id": {}, "quantity": "8"}}]}}"#,
+            rice.id
+        );
+        let (status, _) = send(
+            test_router(&pool),
+            Method::POST,
+            "/api/sales",
+            Some(TOKEN),
+            Some(&request),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn database_failures_are_server_errors() {
+        let pool = testing::pool().await;
+        pool.close().await;
+        let (status, _) = send(
+            test_router(&pool),
+            Method::GET,
+            "/api/stock",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
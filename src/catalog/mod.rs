@@ -0,0 +1,373 @@
+mod sheet;
+mod xlsx;
+
+pub use sheet::Sheet;
+
+use crate::entities::product::Product;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use crate::helpers::{fold_accents, format_quantity, parse_quantity};
+use crate::services::product_service::{ImportRow, ProductRow};
+use anyhow::{bail, Result};
+use std::fmt::Display;
+
+/// Product fields of a catalog file, in the order they are exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Ean,
+    Name,
+    Category,
+    Unit,
+    Stock,
+    PricePurchase,
+    PriceSale,
+    MinStock,
+    Supplier,
+}
+
+impl Field {
+    pub const ALL: [Field; 10] = [
+        Field::Id,
+        Field::Ean,
+        Field::Name,
+        Field::Category,
+        Field::Unit,
+        Field::Stock,
+        Field::PricePurchase,
+        Field::PriceSale,
+        Field::MinStock,
+        Field::Supplier,
+    ];
+
+    /// Column name used on export, which is also recognized on import.
+    pub fn header(&self) -> &'static str {
+        match self {
+            Field::Id => "id",
+            Field::Ean => "ean",
+            Field::Name => "nome",
+            Field::Category => "categoria",
+            Field::Unit => "unidade",
+            Field::Stock => "estoque",
+            Field::PricePurchase => "preco_compra",
+            Field::PriceSale => "preco_venda",
+            Field::MinStock => "estoque_minimo",
+            Field::Supplier => "fornecedor",
+        }
+    }
+
+    /// Other column names used by spreadsheets and previous systems, already
+    /// normalized.
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Field::Id => &["codigo", "cod", "codigo_interno"],
+            Field::Ean => &["gtin", "codigo_de_barras", "codigo_barras", "cod_barras"],
+            Field::Name => &["produto", "descricao", "nome_do_produto"],
+            Field::Category => &["grupo", "departamento", "secao"],
+            Field::Unit => &["un", "und", "unid"],
+            Field::Stock => &["quantidade", "qtd", "qtde", "saldo"],
+            Field::PricePurchase => &["preco_de_compra", "custo", "preco_custo", "compra"],
+            Field::PriceSale => &["preco_de_venda", "preco", "venda", "valor"],
+            Field::MinStock => &["minimo", "estoque_min", "qtd_minima", "ponto_de_pedido"],
+            Field::Supplier => &["fornecedor_padrao", "fabricante", "distribuidor"],
+        }
+    }
+
+    fn parse(value: &str) -> Option<Field> {
+        let value = normalize(value);
+        Field::ALL
+            .into_iter()
+            .find(|field| field.header() == value || field.aliases().contains(&value.as_str()))
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Field::Id => "ID",
+            Field::Ean => "EAN",
+            Field::Name => "NOME",
+            Field::Category => "CATEGORIA",
+            Field::Unit => "UNIDADE",
+            Field::Stock => "ESTOQUE",
+            Field::PricePurchase => "PREÇO DE COMPRA",
+            Field::PriceSale => "PREÇO DE VENDA",
+            Field::MinStock => "ESTOQUE MÍNIMO",
+            Field::Supplier => "FORNECEDOR",
+        })
+    }
+}
+
+/// Which column of the file feeds each field. Fields without a column keep
+/// their current value on the products that are updated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    columns: [Option<usize>; Field::ALL.len()],
+}
+
+impl ColumnMapping {
+    /// Maps the columns whose header matches a field name or alias.
+    pub fn detect(headers: &[String]) -> Self {
+        let mut mapping = Self::default();
+        for (index, header) in headers.iter().enumerate() {
+            if let Some(field) = Field::parse(header)
+                && mapping.get(field).is_none()
+            {
+                mapping.set(field, Some(index));
+            }
+        }
+        mapping
+    }
+
+    pub fn get(&self, field: Field) -> Option<usize> {
+        self.columns[field as usize]
+    }
+
+    pub fn set(&mut self, field: Field, column: Option<usize>) {
+        self.columns[field as usize] = column;
+    }
+
+    /// Maps `field` to the column named `header`, as in `nome=Descrição`.
+    pub fn set_by_name(&mut self, assignment: &str, headers: &[String]) -> Result<()> {
+        let Some((field_name, header)) = assignment.split_once('=') else {
+            bail!("Mapeamento \"{}\" inválido, use campo=coluna", assignment);
+        };
+        let Some(field) = Field::parse(field_name) else {
+            bail!("Campo \"{}\" desconhecido", field_name.trim());
+        };
+        let Some(index) = headers
+            .iter()
+            .position(|h| normalize(h) == normalize(header))
+        else {
+            bail!("Coluna \"{}\" não encontrada no arquivo", header.trim());
+        };
+        self.set(field, Some(index));
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if [Field::Id, Field::Ean, Field::Name]
+            .into_iter()
+            .all(|field| self.get(field).is_none())
+        {
+            bail!("Associe uma coluna ao ID, ao EAN ou ao nome para identificar os produtos");
+        }
+        Ok(())
+    }
+}
+
+/// Reads the rows of the sheet through the mapping. The line numbers count
+/// the header as line 1.
+pub fn parse_rows(sheet: &Sheet, mapping: &ColumnMapping) -> Vec<ImportRow> {
+    sheet
+        .rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row.iter().any(|value| !value.trim().is_empty()))
+        .map(|(index, row)| ImportRow {
+            line: index + 2,
+            row: parse_row(row, mapping),
+        })
+        .collect()
+}
+
+fn parse_row(row: &[String], mapping: &ColumnMapping) -> Result<ProductRow, String> {
+    let field = |field: Field| {
+        mapping
+            .get(field)
+            .and_then(|column| row.get(column))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let price = |name: Field| {
+        field(name)
+            .map(|value| {
+                Money::parse(value)
+                    .filter(|price| !price.is_negative())
+                    .ok_or_else(|| format!("{} \"{}\" inválido", name, value))
+            })
+            .transpose()
+    };
+    Ok(ProductRow {
+        id: field(Field::Id)
+            .map(|id| {
+                id.parse::<i64>()
+                    .ok()
+                    .filter(|id| *id > 0)
+                    .ok_or_else(|| format!("ID \"{}\" inválido", id))
+            })
+            .transpose()?,
+        ean: field(Field::Ean).map(str::to_string),
+        name: field(Field::Name).map(str::to_uppercase),
+        category: field(Field::Category).map(str::to_uppercase),
+        unit: field(Field::Unit)
+            .map(|unit| Unit::parse(unit).ok_or_else(|| format!("Unidade \"{}\" inválida", unit)))
+            .transpose()?,
+        // Read with decimals; the service rejects fractions for "UN" products
+        quantity: field(Field::Stock)
+            .map(|quantity| {
+                parse_quantity(quantity, Unit::Kg)
+                    .ok_or_else(|| format!("Estoque \"{}\" inválido", quantity))
+            })
+            .transpose()?,
+        price_purchase: price(Field::PricePurchase)?,
+        price_sale: price(Field::PriceSale)?,
+        min_stock: field(Field::MinStock)
+            .map(|quantity| {
+                parse_quantity(quantity, Unit::Kg)
+                    .ok_or_else(|| format!("Estoque mínimo \"{}\" inválido", quantity))
+            })
+            .transpose()?,
+        supplier: field(Field::Supplier).map(str::to_uppercase),
+    })
+}
+
+/// The whole catalog with the same columns the import recognizes.
+pub fn to_sheet(products: &[Product]) -> Sheet {
+    Sheet {
+        headers: Field::ALL
+            .iter()
+            .map(|field| field.header().to_string())
+            .collect(),
+        rows: products
+            .iter()
+            .map(|product| {
+                vec![
+                    product.id.to_string(),
+                    product.ean.clone().unwrap_or_default(),
+                    product.name.clone(),
+                    product.category.clone().unwrap_or_default(),
+                    product.unit.to_string(),
+                    format_quantity(product.quantity, product.unit),
+                    product.price_purchase.to_decimal_string(),
+                    product.price_sale.to_decimal_string(),
+                    format_quantity(product.min_stock, product.unit),
+                    product.supplier.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Lowercase ASCII words joined by `_`, so `Preço de Venda` matches
+/// `preco_de_venda`.
+fn normalize(value: &str) -> String {
+    fold_accents(value.trim_start_matches('\u{feff}'))
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn detect_maps_headers_and_aliases() {
+        let headers = strings(&[
+            "\u{feff}Código",
+            "Código de Barras",
+            "Descrição",
+            "Preço de Venda",
+            "Qtd.",
+            "Observação",
+            "Produto",
+        ]);
+        let mapping = ColumnMapping::detect(&headers);
+
+        assert_eq!(mapping.get(Field::Id), Some(0));
+        assert_eq!(mapping.get(Field::Ean), Some(1));
+        // The first matching column wins
+        assert_eq!(mapping.get(Field::Name), Some(2));
+        assert_eq!(mapping.get(Field::PriceSale), Some(3));
+        assert_eq!(mapping.get(Field::Stock), Some(4));
+        assert_eq!(mapping.get(Field::PricePurchase), None);
+        assert_eq!(mapping.get(Field::Supplier), None);
+    }
+
+    #[test]
+    fn set_by_name_maps_a_field_to_a_named_column() {
+        let headers = strings(&["Cód", "Observação"]);
+        let mut mapping = ColumnMapping::detect(&headers);
+
+        mapping.set_by_name("nome=observacao", &headers).unwrap();
+        assert_eq!(mapping.get(Field::Name), Some(1));
+        mapping
+            .set_by_name("Preço de Venda = Cód", &headers)
+            .unwrap();
+        assert_eq!(mapping.get(Field::PriceSale), Some(0));
+
+        for (assignment, expected) in [
+            ("nome", "Mapeamento \"nome\" inválido, use campo=coluna"),
+            ("cor=Cód", "Campo \"cor\" desconhecido"),
+            (
+                "nome=Descrição",
+                "Coluna \"Descrição\" não encontrada no arquivo",
+            ),
+        ] {
+            let error = mapping.set_by_name(assignment, &headers).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn validate_requires_a_column_that_identifies_the_products() {
+        let mut mapping = ColumnMapping::detect(&strings(&["preco", "estoque"]));
+        assert!(mapping.validate().is_err());
+
+        mapping.set(Field::Ean, Some(2));
+        assert!(mapping.validate().is_ok());
+    }
+
+    #[test]
+    fn parse_rows_reads_each_field_and_skips_blank_lines() {
+        let sheet = Sheet {
+            headers: strings(&["id", "ean", "nome", "unidade", "estoque", "preco_venda"]),
+            rows: vec![
+                strings(&["7", "7891000100103", " açúcar ", "kg", "2,5", "1.234,50"]),
+                strings(&["", " ", "", "", "", ""]),
+                strings(&["", "", "pão", "", "", ""]),
+            ],
+        };
+        let rows = parse_rows(&sheet, &ColumnMapping::detect(&sheet.headers));
+
+        assert_eq!(rows.iter().map(|row| row.line).collect::<Vec<_>>(), [2, 4]);
+        let sugar = rows[0].row.as_ref().unwrap();
+        assert_eq!(sugar.id, Some(7));
+        assert_eq!(sugar.ean.as_deref(), Some("7891000100103"));
+        assert_eq!(sugar.name.as_deref(), Some("AÇÚCAR"));
+        assert_eq!(sugar.unit, Some(Unit::Kg));
+        assert_eq!(sugar.quantity, Some(2500));
+        assert_eq!(sugar.price_sale, Some(Money::from_cents(123450)));
+        // Empty cells keep the current values
+        let bread = rows[1].row.as_ref().unwrap();
+        assert_eq!(bread.id, None);
+        assert_eq!(bread.unit, None);
+        assert_eq!(bread.price_sale, None);
+    }
+
+    #[test]
+    fn parse_rows_rejects_invalid_values() {
+        let headers = strings(&["id", "unidade", "estoque", "preco_venda"]);
+        let mapping = ColumnMapping::detect(&headers);
+        for (row, expected) in [
+            (["0", "", "", ""], "ID \"0\" inválido"),
+            (["", "cx", "", ""], "Unidade \"cx\" inválida"),
+            (["", "", "dois", ""], "Estoque \"dois\" inválido"),
+            (["", "", "", "-1,00"], "PREÇO DE VENDA \"-1,00\" inválido"),
+        ] {
+            let sheet = Sheet {
+                headers: headers.clone(),
+                rows: vec![strings(&row)],
+            };
+            let rows = parse_rows(&sheet, &mapping);
+            assert_eq!(rows[0].row.as_ref().unwrap_err(), expected);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::xlsx;
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+
+const CSV_DELIMITER: u8 = b';';
+/// Lets spreadsheet programs recognize the exported CSV as UTF-8.
+const UTF8_BOM: &str = "\u{feff}";
+
+/// Cells of a spreadsheet as text: the header line and the data rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sheet {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Sheet {
+    /// Reads a `.xlsx` workbook (first worksheet) or a CSV file separated by
+    /// `;` or `,`.
+    pub fn read(path: &Path) -> Result<Sheet> {
+        let sheet = if is_xlsx(path) {
+            xlsx::read(path)
+        } else {
+            read_csv(path)
+        }
+        .with_context(|| format!("Não foi possível ler {}", path.display()))?;
+        if sheet.headers.iter().all(|header| header.trim().is_empty()) {
+            bail!("O arquivo {} não tem linha de cabeçalho", path.display());
+        }
+        Ok(sheet)
+    }
+
+    /// Writes the sheet as `.xlsx` or, for any other extension, as CSV
+    /// separated by `;`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if is_xlsx(path) {
+            xlsx::write(self, path)
+        } else {
+            write_csv(self, path)
+        }
+        .with_context(|| format!("Não foi possível gravar {}", path.display()))
+    }
+}
+
+fn is_xlsx(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("xlsx"))
+}
+
+fn read_csv(path: &Path) -> Result<Sheet> {
+    let bytes = std::fs::read(path)?;
+    // Files exported by older systems are often in Latin-1
+    let content = String::from_utf8(bytes)
+        .unwrap_or_else(|e| e.into_bytes().iter().map(|&b| b as char).collect());
+    let content = content.strip_prefix(UTF8_BOM).unwrap_or(&content);
+
+    let header = content.lines().next().unwrap_or_default();
+    let delimiter = if header.contains(';') || !header.contains(',') {
+        CSV_DELIMITER
+    } else {
+        b','
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers()?.iter().map(str::to_string).collect();
+    let rows = reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|e| anyhow!("CSV inválido: {}", e))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Sheet { headers, rows })
+}
+
+fn write_csv(sheet: &Sheet, path: &Path) -> Result<()> {
+    let mut content = UTF8_BOM.as_bytes().to_vec();
+    {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(CSV_DELIMITER)
+            .from_writer(&mut content);
+        writer.write_record(&sheet.headers)?;
+        for row in &sheet.rows {
+            writer.write_record(row)?;
+        }
+        writer.flush()?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A path in the temporary directory, unique to this test run.
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("catalog-{}-{}", std::process::id(), name))
+    }
+
+    fn read_bytes(name: &str, content: &[u8]) -> Sheet {
+        let path = temp_file(name);
+        std::fs::write(&path, content).unwrap();
+        let sheet = Sheet::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        sheet.unwrap()
+    }
+
+    #[test]
+    fn read_detects_the_delimiter_from_the_header() {
+        let semicolon = read_bytes("semicolon.csv", "nome;preco\nARROZ;25,90\n".as_bytes());
+        assert_eq!(semicolon.headers, ["nome", "preco"]);
+        assert_eq!(semicolon.rows, [["ARROZ", "25,90"]]);
+
+        let comma = read_bytes("comma.csv", "nome,preco\nARROZ,\"25,90\"\n".as_bytes());
+        assert_eq!(comma.headers, ["nome", "preco"]);
+        assert_eq!(comma.rows, [["ARROZ", "25,90"]]);
+
+        // A single column has no delimiter to detect
+        let single = read_bytes("single.csv", "nome\nARROZ, TIPO 1\n".as_bytes());
+        assert_eq!(single.rows, [["ARROZ, TIPO 1"]]);
+    }
+
+    #[test]
+    fn read_falls_back_to_latin1_and_strips_the_bom() {
+        let latin1 = read_bytes("latin1.csv", b"descri\xe7\xe3o;pre\xe7o\nP\xc3O;0,50\n");
+        assert_eq!(latin1.headers, ["descrição", "preço"]);
+        assert_eq!(latin1.rows, [["PÃO", "0,50"]]);
+
+        let utf8 = read_bytes("bom.csv", "\u{feff}descrição;preço\nPÃO;0,50\n".as_bytes());
+        assert_eq!(utf8, latin1);
+    }
+
+    #[test]
+    fn read_requires_a_header() {
+        let path = temp_file("empty.csv");
+        std::fs::write(&path, "\n").unwrap();
+        let error = Sheet::read(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!("O arquivo {} não tem linha de cabeçalho", path.display())
+        );
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_every_cell() {
+        let sheet = Sheet {
+            headers: vec!["ean".to_string(), "nome".to_string()],
+            rows: vec![
+                vec![
+                    "0789100010010".to_string(),
+                    "FEIJÃO; PRETO \"1KG\"".to_string(),
+                ],
+                vec![String::new(), "SAL".to_string()],
+            ],
+        };
+        let path = temp_file("round-trip.csv");
+        sheet.write(&path).unwrap();
+        let read = Sheet::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), sheet);
+    }
+}
@@ -0,0 +1,352 @@
+//! Minimal reader and writer of Office Open XML workbooks. Only cell values
+//! are handled; styles, formulas and dates come through as the stored text.
+
+use super::Sheet;
+use anyhow::{anyhow, Result};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const FIRST_WORKSHEET: &str = "xl/worksheets/sheet1.xml";
+
+pub fn read(path: &Path) -> Result<Sheet> {
+    let mut archive =
+        ZipArchive::new(File::open(path)?).map_err(|e| anyhow!("Arquivo XLSX inválido: {}", e))?;
+    let shared_strings = match read_entry(&mut archive, "xl/sharedStrings.xml")? {
+        Some(xml) => read_shared_strings(&xml)?,
+        None => vec![],
+    };
+    let worksheet = first_worksheet(&mut archive)?;
+    let xml = read_entry(&mut archive, &worksheet)?
+        .ok_or_else(|| anyhow!("Planilha {} não encontrada no arquivo", worksheet))?;
+
+    let mut rows = read_rows(&xml, &shared_strings)?.into_iter();
+    let headers = rows.next().unwrap_or_default();
+    Ok(Sheet {
+        headers,
+        rows: rows.collect(),
+    })
+}
+
+pub fn write(sheet: &Sheet, path: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", ROOT_RELATIONSHIPS.to_string()),
+        ("xl/workbook.xml", WORKBOOK.to_string()),
+        (
+            "xl/_rels/workbook.xml.rels",
+            WORKBOOK_RELATIONSHIPS.to_string(),
+        ),
+        (FIRST_WORKSHEET, worksheet_xml(sheet)),
+    ];
+    for (name, content) in parts {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    match archive.by_name(name) {
+        Ok(mut entry) => {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            Ok(Some(content))
+        }
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Path of the first worksheet in tab order, which is not always `sheet1`.
+fn first_worksheet(archive: &mut ZipArchive<File>) -> Result<String> {
+    let (Some(workbook), Some(relationships)) = (
+        read_entry(archive, "xl/workbook.xml")?,
+        read_entry(archive, "xl/_rels/workbook.xml.rels")?,
+    ) else {
+        return Ok(FIRST_WORKSHEET.to_string());
+    };
+    let Some(id) = first_attribute(&workbook, b"sheet", b"r:id")? else {
+        return Ok(FIRST_WORKSHEET.to_string());
+    };
+
+    let mut reader = Reader::from_str(&relationships);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if attribute(&e, b"Id")?.as_deref() == Some(id.as_str())
+                    && let Some(target) = attribute(&e, b"Target")?
+                {
+                    return Ok(match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    });
+                }
+            }
+            Event::Eof => return Ok(FIRST_WORKSHEET.to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn first_attribute(xml: &str, element: &[u8], name: &[u8]) -> Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == element => {
+                return attribute(&e, name);
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        if attribute.key.as_ref() == name {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Text of each `<si>` item, joining the runs of rich text and skipping the
+/// phonetic hints.
+fn read_shared_strings(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut strings = vec![];
+    let mut current = String::new();
+    let (mut in_text, mut in_phonetic) = (false, false);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"t" => in_text = true,
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                b"si" => strings.push(std::mem::take(&mut current)),
+                _ => {}
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Text(text) if in_text && !in_phonetic => current.push_str(&text.unescape()?),
+            Event::Eof => return Ok(strings),
+            _ => {}
+        }
+    }
+}
+
+fn read_rows(xml: &str, shared_strings: &[String]) -> Result<Vec<Vec<String>>> {
+    let mut reader = Reader::from_str(xml);
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut row_index = 0;
+    let mut column = 0;
+    let mut cell_type = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.local_name().as_ref() == b"row" => {
+                row_index = match attribute(&e, b"r")?.and_then(|r| r.parse::<usize>().ok()) {
+                    Some(r) => r.saturating_sub(1),
+                    None => rows.len(),
+                };
+                column = 0;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"row" => {
+                // Rows skipped by the file are empty lines
+                if rows.len() < row_index {
+                    rows.resize(row_index, vec![]);
+                }
+                rows.push(std::mem::take(&mut row));
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"c" => {
+                if let Some(reference) = attribute(&e, b"r")? {
+                    column = column_index(&reference).unwrap_or(column);
+                }
+                cell_type = attribute(&e, b"t")?.unwrap_or_default();
+                value.clear();
+            }
+            Event::End(e) if e.local_name().as_ref() == b"c" => {
+                let text = match cell_type.as_str() {
+                    "s" => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| shared_strings.get(index))
+                        .cloned()
+                        .unwrap_or_default(),
+                    "inlineStr" | "str" | "b" | "e" | "d" => value.clone(),
+                    _ => format_number(&value),
+                };
+                if row.len() <= column {
+                    row.resize(column + 1, String::new());
+                }
+                row[column] = text;
+                column += 1;
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                if let Some(reference) = attribute(&e, b"r")? {
+                    column = column_index(&reference).unwrap_or(column);
+                }
+                column += 1;
+            }
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = true,
+            Event::End(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = false,
+            Event::Text(text) if in_value => value.push_str(&text.unescape()?),
+            Event::Eof => return Ok(rows),
+            _ => {}
+        }
+    }
+}
+
+/// Zero based column of a cell reference such as `AB12`.
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<char> = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let index = letters.iter().fold(0, |index, letter| {
+        index * 26 + (letter.to_ascii_uppercase() as usize - 'A' as usize + 1)
+    });
+    Some(index - 1)
+}
+
+fn column_name(mut index: usize) -> String {
+    let mut name = vec![];
+    loop {
+        name.push((b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.iter().rev().collect()
+}
+
+/// Numbers are stored with a `.` and binary noise, such as
+/// `25.899999999999999`. They are rounded and written the pt-BR way.
+fn format_number(value: &str) -> String {
+    let Ok(number) = value.trim().parse::<f64>() else {
+        return value.to_string();
+    };
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        return format!("{}", number as i64);
+    }
+    let text = format!("{:.6}", number);
+    text.trim_end_matches('0')
+        .trim_end_matches('.')
+        .replace('.', ",")
+}
+
+fn worksheet_xml(sheet: &Sheet) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    );
+    for (index, row) in std::iter::once(&sheet.headers)
+        .chain(sheet.rows.iter())
+        .enumerate()
+    {
+        xml.push_str(&format!(r#"<row r="{}">"#, index + 1));
+        for (column, value) in row.iter().enumerate() {
+            // Every cell is written as text, keeping the leading zeros of codes
+            xml.push_str(&format!(
+                r#"<c r="{}{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                column_name(column),
+                index + 1,
+                escape(value.as_str())
+            ));
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const ROOT_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Planilha1" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+
+const WORKBOOK_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_names_and_indexes_match() {
+        for (index, name) in [(0, "A"), (25, "Z"), (26, "AA"), (27, "AB"), (702, "AAA")] {
+            assert_eq!(column_name(index), name);
+            assert_eq!(column_index(&format!("{}12", name)), Some(index));
+        }
+        assert_eq!(column_index("12"), None);
+    }
+
+    #[test]
+    fn numbers_are_rounded_and_written_with_a_comma() {
+        assert_eq!(format_number("25.899999999999999"), "25,9");
+        assert_eq!(format_number("7891000100103"), "7891000100103");
+        assert_eq!(format_number("-0.5"), "-0,5");
+        assert_eq!(format_number("ARROZ"), "ARROZ");
+    }
+
+    #[test]
+    fn reads_shared_strings_and_numbers() {
+        let shared_strings = read_shared_strings(
+            r#"<sst><si><t>nome</t></si><si><r><t>preço </t></r><r><t>&amp; custo</t></r></si></sst>"#,
+        )
+        .unwrap();
+        assert_eq!(shared_strings, ["nome", "preço & custo"]);
+
+        let rows = read_rows(
+            r#"<worksheet><sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>
+                <row r="3"><c r="A3" t="inlineStr"><is><t>ARROZ</t></is></c><c r="C3"><v>25.899999999999999</v></c></row>
+            </sheetData></worksheet>"#,
+            &shared_strings,
+        )
+        .unwrap();
+        assert_eq!(rows[0], ["nome", "", "preço & custo"]);
+        assert_eq!(rows.last().unwrap(), &["ARROZ", "", "25,9"]);
+    }
+
+    #[test]
+    fn round_trip_keeps_text_and_leading_zeros() {
+        let sheet = Sheet {
+            headers: vec!["ean".to_string(), "nome".to_string(), "preco".to_string()],
+            rows: vec![
+                vec![
+                    "0789100010010".to_string(),
+                    "  PÃO <FRANCÊS> & CIA ".to_string(),
+                    "0,50".to_string(),
+                ],
+                vec![String::new(), "SAL".to_string(), "1,99".to_string()],
+            ],
+        };
+        let path =
+            std::env::temp_dir().join(format!("catalog-{}-round-trip.xlsx", std::process::id()));
+        write(&sheet, &path).unwrap();
+        let read = read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), sheet);
+    }
+}
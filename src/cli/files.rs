@@ -0,0 +1,39 @@
+use crate::entities::product_sale::ProductSaleLine;
+use crate::helpers::{format_quantity, to_local};
+use anyhow::Result;
+use std::io::Write;
+
+const DELIMITER: u8 = b';';
+
+/// Writes one CSV row per sold line, with dates in local time.
+pub fn write_sales(output: impl Write, lines: &[ProductSaleLine]) -> Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(DELIMITER)
+        .from_writer(output);
+    writer.write_record([
+        "venda",
+        "data",
+        "produto_id",
+        "produto",
+        "unidade",
+        "quantidade",
+        "preco",
+        "total",
+    ])?;
+    for line in lines {
+        writer.write_record([
+            line.sale_id.to_string(),
+            to_local(line.sold_at)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            line.product_id.to_string(),
+            line.name.clone(),
+            line.unit.to_string(),
+            format_quantity(line.quantity, line.unit),
+            line.price.to_decimal_string(),
+            line.total.to_decimal_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
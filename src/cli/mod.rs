@@ -0,0 +1,485 @@
+mod files;
+
+use crate::api;
+use crate::catalog::{self, ColumnMapping, Sheet};
+use crate::config::Config;
+use crate::database;
+use crate::helpers::format_quantity;
+use crate::services::backup_service::BackupService;
+use crate::services::product_purchase_service::SqliteProductPurchaseService;
+use crate::services::product_sale_service::{ProductSaleService, SqliteProductSaleService};
+use crate::services::product_service::{ImportAction, ProductService, SqliteProductService};
+use crate::services::user_service::UserService;
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use sqlx::SqlitePool;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Without a command the point of sale window opens. The commands run
+/// without a window, for maintenance and scripts.
+#[derive(Debug, Parser)]
+#[command(version, about = "SS PDV LITE - ponto de venda")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Cria o banco de dados, se necessário, e aplica as migrações pendentes
+    Migrate,
+    /// Faz um backup do banco de dados na pasta configurada
+    Backup,
+    /// Prepara a restauração de um backup, aplicada na próxima vez que o sistema abrir o banco
+    Restore {
+        /// Arquivo de backup
+        file: PathBuf,
+    },
+    /// Cadastra ou atualiza produtos a partir de um arquivo CSV ou XLSX
+    ImportProducts {
+        /// Arquivo com as colunas id, ean, nome, categoria, unidade, estoque, preco_compra e
+        /// preco_venda
+        file: PathBuf,
+        /// Associa um campo a uma coluna de outro nome, como --map nome=Descrição
+        #[arg(long = "map", value_name = "CAMPO=COLUNA")]
+        mappings: Vec<String>,
+        /// Mostra o que seria feito sem alterar o banco de dados
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Exporta o catálogo de produtos, com estoque e preços, em CSV ou XLSX
+    ExportProducts {
+        /// Arquivo de saída; a extensão .xlsx gera uma planilha
+        file: PathBuf,
+    },
+    /// Exporta em CSV os itens vendidos no período
+    ExportSales {
+        /// Primeiro dia, no formato AAAA-MM-DD
+        #[arg(long)]
+        from: NaiveDate,
+        /// Último dia, no formato AAAA-MM-DD
+        #[arg(long)]
+        to: NaiveDate,
+        /// Arquivo de saída; sem ele o CSV é escrito na saída padrão
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Relatórios de vendas
+    Report {
+        #[command(subcommand)]
+        report: ReportCommand,
+    },
+    /// Inicia a API local sem abrir a janela, usando a porta e o token da configuração
+    Serve,
+    /// Usuários do sistema
+    User {
+        #[command(subcommand)]
+        command: UserCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportCommand {
+    /// Total vendido no dia e por produto
+    Daily {
+        /// Dia no formato AAAA-MM-DD; hoje quando omitido
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserCommand {
+    /// Cadastra um usuário
+    Add {
+        username: String,
+        /// Senha; quando omitida é lida da primeira linha da entrada padrão
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+/// Runs the command and returns the process exit code.
+pub async fn run(command: Command) -> i32 {
+    match execute(command).await {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Erro: {:#}", e);
+            1
+        }
+    }
+}
+
+async fn execute(command: Command) -> Result<()> {
+    execute_with_config(command, &Config::load(&Config::path())?).await
+}
+
+async fn execute_with_config(command: Command, config: &Config) -> Result<()> {
+    // Only staged, as the point of sale may have the database open; the
+    // file is swapped the next time the database is opened
+    if let Command::Restore { file } = &command {
+        BackupService::stage(file, &config.database.path()).await?;
+        println!(
+            "Backup {} verificado. Ele substituirá {} na próxima abertura do sistema.",
+            file.display(),
+            config.database.path().display()
+        );
+        return Ok(());
+    }
+
+    let pool = database::open(&config.database).await?;
+    let result = execute_with_pool(command, config, &pool).await;
+    pool.close().await;
+    result
+}
+
+async fn execute_with_pool(command: Command, config: &Config, pool: &SqlitePool) -> Result<()> {
+    match command {
+        Command::Migrate => {
+            println!(
+                "Banco de dados {} atualizado",
+                config.database.path().display()
+            );
+        }
+        Command::Backup => {
+            let backup_service =
+                BackupService::new(pool.clone(), config.database.path(), config.backup.clone());
+            let path = backup_service.backup().await?;
+            println!("Backup salvo em {}", path.display());
+        }
+        Command::Restore { .. } => unreachable!("restore runs without opening the database"),
+        Command::ImportProducts {
+            file,
+            mappings,
+            dry_run,
+        } => {
+            let sheet = Sheet::read(&file)?;
+            let mut mapping = ColumnMapping::detect(&sheet.headers);
+            for assignment in &mappings {
+                mapping.set_by_name(assignment, &sheet.headers)?;
+            }
+            mapping.validate()?;
+            let product_service = SqliteProductService::new(pool.clone(), config.scale_label);
+            let report = product_service
+                .import_products(catalog::parse_rows(&sheet, &mapping), dry_run)
+                .await?;
+            for entry in &report.entries {
+                match &entry.action {
+                    ImportAction::Create if report.dry_run => {
+                        println!("Linha {}: cadastrar {}", entry.line, entry.name)
+                    }
+                    ImportAction::Update(id) if report.dry_run => {
+                        println!("Linha {}: atualizar {} ({})", entry.line, id, entry.name)
+                    }
+                    ImportAction::Reject(reason) => {
+                        println!("Linha {}: rejeitada, {}", entry.line, reason)
+                    }
+                    _ => {}
+                }
+            }
+            println!(
+                "{}{} produto(s) cadastrado(s), {} atualizado(s), {} linha(s) rejeitada(s)",
+                if report.dry_run { "Simulação: " } else { "" },
+                report.created(),
+                report.updated(),
+                report.rejected()
+            );
+        }
+        Command::ExportProducts { file } => {
+            let product_service = SqliteProductService::new(pool.clone(), config.scale_label);
+            let products = product_service.list_catalog().await?;
+            catalog::to_sheet(&products).write(&file)?;
+            println!(
+                "{} produto(s) exportado(s) para {}",
+                products.len(),
+                file.display()
+            );
+        }
+        Command::ExportSales { from, to, output } => {
+            let product_sale_service = SqliteProductSaleService::new(
+                pool.clone(),
+                config.loyalty.clone(),
+                config.credit.clone(),
+            );
+            let lines = product_sale_service.list_lines(from, to).await?;
+            match output {
+                Some(path) => {
+                    files::write_sales(File::create(&path)?, &lines)?;
+                    println!(
+                        "{} item(ns) exportado(s) para {}",
+                        lines.len(),
+                        path.display()
+                    );
+                }
+                None => files::write_sales(io::stdout().lock(), &lines)?,
+            }
+        }
+        Command::Report {
+            report: ReportCommand::Daily { date },
+        } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let report = SqliteProductSaleService::new(
+                pool.clone(),
+                config.loyalty.clone(),
+                config.credit.clone(),
+            )
+            .daily_report(date)
+            .await?;
+            let mut out = io::stdout().lock();
+            writeln!(out, "VENDAS DE {}", report.date.format("%d/%m/%Y"))?;
+            writeln!(out, "Vendas: {}", report.sales)?;
+            writeln!(out, "Total: {}", report.total)?;
+            if !report.products.is_empty() {
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "{:<40} {:>12} {:>16}",
+                    "PRODUTO", "QUANTIDADE", "TOTAL"
+                )?;
+                for product in &report.products {
+                    let name: String = product.name.chars().take(40).collect();
+                    writeln!(
+                        out,
+                        "{:<40} {:>12} {:>16}",
+                        name,
+                        format!(
+                            "{} {}",
+                            format_quantity(product.quantity, product.unit),
+                            product.unit
+                        ),
+                        product.total.to_string()
+                    )?;
+                }
+            }
+        }
+        Command::Serve => {
+            let services = api::Services {
+                product_service: Arc::new(SqliteProductService::new(
+                    pool.clone(),
+                    config.scale_label,
+                )),
+                product_sale_service: Arc::new(SqliteProductSaleService::new(
+                    pool.clone(),
+                    config.loyalty.clone(),
+                    config.credit.clone(),
+                )),
+                product_purchase_service: Arc::new(SqliteProductPurchaseService::new(pool.clone())),
+            };
+            println!("API ouvindo em http://127.0.0.1:{}/api", config.api.port);
+            api::serve(config.api.clone(), api::SharedServices::new(services)).await?;
+        }
+        Command::User {
+            command: UserCommand::Add { username, password },
+        } => {
+            let password = match password {
+                Some(password) => password,
+                None => {
+                    let mut line = String::new();
+                    io::stdin().lock().read_line(&mut line)?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            if password.is_empty() {
+                bail!("Informe a senha com --password ou pela entrada padrão");
+            }
+            let id = UserService::new(pool.clone())
+                .add_user(&username, &password)
+                .await?;
+            println!(
+                "Usuário {} cadastrado com id {}",
+                username.trim().to_lowercase(),
+                id
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, insert_product};
+    use crate::entities::unit::Unit;
+    use crate::services::product_service::ProductService;
+    use std::path::Path;
+
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("ss_pdv_lite").chain(args.iter().copied()))
+            .unwrap()
+            .command
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_each_command() {
+        assert!(Cli::try_parse_from(["ss_pdv_lite"])
+            .unwrap()
+            .command
+            .is_none());
+        assert!(matches!(parse(&["migrate"]), Command::Migrate));
+        assert!(matches!(parse(&["backup"]), Command::Backup));
+        assert!(matches!(
+            parse(&["restore", "backup.db"]),
+            Command::Restore { file } if file == Path::new("backup.db")
+        ));
+        assert!(matches!(
+            parse(&["import-products", "p.csv", "--map", "nome=Descrição", "--map", "ean=GTIN", "--dry-run"]),
+            Command::ImportProducts { file, mappings, dry_run: true }
+                if file == Path::new("p.csv") && mappings == ["nome=Descrição", "ean=GTIN"]
+        ));
+        assert!(matches!(
+            parse(&["import-products", "p.csv"]),
+            Command::ImportProducts { dry_run: false, .. }
+        ));
+        assert!(matches!(
+            parse(&["export-products", "p.xlsx"]),
+            Command::ExportProducts { file } if file == Path::new("p.xlsx")
+        ));
+        assert!(matches!(
+            parse(&["export-sales", "--from", "2026-01-01", "--to", "2026-01-31", "-o", "v.csv"]),
+            Command::ExportSales { from, to, output: Some(output) }
+                if from == NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+                    && to == NaiveDate::from_ymd_opt(2026, 1, 31).unwrap()
+                    && output == Path::new("v.csv")
+        ));
+        assert!(matches!(
+            parse(&["report", "daily", "--date", "2026-03-02"]),
+            Command::Report {
+                report: ReportCommand::Daily { date: Some(_) }
+            }
+        ));
+        assert!(matches!(
+            parse(&["report", "daily"]),
+            Command::Report {
+                report: ReportCommand::Daily { date: None }
+            }
+        ));
+        assert!(matches!(parse(&["serve"]), Command::Serve));
+        assert!(matches!(
+            parse(&["user", "add", "maria", "--password", "segredo"]),
+            Command::User { command: UserCommand::Add { username, password: Some(password) } }
+                if username == "maria" && password == "segredo"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        for args in [
+            &["ss_pdv_lite", "restore"][..],
+            &["ss_pdv_lite", "export-sales", "--from", "2026-01-01"],
+            &[
+                "ss_pdv_lite",
+                "export-sales",
+                "--from",
+                "01/01/2026",
+                "--to",
+                "2026-01-31",
+            ],
+            &["ss_pdv_lite", "report"],
+            &["ss_pdv_lite", "vender"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn import_products_previews_on_a_dry_run_and_then_imports() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, testing::units(3)).await;
+        let dir = testing::temp_dir("cli-import");
+        let file = dir.join("produtos.csv");
+        std::fs::write(
+            &file,
+            format!(
+                "Código;Descrição;Preço de Venda\n{};;27,90\n;SAL 1KG;2,99\n",
+                rice.id
+            ),
+        )
+        .unwrap();
+        let import = |dry_run| Command::ImportProducts {
+            file: file.clone(),
+            mappings: vec!["nome=Descrição".to_string()],
+            dry_run,
+        };
+        let config = Config::default();
+        let product_service = testing::product_service(&pool);
+
+        execute_with_pool(import(true), &config, &pool)
+            .await
+            .unwrap();
+        assert_eq!(product_service.list_catalog().await.unwrap().len(), 1);
+
+        execute_with_pool(import(false), &config, &pool)
+            .await
+            .unwrap();
+        let catalog = product_service.list_catalog().await.unwrap();
+        let names: Vec<&str> = catalog
+            .iter()
+            .map(|product| product.name.as_str())
+            .collect();
+        assert_eq!(names, ["ARROZ", "SAL 1KG"]);
+        assert_eq!(catalog[0].price_sale.to_string(), "R$ 27,90");
+
+        let error = execute_with_pool(
+            Command::ImportProducts {
+                file: file.clone(),
+                mappings: vec!["nome=Nome".to_string()],
+                dry_run: false,
+            },
+            &config,
+            &pool,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Coluna \"Nome\" não encontrada no arquivo"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restore_replaces_the_database_the_next_time_it_opens() {
+        let dir = testing::temp_dir("cli-restore");
+        let mut config = Config::default();
+        config.database.url = format!("sqlite:{}", dir.join("pdv.db").display());
+        config.backup.folder = dir.join("backups");
+
+        let pool = database::open(&config.database).await.unwrap();
+        insert_product(&pool, "ARROZ", Unit::Un, 0, 2000, 2590).await;
+        let backup =
+            BackupService::new(pool.clone(), config.database.path(), config.backup.clone())
+                .backup()
+                .await
+                .unwrap();
+        insert_product(&pool, "FEIJÃO", Unit::Un, 0, 500, 850).await;
+        pool.close().await;
+
+        execute_with_config(Command::Restore { file: backup }, &config)
+            .await
+            .unwrap();
+        let pool = database::open(&config.database).await.unwrap();
+        let catalog = testing::product_service(&pool)
+            .list_catalog()
+            .await
+            .unwrap();
+        pool.close().await;
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].name, "ARROZ");
+        assert!(dir.join("pdv.db.pre-restore").exists());
+
+        let error = execute_with_config(
+            Command::Restore {
+                file: dir.join("pdv.db.pre-restore.missing"),
+            },
+            &config,
+        )
+        .await;
+        assert!(error.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
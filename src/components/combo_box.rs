@@ -56,6 +56,7 @@
 //! ```
 
 use crate::components;
+use crate::helpers::search;
 use iced::advanced::text::LineHeight;
 use iced::advanced::{layout, mouse, renderer, text, widget, Clipboard, Layout, Shell, Widget};
 use iced::keyboard::key;
@@ -153,6 +154,7 @@ where
     on_open: Option<Message>,
     on_close: Option<Message>,
     on_input: Option<Box<dyn Fn(String) -> Message>>,
+    on_submit: Option<Box<dyn Fn(String) -> Message>>,
     menu_class: <Theme as menu::Catalog>::Class<'a>,
     padding: Padding,
     size: Option<f32>,
@@ -187,6 +189,7 @@ where
             on_selected: Box::new(on_selected),
             on_option_hovered: None,
             on_input: None,
+            on_submit: None,
             on_open: None,
             on_close: None,
             menu_class: <Theme as iced::widget::combo_box::Catalog>::default_menu(),
@@ -202,6 +205,13 @@ where
         self
     }
 
+    /// Sets the message that should be produced when Enter is pressed and no
+    /// option is available to be selected, e.g. after a barcode is scanned.
+    pub fn on_submit(mut self, on_submit: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_submit = Some(Box::new(on_submit));
+        self
+    }
+
     /// Sets the message that will be produced when an option of the
     /// [`ComboBox`] is hovered using the arrow keys.
     pub fn on_option_hovered(mut self, on_option_hovered: impl Fn(T) -> Message + 'static) -> Self {
@@ -223,6 +233,13 @@ where
         self
     }
 
+    /// Sets the [`text_input::Id`] of the [`ComboBox`], so it can be focused
+    /// with [`text_input::focus`].
+    pub fn id(mut self, id: text_input::Id) -> Self {
+        self.text_input = self.text_input.id(id);
+        self
+    }
+
     /// Sets the [`Padding`] of the [`ComboBox`].
     pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
         self.padding = padding.into();
@@ -292,7 +309,6 @@ where
     }
 
     /// Sets the style class of the input of the [`ComboBox`].
-    #[cfg(feature = "advanced")]
     #[must_use]
     pub fn input_class(
         mut self,
@@ -303,7 +319,6 @@ where
     }
 
     /// Sets the style class of the menu of the [`ComboBox`].
-    #[cfg(feature = "advanced")]
     #[must_use]
     pub fn menu_class(mut self, class: impl Into<<Theme as menu::Catalog>::Class<'a>>) -> Self {
         self.menu_class = class.into();
@@ -315,13 +330,15 @@ where
 #[derive(Debug, Clone)]
 pub struct State<T> {
     options: Vec<T>,
+    search_text: fn(&T) -> String,
+    query: fn(&str) -> &str,
     inner: RefCell<Inner<T>>,
 }
 
 #[derive(Debug, Clone)]
 struct Inner<T> {
     value: String,
-    option_matchers: Vec<String>,
+    option_matchers: Vec<Vec<String>>,
     filtered_options: Filtered<T>,
 }
 
@@ -345,8 +362,10 @@ where
     pub fn with_selection(options: Vec<T>, selection: Option<&T>) -> Self {
         let value = selection.map(T::to_string).unwrap_or_default();
 
-        // Pre-build "matcher" strings ahead of time so that search is fast
-        let option_matchers = build_matchers(&options);
+        // Pre-build "matcher" words ahead of time so that search is fast
+        let search_text: fn(&T) -> String = T::to_string;
+        let query: fn(&str) -> &str = |value| value;
+        let option_matchers = build_matchers(&options, search_text);
 
         let filtered_options = Filtered::new(
             search(&options, &option_matchers, &value)
@@ -356,6 +375,8 @@ where
 
         Self {
             options,
+            search_text,
+            query,
             inner: RefCell::new(Inner {
                 value,
                 option_matchers,
@@ -372,16 +393,38 @@ where
         &self.options
     }
 
+    /// Searches the options by `search_text` instead of the text they show,
+    /// such as to also find products by barcode or category.
+    pub fn search_by(mut self, search_text: fn(&T) -> String) -> Self {
+        self.search_text = search_text;
+        let options = std::mem::take(&mut self.options);
+        self.change_options(options);
+        self
+    }
+
+    /// Filters the options by the part of the typed text that `query`
+    /// returns, such as to skip a quantity typed before the product.
+    pub fn query_by(mut self, query: fn(&str) -> &str) -> Self {
+        self.query = query;
+        let options = std::mem::take(&mut self.options);
+        self.change_options(options);
+        self
+    }
+
     pub fn change_options(&mut self, options: Vec<T>) {
         self.options = options;
 
-        let option_matchers = build_matchers(&self.options);
+        let option_matchers = build_matchers(&self.options, self.search_text);
         self.with_inner_mut(|inner| {
             inner.option_matchers = option_matchers;
             inner.filtered_options.update(
-                search(&self.options, &inner.option_matchers, &inner.value)
-                    .cloned()
-                    .collect(),
+                search(
+                    &self.options,
+                    &inner.option_matchers,
+                    (self.query)(&inner.value),
+                )
+                .cloned()
+                .collect(),
             );
         });
     }
@@ -486,14 +529,6 @@ where
         renderer: &Renderer,
         limits: &layout::Limits,
     ) -> layout::Node {
-        let is_focused = {
-            let text_input_state = tree.children[0]
-                .state
-                .downcast_ref::<text_input::State<Renderer::Paragraph>>();
-
-            text_input_state.is_focused()
-        };
-
         self.text_input
             .layout(&mut tree.children[0], renderer, limits, None)
     }
@@ -572,9 +607,13 @@ where
                 state.value = new_value;
 
                 state.filtered_options.update(
-                    search(&self.state.options, &state.option_matchers, &state.value)
-                        .cloned()
-                        .collect(),
+                    search(
+                        &self.state.options,
+                        &state.option_matchers,
+                        (self.state.query)(&state.value),
+                    )
+                    .cloned()
+                    .collect(),
                 );
             });
             shell.invalidate_layout();
@@ -590,14 +629,12 @@ where
 
         if is_focused {
             self.state.with_inner(|state| {
-                if !started_focused {
-                    if let Some(on_option_hovered) = &mut self.on_option_hovered {
-                        let hovered_option = menu.hovered_option.unwrap_or(0);
+                if !started_focused && let Some(on_option_hovered) = &mut self.on_option_hovered {
+                    let hovered_option = menu.hovered_option.unwrap_or(0);
 
-                        if let Some(option) = state.filtered_options.options.get(hovered_option) {
-                            shell.publish(on_option_hovered(option.clone()));
-                            published_message_to_shell = true;
-                        }
+                    if let Some(option) = state.filtered_options.options.get(hovered_option) {
+                        shell.publish(on_option_hovered(option.clone()));
+                        published_message_to_shell = true;
                     }
                 }
 
@@ -610,10 +647,15 @@ where
                     let shift_modifer = modifiers.shift();
                     match (named_key, shift_modifer) {
                         (key::Named::Enter, _) => {
-                            if let Some(index) = &menu.hovered_option {
-                                if let Some(option) = state.filtered_options.options.get(*index) {
-                                    menu.new_selection = Some(option.clone());
-                                }
+                            if let Some(index) = &menu.hovered_option
+                                && let Some(option) = state.filtered_options.options.get(*index)
+                            {
+                                menu.new_selection = Some(option.clone());
+                            } else if let Some(on_submit) = &self.on_submit
+                                && !state.value.is_empty()
+                            {
+                                shell.publish(on_submit(state.value.clone()));
+                                published_message_to_shell = true;
                             }
 
                             event_status = event::Status::Captured;
@@ -630,15 +672,14 @@ where
                                 menu.hovered_option = Some(0);
                             }
 
-                            if let Some(on_option_hovered) = &mut self.on_option_hovered {
-                                if let Some(option) = menu
+                            if let Some(on_option_hovered) = &mut self.on_option_hovered
+                                && let Some(option) = menu
                                     .hovered_option
                                     .and_then(|index| state.filtered_options.options.get(index))
-                                {
-                                    // Notify the selection
-                                    shell.publish((on_option_hovered)(option.clone()));
-                                    published_message_to_shell = true;
-                                }
+                            {
+                                // Notify the selection
+                                shell.publish((on_option_hovered)(option.clone()));
+                                published_message_to_shell = true;
                             }
 
                             event_status = event::Status::Captured;
@@ -659,15 +700,14 @@ where
                                 menu.hovered_option = Some(0);
                             }
 
-                            if let Some(on_option_hovered) = &mut self.on_option_hovered {
-                                if let Some(option) = menu
+                            if let Some(on_option_hovered) = &mut self.on_option_hovered
+                                && let Some(option) = menu
                                     .hovered_option
                                     .and_then(|index| state.filtered_options.options.get(index))
-                                {
-                                    // Notify the selection
-                                    shell.publish((on_option_hovered)(option.clone()));
-                                    published_message_to_shell = true;
-                                }
+                            {
+                                // Notify the selection
+                                shell.publish((on_option_hovered)(option.clone()));
+                                published_message_to_shell = true;
                             }
 
                             event_status = event::Status::Captured;
@@ -760,7 +800,11 @@ where
             text_input_state.is_focused()
         };
 
-        let selection = None;
+        let selection = if is_focused || self.selection.is_empty() {
+            None
+        } else {
+            Some(&self.selection)
+        };
 
         self.text_input.draw(
             &tree.children[0],
@@ -865,43 +909,31 @@ pub trait Catalog: text_input::Catalog + menu::Catalog {
 
 impl Catalog for Theme {}
 
-fn search<'a, T, A>(
+/// Options matching every word of the query, closest first. Accents and
+/// case are ignored and small typos are forgiven, see [`search::cost`].
+fn search<'a, T: 'a>(
     options: impl IntoIterator<Item = T> + 'a,
-    option_matchers: impl IntoIterator<Item = &'a A> + 'a,
+    option_matchers: impl IntoIterator<Item = &'a Vec<String>> + 'a,
     query: &'a str,
-) -> impl Iterator<Item = T> + 'a
-where
-    A: AsRef<str> + 'a,
-{
-    let query: Vec<String> = query
-        .to_lowercase()
-        .split(|c: char| !c.is_ascii_alphanumeric())
-        .map(String::from)
-        .collect();
+) -> impl Iterator<Item = T> + 'a {
+    let query = search::terms(query);
 
-    options
+    let mut matches: Vec<(u32, T)> = options
         .into_iter()
         .zip(option_matchers)
-        // Make sure each part of the query is found in the option
-        .filter_map(move |(option, matcher)| {
-            if query.iter().all(|part| matcher.as_ref().contains(part)) {
-                Some(option)
-            } else {
-                None
-            }
-        })
+        .filter_map(|(option, words)| search::cost(&query, words).map(|cost| (cost, option)))
+        .collect();
+    // Stable, so options keep their order among equally close matches
+    matches.sort_by_key(|(cost, _)| *cost);
+    matches.into_iter().map(|(_, option)| option)
 }
 
-fn build_matchers<'a, T>(options: impl IntoIterator<Item = T> + 'a) -> Vec<String>
-where
-    T: Display + 'a,
-{
+fn build_matchers<'a, T: 'a>(
+    options: impl IntoIterator<Item = &'a T> + 'a,
+    search_text: fn(&T) -> String,
+) -> Vec<Vec<String>> {
     options
         .into_iter()
-        .map(|opt| {
-            let mut matcher = opt.to_string();
-            matcher.retain(|c| c.is_ascii_alphanumeric());
-            matcher.to_lowercase()
-        })
+        .map(|option| search::terms(&search_text(option)))
         .collect()
 }
@@ -1,2 +1,2 @@
-mod search_bar;
-pub mod combo_box;
\ No newline at end of file
+pub mod combo_box;
+pub mod notification;
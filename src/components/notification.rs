@@ -0,0 +1,146 @@
+use iced::widget::{button, column, container, row, stack, text};
+use iced::{border, Alignment, Color, Element, Length, Theme};
+use std::time::{Duration, Instant};
+
+/// How long success and warning toasts stay on screen. Errors stay until the
+/// operator closes them.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const TOAST_WIDTH: f32 = 360.0;
+const WARNING_COLOR: Color = Color::from_rgb(0.96, 0.65, 0.14);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Success,
+    Warning,
+    Error,
+}
+
+/// A message for the operator, raised by any screen and shown by the
+/// application as a toast.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+}
+
+impl Notification {
+    pub fn success(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Success,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Warning,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Error,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Toast {
+    id: u64,
+    notification: Notification,
+    created_at: Instant,
+}
+
+/// Toasts currently on screen, newest last.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    toasts: Vec<Toast>,
+    next_id: u64,
+}
+
+impl Notifications {
+    pub fn push(&mut self, notification: Notification) {
+        self.toasts.push(Toast {
+            id: self.next_id,
+            notification,
+            created_at: Instant::now(),
+        });
+        self.next_id += 1;
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    /// Removes the success and warning toasts older than [`TOAST_DURATION`].
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts.retain(|toast| {
+            toast.notification.level == Level::Error
+                || now.duration_since(toast.created_at) < TOAST_DURATION
+        });
+    }
+
+    /// Whether some toast will expire on its own and needs the timer.
+    pub fn has_expiring(&self) -> bool {
+        self.toasts
+            .iter()
+            .any(|toast| toast.notification.level != Level::Error)
+    }
+
+    /// Draws the toasts over the bottom right corner of `content`.
+    pub fn view<'a, Message: Clone + 'a>(
+        &'a self,
+        content: Element<'a, Message>,
+        on_dismiss: impl Fn(u64) -> Message + 'a,
+    ) -> Element<'a, Message> {
+        if self.toasts.is_empty() {
+            return content;
+        }
+
+        let mut toasts = column![].spacing(8).width(Length::Fixed(TOAST_WIDTH));
+        for toast in &self.toasts {
+            let level = toast.notification.level;
+            toasts = toasts.push(
+                container(
+                    row![
+                        text(&toast.notification.message).width(Length::Fill),
+                        button("X").on_press(on_dismiss(toast.id)),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                )
+                .padding(12)
+                .width(Length::Fill)
+                .style(move |theme| toast_style(theme, level)),
+            );
+        }
+
+        stack![
+            content,
+            container(toasts)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_right(Length::Fill)
+                .align_bottom(Length::Fill)
+                .padding(16),
+        ]
+        .into()
+    }
+}
+
+fn toast_style(theme: &Theme, level: Level) -> container::Style {
+    let palette = theme.palette();
+    let background = match level {
+        Level::Success => palette.success,
+        Level::Warning => WARNING_COLOR,
+        Level::Error => palette.danger,
+    };
+    container::Style {
+        background: Some(background.into()),
+        text_color: Some(Color::WHITE),
+        border: border::rounded(4),
+        ..container::Style::default()
+    }
+}
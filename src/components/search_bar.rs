@@ -1,7 +0,0 @@
-pub struct SearchBar {
-
-}
-
-pub struct State {
-
-}
\ No newline at end of file
@@ -0,0 +1,771 @@
+use crate::helpers::document::is_valid_cnpj;
+use crate::helpers::money::Percent;
+use crate::printing::labels::LabelFormat;
+use crate::printing::PrinterTarget;
+use anyhow::{anyhow, bail, Result};
+use iced::keyboard::key::Named;
+use iced::keyboard::Key;
+use iced::Theme;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable that overrides the configuration file path.
+pub const CONFIG_PATH_ENV: &str = "SS_PDV_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+const DEFAULT_DATABASE_URL: &str = "sqlite:database.db";
+const DEFAULT_STORE_NAME: &str = "SS PDV LITE";
+const DEFAULT_MARKUP: f64 = 30.0;
+pub const MAX_MARKUP: f64 = 1000.0;
+const DEFAULT_THEME: &str = "Tokyo Night Light";
+const DEFAULT_SCALE_BAUD_RATE: u32 = 9600;
+const DEFAULT_SCALE_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_SCALE_LABEL_CODE_LENGTH: usize = 5;
+const DEFAULT_LABEL_PDF_PATH: &str = "etiquetas.pdf";
+const DEFAULT_BACKUP_FOLDER: &str = "backups";
+const DEFAULT_BACKUP_INTERVAL_HOURS: u64 = 24;
+const DEFAULT_BACKUP_KEEP_DAILY: usize = 7;
+const DEFAULT_BACKUP_KEEP_WEEKLY: usize = 4;
+const DEFAULT_API_PORT: u16 = 8787;
+const DEFAULT_POINTS_PER_REAL: u32 = 1;
+const DEFAULT_POINTS_EXPIRATION_MONTHS: u32 = 12;
+const DEFAULT_POINT_VALUE_CENTS: i64 = 1;
+const DEFAULT_CREDIT_DUE_DAYS: u32 = 30;
+/// Tokens shorter than this are too easy to guess.
+const MIN_API_TOKEN_LENGTH: usize = 16;
+
+/// Application settings, kept in `config.toml` next to the executable (or in
+/// the file named by `SS_PDV_CONFIG`). Missing sections take their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub database: DatabaseConfig,
+    pub backup: BackupConfig,
+    pub store: StoreConfig,
+    pub printer: PrinterConfig,
+    pub labels: LabelPrinterConfig,
+    pub purchase: PurchaseConfig,
+    pub loyalty: LoyaltyConfig,
+    pub credit: CreditConfig,
+    pub appearance: AppearanceConfig,
+    pub hotkeys: HotkeysConfig,
+    pub scale: ScaleConfig,
+    pub scale_label: ScaleLabelConfig,
+    pub api: ApiConfig,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        env::var(CONFIG_PATH_ENV)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    /// Reads and validates the configuration file, writing one with the
+    /// default settings on first run.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            let config = Config::default();
+            config.save(path)?;
+            return Ok(config);
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Não foi possível ler {}: {}", path.display(), e))?;
+        let config: Config = toml::from_str(&content).map_err(|e| {
+            anyhow!(
+                "Arquivo de configuração {} inválido:\n{}",
+                path.display(),
+                e
+            )
+        })?;
+        config.validate().map_err(|e| {
+            anyhow!(
+                "Arquivo de configuração {} inválido:\n{}",
+                path.display(),
+                e
+            )
+        })?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.validate()?;
+        let content = toml::to_string_pretty(self)?;
+        std::fs::write(path, content)
+            .map_err(|e| anyhow!("Não foi possível gravar {}: {}", path.display(), e))
+    }
+
+    /// Checks every setting and reports all problems at once, one per line.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self
+            .database
+            .url
+            .strip_prefix("sqlite:")
+            .is_none_or(|path| path.trim().is_empty())
+        {
+            errors.push(format!(
+                "database.url deve começar com \"sqlite:\" seguido do caminho do banco, recebido \"{}\"",
+                self.database.url
+            ));
+        }
+        if self.backup.folder.as_os_str().is_empty() {
+            errors.push("backup.folder não pode ficar vazio".to_string());
+        }
+        if self.backup.interval_hours == 0 {
+            errors.push("backup.interval_hours deve ser maior que zero".to_string());
+        }
+        if self.backup.keep_daily == 0 {
+            errors.push("backup.keep_daily deve manter ao menos uma cópia".to_string());
+        }
+        if self.store.name.trim().is_empty() {
+            errors.push("store.name não pode ficar vazio".to_string());
+        }
+        if !self.store.cnpj.trim().is_empty() && !is_valid_cnpj(&self.store.cnpj) {
+            errors.push(format!(
+                "store.cnpj \"{}\" não é um CNPJ válido",
+                self.store.cnpj
+            ));
+        }
+        if !(0.0..=MAX_MARKUP).contains(&self.purchase.default_markup) {
+            errors.push(format!(
+                "purchase.default_markup deve estar entre 0 e {}, recebido {}",
+                MAX_MARKUP, self.purchase.default_markup
+            ));
+        }
+        if self.loyalty.point_value <= 0 {
+            errors.push(format!(
+                "loyalty.point_value deve ser maior que zero, recebido {}",
+                self.loyalty.point_value
+            ));
+        }
+        if self
+            .loyalty
+            .category_bonus
+            .keys()
+            .any(|category| category.trim().is_empty())
+        {
+            errors.push("loyalty.category_bonus tem uma categoria sem nome".to_string());
+        }
+        if self.credit.due_days == 0 {
+            errors.push("credit.due_days deve ser maior que zero".to_string());
+        }
+        if self.appearance.theme().is_none() {
+            errors.push(format!(
+                "appearance.theme \"{}\" desconhecido, use um de: {}",
+                self.appearance.theme,
+                Theme::ALL
+                    .iter()
+                    .map(|theme| theme.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        let mut used_keys = Vec::new();
+        for (name, value) in self.hotkeys.entries() {
+            match parse_hotkey(value) {
+                Some(key) if used_keys.contains(&key) => {
+                    errors.push(format!(
+                        "hotkeys.{}: a tecla {} já está em uso",
+                        name, value
+                    ));
+                }
+                Some(key) => used_keys.push(key),
+                None => errors.push(format!(
+                    "hotkeys.{}: \"{}\" não é uma tecla válida, use F1 a F12",
+                    name, value
+                )),
+            }
+        }
+        if self.scale.baud_rate == 0 {
+            errors.push("scale.baud_rate deve ser maior que zero".to_string());
+        }
+        if self.scale.timeout_ms == 0 {
+            errors.push("scale.timeout_ms deve ser maior que zero".to_string());
+        }
+        if !(4..=6).contains(&self.scale_label.code_length) {
+            errors.push(format!(
+                "scale_label.code_length deve estar entre 4 e 6, recebido {}",
+                self.scale_label.code_length
+            ));
+        } else if self.scale_label.value_check_digit
+            && !(4..=5).contains(&self.scale_label.value_length())
+        {
+            errors.push(format!(
+                "scale_label.value_check_digit só existe para valores de 4 ou 5 dígitos, use code_length 5 ou 6, recebido {}",
+                self.scale_label.code_length
+            ));
+        }
+        if self.labels.pdf_path.as_os_str().is_empty() {
+            errors.push("labels.pdf_path não pode ficar vazio".to_string());
+        }
+        if self.api.port == 0 {
+            errors.push("api.port deve ser maior que zero".to_string());
+        }
+        if self.api.enabled
+            && let Err(e) = self.api.validate_token()
+        {
+            errors.push(e.to_string());
+        }
+
+        if !errors.is_empty() {
+            bail!(errors.join("\n"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// SQLite connection string, such as `sqlite:database.db`.
+    pub url: String,
+}
+
+impl DatabaseConfig {
+    /// Path of the database file, without the `sqlite:` scheme and options.
+    pub fn path(&self) -> PathBuf {
+        let path = self.url.strip_prefix("sqlite:").unwrap_or(&self.url);
+        let path = path.strip_prefix("//").unwrap_or(path);
+        PathBuf::from(path.split('?').next().unwrap_or_default())
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_DATABASE_URL.to_string(),
+        }
+    }
+}
+
+/// Database copies made with `VACUUM INTO` every `interval_hours`. The newest
+/// copy of each of the last `keep_daily` days and `keep_weekly` weeks is kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub folder: PathBuf,
+    pub interval_hours: u64,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl BackupConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_hours * 60 * 60)
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::from(DEFAULT_BACKUP_FOLDER),
+            interval_hours: DEFAULT_BACKUP_INTERVAL_HOURS,
+            keep_daily: DEFAULT_BACKUP_KEEP_DAILY,
+            keep_weekly: DEFAULT_BACKUP_KEEP_WEEKLY,
+        }
+    }
+}
+
+/// Store identification printed on receipts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreConfig {
+    pub name: String,
+    pub cnpj: String,
+    pub address: String,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_STORE_NAME.to_string(),
+            cnpj: String::new(),
+            address: String::new(),
+        }
+    }
+}
+
+/// Receipt printer: a file or device path, or `tcp://host:port`. Empty when
+/// there is no printer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrinterConfig {
+    pub receipt: String,
+}
+
+impl PrinterConfig {
+    pub fn receipt_target(&self) -> Option<PrinterTarget> {
+        PrinterTarget::parse(&self.receipt)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PurchaseConfig {
+    /// Markup percentage suggested for new products on a purchase.
+    pub default_markup: f64,
+}
+
+impl PurchaseConfig {
+    pub fn markup(&self) -> Percent {
+        Percent::from_f64(self.default_markup)
+    }
+}
+
+impl Default for PurchaseConfig {
+    fn default() -> Self {
+        Self {
+            default_markup: DEFAULT_MARKUP,
+        }
+    }
+}
+
+/// Loyalty points of identified customers. Points are earned on what the
+/// customer pays, with extra points on some categories, and each one is
+/// worth `point_value` centavos when used to pay a sale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoyaltyConfig {
+    pub enabled: bool,
+    /// Points earned per real paid.
+    pub points_per_real: u32,
+    /// Extra points per real spent on products of each category.
+    pub category_bonus: BTreeMap<String, u32>,
+    /// Months until earned points expire, 0 for never.
+    pub expiration_months: u32,
+    pub point_value: i64,
+}
+
+impl Default for LoyaltyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            points_per_real: DEFAULT_POINTS_PER_REAL,
+            category_bonus: BTreeMap::new(),
+            expiration_months: DEFAULT_POINTS_EXPIRATION_MONTHS,
+            point_value: DEFAULT_POINT_VALUE_CENTS,
+        }
+    }
+}
+
+/// Store credit ("fiado") sales, which fall due `due_days` after the sale.
+/// Customers with overdue charges cannot buy on credit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreditConfig {
+    pub due_days: u32,
+}
+
+impl Default for CreditConfig {
+    fn default() -> Self {
+        Self {
+            due_days: DEFAULT_CREDIT_DUE_DAYS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfig {
+    /// Name of one of the iced built-in themes, such as `Tokyo Night Light`.
+    pub theme: String,
+}
+
+impl AppearanceConfig {
+    pub fn theme(&self) -> Option<Theme> {
+        Theme::ALL
+            .iter()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(self.theme.trim()))
+            .cloned()
+    }
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            theme: DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+/// Screen the hotkeys navigate to, or a command of the checkout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Home,
+    Purchase,
+    Products,
+    Sales,
+    Customers,
+    Financial,
+    Settings,
+    Search,
+    CancelSale,
+    Payment,
+}
+
+/// Function keys bound to each screen and to the checkout commands, written
+/// as `F1` to `F12`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeysConfig {
+    pub home: String,
+    pub purchase: String,
+    pub products: String,
+    pub sales: String,
+    pub customers: String,
+    pub financial: String,
+    pub settings: String,
+    pub search: String,
+    pub cancel_sale: String,
+    pub payment: String,
+}
+
+impl HotkeysConfig {
+    fn entries(&self) -> [(&'static str, &str); 10] {
+        [
+            ("home", &self.home),
+            ("purchase", &self.purchase),
+            ("products", &self.products),
+            ("sales", &self.sales),
+            ("customers", &self.customers),
+            ("financial", &self.financial),
+            ("settings", &self.settings),
+            ("search", &self.search),
+            ("cancel_sale", &self.cancel_sale),
+            ("payment", &self.payment),
+        ]
+    }
+
+    pub fn action(&self, key: &Key) -> Option<HotkeyAction> {
+        let bindings = [
+            (&self.home, HotkeyAction::Home),
+            (&self.purchase, HotkeyAction::Purchase),
+            (&self.products, HotkeyAction::Products),
+            (&self.sales, HotkeyAction::Sales),
+            (&self.customers, HotkeyAction::Customers),
+            (&self.financial, HotkeyAction::Financial),
+            (&self.settings, HotkeyAction::Settings),
+            (&self.search, HotkeyAction::Search),
+            (&self.cancel_sale, HotkeyAction::CancelSale),
+            (&self.payment, HotkeyAction::Payment),
+        ];
+        bindings
+            .into_iter()
+            .find(|(value, _)| parse_hotkey(value).as_ref() == Some(key))
+            .map(|(_, action)| action)
+    }
+}
+
+impl Default for HotkeysConfig {
+    fn default() -> Self {
+        Self {
+            home: "F1".to_string(),
+            purchase: "F2".to_string(),
+            products: "F3".to_string(),
+            sales: "F6".to_string(),
+            customers: "F9".to_string(),
+            financial: "F4".to_string(),
+            settings: "F5".to_string(),
+            search: "F7".to_string(),
+            cancel_sale: "F8".to_string(),
+            payment: "F10".to_string(),
+        }
+    }
+}
+
+fn parse_hotkey(value: &str) -> Option<Key> {
+    let named = match value.trim().to_ascii_uppercase().as_str() {
+        "F1" => Named::F1,
+        "F2" => Named::F2,
+        "F3" => Named::F3,
+        "F4" => Named::F4,
+        "F5" => Named::F5,
+        "F6" => Named::F6,
+        "F7" => Named::F7,
+        "F8" => Named::F8,
+        "F9" => Named::F9,
+        "F10" => Named::F10,
+        "F11" => Named::F11,
+        "F12" => Named::F12,
+        _ => return None,
+    };
+    Some(Key::Named(named))
+}
+
+/// Checkout scale settings. The scale is disabled when no device is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScaleConfig {
+    pub device: Option<String>,
+    pub baud_rate: u32,
+    pub timeout_ms: u64,
+}
+
+impl ScaleConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+impl Default for ScaleConfig {
+    fn default() -> Self {
+        Self {
+            device: None,
+            baud_rate: DEFAULT_SCALE_BAUD_RATE,
+            timeout_ms: DEFAULT_SCALE_TIMEOUT_MS,
+        }
+    }
+}
+
+/// What the value field of a scale label carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ScaleLabelValue {
+    /// Total price in centavos.
+    Price,
+    /// Weight in grams.
+    Weight,
+}
+
+impl ScaleLabelValue {
+    pub const ALL: [ScaleLabelValue; 2] = [ScaleLabelValue::Price, ScaleLabelValue::Weight];
+}
+
+impl std::fmt::Display for ScaleLabelValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaleLabelValue::Price => f.write_str("PREÇO"),
+            ScaleLabelValue::Weight => f.write_str("PESO"),
+        }
+    }
+}
+
+/// Layout of the variable-measure EAN-13 labels printed by the store scale:
+/// `2`, the product code, an optional value check digit, the value and the
+/// EAN check digit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScaleLabelConfig {
+    pub code_length: usize,
+    pub value: ScaleLabelValue,
+    pub value_check_digit: bool,
+}
+
+impl ScaleLabelConfig {
+    /// Number of digits left for the value field.
+    pub fn value_length(&self) -> usize {
+        11 - self.code_length - usize::from(self.value_check_digit)
+    }
+}
+
+impl Default for ScaleLabelConfig {
+    fn default() -> Self {
+        Self {
+            code_length: DEFAULT_SCALE_LABEL_CODE_LENGTH,
+            value: ScaleLabelValue::Price,
+            value_check_digit: false,
+        }
+    }
+}
+
+/// Label printer settings. `target` is a file path or `tcp://host:port`,
+/// empty when labels are only saved as PDF.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelPrinterConfig {
+    pub target: String,
+    pub format: LabelFormat,
+    pub pdf_path: PathBuf,
+}
+
+impl LabelPrinterConfig {
+    pub fn target(&self) -> Option<PrinterTarget> {
+        PrinterTarget::parse(&self.target)
+    }
+}
+
+impl Default for LabelPrinterConfig {
+    fn default() -> Self {
+        Self {
+            target: String::new(),
+            format: LabelFormat::Zpl,
+            pdf_path: PathBuf::from(DEFAULT_LABEL_PDF_PATH),
+        }
+    }
+}
+
+/// Local HTTP API for other tools of the store. It only listens on
+/// `127.0.0.1:port` and every request must send `Authorization: Bearer
+/// <token>`. Changes take effect on the next start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl ApiConfig {
+    /// The `serve` command checks the token even with the API disabled.
+    pub fn validate_token(&self) -> Result<()> {
+        if self.token.trim().chars().count() < MIN_API_TOKEN_LENGTH {
+            bail!(
+                "api.token deve ter ao menos {} caracteres",
+                MIN_API_TOKEN_LENGTH
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_API_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Change = fn(&mut Config);
+
+    fn errors(config: &Config) -> Vec<String> {
+        match config.validate() {
+            Ok(()) => vec![],
+            Err(e) => e.to_string().lines().map(str::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn default_settings_are_valid() {
+        let mut config = Config::default();
+        assert!(errors(&config).is_empty());
+        // The default 5 digit code leaves a 5 digit value to check
+        config.scale_label.value_check_digit = true;
+        assert!(errors(&config).is_empty());
+    }
+
+    #[test]
+    fn missing_sections_and_fields_take_their_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            [store]
+            name = "MERCADINHO BOA VISTA"
+
+            [backup]
+            keep_daily = 3
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.store.name, "MERCADINHO BOA VISTA");
+        assert_eq!(config.backup.keep_daily, 3);
+        assert_eq!(config.backup.interval_hours, DEFAULT_BACKUP_INTERVAL_HOURS);
+        assert_eq!(config.database, DatabaseConfig::default());
+        assert_eq!(config.hotkeys, HotkeysConfig::default());
+        assert!(errors(&config).is_empty());
+    }
+
+    #[test]
+    fn reports_each_invalid_field() {
+        let cases: Vec<(Change, &str)> = vec![
+            (
+                |c| c.database.url = "database.db".to_string(),
+                "database.url deve começar com \"sqlite:\" seguido do caminho do banco, recebido \"database.db\"",
+            ),
+            (
+                |c| c.backup.folder = PathBuf::new(),
+                "backup.folder não pode ficar vazio",
+            ),
+            (
+                |c| c.backup.keep_daily = 0,
+                "backup.keep_daily deve manter ao menos uma cópia",
+            ),
+            (
+                |c| c.store.name = " ".to_string(),
+                "store.name não pode ficar vazio",
+            ),
+            (
+                |c| c.store.cnpj = "11.222.333/0001-00".to_string(),
+                "store.cnpj \"11.222.333/0001-00\" não é um CNPJ válido",
+            ),
+            (
+                |c| c.purchase.default_markup = -1.0,
+                "purchase.default_markup deve estar entre 0 e 1000, recebido -1",
+            ),
+            (
+                |c| c.loyalty.point_value = 0,
+                "loyalty.point_value deve ser maior que zero, recebido 0",
+            ),
+            (
+                |c| {
+                    c.loyalty.category_bonus.insert(" ".to_string(), 2);
+                },
+                "loyalty.category_bonus tem uma categoria sem nome",
+            ),
+            (
+                |c| c.credit.due_days = 0,
+                "credit.due_days deve ser maior que zero",
+            ),
+            (
+                |c| c.hotkeys.search = "F1".to_string(),
+                "hotkeys.search: a tecla F1 já está em uso",
+            ),
+            (
+                |c| c.hotkeys.payment = "Enter".to_string(),
+                "hotkeys.payment: \"Enter\" não é uma tecla válida, use F1 a F12",
+            ),
+            (
+                |c| c.scale_label.code_length = 7,
+                "scale_label.code_length deve estar entre 4 e 6, recebido 7",
+            ),
+            (
+                |c| {
+                    c.scale_label.code_length = 4;
+                    c.scale_label.value_check_digit = true;
+                },
+                "scale_label.value_check_digit só existe para valores de 4 ou 5 dígitos, use code_length 5 ou 6, recebido 4",
+            ),
+            (
+                |c| c.api.enabled = true,
+                "api.token deve ter ao menos 16 caracteres",
+            ),
+        ];
+
+        for (change, expected) in cases {
+            let mut config = Config::default();
+            change(&mut config);
+            assert_eq!(errors(&config), [expected]);
+        }
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        let mut config = Config::default();
+        config.store.name.clear();
+        config.credit.due_days = 0;
+        config.api.port = 0;
+
+        assert_eq!(
+            errors(&config),
+            [
+                "store.name não pode ficar vazio",
+                "credit.due_days deve ser maior que zero",
+                "api.port deve ser maior que zero",
+            ]
+        );
+    }
+}
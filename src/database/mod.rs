@@ -0,0 +1,179 @@
+use crate::config::DatabaseConfig;
+use crate::services::backup_service::BackupService;
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+// Primary SQLite result codes, see https://www.sqlite.org/rescode.html
+const SQLITE_BUSY: i64 = 5;
+const SQLITE_LOCKED: i64 = 6;
+const SQLITE_READONLY: i64 = 8;
+const SQLITE_CORRUPT: i64 = 11;
+const SQLITE_CANTOPEN: i64 = 14;
+const SQLITE_NOTADB: i64 = 26;
+
+/// Applies a staged restore, opens the database (creating the file when it
+/// is missing) and runs the pending migrations. Errors are explained in terms
+/// the operator can act on.
+pub async fn open(config: &DatabaseConfig) -> Result<SqlitePool> {
+    let path = config.path();
+    BackupService::apply_pending_restore(&path)?;
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.exists()
+    {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Não foi possível criar a pasta {}: {}", parent.display(), e))?;
+    }
+
+    let options = SqliteConnectOptions::from_str(&config.url)
+        .map_err(|e| {
+            anyhow!(
+                "Endereço do banco de dados \"{}\" inválido: {}",
+                config.url,
+                e
+            )
+        })?
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(|e| describe(&e, &path))?;
+
+    if let Err(e) = MIGRATOR.run(&pool).await {
+        pool.close().await;
+        return Err(match e {
+            MigrateError::Execute(e) => describe(&e, &path),
+            e => anyhow!(
+                "O banco de dados {} não é compatível com esta versão do sistema: {}",
+                path.display(),
+                e
+            ),
+        });
+    }
+    Ok(pool)
+}
+
+fn describe(error: &sqlx::Error, path: &Path) -> anyhow::Error {
+    let code = match error {
+        sqlx::Error::Database(e) => e
+            .code()
+            .and_then(|code| code.parse::<i64>().ok())
+            .map(|code| code & 0xff),
+        _ => None,
+    };
+    let explanation = match code {
+        Some(SQLITE_BUSY | SQLITE_LOCKED) => {
+            "O banco de dados está em uso por outro programa. Feche outras janelas do sistema e tente novamente."
+        }
+        Some(SQLITE_READONLY) => {
+            "O banco de dados está protegido contra gravação. Verifique as permissões do arquivo."
+        }
+        Some(SQLITE_CORRUPT | SQLITE_NOTADB) => {
+            "O arquivo do banco de dados está corrompido ou não é um banco do sistema. Restaure um backup ou crie um novo banco."
+        }
+        Some(SQLITE_CANTOPEN) => {
+            "Não foi possível abrir o arquivo do banco de dados. Verifique se a pasta existe e se há permissão de acesso."
+        }
+        _ => "Não foi possível abrir o banco de dados.",
+    };
+    anyhow!(
+        "{}\n\nArquivo: {}\nDetalhes: {}",
+        explanation,
+        path.display(),
+        error
+    )
+}
+
+/// Moves the current database file out of the way so a new one can be
+/// created, keeping it as `<file>.old-<timestamp>` in case it can be repaired.
+pub fn set_aside(config: &DatabaseConfig) -> Result<Option<PathBuf>> {
+    let path = config.path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".old-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    let target = PathBuf::from(name);
+    for suffix in ["", "-wal", "-shm"] {
+        let mut from = path.as_os_str().to_owned();
+        from.push(suffix);
+        let mut to = target.as_os_str().to_owned();
+        to.push(suffix);
+        if Path::new(&from).exists() {
+            std::fs::rename(&from, &to).map_err(|e| {
+                anyhow!(
+                    "Não foi possível mover {}: {}",
+                    Path::new(&from).display(),
+                    e
+                )
+            })?;
+        }
+    }
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+pub mod testing;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(path: &Path) -> DatabaseConfig {
+        DatabaseConfig {
+            url: format!("sqlite:{}", path.display()),
+        }
+    }
+
+    #[tokio::test]
+    async fn open_creates_a_missing_database_and_its_folder() {
+        let dir = testing::temp_dir("open-missing");
+        let path = dir.join("dados").join("pdv.db");
+
+        let pool = open(&config(&path)).await.unwrap();
+        pool.close().await;
+        assert!(path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn open_explains_a_file_that_is_not_a_database() {
+        let dir = testing::temp_dir("open-corrupt");
+        let path = dir.join("pdv.db");
+        std::fs::write(&path, "isto não é um banco de dados SQLite").unwrap();
+
+        let error = open(&config(&path)).await.unwrap_err().to_string();
+        assert!(
+            error.starts_with("O arquivo do banco de dados está corrompido"),
+            "{}",
+            error
+        );
+        assert!(error.contains(&format!("Arquivo: {}", path.display())));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn set_aside_moves_the_database_and_its_journals() {
+        let dir = testing::temp_dir("set-aside");
+        let path = dir.join("pdv.db");
+        assert_eq!(set_aside(&config(&path)).unwrap(), None);
+
+        std::fs::write(&path, "banco").unwrap();
+        std::fs::write(dir.join("pdv.db-wal"), "diário").unwrap();
+        let target = set_aside(&config(&path)).unwrap().unwrap();
+        assert!(!path.exists());
+        assert!(!dir.join("pdv.db-wal").exists());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "banco");
+        let mut journal = target.into_os_string();
+        journal.push("-wal");
+        assert!(Path::new(&journal).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
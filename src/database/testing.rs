@@ -0,0 +1,112 @@
+//! In-memory databases for tests, with every migration applied.
+
+use super::MIGRATOR;
+use crate::config::{CreditConfig, LoyaltyConfig, ScaleLabelConfig};
+use crate::entities::product::Product;
+use crate::entities::product_sale::SaleItem;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use crate::helpers::QUANTITY_SCALE;
+use crate::repositories::product_repository::ProductRepository;
+use crate::services::product_sale_service::SqliteProductSaleService;
+use crate::services::product_service::SqliteProductService;
+use chrono::Utc;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A fresh database that lives as long as the pool. Each SQLite connection
+/// to `:memory:` opens a different database, so the pool keeps exactly one
+/// connection open.
+pub async fn pool() -> SqlitePool {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:").expect("valid url");
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .expect("in-memory database");
+    MIGRATOR.run(&pool).await.expect("migrations");
+    pool
+}
+
+/// Registers a product with `stock` whole units, or thousandths for
+/// fractional units, and returns it with its id.
+pub async fn insert_product(
+    pool: &SqlitePool,
+    name: &str,
+    unit: Unit,
+    stock: i64,
+    price_purchase: i64,
+    price_sale: i64,
+) -> Product {
+    let mut product = Product::new(
+        0,
+        name.to_string(),
+        Money::from_cents(price_sale),
+        Money::from_cents(price_purchase),
+        stock,
+        unit,
+        None,
+        Utc::now().naive_local(),
+    );
+    product.id = ProductRepository::insert(pool, &product)
+        .await
+        .expect("insert product");
+    product
+}
+
+/// Rice bought at 20,00 and sold at 25,90, the product most tests trade.
+pub async fn rice(pool: &SqlitePool, stock: i64) -> Product {
+    insert_product(pool, "ARROZ", Unit::Un, stock, 2000, 2590).await
+}
+
+/// `quantity` of `product` as a line of a new sale.
+pub fn sold(product: &Product, quantity: i64) -> SaleItem {
+    Product {
+        quantity,
+        ..product.clone()
+    }
+    .into()
+}
+
+/// The sale service with loyalty and store credit left at their defaults.
+pub fn sale_service(pool: &SqlitePool) -> SqliteProductSaleService {
+    SqliteProductSaleService::new(
+        pool.clone(),
+        LoyaltyConfig::default(),
+        CreditConfig::default(),
+    )
+}
+
+/// The product service with the default scale label layout.
+pub fn product_service(pool: &SqlitePool) -> SqliteProductService {
+    SqliteProductService::new(pool.clone(), ScaleLabelConfig::default())
+}
+
+/// `units` whole units in the fixed point representation.
+pub fn units(units: i64) -> i64 {
+    units * QUANTITY_SCALE
+}
+
+pub async fn stock(pool: &SqlitePool, id: i64) -> i64 {
+    ProductRepository::find_by_id(pool, id)
+        .await
+        .expect("find product")
+        .expect("product exists")
+        .quantity
+}
+
+/// An empty directory under the system temporary folder, unique to the test
+/// process and `name`.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ss-pdv-{}-{}", std::process::id(), name));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).expect("clear temporary directory");
+    }
+    std::fs::create_dir_all(&dir).expect("create temporary directory");
+    dir
+}
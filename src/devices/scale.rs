@@ -0,0 +1,159 @@
+use crate::config::ScaleConfig;
+use anyhow::{anyhow, bail, Result};
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+const ENQ: u8 = 0x05;
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+
+/// Weight frame sent back by Toledo/Filizola scales after an ENQ request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleReading {
+    /// Stable weight in grams, which matches the 3 decimal places of quantities.
+    Stable(i64),
+    Unstable,
+    Overload,
+    Negative,
+}
+
+/// Checkout scale reachable through a serial device path (or a pseudo-terminal).
+#[derive(Debug, Clone)]
+pub struct Scale {
+    device: String,
+    baud_rate: u32,
+    timeout: Duration,
+}
+
+impl Scale {
+    pub fn new(device: String, baud_rate: u32, timeout: Duration) -> Self {
+        Self {
+            device,
+            baud_rate,
+            timeout,
+        }
+    }
+
+    pub fn from_config(config: &ScaleConfig) -> Option<Self> {
+        config
+            .device
+            .clone()
+            .map(|device| Self::new(device, config.baud_rate, config.timeout()))
+    }
+
+    /// Requests the current weight and returns it only when the scale reports
+    /// a stable reading.
+    pub async fn read_weight(&self) -> Result<i64> {
+        let scale = self.clone();
+        let reading = tokio::task::spawn_blocking(move || scale.request_reading()).await??;
+        match reading {
+            ScaleReading::Stable(weight) if weight > 0 => Ok(weight),
+            ScaleReading::Stable(_) => Err(anyhow!("Balança sem peso")),
+            ScaleReading::Unstable => Err(anyhow!("Peso instável, aguarde e tente novamente")),
+            ScaleReading::Overload => Err(anyhow!("Sobrecarga na balança")),
+            ScaleReading::Negative => Err(anyhow!("Peso negativo, verifique a tara da balança")),
+        }
+    }
+
+    fn request_reading(&self) -> Result<ScaleReading> {
+        let mut port = serialport::new(&self.device, self.baud_rate)
+            .timeout(self.timeout)
+            .open()
+            .map_err(|e| anyhow!("Não foi possível abrir a balança em {}: {}", self.device, e))?;
+        port.write_all(&[ENQ])?;
+        port.flush()?;
+
+        let started = Instant::now();
+        let mut frame = Vec::new();
+        let mut buffer = [0u8; 32];
+        while started.elapsed() < self.timeout {
+            match port.read(&mut buffer) {
+                Ok(0) => {}
+                Ok(read) => {
+                    frame.extend_from_slice(&buffer[..read]);
+                    if let Some(reading) = parse_frame(&frame) {
+                        return Ok(reading);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::TimedOut => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        bail!("A balança não respondeu")
+    }
+}
+
+/// Parses a `STX <weight> ETX` frame. The weight is either 5/6 digits in grams
+/// or a decimal value in kilograms ("01.250"); all-`I`, all-`S` and all-`N`
+/// payloads flag unstable, overload and negative readings.
+pub fn parse_frame(frame: &[u8]) -> Option<ScaleReading> {
+    let start = frame.iter().position(|&b| b == STX)?;
+    let end = start + frame[start..].iter().position(|&b| b == ETX)?;
+    let payload = std::str::from_utf8(&frame[start + 1..end]).ok()?.trim();
+    if payload.is_empty() {
+        return None;
+    }
+    if payload.chars().all(|c| c == 'I') {
+        return Some(ScaleReading::Unstable);
+    }
+    if payload.chars().all(|c| c == 'S') {
+        return Some(ScaleReading::Overload);
+    }
+    if payload.chars().all(|c| c == 'N') {
+        return Some(ScaleReading::Negative);
+    }
+    let grams = match payload.split_once(['.', ',']) {
+        Some((kg, g)) if g.len() == 3 => kg.parse::<i64>().ok()? * 1000 + g.parse::<i64>().ok()?,
+        Some(_) => return None,
+        None => payload.parse::<i64>().ok()?,
+    };
+    Some(ScaleReading::Stable(grams))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stable_frames() {
+        assert_eq!(
+            parse_frame(b"\x0201250\x03"),
+            Some(ScaleReading::Stable(1250))
+        );
+        assert_eq!(
+            parse_frame(b"\x02001250\x03"),
+            Some(ScaleReading::Stable(1250))
+        );
+        assert_eq!(
+            parse_frame(b"\x0201.250\x03"),
+            Some(ScaleReading::Stable(1250))
+        );
+        assert_eq!(
+            parse_frame(b"\x02 2,005\x03"),
+            Some(ScaleReading::Stable(2005))
+        );
+        // Noise before the frame is skipped
+        assert_eq!(
+            parse_frame(b"\x00\x0200500\x03"),
+            Some(ScaleReading::Stable(500))
+        );
+    }
+
+    #[test]
+    fn parses_status_frames() {
+        assert_eq!(parse_frame(b"\x02IIIII\x03"), Some(ScaleReading::Unstable));
+        assert_eq!(parse_frame(b"\x02SSSSS\x03"), Some(ScaleReading::Overload));
+        assert_eq!(parse_frame(b"\x02NNNNN\x03"), Some(ScaleReading::Negative));
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        assert_eq!(parse_frame(b"01250\x03"), None);
+        assert_eq!(parse_frame(b"\x0201250"), None);
+        assert_eq!(parse_frame(b"\x02\x03"), None);
+        assert_eq!(parse_frame(b"\x02  \x03"), None);
+        assert_eq!(parse_frame(b"\x0201.25\x03"), None);
+        assert_eq!(parse_frame(b"\x0201A50\x03"), None);
+        assert_eq!(parse_frame(b"\x02IISII\x03"), None);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::helpers::money::Money;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Clone)]
+pub struct Customer {
+    pub id: i64,
+    pub name: String,
+    pub cpf: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    /// Most the customer may owe on store credit, zero when not allowed.
+    pub credit_limit: Money,
+    pub created_at: NaiveDateTime,
+}
+
+impl Customer {
+    pub fn new(
+        id: i64,
+        name: String,
+        cpf: Option<String>,
+        phone: Option<String>,
+        email: Option<String>,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            cpf,
+            phone,
+            email,
+            credit_limit: Money::ZERO,
+            created_at,
+        }
+    }
+}
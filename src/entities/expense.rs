@@ -0,0 +1,84 @@
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum ExpenseCategory {
+    #[sqlx(rename = "RENT")]
+    Rent,
+    #[sqlx(rename = "UTILITIES")]
+    Utilities,
+    #[sqlx(rename = "PAYROLL")]
+    Payroll,
+    #[sqlx(rename = "TAXES")]
+    Taxes,
+    #[sqlx(rename = "MAINTENANCE")]
+    Maintenance,
+    #[default]
+    #[sqlx(rename = "OTHER")]
+    Other,
+}
+
+impl ExpenseCategory {
+    pub const ALL: [ExpenseCategory; 6] = [
+        ExpenseCategory::Rent,
+        ExpenseCategory::Utilities,
+        ExpenseCategory::Payroll,
+        ExpenseCategory::Taxes,
+        ExpenseCategory::Maintenance,
+        ExpenseCategory::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpenseCategory::Rent => "ALUGUEL",
+            ExpenseCategory::Utilities => "ÁGUA, LUZ E TELEFONE",
+            ExpenseCategory::Payroll => "SALÁRIOS",
+            ExpenseCategory::Taxes => "IMPOSTOS E TAXAS",
+            ExpenseCategory::Maintenance => "MANUTENÇÃO",
+            ExpenseCategory::Other => "OUTRAS",
+        }
+    }
+}
+
+impl Display for ExpenseCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Money spent running the shop, other than buying goods.
+#[derive(Debug, Clone)]
+pub struct Expense {
+    pub id: i64,
+    pub category: ExpenseCategory,
+    pub description: String,
+    pub amount: Money,
+    pub paid_on: NaiveDate,
+    pub payment_method: PaymentMethod,
+    pub created_at: NaiveDateTime,
+}
+
+impl Expense {
+    pub fn new(
+        id: i64,
+        category: ExpenseCategory,
+        description: String,
+        amount: Money,
+        paid_on: NaiveDate,
+        payment_method: PaymentMethod,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id,
+            category,
+            description,
+            amount,
+            paid_on,
+            payment_method,
+            created_at,
+        }
+    }
+}
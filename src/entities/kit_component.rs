@@ -0,0 +1,22 @@
+use crate::entities::unit::Unit;
+use crate::helpers::QUANTITY_SCALE;
+
+/// A product in a kit, with what the kit takes of it and what is in stock.
+#[derive(Debug, Clone)]
+pub struct KitComponent {
+    pub component_id: i64,
+    pub name: String,
+    pub unit: Unit,
+    /// Quantity of the component in one kit.
+    pub quantity: i64,
+    pub stock: i64,
+}
+
+impl KitComponent {
+    /// Quantity of the component taken by `kits` whole kits, or `None` when
+    /// it doesn't fit in the stock column.
+    pub fn quantity_for(&self, kits: i64) -> Option<i64> {
+        i64::try_from(i128::from(self.quantity) * i128::from(kits) / i128::from(QUANTITY_SCALE))
+            .ok()
+    }
+}
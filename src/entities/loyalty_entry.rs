@@ -0,0 +1,62 @@
+use chrono::NaiveDateTime;
+
+/// Why points entered or left a customer's balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum LoyaltyKind {
+    /// Points earned on a sale.
+    #[sqlx(rename = "EARN")]
+    Earn,
+    /// Points used to pay part of a sale.
+    #[sqlx(rename = "REDEEM")]
+    Redeem,
+    /// Earned points taken back after a return or void.
+    #[sqlx(rename = "REVERSAL")]
+    Reversal,
+    /// Redeemed points given back after a return or void.
+    #[sqlx(rename = "REFUND")]
+    Refund,
+}
+
+impl LoyaltyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoyaltyKind::Earn => "GANHO",
+            LoyaltyKind::Redeem => "USO",
+            LoyaltyKind::Reversal => "ESTORNO",
+            LoyaltyKind::Refund => "DEVOLUÇÃO",
+        }
+    }
+}
+
+/// A line of the loyalty points ledger. `points` is positive for earned and
+/// refunded points and negative for redeemed and reversed ones.
+#[derive(Debug, Clone)]
+pub struct LoyaltyEntry {
+    pub customer_id: i64,
+    pub sale_id: Option<i64>,
+    pub kind: LoyaltyKind,
+    pub points: i64,
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl LoyaltyEntry {
+    pub fn new(
+        customer_id: i64,
+        sale_id: Option<i64>,
+        kind: LoyaltyKind,
+        points: i64,
+        expires_at: Option<NaiveDateTime>,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            customer_id,
+            sale_id,
+            kind,
+            points,
+            expires_at,
+            created_at,
+        }
+    }
+}
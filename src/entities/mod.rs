@@ -1,5 +1,16 @@
+pub mod abc_class;
+pub mod customer;
+pub mod expense;
+pub mod kit_component;
+pub mod loyalty_entry;
+pub mod payable;
+pub mod payment_method;
 pub mod product;
-pub mod purchase;
 pub mod product_purchase;
 pub mod product_sale;
-pub mod sale;
\ No newline at end of file
+pub mod product_sale_return;
+pub mod purchase;
+pub mod receivable_entry;
+pub mod sale;
+pub mod unit;
+pub mod user;
@@ -0,0 +1,55 @@
+use crate::helpers::money::Money;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// A title owed to a supplier: one installment of a purchase, open until
+/// `paid_at` is set.
+#[derive(Debug, Clone)]
+pub struct Payable {
+    pub id: i64,
+    pub purchase_id: Option<i64>,
+    pub supplier: String,
+    /// Invoice number.
+    pub document: Option<String>,
+    pub installment: i64,
+    pub installments: i64,
+    pub amount: Money,
+    pub due_date: NaiveDate,
+    pub paid_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl Payable {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: i64,
+        purchase_id: Option<i64>,
+        supplier: String,
+        document: Option<String>,
+        installment: i64,
+        installments: i64,
+        amount: Money,
+        due_date: NaiveDate,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id,
+            purchase_id,
+            supplier,
+            document,
+            installment,
+            installments,
+            amount,
+            due_date,
+            paid_at: None,
+            created_at,
+        }
+    }
+
+    pub fn is_paid(&self) -> bool {
+        self.paid_at.is_some()
+    }
+
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_paid() && self.due_date < today
+    }
+}
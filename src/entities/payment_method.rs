@@ -0,0 +1,71 @@
+use std::fmt::Display;
+
+/// How a sale or a supplier title was paid. Store credit ("fiado") is
+/// charged to the customer's account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum PaymentMethod {
+    #[default]
+    #[sqlx(rename = "CASH")]
+    Cash,
+    #[sqlx(rename = "CARD")]
+    Card,
+    #[sqlx(rename = "PIX")]
+    Pix,
+    #[sqlx(rename = "STORE_CREDIT")]
+    StoreCredit,
+    #[sqlx(rename = "BANK_SLIP")]
+    BankSlip,
+}
+
+impl PaymentMethod {
+    pub const ALL: [PaymentMethod; 4] = [
+        PaymentMethod::Cash,
+        PaymentMethod::Card,
+        PaymentMethod::Pix,
+        PaymentMethod::StoreCredit,
+    ];
+
+    /// Ways the shop pays its suppliers.
+    pub const PAYABLE: [PaymentMethod; 4] = [
+        PaymentMethod::BankSlip,
+        PaymentMethod::Pix,
+        PaymentMethod::Cash,
+        PaymentMethod::Card,
+    ];
+
+    /// Parses a method by its code, such as `PIX` or `STORE_CREDIT`, or by
+    /// its name, ignoring case.
+    pub fn parse(value: &str) -> Option<PaymentMethod> {
+        let value = value.trim();
+        PaymentMethod::ALL.into_iter().find(|method| {
+            method.code().eq_ignore_ascii_case(value) || method.as_str().eq_ignore_ascii_case(value)
+        })
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "CASH",
+            PaymentMethod::Card => "CARD",
+            PaymentMethod::Pix => "PIX",
+            PaymentMethod::StoreCredit => "STORE_CREDIT",
+            PaymentMethod::BankSlip => "BANK_SLIP",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "DINHEIRO",
+            PaymentMethod::Card => "CARTÃO",
+            PaymentMethod::Pix => "PIX",
+            PaymentMethod::StoreCredit => "FIADO",
+            PaymentMethod::BankSlip => "BOLETO",
+        }
+    }
+}
+
+impl Display for PaymentMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
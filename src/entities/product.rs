@@ -1,24 +1,40 @@
-use chrono::{Local, NaiveDateTime};
+use crate::entities::abc_class::AbcClass;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use chrono::NaiveDateTime;
 
 #[derive(Debug, Clone)]
 pub struct Product {
     pub id: i64,
     pub name: String,
-    pub price_sale: i64,
-    pub price_purchase: i64,
+    pub price_sale: Money,
+    pub price_purchase: Money,
     pub quantity: i64,
+    pub unit: Unit,
     pub ean: Option<String>,
+    pub category: Option<String>,
+    /// Set when an ABC curve is saved.
+    pub abc_class: Option<AbcClass>,
+    /// Stock below which the product must be bought again.
+    pub min_stock: i64,
+    /// Supplier of the last purchase.
+    pub supplier: Option<String>,
+    /// Sold as one item made of other products. Its stock is the number of
+    /// whole kits the components make.
+    pub is_kit: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: Option<NaiveDateTime>,
 }
 
 impl Product {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: i64,
         name: String,
-        price_sale: i64,
-        price_purchase: i64,
+        price_sale: Money,
+        price_purchase: Money,
         quantity: i64,
+        unit: Unit,
         ean: Option<String>,
         created_at: NaiveDateTime,
     ) -> Self {
@@ -28,7 +44,13 @@ impl Product {
             price_sale,
             price_purchase,
             quantity,
+            unit,
             ean,
+            category: None,
+            abc_class: None,
+            min_stock: 0,
+            supplier: None,
+            is_kit: false,
             created_at,
             updated_at: None,
         }
@@ -1,36 +1,105 @@
+use crate::entities::product::Product;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
 use chrono::NaiveDateTime;
 
+/// A line of a new sale: the product with the sold quantity in `quantity`.
+/// Lines read from a price label carry the printed total, which is charged
+/// as is instead of price × quantity.
+#[derive(Debug, Clone)]
+pub struct SaleItem {
+    pub product: Product,
+    pub total: Option<Money>,
+}
+
+impl SaleItem {
+    /// Total charged for the line, `None` on overflow.
+    pub fn total(&self) -> Option<Money> {
+        self.total.or_else(|| {
+            self.product
+                .price_sale
+                .checked_times_quantity(self.product.quantity)
+        })
+    }
+}
+
+impl From<Product> for SaleItem {
+    fn from(product: Product) -> Self {
+        Self {
+            product,
+            total: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProductSale {
-    pub id: i64,
     pub product_id: i64,
     pub sale_id: i64,
-    pub price: i64,
+    pub price: Money,
     pub quantity: i64,
-    pub total: i64,
+    pub total: Money,
     pub created_at: NaiveDateTime,
-    pub updated_at: Option<NaiveDateTime>,
 }
 
 impl ProductSale {
     pub fn new(
-        id: i64,
         product_id: i64,
         sale_id: i64,
-        price: i64,
+        price: Money,
         quantity: i64,
-        total: i64,
+        total: Money,
         created_at: NaiveDateTime,
     ) -> Self {
         Self {
-            id,
             product_id,
             sale_id,
             price,
             quantity,
             total,
             created_at,
-            updated_at: None,
         }
     }
 }
+
+/// A sold line with the sale date and the product name, for reports and
+/// exports.
+#[derive(Debug, Clone)]
+pub struct ProductSaleLine {
+    pub sale_id: i64,
+    pub sold_at: NaiveDateTime,
+    pub product_id: i64,
+    pub name: String,
+    pub unit: Unit,
+    pub price: Money,
+    pub quantity: i64,
+    pub total: Money,
+    /// Current purchase price of the product.
+    pub cost: Money,
+}
+
+/// A line of one sale with the product name and what was already returned.
+#[derive(Debug, Clone)]
+pub struct SoldItem {
+    pub id: i64,
+    pub product_id: i64,
+    pub name: String,
+    pub unit: Unit,
+    pub price: Money,
+    pub quantity: i64,
+    pub returned_quantity: i64,
+    pub total: Money,
+}
+
+impl SoldItem {
+    /// Quantity the customer still holds and may return.
+    pub fn remaining(&self) -> i64 {
+        self.quantity - self.returned_quantity
+    }
+
+    /// Part of the charged total for `quantity` of the line, so the returns
+    /// of a line add up to what was charged for it, label totals included.
+    pub fn value_of(&self, quantity: i64) -> Option<Money> {
+        self.total.checked_ratio(quantity, self.quantity)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::helpers::money::Money;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Clone)]
+pub struct ProductSaleReturn {
+    pub product_sale_id: i64,
+    pub quantity: i64,
+    pub total: Money,
+    pub created_at: NaiveDateTime,
+}
+
+impl ProductSaleReturn {
+    pub fn new(
+        product_sale_id: i64,
+        quantity: i64,
+        total: Money,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            product_sale_id,
+            quantity,
+            total,
+            created_at,
+        }
+    }
+}
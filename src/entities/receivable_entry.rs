@@ -0,0 +1,59 @@
+use crate::helpers::money::Money;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Why a customer's account changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum ReceivableKind {
+    /// A sale on store credit.
+    #[sqlx(rename = "CHARGE")]
+    Charge,
+    /// Money received from the customer.
+    #[sqlx(rename = "PAYMENT")]
+    Payment,
+    /// A return or void of a sale on store credit.
+    #[sqlx(rename = "CREDIT")]
+    Credit,
+}
+
+impl ReceivableKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceivableKind::Charge => "COMPRA",
+            ReceivableKind::Payment => "PAGAMENTO",
+            ReceivableKind::Credit => "ESTORNO",
+        }
+    }
+}
+
+/// A line of a customer's account. `amount` is positive for charges and
+/// negative for payments and credits. Charges fall due on `due_date`.
+#[derive(Debug, Clone)]
+pub struct ReceivableEntry {
+    pub customer_id: i64,
+    pub sale_id: Option<i64>,
+    pub kind: ReceivableKind,
+    pub amount: Money,
+    pub due_date: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+}
+
+impl ReceivableEntry {
+    pub fn new(
+        customer_id: i64,
+        sale_id: Option<i64>,
+        kind: ReceivableKind,
+        amount: Money,
+        due_date: Option<NaiveDate>,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            customer_id,
+            sale_id,
+            kind,
+            amount,
+            due_date,
+            created_at,
+        }
+    }
+}
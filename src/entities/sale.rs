@@ -1,20 +1,46 @@
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
 use chrono::NaiveDateTime;
 
 #[derive(Debug, Clone)]
 pub struct Sale {
     pub id: i64,
-    pub total: i64,
+    pub total: Money,
     pub created_at: NaiveDateTime,
-    pub updated_at: Option<NaiveDateTime>,
+    pub voided_at: Option<NaiveDateTime>,
+    pub customer_id: Option<i64>,
+    /// Part of the total paid with loyalty points.
+    pub loyalty_discount: Money,
+    pub payment_method: PaymentMethod,
 }
 
 impl Sale {
-    pub fn new(id: i64, total: i64, created_at: NaiveDateTime) -> Self {
+    pub fn new(id: i64, total: Money, created_at: NaiveDateTime) -> Self {
         Self {
             id,
             total,
             created_at,
-            updated_at: None,
+            voided_at: None,
+            customer_id: None,
+            loyalty_discount: Money::ZERO,
+            payment_method: PaymentMethod::default(),
         }
     }
+
+    /// What the customer paid besides the points.
+    pub fn amount_due(&self) -> Money {
+        self.total - self.loyalty_discount
+    }
+
+    pub fn is_voided(&self) -> bool {
+        self.voided_at.is_some()
+    }
+}
+
+/// Purchases of a customer, for the customer history.
+#[derive(Debug, Clone)]
+pub struct CustomerSummary {
+    pub sales: i64,
+    pub total_spent: Money,
+    pub last_visit: Option<NaiveDateTime>,
 }
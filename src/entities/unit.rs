@@ -0,0 +1,50 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum Unit {
+    #[default]
+    #[sqlx(rename = "UN")]
+    Un,
+    #[sqlx(rename = "KG")]
+    Kg,
+    #[sqlx(rename = "M")]
+    M,
+    #[sqlx(rename = "L")]
+    L,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 4] = [Unit::Un, Unit::Kg, Unit::M, Unit::L];
+
+    pub fn is_fractional(&self) -> bool {
+        !matches!(self, Unit::Un)
+    }
+
+    /// Products sold by weight read their quantity from the checkout scale.
+    pub fn is_weighed(&self) -> bool {
+        matches!(self, Unit::Kg)
+    }
+
+    /// Parses a unit name such as `kg` or `UN`, ignoring case.
+    pub fn parse(value: &str) -> Option<Unit> {
+        Unit::ALL
+            .into_iter()
+            .find(|unit| unit.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Un => "UN",
+            Unit::Kg => "KG",
+            Unit::M => "M",
+            Unit::L => "L",
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
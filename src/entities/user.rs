@@ -0,0 +1,18 @@
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Clone)]
+pub struct User {
+    pub username: String,
+    pub password_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl User {
+    pub fn new(username: String, password_hash: String, created_at: NaiveDateTime) -> Self {
+        Self {
+            username,
+            password_hash,
+            created_at,
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! ABC (Pareto) curve: items ranked by value, the ones making the first 80%
+//! of the total are A, up to 95% B and the rest C.
+
+use super::money::{Money, Percent};
+use crate::entities::abc_class::AbcClass;
+
+/// Cumulative share, in hundredths of a percent, up to which items are A.
+const A_LIMIT: i64 = 80_00;
+/// Cumulative share up to which items are B.
+const B_LIMIT: i64 = 95_00;
+
+/// Place of an item in the curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranked {
+    /// Position of the item in the values given.
+    pub index: usize,
+    pub share: Percent,
+    /// Share of this item and all ranked before it.
+    pub cumulative: Percent,
+    pub class: AbcClass,
+}
+
+/// Ranks `values` from the largest. An item is classified by the share of
+/// the items before it, so the one crossing 80% is still A. Items without
+/// a positive value are always C. `None` when the total overflows.
+pub fn rank(values: &[Money]) -> Option<Vec<Ranked>> {
+    let total = Money::checked_sum(values.iter().copied().filter(|value| *value > Money::ZERO))?;
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(values[index]));
+
+    let mut running = Money::ZERO;
+    let ranked = order
+        .into_iter()
+        .map(|index| {
+            let value = values[index];
+            let before = running.share_of(total).unwrap_or_default().hundredths();
+            let class = if value <= Money::ZERO {
+                AbcClass::C
+            } else if before < A_LIMIT {
+                AbcClass::A
+            } else if before < B_LIMIT {
+                AbcClass::B
+            } else {
+                AbcClass::C
+            };
+            if value > Money::ZERO {
+                running += value;
+            }
+            Ranked {
+                index,
+                share: value.share_of(total).unwrap_or_default(),
+                cumulative: running.share_of(total).unwrap_or_default(),
+                class,
+            }
+        })
+        .collect();
+    Some(ranked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(values: &[i64]) -> Vec<Money> {
+        values.iter().copied().map(Money::from_cents).collect()
+    }
+
+    #[test]
+    fn ranks_by_value_and_classifies_by_cumulative_share() {
+        let ranked = rank(&money(&[500, 7000, 1500, 300, 700])).unwrap();
+
+        let order: Vec<usize> = ranked.iter().map(|item| item.index).collect();
+        assert_eq!(order, vec![1, 2, 4, 0, 3]);
+        let classes: Vec<AbcClass> = ranked.iter().map(|item| item.class).collect();
+        assert_eq!(
+            classes,
+            vec![
+                AbcClass::A,
+                AbcClass::A,
+                AbcClass::B,
+                AbcClass::B,
+                AbcClass::C
+            ]
+        );
+        assert_eq!(ranked[0].share, Percent::parse("70").unwrap());
+        assert_eq!(ranked[1].cumulative, Percent::parse("85").unwrap());
+        assert_eq!(ranked[4].cumulative, Percent::parse("100").unwrap());
+    }
+
+    #[test]
+    fn items_without_value_are_c() {
+        let ranked = rank(&money(&[0, 1000, -200])).unwrap();
+
+        let classes: Vec<AbcClass> = ranked.iter().map(|item| item.class).collect();
+        assert_eq!(classes, vec![AbcClass::A, AbcClass::C, AbcClass::C]);
+        assert_eq!(ranked[2].share, Percent::parse("-20").unwrap());
+        assert_eq!(ranked[2].cumulative, Percent::parse("100").unwrap());
+        assert!(rank(&[]).unwrap().is_empty());
+        assert!(rank(&money(&[i64::MAX, 1])).is_none());
+    }
+}
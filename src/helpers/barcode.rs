@@ -0,0 +1,296 @@
+use crate::config::{ScaleLabelConfig, ScaleLabelValue};
+
+/// Prefix of in-store, variable-measure EAN-13 codes.
+pub const SCALE_LABEL_PREFIX: char = '2';
+/// Prefix of the EAN-13 codes generated for products without a barcode. It is
+/// part of the 040-049 range GS1 keeps for use within a company, so it never
+/// clashes with a product's own code nor reads as a scale label.
+const INTERNAL_EAN_PREFIX: &str = "04";
+const GTIN_LENGTHS: [usize; 4] = [8, 12, 13, 14];
+
+/// Check digit of a GTIN payload (all digits except the check digit).
+pub fn gtin_check_digit(payload: &str) -> Option<u32> {
+    let mut sum = 0;
+    for (position, c) in payload.chars().rev().enumerate() {
+        let digit = c.to_digit(10)?;
+        sum += if position % 2 == 0 { digit * 3 } else { digit };
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+/// Accepts a partially typed EAN: digits only, up to the 14 digits of a GTIN-14.
+pub fn validate_ean_input(value: &str) -> bool {
+    value.len() <= 14 && value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks the length (GTIN-8/12/13/14) and the check digit of a barcode.
+pub fn is_valid_gtin(code: &str) -> bool {
+    GTIN_LENGTHS.contains(&code.len())
+        && code.chars().all(|c| c.is_ascii_digit())
+        && has_valid_check_digit(code)
+}
+
+/// In-store EAN-13 for the product with the given id.
+pub fn internal_ean13(product_id: i64) -> String {
+    let payload = format!("{}{:010}", INTERNAL_EAN_PREFIX, product_id);
+    let check_digit = gtin_check_digit(&payload).unwrap_or(0);
+    format!("{}{}", payload, check_digit)
+}
+
+fn has_valid_check_digit(code: &str) -> bool {
+    let Some(last) = code.chars().last().and_then(|c| c.to_digit(10)) else {
+        return false;
+    };
+    gtin_check_digit(&code[..code.len() - 1]) == Some(last)
+}
+
+/// Value read from a scale label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaleLabel {
+    /// Internal product code.
+    pub code: i64,
+    pub value: ScaleLabelValue,
+    /// Total in centavos or weight in grams, depending on `value`.
+    pub amount: i64,
+}
+
+/// Decodes a variable-measure EAN-13 printed by the scale. Returns `None`
+/// for codes that do not follow `layout` or have a wrong check digit, either
+/// the EAN one or the one of the value.
+pub fn decode_scale_label(code: &str, layout: &ScaleLabelConfig) -> Option<ScaleLabel> {
+    if code.len() != 13
+        || !code.starts_with(SCALE_LABEL_PREFIX)
+        || !code.chars().all(|c| c.is_ascii_digit())
+        || !has_valid_check_digit(code)
+    {
+        return None;
+    }
+    let code_start = 1;
+    let value_start = code_start + layout.code_length + usize::from(layout.value_check_digit);
+    let value_end = value_start + layout.value_length();
+    if layout.value_check_digit {
+        let check_digit = code[value_start - 1..value_start].parse().ok();
+        if value_check_digit(&code[value_start..value_end]) != check_digit {
+            return None;
+        }
+    }
+    Some(ScaleLabel {
+        code: code[code_start..code_start + layout.code_length]
+            .parse()
+            .ok()?,
+        value: layout.value,
+        amount: code[value_start..value_end].parse().ok()?,
+    })
+}
+
+/// Weighted digits of the GS1 price check digit: `2-` takes the units of
+/// 2 × d less its tens, `3` the units of 3 × d, and `5+`/`5-` the units of
+/// 5 × d plus or less its tens.
+const WEIGHT_2_MINUS: [u32; 10] = [0, 2, 4, 6, 8, 9, 1, 3, 5, 7];
+const WEIGHT_3: [u32; 10] = [0, 3, 6, 9, 2, 5, 8, 1, 4, 7];
+const WEIGHT_5_PLUS: [u32; 10] = [0, 5, 1, 6, 2, 7, 3, 8, 4, 9];
+const WEIGHT_5_MINUS: [u32; 10] = [0, 5, 9, 4, 8, 3, 7, 2, 6, 1];
+
+/// Check digit of the 4 or 5 digit value of a scale label, as defined by GS1
+/// for variable-measure prices. `None` for other lengths.
+pub fn value_check_digit(value: &str) -> Option<u32> {
+    let digits: Vec<usize> = value
+        .chars()
+        .map(|c| c.to_digit(10).map(|d| d as usize))
+        .collect::<Option<_>>()?;
+    match digits[..] {
+        [a, b, c, d] => {
+            Some((WEIGHT_2_MINUS[a] + WEIGHT_2_MINUS[b] + WEIGHT_3[c] + WEIGHT_5_MINUS[d]) * 3 % 10)
+        }
+        [a, b, c, d, e] => {
+            let sum = WEIGHT_5_PLUS[a]
+                + WEIGHT_2_MINUS[b]
+                + WEIGHT_5_MINUS[c]
+                + WEIGHT_5_PLUS[d]
+                + WEIGHT_2_MINUS[e];
+            // The check digit is the one whose `5-` weight completes the ten
+            let missing = (10 - sum % 10) % 10;
+            WEIGHT_5_MINUS
+                .iter()
+                .position(|weight| *weight == missing)
+                .map(|digit| digit as u32)
+        }
+        _ => None,
+    }
+}
+
+const L_CODES: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+const G_CODES: [&str; 10] = [
+    "0100111", "0110011", "0011011", "0100001", "0011101", "0111001", "0000101", "0010001",
+    "0001001", "0010111",
+];
+const R_CODES: [&str; 10] = [
+    "1110010", "1100110", "1101100", "1000010", "1011100", "1001110", "1010000", "1000100",
+    "1001000", "1110100",
+];
+/// Left half parity (L or G) of an EAN-13, chosen by its first digit.
+const EAN13_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// Bar modules (true = black) of an EAN-13, UPC-A or EAN-8 code, for drawing
+/// it on labels. GTIN-14 codes have no EAN symbol and return `None`.
+pub fn ean_modules(code: &str) -> Option<Vec<bool>> {
+    if !is_valid_gtin(code) {
+        return None;
+    }
+    let code = if code.len() == 12 {
+        format!("0{}", code)
+    } else {
+        code.to_string()
+    };
+    let digits: Vec<usize> = code
+        .chars()
+        .map(|c| c.to_digit(10).map(|d| d as usize))
+        .collect::<Option<_>>()?;
+    let mut pattern = String::from("101");
+    match digits.len() {
+        13 => {
+            let parity = EAN13_PARITY[digits[0]];
+            for (digit, parity) in digits[1..7].iter().zip(parity.chars()) {
+                pattern.push_str(if parity == 'L' {
+                    L_CODES[*digit]
+                } else {
+                    G_CODES[*digit]
+                });
+            }
+            pattern.push_str("01010");
+            digits[7..]
+                .iter()
+                .for_each(|d| pattern.push_str(R_CODES[*d]));
+        }
+        8 => {
+            digits[..4]
+                .iter()
+                .for_each(|d| pattern.push_str(L_CODES[*d]));
+            pattern.push_str("01010");
+            digits[4..]
+                .iter()
+                .for_each(|d| pattern.push_str(R_CODES[*d]));
+        }
+        _ => return None,
+    }
+    pattern.push_str("101");
+    Some(pattern.chars().map(|c| c == '1').collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends the EAN check digit to the first 12 digits.
+    fn ean13(payload: &str) -> String {
+        format!("{}{}", payload, gtin_check_digit(payload).unwrap())
+    }
+
+    fn layout(
+        code_length: usize,
+        value: ScaleLabelValue,
+        value_check_digit: bool,
+    ) -> ScaleLabelConfig {
+        ScaleLabelConfig {
+            code_length,
+            value,
+            value_check_digit,
+        }
+    }
+
+    #[test]
+    fn decodes_price_and_weight_labels() {
+        let price = layout(5, ScaleLabelValue::Price, false);
+        assert_eq!(
+            decode_scale_label(&ean13("200123001234"), &price),
+            Some(ScaleLabel {
+                code: 123,
+                value: ScaleLabelValue::Price,
+                amount: 1234,
+            })
+        );
+        let weight = layout(4, ScaleLabelValue::Weight, false);
+        assert_eq!(
+            decode_scale_label(&ean13("200120001250"), &weight),
+            Some(ScaleLabel {
+                code: 12,
+                value: ScaleLabelValue::Weight,
+                amount: 1250,
+            })
+        );
+    }
+
+    #[test]
+    fn verifies_the_value_check_digit() {
+        assert_eq!(value_check_digit("2875"), Some(9));
+        assert_eq!(value_check_digit("14685"), Some(6));
+        assert_eq!(value_check_digit("123456"), None);
+
+        let five_digits = layout(5, ScaleLabelValue::Price, true);
+        assert_eq!(
+            decode_scale_label(&ean13("200123101234"), &five_digits).map(|l| (l.code, l.amount)),
+            Some((123, 1234))
+        );
+        assert_eq!(
+            decode_scale_label(&ean13("200123901234"), &five_digits),
+            None
+        );
+        let four_digits = layout(6, ScaleLabelValue::Weight, true);
+        assert_eq!(
+            decode_scale_label(&ean13("200123491234"), &four_digits).map(|l| (l.code, l.amount)),
+            Some((1234, 1234))
+        );
+        assert_eq!(
+            decode_scale_label(&ean13("200123401234"), &four_digits),
+            None
+        );
+        // Without a value check digit, that position is part of the value
+        let without = layout(5, ScaleLabelValue::Price, false);
+        assert_eq!(
+            decode_scale_label(&ean13("200123901234"), &without).map(|l| (l.code, l.amount)),
+            Some((123, 901234))
+        );
+    }
+
+    #[test]
+    fn rejects_other_codes() {
+        let price = layout(5, ScaleLabelValue::Price, false);
+        let code = ean13("200123001234");
+        let wrong_check_digit = format!("{}{}", &code[..12], (code.as_bytes()[12] - b'0' + 1) % 10);
+        assert_eq!(decode_scale_label(&wrong_check_digit, &price), None);
+        // Not in the in-store range, or not 13 digits long
+        assert_eq!(decode_scale_label(&ean13("789123001234"), &price), None);
+        assert_eq!(decode_scale_label(&code[..12], &price), None);
+        assert_eq!(decode_scale_label(&format!("{}0", code), &price), None);
+    }
+
+    #[test]
+    fn validates_gtin_lengths_and_check_digits() {
+        assert!(is_valid_gtin("7891000315507"));
+        assert!(is_valid_gtin("96385074"));
+        assert!(is_valid_gtin("036000291452"));
+        assert!(is_valid_gtin("17891000315504"));
+        assert!(!is_valid_gtin("7891000315508"));
+        assert!(!is_valid_gtin("789100031550"));
+        assert!(!is_valid_gtin("78910003155O7"));
+        assert!(!is_valid_gtin(""));
+    }
+
+    #[test]
+    fn internal_ean13_is_a_valid_in_store_code() {
+        let code = internal_ean13(42);
+        assert_eq!(code, ean13("040000000042"));
+        assert!(is_valid_gtin(&code));
+        assert_eq!(
+            decode_scale_label(&code, &ScaleLabelConfig::default()),
+            None
+        );
+        assert_ne!(internal_ean13(43), code);
+    }
+}
@@ -0,0 +1,104 @@
+/// Keeps only the digits of a formatted document, such as `12.345.678/0001-95`.
+pub fn only_digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Checks the two CNPJ check digits. Punctuation is ignored.
+pub fn is_valid_cnpj(value: &str) -> bool {
+    let digits: Vec<u32> = only_digits(value)
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    if digits.len() != 14 || digits.iter().all(|d| *d == digits[0]) {
+        return false;
+    }
+
+    let check_digit = |length: usize| {
+        let sum: u32 = digits[..length]
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, digit)| digit * (2 + (i as u32 % 8)))
+            .sum();
+        match sum % 11 {
+            0 | 1 => 0,
+            rest => 11 - rest,
+        }
+    };
+    check_digit(12) == digits[12] && check_digit(13) == digits[13]
+}
+
+/// Checks the two CPF check digits. Punctuation is ignored.
+pub fn is_valid_cpf(value: &str) -> bool {
+    let digits: Vec<u32> = only_digits(value)
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    if digits.len() != 11 || digits.iter().all(|d| *d == digits[0]) {
+        return false;
+    }
+
+    let check_digit = |length: usize| {
+        let sum: u32 = digits[..length]
+            .iter()
+            .enumerate()
+            .map(|(i, digit)| digit * (length as u32 + 1 - i as u32))
+            .sum();
+        match sum % 11 {
+            0 | 1 => 0,
+            rest => 11 - rest,
+        }
+    };
+    check_digit(9) == digits[9] && check_digit(10) == digits[10]
+}
+
+/// Writes the 11 digits of a CPF as `123.456.789-09`.
+pub fn format_cpf(digits: &str) -> String {
+    if digits.len() != 11 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return digits.to_string();
+    }
+    format!(
+        "{}.{}.{}-{}",
+        &digits[..3],
+        &digits[3..6],
+        &digits[6..9],
+        &digits[9..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_cpf_check_digits() {
+        assert!(is_valid_cpf("529.982.247-25"));
+        assert!(is_valid_cpf("52998224725"));
+        assert!(is_valid_cpf("123.456.789-09"));
+        assert!(!is_valid_cpf("529.982.247-24"));
+        assert!(!is_valid_cpf("529.982.247-15"));
+        assert!(!is_valid_cpf("111.111.111-11"));
+        assert!(!is_valid_cpf("00000000000"));
+        assert!(!is_valid_cpf("5299822472"));
+        assert!(!is_valid_cpf(""));
+    }
+
+    #[test]
+    fn validates_cnpj_check_digits() {
+        assert!(is_valid_cnpj("11.222.333/0001-81"));
+        assert!(is_valid_cnpj("11222333000181"));
+        assert!(is_valid_cnpj("12.345.678/0001-95"));
+        assert!(!is_valid_cnpj("11.222.333/0001-82"));
+        assert!(!is_valid_cnpj("11.222.333/0001-91"));
+        assert!(!is_valid_cnpj("11.111.111/1111-11"));
+        assert!(!is_valid_cnpj("1122233300018"));
+    }
+
+    #[test]
+    fn formats_only_whole_cpfs() {
+        assert_eq!(format_cpf("52998224725"), "529.982.247-25");
+        assert_eq!(format_cpf("5299822472"), "5299822472");
+        assert_eq!(format_cpf("529.982.247-25"), "529.982.247-25");
+        assert_eq!(only_digits("12.345.678/0001-95"), "12345678000195");
+    }
+}
@@ -0,0 +1,221 @@
+//! Loyalty points balance. Earned and refunded points form lots that expire
+//! on their own date, and redeemed or reversed points are taken from the
+//! oldest lots first.
+
+use super::fold_accents;
+use super::money::Money;
+use crate::config::LoyaltyConfig;
+use crate::entities::loyalty_entry::LoyaltyEntry;
+use chrono::{Months, NaiveDateTime};
+use std::collections::VecDeque;
+
+/// Points earned on a sale where `paid` was paid without points and `lines`
+/// are the category and total of each sold line. Fractions of a point are
+/// dropped.
+pub fn points_earned<'a>(
+    config: &LoyaltyConfig,
+    paid: Money,
+    lines: impl IntoIterator<Item = (Option<&'a str>, Money)>,
+) -> i64 {
+    if !config.enabled {
+        return 0;
+    }
+    let points = |value: Money, per_real: u32| {
+        (i128::from(value.cents().max(0)) * i128::from(per_real) / 100) as i64
+    };
+    let bonus: i64 = lines
+        .into_iter()
+        .map(|(category, total)| points(total, bonus(config, category)))
+        .sum();
+    points(paid, config.points_per_real) + bonus
+}
+
+/// Extra points per real of a category, which is compared ignoring case and
+/// accents.
+pub fn bonus(config: &LoyaltyConfig, category: Option<&str>) -> u32 {
+    let Some(category) = category else {
+        return 0;
+    };
+    let key = |value: &str| fold_accents(value.trim()).to_uppercase();
+    let category = key(category);
+    config
+        .category_bonus
+        .iter()
+        .find(|(name, _)| key(name) == category)
+        .map(|(_, bonus)| *bonus)
+        .unwrap_or(0)
+}
+
+/// When points earned at `earned_at` expire.
+pub fn expires_at(config: &LoyaltyConfig, earned_at: NaiveDateTime) -> Option<NaiveDateTime> {
+    if config.expiration_months == 0 {
+        return None;
+    }
+    earned_at.checked_add_months(Months::new(config.expiration_months))
+}
+
+/// Value of `points` when used to pay.
+pub fn value(config: &LoyaltyConfig, points: i64) -> Money {
+    Money::from_cents(points.saturating_mul(config.point_value))
+}
+
+/// Most points that can be used to pay `value`.
+pub fn points_for(config: &LoyaltyConfig, value: Money) -> i64 {
+    if config.point_value <= 0 {
+        return 0;
+    }
+    value.cents().max(0) / config.point_value
+}
+
+/// Points a customer can use at `now`. `entries` must be oldest first. It is
+/// negative when more points were reversed than there were left.
+pub fn balance(entries: &[LoyaltyEntry], now: NaiveDateTime) -> i64 {
+    let mut lots: VecDeque<(i64, Option<NaiveDateTime>)> = VecDeque::new();
+    // Points taken when no lot was left, paid by the next points earned
+    let mut debt = 0;
+    for entry in entries {
+        lots.retain(|(_, expires_at)| is_valid(*expires_at, entry.created_at));
+        if entry.points >= 0 {
+            let paid = debt.min(entry.points);
+            debt -= paid;
+            if entry.points > paid {
+                lots.push_back((entry.points - paid, entry.expires_at));
+            }
+            continue;
+        }
+        let mut taken = -entry.points;
+        while taken > 0 {
+            let Some((points, _)) = lots.front_mut() else {
+                debt += taken;
+                break;
+            };
+            let used = taken.min(*points);
+            *points -= used;
+            taken -= used;
+            if *points == 0 {
+                lots.pop_front();
+            }
+        }
+    }
+    lots.iter()
+        .filter(|(_, expires_at)| is_valid(*expires_at, now))
+        .map(|(points, _)| points)
+        .sum::<i64>()
+        - debt
+}
+
+/// Part of `points` matching `part` of `whole`, rounded down.
+pub fn share(points: i64, part: Money, whole: Money) -> i64 {
+    if whole.cents() <= 0 {
+        return 0;
+    }
+    let part = part.cents().clamp(0, whole.cents());
+    (i128::from(points) * i128::from(part) / i128::from(whole.cents())) as i64
+}
+
+fn is_valid(expires_at: Option<NaiveDateTime>, at: NaiveDateTime) -> bool {
+    expires_at.is_none_or(|expires_at| expires_at > at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::loyalty_entry::LoyaltyKind;
+    use chrono::{Days, NaiveDate};
+
+    fn day(day: u64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .checked_add_days(Days::new(day))
+            .unwrap()
+    }
+
+    fn entry(kind: LoyaltyKind, points: i64, at: u64, expires: Option<u64>) -> LoyaltyEntry {
+        LoyaltyEntry::new(1, None, kind, points, expires.map(day), day(at))
+    }
+
+    #[test]
+    fn expired_lots_leave_the_balance() {
+        let entries = [
+            entry(LoyaltyKind::Earn, 100, 0, Some(30)),
+            entry(LoyaltyKind::Earn, 50, 10, Some(40)),
+            entry(LoyaltyKind::Earn, 20, 20, None),
+        ];
+        assert_eq!(balance(&entries, day(25)), 170);
+        assert_eq!(balance(&entries, day(35)), 70);
+        assert_eq!(balance(&entries, day(45)), 20);
+    }
+
+    #[test]
+    fn redeemed_points_come_from_the_oldest_lots() {
+        let entries = [
+            entry(LoyaltyKind::Earn, 100, 0, Some(30)),
+            entry(LoyaltyKind::Earn, 50, 10, Some(40)),
+            entry(LoyaltyKind::Redeem, -120, 15, None),
+        ];
+        assert_eq!(balance(&entries, day(20)), 30);
+        // The first lot was used up, so nothing is lost when it expires
+        assert_eq!(balance(&entries, day(35)), 30);
+        assert_eq!(balance(&entries, day(45)), 0);
+    }
+
+    #[test]
+    fn reversals_beyond_the_balance_are_paid_by_later_points() {
+        let entries = [
+            entry(LoyaltyKind::Earn, 100, 0, None),
+            entry(LoyaltyKind::Redeem, -100, 1, None),
+            entry(LoyaltyKind::Reversal, -40, 2, None),
+        ];
+        assert_eq!(balance(&entries, day(3)), -40);
+        let mut entries = entries.to_vec();
+        entries.push(entry(LoyaltyKind::Earn, 60, 4, None));
+        assert_eq!(balance(&entries, day(5)), 20);
+    }
+
+    #[test]
+    fn share_rounds_down() {
+        let whole = Money::from_cents(3000);
+        assert_eq!(share(100, Money::from_cents(1000), whole), 33);
+        assert_eq!(share(100, whole, whole), 100);
+        assert_eq!(share(100, Money::from_cents(5000), whole), 100);
+        assert_eq!(share(100, whole, Money::ZERO), 0);
+    }
+
+    #[test]
+    fn categories_earn_bonus_points_ignoring_case_and_accents() {
+        let config = LoyaltyConfig {
+            enabled: true,
+            points_per_real: 1,
+            category_bonus: [("Açougue".to_string(), 2)].into(),
+            ..LoyaltyConfig::default()
+        };
+        let lines = [
+            (Some("ACOUGUE"), Money::from_cents(1050)),
+            (Some("LIMPEZA"), Money::from_cents(500)),
+            (None, Money::from_cents(300)),
+        ];
+        // 18,50 paid plus 2 points per real on 10,50 of meat
+        assert_eq!(
+            points_earned(&config, Money::from_cents(1850), lines),
+            18 + 21
+        );
+        let disabled = LoyaltyConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(points_earned(&disabled, Money::from_cents(1850), lines), 0);
+    }
+
+    #[test]
+    fn points_pay_up_to_the_value_of_the_sale() {
+        let config = LoyaltyConfig {
+            point_value: 5,
+            ..LoyaltyConfig::default()
+        };
+        assert_eq!(value(&config, 40), Money::from_cents(200));
+        assert_eq!(points_for(&config, Money::from_cents(212)), 42);
+        assert_eq!(points_for(&config, Money::from_cents(-100)), 0);
+    }
+}
@@ -1,40 +1,178 @@
-const ONE_VALUE_I64: i64 = 100;
-const ONE_VALUE_F64: f64 = 100.0;
+pub mod abc;
+pub mod barcode;
+pub mod document;
+pub mod loyalty;
+pub mod money;
+pub mod payable;
+pub mod receivable;
+pub mod replenishment;
+pub mod search;
 
-pub fn format_int_to_decimal(valor: i64) -> String {
-    let part_int = valor / ONE_VALUE_I64;
-    let part_float = (valor % ONE_VALUE_I64).abs();
-    format!("R$ {},{:02}", part_int, part_float)
-}
+use crate::entities::unit::Unit;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use money::Money;
+
+/// Quantities are stored as integers with 3 fixed decimal places.
+pub const QUANTITY_SCALE: i64 = 1000;
+const QUANTITY_DECIMALS: usize = 3;
 
-pub fn validate_float(value: &str) -> bool {
+/// Checks a quantity while it is being typed: fractional units accept up to
+/// 3 decimal places, "UN" only whole numbers.
+pub fn validate_quantity(value: &str, unit: Unit) -> bool {
     if value.is_empty() {
         return true;
     }
-    value.replace(",", ".").parse::<f64>().is_ok()
+    let (int_part, dec_part) = match value.split_once([',', '.']) {
+        Some((int_part, dec_part)) => (int_part, Some(dec_part)),
+        None => (value, None),
+    };
+    if !int_part.chars().all(|c| c.is_ascii_digit()) || int_part.len() > 9 {
+        return false;
+    }
+    match dec_part {
+        None => true,
+        Some(dec_part) => {
+            unit.is_fractional()
+                && dec_part.len() <= QUANTITY_DECIMALS
+                && dec_part.chars().all(|c| c.is_ascii_digit())
+        }
+    }
 }
 
-pub fn validate_float_range(value: &str, min: f64, max: f64) -> bool {
-    if value.is_empty() {
-        return true;
+/// Parses a typed quantity into its fixed point representation.
+pub fn parse_quantity(value: &str, unit: Unit) -> Option<i64> {
+    if value.is_empty() || !validate_quantity(value, unit) {
+        return None;
     }
-    if let Ok(v) = value.replace(",", ".").parse::<f64>() {
-        return v >= min && v <= max;
+    let (int_part, dec_part) = value.split_once([',', '.']).unwrap_or((value, ""));
+    let int_value = if int_part.is_empty() {
+        0
+    } else {
+        int_part.parse::<i64>().ok()?
+    };
+    let dec_value = format!("{:0<width$}", dec_part, width = QUANTITY_DECIMALS)
+        .parse::<i64>()
+        .ok()?;
+    Some(int_value * QUANTITY_SCALE + dec_value)
+}
+
+/// Formats a fixed point quantity, without decimals for "UN" products.
+pub fn format_quantity(value: i64, unit: Unit) -> String {
+    if unit.is_fractional() {
+        let sign = if value < 0 { "-" } else { "" };
+        format!(
+            "{}{},{:03}",
+            sign,
+            (value / QUANTITY_SCALE).abs(),
+            (value % QUANTITY_SCALE).abs()
+        )
+    } else {
+        (value / QUANTITY_SCALE).to_string()
     }
-    false
 }
 
-pub fn validate_int(value: &str) -> bool {
-    if value.is_empty() {
-        return true;
+/// Fixed point quantity that, at `price` per unit, adds up to `total`.
+/// Picks the closest quantity when the exact total cannot be reached.
+pub fn quantity_for_total(price: Money, total: Money) -> i64 {
+    if price <= Money::ZERO {
+        return 0;
     }
-    value.parse::<i32>().is_ok()
+    let cents = i128::from(price.cents());
+    let quantity = (i128::from(total.cents()) * i128::from(QUANTITY_SCALE) + cents / 2) / cents;
+    let quantity = i64::try_from(quantity).unwrap_or(i64::MAX);
+    [quantity, quantity - 1, quantity + 1]
+        .into_iter()
+        .find(|&candidate| price.checked_times_quantity(candidate) == Some(total))
+        .unwrap_or(quantity)
+}
+
+/// Records are stamped in UTC. This is the UTC instant at which the local
+/// day `date` starts, to filter records by the day seen by the operator.
+pub fn local_day_start(date: NaiveDate) -> NaiveDateTime {
+    let midnight = date.and_time(chrono::NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|start| start.naive_utc())
+        .unwrap_or(midnight)
+}
+
+/// Converts a UTC timestamp from the database to local time for display.
+pub fn to_local(value: NaiveDateTime) -> NaiveDateTime {
+    Utc.from_utc_datetime(&value)
+        .with_timezone(&Local)
+        .naive_local()
 }
 
-pub fn f64_to_i64(value: f64) -> i64 {
-    (value * ONE_VALUE_F64).round() as i64
+/// Replaces Portuguese accented letters with their plain ASCII counterpart.
+pub fn fold_accents(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'ç' => 'c',
+            'Ç' => 'C',
+            'ñ' => 'n',
+            'Ñ' => 'N',
+            c => c,
+        })
+        .collect()
 }
 
-pub fn i64_to_f64(value: i64) -> f64 {
-    (value as f64) / ONE_VALUE_F64
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_quantity_reads_the_comma_and_pads_the_decimals() {
+        assert_eq!(parse_quantity("1,5", Unit::Kg), Some(1500));
+        assert_eq!(parse_quantity("0,125", Unit::Kg), Some(125));
+        assert_eq!(parse_quantity(",75", Unit::L), Some(750));
+        assert_eq!(parse_quantity("2.05", Unit::M), Some(2050));
+        assert_eq!(parse_quantity("3", Unit::Un), Some(3000));
+        assert_eq!(parse_quantity("3,", Unit::Kg), Some(3000));
+    }
+
+    #[test]
+    fn quantities_with_decimals_are_rejected_for_units_and_past_3_places() {
+        assert_eq!(parse_quantity("1,5", Unit::Un), None);
+        assert_eq!(parse_quantity("1,2345", Unit::Kg), None);
+        assert_eq!(parse_quantity("", Unit::Kg), None);
+        assert_eq!(parse_quantity("-1", Unit::Kg), None);
+        assert_eq!(parse_quantity("1,5,0", Unit::Kg), None);
+        assert_eq!(parse_quantity("1 kg", Unit::Kg), None);
+        assert_eq!(parse_quantity("1234567890", Unit::Un), None);
+        // Still typing
+        assert!(validate_quantity("", Unit::Un));
+        assert!(validate_quantity("1,", Unit::Kg));
+        assert!(!validate_quantity("1,", Unit::Un));
+    }
+
+    #[test]
+    fn format_quantity_shows_3_decimals_only_for_fractional_units() {
+        assert_eq!(format_quantity(1500, Unit::Kg), "1,500");
+        assert_eq!(format_quantity(5, Unit::L), "0,005");
+        assert_eq!(format_quantity(-250, Unit::Kg), "-0,250");
+        assert_eq!(format_quantity(3000, Unit::Un), "3");
+        // A fraction of a unit is cut, never rounded up
+        assert_eq!(format_quantity(2999, Unit::Un), "2");
+    }
+
+    #[test]
+    fn quantity_for_total_rounds_to_the_closest_gram() {
+        let price = Money::from_cents(3999);
+        assert_eq!(quantity_for_total(price, Money::from_cents(3999)), 1000);
+        // 12,34 / 39,99 = 0,30857...
+        assert_eq!(quantity_for_total(price, Money::from_cents(1234)), 309);
+        assert_eq!(quantity_for_total(Money::ZERO, Money::from_cents(1234)), 0);
+    }
 }
@@ -0,0 +1,486 @@
+use crate::helpers::QUANTITY_SCALE;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Neg, Sub};
+
+const CENTS_PER_UNIT: i64 = 100;
+/// Percentages are kept in hundredths of a percent, so 100% is 10 000.
+const PERCENT_SCALE: i64 = 10_000;
+
+/// An amount of money in centavos. Arithmetic is done on integers only, and
+/// every operation that divides rounds half-up (away from zero).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Self(cents)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    pub fn saturating_add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+
+    /// Adds up the amounts, failing on overflow instead of wrapping.
+    pub fn checked_sum(values: impl IntoIterator<Item = Money>) -> Option<Money> {
+        values
+            .into_iter()
+            .try_fold(Money::ZERO, |total, value| total.checked_add(value))
+    }
+
+    /// Price of `quantity` units at this unit price, with the quantity in
+    /// fixed point (see [`QUANTITY_SCALE`]).
+    pub fn checked_times_quantity(self, quantity: i64) -> Option<Money> {
+        let value = div_round_half_up(
+            i128::from(self.0) * i128::from(quantity),
+            i128::from(QUANTITY_SCALE),
+        );
+        i64::try_from(value).ok().map(Money)
+    }
+
+    /// Same as [`Money::checked_times_quantity`], clamped to the `i64` range.
+    /// Meant for on-screen previews; services use the checked version.
+    pub fn saturating_times_quantity(self, quantity: i64) -> Money {
+        self.checked_times_quantity(quantity).unwrap_or_else(|| {
+            if self.is_negative() != (quantity < 0) {
+                Money(i64::MIN)
+            } else {
+                Money(i64::MAX)
+            }
+        })
+    }
+
+    /// This amount scaled by `part / whole`, such as the value of part of a
+    /// sold quantity. `None` when `whole` is zero or on overflow.
+    pub fn checked_ratio(self, part: i64, whole: i64) -> Option<Money> {
+        if whole == 0 {
+            return None;
+        }
+        let value = div_round_half_up(i128::from(self.0) * i128::from(part), i128::from(whole));
+        i64::try_from(value).ok().map(Money)
+    }
+
+    /// `percent` of this amount.
+    pub fn checked_percentage(self, percent: Percent) -> Option<Money> {
+        let value = div_round_half_up(
+            i128::from(self.0) * i128::from(percent.0),
+            i128::from(PERCENT_SCALE),
+        );
+        i64::try_from(value).ok().map(Money)
+    }
+
+    /// This amount increased by `percent`, as a sale price from its cost and
+    /// markup.
+    pub fn checked_with_markup(self, percent: Percent) -> Option<Money> {
+        self.checked_add(self.checked_percentage(percent)?)
+    }
+
+    /// Markup of this sale price over `cost`, `None` when the cost is zero.
+    pub fn markup_over(self, cost: Money) -> Option<Percent> {
+        if cost.0 == 0 {
+            return None;
+        }
+        let value = div_round_half_up(
+            (i128::from(self.0) - i128::from(cost.0)) * i128::from(PERCENT_SCALE),
+            i128::from(cost.0),
+        );
+        i64::try_from(value).ok().map(Percent)
+    }
+
+    /// Share of `whole` this amount is, `None` when `whole` is zero.
+    pub fn share_of(self, whole: Money) -> Option<Percent> {
+        if whole.0 == 0 {
+            return None;
+        }
+        let value = div_round_half_up(
+            i128::from(self.0) * i128::from(PERCENT_SCALE),
+            i128::from(whole.0),
+        );
+        i64::try_from(value).ok().map(Percent)
+    }
+
+    /// Parses a pt-BR amount such as `1.234,56`, `R$ 10,5` or `-0,50`. A
+    /// single `.` followed by one or two digits is also read as the decimal
+    /// separator, so `10.50` is ten reais and fifty centavos.
+    pub fn parse(value: &str) -> Option<Money> {
+        let value = value.trim();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, value),
+        };
+        let value = value.strip_prefix("R$").unwrap_or(value).trim_start();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(rest) if !negative => (true, rest),
+            Some(_) => return None,
+            None => (negative, value),
+        };
+
+        let (int_part, dec_part) = match value.split_once(',') {
+            Some(parts) => parts,
+            None => match value.rsplit_once('.') {
+                Some((int_part, dec_part))
+                    if (1..=2).contains(&dec_part.len()) && !int_part.contains('.') =>
+                {
+                    (int_part, dec_part)
+                }
+                _ => (value, ""),
+            },
+        };
+        if dec_part.len() > 2 || !dec_part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        // `,5` has no whole units, but a blank amount is not zero
+        let units = match int_part {
+            "" if !dec_part.is_empty() => 0,
+            int_part => parse_grouped(int_part)?,
+        };
+        let cents = format!("{:0<2}", dec_part).parse::<i64>().ok()?;
+        let value = units.checked_mul(CENTS_PER_UNIT)?.checked_add(cents)?;
+        Some(Money(if negative { -value } else { value }))
+    }
+
+    /// Whether `value` can still become a valid amount as the operator keeps
+    /// typing, such as `12,` or `1.234,5`.
+    pub fn validate_input(value: &str) -> bool {
+        let value = value.trim();
+        value.is_empty()
+            || value == "-"
+            || Money::parse(value).is_some()
+            || Money::parse(&format!("{}0", value)).is_some()
+    }
+
+    /// The amount without the currency symbol, such as `1.234,56`.
+    pub fn to_decimal_string(self) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        let units = value / CENTS_PER_UNIT as u64;
+        let cents = value % CENTS_PER_UNIT as u64;
+        format!("{}{},{:02}", sign, group_thousands(units), cents)
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self.to_decimal_string();
+        match value.strip_prefix('-') {
+            Some(value) => write!(f, "-R$ {}", value),
+            None => write!(f, "R$ {}", value),
+        }
+    }
+}
+
+// The operators saturate at the limits of `i64`, so they never panic. Sums
+// that must report an overflow go through the `checked_*` methods.
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.saturating_neg())
+    }
+}
+
+/// A percentage with two decimal places, such as a markup of `30,5%`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Percent(i64);
+
+impl Percent {
+    pub const fn hundredths(self) -> i64 {
+        self.0
+    }
+
+    /// Converts a percentage read from the configuration file.
+    pub fn from_f64(value: f64) -> Self {
+        Self((value * 100.0).round() as i64)
+    }
+
+    /// Parses `30`, `30,5` or `-12,75`, with up to two decimal places.
+    pub fn parse(value: &str) -> Option<Percent> {
+        let value = value.trim();
+        let value = value.strip_suffix('%').unwrap_or(value).trim_end();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (int_part, dec_part) = value.split_once([',', '.']).unwrap_or((value, ""));
+        if int_part.is_empty()
+            || int_part.len() > 9
+            || dec_part.len() > 2
+            || !int_part.chars().all(|c| c.is_ascii_digit())
+            || !dec_part.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let value = int_part.parse::<i64>().ok()? * 100
+            + format!("{:0<2}", dec_part).parse::<i64>().ok()?;
+        Some(Percent(if negative { -value } else { value }))
+    }
+
+    /// Whether `value` can still become a valid percentage as the operator
+    /// keeps typing.
+    pub fn validate_input(value: &str) -> bool {
+        let value = value.trim();
+        value.is_empty()
+            || value == "-"
+            || Percent::parse(value).is_some()
+            || Percent::parse(&format!("{}0", value)).is_some()
+    }
+}
+
+impl Display for Percent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(f, "{}{},{:02}", sign, value / 100, value % 100)
+    }
+}
+
+/// Integer division of `numerator` by a positive `denominator`, rounding
+/// halves away from zero.
+fn div_round_half_up(numerator: i128, denominator: i128) -> i128 {
+    let half = denominator / 2;
+    if numerator >= 0 {
+        (numerator + half) / denominator
+    } else {
+        (numerator - half) / denominator
+    }
+}
+
+/// Parses whole units written either as plain digits or grouped by `.` in
+/// thousands (`1.234.567`).
+fn parse_grouped(value: &str) -> Option<i64> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut groups = value.split('.');
+    let first = groups.next()?;
+    let mut digits = first.to_string();
+    let grouped = value.contains('.');
+    if first.is_empty() || (grouped && first.len() > 3) {
+        return None;
+    }
+    for group in groups {
+        if group.len() != 3 {
+            return None;
+        }
+        digits.push_str(group);
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn formats_pt_br_currency() {
+        assert_eq!(Money::from_cents(0).to_string(), "R$ 0,00");
+        assert_eq!(Money::from_cents(5).to_string(), "R$ 0,05");
+        assert_eq!(Money::from_cents(-50).to_string(), "-R$ 0,50");
+        assert_eq!(
+            Money::from_cents(123_456_789).to_string(),
+            "R$ 1.234.567,89"
+        );
+        assert_eq!(Money::from_cents(-100_000).to_string(), "-R$ 1.000,00");
+    }
+
+    #[test]
+    fn parses_pt_br_currency() {
+        assert_eq!(Money::parse("1.234,56"), Some(Money::from_cents(123_456)));
+        assert_eq!(Money::parse("R$ 10,5"), Some(Money::from_cents(1050)));
+        assert_eq!(Money::parse("-R$ 0,50"), Some(Money::from_cents(-50)));
+        assert_eq!(Money::parse("10.50"), Some(Money::from_cents(1050)));
+        assert_eq!(Money::parse("1.234"), Some(Money::from_cents(123_400)));
+        assert_eq!(Money::parse(",5"), Some(Money::from_cents(50)));
+        assert_eq!(Money::parse("12,345"), None);
+        assert_eq!(Money::parse("1.23.4"), None);
+        assert_eq!(Money::parse("abc"), None);
+        assert_eq!(Money::parse(""), None);
+        assert_eq!(Money::parse("R$"), None);
+        assert_eq!(Money::parse(" , "), None);
+        assert!(Money::validate_input("1.234,"));
+        assert!(!Money::validate_input("1,2,3"));
+    }
+
+    #[test]
+    fn rounds_half_up() {
+        // 0,333 kg at R$ 10,00/kg is R$ 3,33; 0,125 kg at R$ 0,10/kg is 1,25 centavo
+        assert_eq!(
+            Money::from_cents(1000).checked_times_quantity(333),
+            Some(Money::from_cents(333))
+        );
+        assert_eq!(
+            Money::from_cents(10).checked_times_quantity(125),
+            Some(Money::from_cents(1))
+        );
+        assert_eq!(
+            Money::from_cents(10).checked_times_quantity(150),
+            Some(Money::from_cents(2))
+        );
+        assert_eq!(
+            Money::from_cents(-10).checked_times_quantity(150),
+            Some(Money::from_cents(-2))
+        );
+    }
+
+    #[test]
+    fn applies_markup() {
+        let cost = Money::from_cents(1000);
+        let markup = Percent::parse("30").unwrap();
+        assert_eq!(
+            cost.checked_with_markup(markup),
+            Some(Money::from_cents(1300))
+        );
+        assert_eq!(Money::from_cents(1300).markup_over(cost), Some(markup));
+        assert_eq!(Money::from_cents(1300).markup_over(Money::ZERO), None);
+        assert_eq!(Percent::parse("12,5").unwrap().to_string(), "12,50");
+    }
+
+    #[test]
+    fn computes_the_share_of_a_whole() {
+        let revenue = Money::from_cents(3000);
+        assert_eq!(
+            Money::from_cents(1000).share_of(revenue),
+            Percent::parse("33,33")
+        );
+        assert_eq!(
+            Money::from_cents(-750).share_of(revenue),
+            Percent::parse("-25")
+        );
+        assert_eq!(revenue.share_of(Money::ZERO), None);
+    }
+
+    #[test]
+    fn checked_operations_detect_overflow() {
+        let max = Money::from_cents(i64::MAX);
+        assert_eq!(max.checked_add(Money::from_cents(1)), None);
+        assert_eq!(max.checked_times_quantity(2 * QUANTITY_SCALE), None);
+        assert_eq!(Money::checked_sum([max, Money::from_cents(1)]), None);
+    }
+
+    #[test]
+    fn operators_saturate_instead_of_panicking() {
+        let max = Money::from_cents(i64::MAX);
+        let min = Money::from_cents(i64::MIN);
+        assert_eq!(max + Money::from_cents(1), max);
+        assert_eq!(min - Money::from_cents(1), min);
+        assert_eq!(-min, max);
+    }
+
+    proptest! {
+        #[test]
+        fn format_then_parse_round_trips(cents in -1_000_000_000_000i64..1_000_000_000_000) {
+            let money = Money::from_cents(cents);
+            prop_assert_eq!(Money::parse(&money.to_string()), Some(money));
+            prop_assert_eq!(Money::parse(&money.to_decimal_string()), Some(money));
+        }
+
+        #[test]
+        fn negative_amounts_format_like_positive_ones(cents in 1i64..1_000_000_000_000) {
+            let positive = Money::from_cents(cents).to_string();
+            let negative = Money::from_cents(-cents).to_string();
+            prop_assert_eq!(negative, format!("-{}", positive));
+        }
+
+        #[test]
+        fn one_unit_costs_the_unit_price(cents in any::<i64>()) {
+            let money = Money::from_cents(cents);
+            prop_assert_eq!(money.checked_times_quantity(QUANTITY_SCALE), Some(money));
+        }
+
+        #[test]
+        fn quantity_total_is_within_half_a_centavo(
+            cents in -10_000_000i64..10_000_000,
+            quantity in -10_000_000i64..10_000_000,
+        ) {
+            let total = Money::from_cents(cents).checked_times_quantity(quantity).unwrap();
+            let exact = i128::from(cents) * i128::from(quantity);
+            let error = (i128::from(total.cents()) * i128::from(QUANTITY_SCALE) - exact).abs();
+            prop_assert!(error * 2 <= i128::from(QUANTITY_SCALE));
+        }
+
+        #[test]
+        fn checked_add_matches_wide_arithmetic(a in any::<i64>(), b in any::<i64>()) {
+            let wide = i128::from(a) + i128::from(b);
+            let expected = i64::try_from(wide).ok().map(Money::from_cents);
+            prop_assert_eq!(Money::from_cents(a).checked_add(Money::from_cents(b)), expected);
+        }
+
+        #[test]
+        fn markup_round_trips_within_rounding(
+            cost in 1i64..100_000_000,
+            hundredths in 0i64..100_000,
+        ) {
+            let cost = Money::from_cents(cost);
+            let markup = Percent(hundredths);
+            let sale = cost.checked_with_markup(markup).unwrap();
+            let back = sale.markup_over(cost).unwrap();
+            // The sale price is rounded to the centavo, which moves the markup by
+            // at most half a centavo over the cost.
+            let tolerance = PERCENT_SCALE / (2 * cost.cents()) + 1;
+            prop_assert!((back.hundredths() - hundredths).abs() <= tolerance);
+        }
+
+        #[test]
+        fn percent_format_then_parse_round_trips(hundredths in -100_000_000i64..100_000_000) {
+            let percent = Percent(hundredths);
+            prop_assert_eq!(Percent::parse(&percent.to_string()), Some(percent));
+        }
+    }
+}
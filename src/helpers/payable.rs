@@ -0,0 +1,85 @@
+//! Payment terms of a purchase: a single due date or installments in days,
+//! such as `30/60/90`.
+
+use super::money::Money;
+use chrono::{Days, NaiveDate};
+
+/// Longest term accepted for an installment, in days.
+const MAX_TERM_DAYS: u64 = 3650;
+
+/// Due dates of the installments. `terms` is either a date (`DD/MM/AAAA`)
+/// or days after `purchased_on` separated by `/`, in any order.
+pub fn parse_terms(terms: &str, purchased_on: NaiveDate) -> Option<Vec<NaiveDate>> {
+    let terms = terms.trim();
+    if let Some((_, year)) = terms.rsplit_once('/')
+        && year.len() == 4
+    {
+        return NaiveDate::parse_from_str(terms, "%d/%m/%Y")
+            .ok()
+            .map(|date| vec![date]);
+    }
+    let mut days = terms
+        .split('/')
+        .map(|days| days.trim().parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if days.iter().any(|&days| days > MAX_TERM_DAYS) {
+        return None;
+    }
+    days.sort_unstable();
+    days.dedup();
+    days.into_iter()
+        .map(|days| purchased_on.checked_add_days(Days::new(days)))
+        .collect()
+}
+
+/// Splits `total` into `count` installments. The centavos that don't divide
+/// evenly go to the first one.
+pub fn split(total: Money, count: usize) -> Vec<Money> {
+    if count == 0 {
+        return vec![];
+    }
+    let cents = total.cents();
+    let count_cents = count as i64;
+    let (part, rest) = (cents / count_cents, cents % count_cents);
+    (0..count)
+        .map(|index| Money::from_cents(if index == 0 { part + rest } else { part }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_a_due_date_or_days() {
+        let today = date(3, 1);
+        assert_eq!(parse_terms("15/03/2026", today), Some(vec![date(3, 15)]));
+        assert_eq!(
+            parse_terms("60/30/ 90", today),
+            Some(vec![date(3, 31), date(4, 30), date(5, 30)])
+        );
+        assert_eq!(parse_terms("0", today), Some(vec![today]));
+        assert_eq!(parse_terms("31/02/2026", today), None);
+        assert_eq!(parse_terms("30/x", today), None);
+        assert_eq!(parse_terms("", today), None);
+        assert_eq!(parse_terms("9999", today), None);
+    }
+
+    #[test]
+    fn split_keeps_the_total() {
+        let parts = split(Money::from_cents(10_000), 3);
+        assert_eq!(
+            parts,
+            vec![
+                Money::from_cents(3334),
+                Money::from_cents(3333),
+                Money::from_cents(3333)
+            ]
+        );
+        assert_eq!(split(Money::from_cents(500), 0), vec![]);
+    }
+}
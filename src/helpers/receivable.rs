@@ -0,0 +1,137 @@
+//! Customer account balance. Credits of a sale pay its own charge first, and
+//! payments pay the oldest charges, so what remains open shows what is due.
+
+use super::money::Money;
+use crate::config::CreditConfig;
+use crate::entities::receivable_entry::ReceivableEntry;
+use chrono::{Days, NaiveDate};
+
+/// When a store credit sale made on `sold_on` falls due.
+pub fn due_date(config: &CreditConfig, sold_on: NaiveDate) -> Option<NaiveDate> {
+    sold_on.checked_add_days(Days::new(u64::from(config.due_days)))
+}
+
+/// A charge not fully paid yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenCharge {
+    pub sale_id: Option<i64>,
+    pub remaining: Money,
+    pub due_date: Option<NaiveDate>,
+}
+
+/// What the customer owes: charges minus payments and credits.
+pub fn balance(entries: &[ReceivableEntry]) -> Money {
+    entries.iter().fold(Money::ZERO, |total, entry| {
+        total.saturating_add(entry.amount)
+    })
+}
+
+/// Charges still owed, oldest first. `entries` must be oldest first.
+pub fn open_charges(entries: &[ReceivableEntry]) -> Vec<OpenCharge> {
+    let mut charges: Vec<OpenCharge> = vec![];
+    // Paid when there was nothing to pay, used on the next charges
+    let mut advance = Money::ZERO;
+    for entry in entries {
+        if entry.amount > Money::ZERO {
+            let used = advance.min(entry.amount);
+            advance = advance - used;
+            charges.push(OpenCharge {
+                sale_id: entry.sale_id,
+                remaining: entry.amount - used,
+                due_date: entry.due_date,
+            });
+            continue;
+        }
+        let mut left = -entry.amount;
+        let own_sale = charges
+            .iter()
+            .position(|charge| entry.sale_id.is_some() && charge.sale_id == entry.sale_id);
+        let order = own_sale.into_iter().chain(0..charges.len());
+        for index in order {
+            let charge = &mut charges[index];
+            let used = left.min(charge.remaining);
+            charge.remaining = charge.remaining - used;
+            left = left - used;
+        }
+        advance += left;
+    }
+    charges.retain(|charge| charge.remaining > Money::ZERO);
+    charges
+}
+
+/// Part of the balance past its due date on `today`.
+pub fn overdue(entries: &[ReceivableEntry], today: NaiveDate) -> Money {
+    open_charges(entries)
+        .iter()
+        .filter(|charge| charge.due_date.is_some_and(|due_date| due_date < today))
+        .fold(Money::ZERO, |total, charge| {
+            total.saturating_add(charge.remaining)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::receivable_entry::ReceivableKind;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn entry(
+        kind: ReceivableKind,
+        sale_id: Option<i64>,
+        cents: i64,
+        due: Option<u32>,
+    ) -> ReceivableEntry {
+        let created_at = date(1).and_hms_opt(12, 0, 0).unwrap();
+        ReceivableEntry::new(
+            1,
+            sale_id,
+            kind,
+            Money::from_cents(cents),
+            due.map(date),
+            created_at,
+        )
+    }
+
+    #[test]
+    fn payments_settle_the_oldest_charges_first() {
+        let entries = [
+            entry(ReceivableKind::Charge, Some(1), 5000, Some(10)),
+            entry(ReceivableKind::Charge, Some(2), 3000, Some(20)),
+            entry(ReceivableKind::Payment, None, -6000, None),
+        ];
+        assert_eq!(balance(&entries), Money::from_cents(2000));
+        assert_eq!(
+            open_charges(&entries),
+            vec![OpenCharge {
+                sale_id: Some(2),
+                remaining: Money::from_cents(2000),
+                due_date: Some(date(20)),
+            }]
+        );
+        assert_eq!(overdue(&entries, date(15)), Money::ZERO);
+        assert_eq!(overdue(&entries, date(21)), Money::from_cents(2000));
+    }
+
+    #[test]
+    fn credits_go_to_their_own_sale() {
+        let entries = [
+            entry(ReceivableKind::Charge, Some(1), 5000, Some(10)),
+            entry(ReceivableKind::Charge, Some(2), 3000, Some(20)),
+            entry(ReceivableKind::Credit, Some(2), -3000, None),
+        ];
+        assert_eq!(overdue(&entries, date(25)), Money::from_cents(5000));
+    }
+
+    #[test]
+    fn overpayments_count_toward_later_charges() {
+        let entries = [
+            entry(ReceivableKind::Payment, None, -1000, None),
+            entry(ReceivableKind::Charge, Some(1), 3000, Some(10)),
+        ];
+        assert_eq!(balance(&entries), Money::from_cents(2000));
+        assert_eq!(overdue(&entries, date(11)), Money::from_cents(2000));
+    }
+}
@@ -0,0 +1,78 @@
+//! Purchase suggestion: enough stock to cover the coming days at the pace
+//! the product has been selling, on top of its minimum stock.
+
+use super::QUANTITY_SCALE;
+use crate::entities::unit::Unit;
+
+/// Average quantity sold per day, with 3 fixed decimals like the stock.
+pub fn daily_average(sold: i64, days: u32) -> i64 {
+    if days == 0 {
+        return 0;
+    }
+    sold.max(0) / i64::from(days)
+}
+
+/// Quantity to buy so the stock lasts `coverage_days` after keeping
+/// `min_stock`, given `sold` in the last `history_days`. Rounded up to
+/// whole units for products sold by unit; zero when the stock is enough.
+pub fn suggested_quantity(
+    sold: i64,
+    history_days: u32,
+    coverage_days: u32,
+    stock: i64,
+    min_stock: i64,
+    unit: Unit,
+) -> i64 {
+    let demand = if history_days == 0 {
+        0
+    } else {
+        let sold = i128::from(sold.max(0)) * i128::from(coverage_days);
+        let days = i128::from(history_days);
+        (sold + days - 1) / days
+    };
+    let needed = demand + i128::from(min_stock.max(0)) - i128::from(stock);
+    if needed <= 0 {
+        return 0;
+    }
+    let needed = if unit.is_fractional() {
+        needed
+    } else {
+        let scale = i128::from(QUANTITY_SCALE);
+        (needed + scale - 1) / scale * scale
+    };
+    i64::try_from(needed).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_the_coming_days_on_top_of_the_minimum_stock() {
+        // 30 sold in 30 days, 15 days of coverage, 5 minimum, 8 in stock
+        assert_eq!(
+            suggested_quantity(30_000, 30, 15, 8_000, 5_000, Unit::Un),
+            12_000
+        );
+        // Fractions of a unit are rounded up
+        assert_eq!(suggested_quantity(10_000, 30, 15, 0, 0, Unit::Un), 5_000);
+        assert_eq!(suggested_quantity(10_000, 30, 10, 0, 0, Unit::Un), 4_000);
+        assert_eq!(suggested_quantity(10_000, 30, 10, 0, 0, Unit::Kg), 3_334);
+    }
+
+    #[test]
+    fn suggests_nothing_when_the_stock_is_enough() {
+        assert_eq!(
+            suggested_quantity(30_000, 30, 15, 20_000, 5_000, Unit::Un),
+            0
+        );
+        assert_eq!(suggested_quantity(0, 30, 15, 0, 0, Unit::Un), 0);
+        // Negative stock is bought back too
+        assert_eq!(
+            suggested_quantity(0, 30, 15, -2_000, 1_000, Unit::Un),
+            3_000
+        );
+        assert_eq!(suggested_quantity(30_000, 0, 15, 0, 0, Unit::Un), 0);
+        assert_eq!(daily_average(45_000, 30), 1_500);
+    }
+}
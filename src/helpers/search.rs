@@ -0,0 +1,187 @@
+//! Product search shared by the database queries and the combo box filter:
+//! accents and case are ignored, terms match word prefixes and a few typos
+//! are forgiven, with closer matches ranked first.
+
+use super::fold_accents;
+
+/// Typos forgiven in the longest terms.
+const MAX_TYPOS: usize = 2;
+
+/// Lowercase words without accents, so "Café Pilão" gives `["cafe", "pilao"]`.
+pub fn terms(value: &str) -> Vec<String> {
+    fold_accents(value)
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// FTS5 query requiring every term as a word prefix, or `None` without terms.
+pub fn prefix_query(terms: &[String]) -> Option<String> {
+    join_query(terms.iter().map(String::as_str), " ")
+}
+
+/// Words of the index tried for each term when a typo leaves
+/// [`prefix_query`] empty. The closest ones are kept.
+const TYPO_CANDIDATES: usize = 50;
+
+/// FTS5 query for when a typo leaves [`prefix_query`] empty: each term
+/// matches as a prefix or as any word of `vocabulary` within [`cost`] of it,
+/// such as the words of the index. `None` without terms.
+pub fn typo_query(terms: &[String], vocabulary: &[String]) -> Option<String> {
+    let groups: Vec<String> = terms
+        .iter()
+        .map(|term| {
+            let mut candidates: Vec<(u32, &str)> = vocabulary
+                .iter()
+                .filter_map(|word| term_cost(term, word).map(|cost| (cost, word.as_str())))
+                .collect();
+            candidates.sort_unstable();
+            let words = candidates
+                .into_iter()
+                .take(TYPO_CANDIDATES)
+                .map(|(_, word)| format!("\"{}\"", word));
+            let alternatives: Vec<String> = std::iter::once(format!("\"{}\"*", term))
+                .chain(words)
+                .collect();
+            format!("({})", alternatives.join(" OR "))
+        })
+        .collect();
+    (!groups.is_empty()).then(|| groups.join(" AND "))
+}
+
+/// Shortest word of the index that [`typo_query`] may offer for `terms`.
+pub fn typo_min_length(terms: &[String]) -> usize {
+    terms
+        .iter()
+        .map(|term| term.chars().count().saturating_sub(MAX_TYPOS))
+        .min()
+        .unwrap_or(0)
+        .max(1)
+}
+
+fn join_query<'a>(terms: impl Iterator<Item = &'a str>, separator: &str) -> Option<String> {
+    let query = terms
+        .map(|term| format!("\"{}\"*", term))
+        .collect::<Vec<_>>()
+        .join(separator);
+    (!query.is_empty()).then_some(query)
+}
+
+/// How far `text` is from the search: 0 when every term is a whole word of
+/// it, more for prefixes, substrings and typos, `None` when some term is not
+/// found at all. `query` and `words` come from [`terms`].
+pub fn cost(query: &[String], words: &[String]) -> Option<u32> {
+    query
+        .iter()
+        .map(|term| words.iter().filter_map(|word| term_cost(term, word)).min())
+        .sum()
+}
+
+fn term_cost(term: &str, word: &str) -> Option<u32> {
+    if word == term {
+        return Some(0);
+    }
+    if word.starts_with(term) {
+        return Some(1);
+    }
+    if word.contains(term) {
+        return Some(2);
+    }
+    let term: Vec<char> = term.chars().collect();
+    let allowed = match term.len() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => MAX_TYPOS,
+    };
+    let word: Vec<char> = word.chars().collect();
+    // Compared with the start of the word, so an incomplete word still counts
+    let shortest = term.len().saturating_sub(allowed).max(1);
+    let longest = (term.len() + allowed).min(word.len());
+    (shortest..=longest)
+        .map(|end| distance(&term, &word[..end]))
+        .min()
+        .filter(|typos| *typos <= allowed)
+        .map(|typos| 2 + typos as u32)
+}
+
+/// Edit distance counting a swap of two neighbouring letters as one typo.
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut previous2: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(previous2[j - 2] + 1);
+            }
+        }
+        previous2 = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost_of(query: &str, text: &str) -> Option<u32> {
+        cost(&terms(query), &terms(text))
+    }
+
+    #[test]
+    fn ignores_accents_and_case() {
+        assert_eq!(terms("Café  PILÃO-500g"), vec!["cafe", "pilao", "500g"]);
+        assert_eq!(cost_of("CAFÉ", "cafe pilao"), Some(0));
+        assert_eq!(cost_of("cafe", "CAFÉ PILÃO"), Some(0));
+    }
+
+    #[test]
+    fn ranks_words_before_prefixes_substrings_and_typos() {
+        let word = cost_of("cafe", "cafe pilao");
+        let prefix = cost_of("caf", "cafe pilao");
+        let substring = cost_of("afe", "cafe pilao");
+        let typo = cost_of("cfae", "cafe pilao");
+        assert!(word < prefix && prefix < substring && substring < typo);
+        assert_eq!(cost_of("pilao cafe", "cafe pilao"), Some(0));
+    }
+
+    #[test]
+    fn forgives_typos_by_length() {
+        assert_eq!(cost_of("arros", "ARROZ TIO JOAO"), Some(3));
+        assert_eq!(cost_of("biscioto", "BISCOITO RECHEADO"), Some(3));
+        assert_eq!(cost_of("refrigerente", "REFRIGERANTE COLA"), Some(3));
+        assert_eq!(cost_of("feijoa", "FEIJAO"), Some(3));
+        assert_eq!(cost_of("oel", "OLEO"), None);
+        assert_eq!(cost_of("acucar", "ARROZ"), None);
+        assert_eq!(cost_of("cafe leite", "CAFE PILAO"), None);
+    }
+
+    #[test]
+    fn builds_fts_queries() {
+        let query = terms("café pil");
+        assert_eq!(prefix_query(&query).unwrap(), "\"cafe\"* \"pil\"*");
+        assert_eq!(prefix_query(&terms(" - ")), None);
+    }
+
+    #[test]
+    fn typo_queries_offer_the_close_words_of_the_index() {
+        let vocabulary = terms("cafe pilao leite acucar cafeteira");
+        // A typo in the first letters is found too
+        assert_eq!(
+            typo_query(&terms("xafe plao"), &vocabulary).unwrap(),
+            "(\"xafe\"* OR \"cafe\" OR \"cafeteira\") AND (\"plao\"* OR \"pilao\")"
+        );
+        assert_eq!(
+            typo_query(&terms("leit"), &vocabulary).unwrap(),
+            "(\"leit\"* OR \"leite\")"
+        );
+        assert_eq!(typo_query(&terms(" - "), &vocabulary), None);
+        assert_eq!(typo_min_length(&terms("xafe refrigerente")), 2);
+    }
+}
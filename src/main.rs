@@ -1,159 +1,597 @@
+mod api;
+mod catalog;
+mod cli;
 mod components;
 mod config;
+mod database;
+mod devices;
 mod entities;
 mod helpers;
+mod printing;
 mod repositories;
 mod screens;
 mod services;
 
-use crate::services::product_purchase_service::ProductPurchaseService;
-use crate::services::product_sale_service::ProductSaleService;
-use crate::services::product_service::ProductService;
-use iced::keyboard::key::Named;
-use iced::keyboard::{on_key_press, Key};
-use iced::widget::{button, column, container, horizontal_rule, row};
-use iced::{Element, Length, Subscription, Task, Theme};
-use sqlx::migrate::Migrator;
+use crate::components::notification::{Notification, Notifications};
+use crate::config::{Config, HotkeyAction};
+use crate::devices::scale::Scale;
+use crate::services::abc_service::{AbcService, SqliteAbcService};
+use crate::services::backup_service::BackupService;
+use crate::services::cash_flow_service::{CashFlowService, SqliteCashFlowService};
+use crate::services::customer_service::{CustomerService, SqliteCustomerService};
+use crate::services::expense_service::{ExpenseService, SqliteExpenseService};
+use crate::services::payable_service::{PayableService, SqlitePayableService};
+use crate::services::product_purchase_service::{
+    ProductPurchaseService, SqliteProductPurchaseService,
+};
+use crate::services::product_sale_service::{ProductSaleService, SqliteProductSaleService};
+use crate::services::product_service::{ProductService, SqliteProductService};
+use crate::services::receivable_service::{ReceivableService, SqliteReceivableService};
+use clap::Parser;
+use iced::keyboard::{self, Key};
+use iced::widget::{button, column, container, horizontal_rule, row, text};
+use iced::{event, Element, Event, Length, Subscription, Task, Theme};
 use sqlx::SqlitePool;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-static MIGRATOR: Migrator = sqlx::migrate!();
+/// How often the automatic backup checks whether a new copy is due.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How often toasts are checked for expiry while some are on screen.
+const NOTIFICATION_CHECK_INTERVAL: Duration = Duration::from_millis(500);
 
 #[tokio::main]
 async fn main() -> iced::Result {
-    let pool = SqlitePool::connect("sqlite:database.db").await.unwrap();
-    MIGRATOR.run(&pool).await.unwrap();
-    let product_service = Arc::new(ProductService::new(pool.clone()));
-    let product_purchase_service = Arc::new(ProductPurchaseService::new(pool.clone()));
-    let product_sale_service = Arc::new(ProductSaleService::new(pool.clone()));
-
-    iced::application("Teste", State::update, State::view)
-        .subscription(State::subscription)
-        .theme(State::theme)
+    if let Some(command) = cli::Cli::parse().command {
+        std::process::exit(cli::run(command).await);
+    }
+
+    iced::application(App::title, App::update, App::view)
+        .subscription(App::subscription)
+        .theme(App::theme)
         .centered()
-        .run_with(|| {
-            State::new(
-                product_purchase_service,
-                product_sale_service,
-                product_service,
-            )
-        })
+        .run_with(App::new)
+}
+
+/// The application shows the startup screen until the configuration is
+/// loaded and the database is open.
+#[derive(Debug)]
+enum App {
+    Startup(Box<screens::startup::State>),
+    Running(Box<State>),
+}
+
+impl App {
+    fn new() -> (Self, Task<Message>) {
+        (
+            App::Startup(Box::new(screens::startup::State::new(Config::path()))),
+            Task::done(Message::Startup(screens::startup::Message::Start)),
+        )
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        match self {
+            App::Startup(state) => state.view().map(Message::Startup),
+            App::Running(state) => state.view(),
+        }
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match (self, message) {
+            (App::Startup(_), Message::Startup(screens::startup::Message::Exit)) => iced::exit(),
+            (
+                app @ App::Startup(_),
+                Message::Startup(screens::startup::Message::Started(pool, config)),
+            ) => {
+                let config_path = Config::path();
+                let (state, task) = State::new(pool, config_path, *config);
+                *app = App::Running(Box::new(state));
+                task
+            }
+            (App::Startup(state), Message::Startup(message)) => {
+                state.update(message).map(Message::Startup)
+            }
+            (App::Running(state), message) => state.update(message),
+            _ => Task::none(),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            App::Startup(_) => "SS PDV LITE".to_string(),
+            App::Running(state) => state.title(),
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        let config = match self {
+            App::Startup(state) => state.config(),
+            App::Running(state) => Some(&state.config),
+        };
+        config
+            .and_then(|config| config.appearance.theme())
+            .unwrap_or(Theme::TokyoNightLight)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        match self {
+            App::Startup(_) => Subscription::none(),
+            App::Running(state) => state.subscription(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     NavigateToHome,
     NavigateToAddPurchase,
+    NavigateToProducts,
+    NavigateToSales,
+    NavigateToCustomers,
+    NavigateToFinancial,
+    NavigateToSettings,
+    KeyPressed(Key, event::Status),
+    BackupTick,
+    BackupDone(Result<Option<PathBuf>, String>),
+    BackedUp(Result<PathBuf, String>),
+    ApiStopped(Result<(), String>),
+    DismissNotification(u64),
+    ExpireNotifications(Instant),
     Home(screens::home::Message),
     AddPurchase(screens::add_purchase::Message),
+    Products(screens::products::Message),
+    SalesHistory(screens::sales_history::Message),
+    Customers(screens::customers::Message),
+    Financial(screens::financial::Message),
+    Labels(screens::labels::Message),
+    Settings(screens::settings::Message),
+    Backup(screens::backup::Message),
+    Catalog(screens::catalog::Message),
+    AbcCurve(screens::abc_curve::Message),
+    Startup(screens::startup::Message),
 }
 
 #[derive(Debug)]
 enum Screen {
-    Home(screens::home::State),
+    Home(Box<screens::home::State>),
     AddPurchase(screens::add_purchase::State),
+    Products(screens::products::State),
+    SalesHistory(screens::sales_history::State),
+    Customers(screens::customers::State),
+    Financial(screens::financial::State),
+    Labels(screens::labels::State),
+    Settings(Box<screens::settings::State>),
+    Backup(screens::backup::State),
+    Catalog(screens::catalog::State),
+    AbcCurve(screens::abc_curve::State),
 }
 
 #[derive(Debug)]
 struct State {
     screen: Screen,
-    product_purchase_service: Arc<ProductPurchaseService>,
-    product_sale_service: Arc<ProductSaleService>,
-    product_service: Arc<ProductService>,
+    product_purchase_service: Arc<dyn ProductPurchaseService>,
+    product_sale_service: Arc<dyn ProductSaleService>,
+    product_service: Arc<dyn ProductService>,
+    customer_service: Arc<dyn CustomerService>,
+    receivable_service: Arc<dyn ReceivableService>,
+    payable_service: Arc<dyn PayableService>,
+    expense_service: Arc<dyn ExpenseService>,
+    cash_flow_service: Arc<dyn CashFlowService>,
+    abc_service: Arc<dyn AbcService>,
+    backup_service: Arc<BackupService>,
+    scale: Option<Arc<Scale>>,
+    /// Services of the local API, when it was started.
+    api_services: Option<api::SharedServices>,
+    pool: SqlitePool,
+    config_path: PathBuf,
+    config: Config,
+    notifications: Notifications,
 }
 
 impl State {
-    fn new(
-        product_purchase_service: Arc<ProductPurchaseService>,
-        product_sale_service: Arc<ProductSaleService>,
-        product_service: Arc<ProductService>,
-    ) -> (Self, Task<Message>) {
+    fn new(pool: SqlitePool, config_path: PathBuf, config: Config) -> (Self, Task<Message>) {
+        let scale = Scale::from_config(&config.scale).map(Arc::new);
+        let product_service: Arc<dyn ProductService> =
+            Arc::new(SqliteProductService::new(pool.clone(), config.scale_label));
+        let product_purchase_service: Arc<dyn ProductPurchaseService> =
+            Arc::new(SqliteProductPurchaseService::new(pool.clone()));
+        let product_sale_service: Arc<dyn ProductSaleService> =
+            Arc::new(SqliteProductSaleService::new(
+                pool.clone(),
+                config.loyalty.clone(),
+                config.credit.clone(),
+            ));
+        let customer_service: Arc<dyn CustomerService> =
+            Arc::new(SqliteCustomerService::new(pool.clone()));
+        let receivable_service: Arc<dyn ReceivableService> =
+            Arc::new(SqliteReceivableService::new(pool.clone()));
+        let payable_service: Arc<dyn PayableService> =
+            Arc::new(SqlitePayableService::new(pool.clone()));
+        let expense_service: Arc<dyn ExpenseService> =
+            Arc::new(SqliteExpenseService::new(pool.clone()));
+        let cash_flow_service: Arc<dyn CashFlowService> =
+            Arc::new(SqliteCashFlowService::new(pool.clone()));
+        let abc_service: Arc<dyn AbcService> = Arc::new(SqliteAbcService::new(pool.clone()));
+        let backup_service = Arc::new(BackupService::new(
+            pool.clone(),
+            config.database.path(),
+            config.backup.clone(),
+        ));
+        let api_services = config.api.enabled.then(|| {
+            api::SharedServices::new(api::Services {
+                product_service: product_service.clone(),
+                product_sale_service: product_sale_service.clone(),
+                product_purchase_service: product_purchase_service.clone(),
+            })
+        });
+        let api_task = match &api_services {
+            Some(services) => Task::perform(
+                api::serve(config.api.clone(), services.clone()),
+                |result: anyhow::Result<()>| Message::ApiStopped(result.map_err(|e| e.to_string())),
+            ),
+            None => Task::none(),
+        };
         (
             Self {
-                screen: Screen::Home(screens::home::State::new(
+                screen: Screen::Home(Box::new(screens::home::State::new(
                     product_service.clone(),
                     product_sale_service.clone(),
-                )),
+                    customer_service.clone(),
+                    scale.clone(),
+                    config.loyalty.clone(),
+                    config.hotkeys.clone(),
+                ))),
                 product_purchase_service,
                 product_sale_service,
                 product_service,
+                customer_service,
+                receivable_service,
+                payable_service,
+                expense_service,
+                cash_flow_service,
+                abc_service,
+                backup_service,
+                scale,
+                api_services,
+                pool,
+                config_path,
+                config,
+                notifications: Notifications::default(),
             },
-            Task::none(),
+            Task::batch([
+                Task::done(Message::BackupTick),
+                Task::done(Message::Home(screens::home::Message::FocusSearch)),
+                api_task,
+            ]),
         )
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let content = match &self.screen {
             Screen::Home(state) => state.view().map(Message::Home),
             Screen::AddPurchase(state) => state.view().map(Message::AddPurchase),
+            Screen::Products(state) => state.view().map(Message::Products),
+            Screen::SalesHistory(state) => state.view().map(Message::SalesHistory),
+            Screen::Customers(state) => state.view().map(Message::Customers),
+            Screen::Financial(state) => state.view().map(Message::Financial),
+            Screen::Labels(state) => state.view().map(Message::Labels),
+            Screen::Settings(state) => state.view().map(Message::Settings),
+            Screen::Backup(state) => state.view().map(Message::Backup),
+            Screen::Catalog(state) => state.view().map(Message::Catalog),
+            Screen::AbcCurve(state) => state.view().map(Message::AbcCurve),
         };
-        column![
+        let hotkeys = &self.config.hotkeys;
+        let screen = column![
             container(content)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .padding(16),
             horizontal_rule(1),
             row![
-                button("(F1) INICIO").on_press(Message::NavigateToHome),
-                button("(F2) PRODUTOS").on_press(Message::NavigateToAddPurchase),
-                button("(F3) ESTOQUE"),
-                button("(F4) FINANCEIRO"),
+                button(text(format!("({}) INICIO", hotkeys.home)))
+                    .on_press(Message::NavigateToHome),
+                button(text(format!("({}) PRODUTOS", hotkeys.purchase)))
+                    .on_press(Message::NavigateToAddPurchase),
+                button(text(format!("({}) ESTOQUE", hotkeys.products)))
+                    .on_press(Message::NavigateToProducts),
+                button(text(format!("({}) VENDAS", hotkeys.sales)))
+                    .on_press(Message::NavigateToSales),
+                button(text(format!("({}) CLIENTES", hotkeys.customers)))
+                    .on_press(Message::NavigateToCustomers),
+                button(text(format!("({}) FINANCEIRO", hotkeys.financial)))
+                    .on_press(Message::NavigateToFinancial),
+                button(text(format!("({}) CONFIGURAÇÕES", hotkeys.settings)))
+                    .on_press(Message::NavigateToSettings),
             ]
             .padding(16)
             .spacing(16)
-        ]
-        .into()
+        ];
+        self.notifications
+            .view(screen.into(), Message::DismissNotification)
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
+            Message::Home(screens::home::Message::Notify(notification))
+            | Message::AddPurchase(screens::add_purchase::Message::Notify(notification))
+            | Message::Products(screens::products::Message::Notify(notification))
+            | Message::SalesHistory(screens::sales_history::Message::Notify(notification))
+            | Message::Customers(screens::customers::Message::Notify(notification))
+            | Message::Financial(screens::financial::Message::Notify(notification))
+            | Message::Labels(screens::labels::Message::Notify(notification))
+            | Message::Settings(screens::settings::Message::Notify(notification))
+            | Message::Backup(screens::backup::Message::Notify(notification))
+            | Message::Catalog(screens::catalog::Message::Notify(notification))
+            | Message::AbcCurve(screens::abc_curve::Message::Notify(notification)) => {
+                self.notifications.push(notification);
+            }
+            Message::DismissNotification(id) => {
+                self.notifications.dismiss(id);
+            }
+            Message::ExpireNotifications(now) => {
+                self.notifications.expire(now);
+            }
             Message::Home(message) => {
                 if let Screen::Home(state) = &mut self.screen {
                     return state.update(message).map(Message::Home);
                 }
             }
+            Message::AddPurchase(screens::add_purchase::Message::PrintLabels(labels))
+            | Message::Products(screens::products::Message::PrintLabels(labels)) => {
+                self.screen = Screen::Labels(screens::labels::State::new(
+                    self.config.labels.clone(),
+                    labels,
+                ));
+            }
             Message::AddPurchase(message) => {
                 if let Screen::AddPurchase(state) = &mut self.screen {
                     return state.update(message).map(Message::AddPurchase);
                 }
             }
+            Message::Products(screens::products::Message::OpenCatalog) => {
+                self.screen =
+                    Screen::Catalog(screens::catalog::State::new(self.product_service.clone()));
+            }
+            Message::Products(screens::products::Message::OpenAbcCurve) => {
+                self.screen =
+                    Screen::AbcCurve(screens::abc_curve::State::new(self.abc_service.clone()));
+                return Task::done(Message::AbcCurve(screens::abc_curve::Message::Search));
+            }
+            Message::AbcCurve(message) => {
+                if let Screen::AbcCurve(state) = &mut self.screen {
+                    return state.update(message).map(Message::AbcCurve);
+                }
+            }
+            Message::Products(message) => {
+                if let Screen::Products(state) = &mut self.screen {
+                    return state.update(message).map(Message::Products);
+                }
+            }
+            Message::SalesHistory(message) => {
+                if let Screen::SalesHistory(state) = &mut self.screen {
+                    return state.update(message).map(Message::SalesHistory);
+                }
+            }
+            Message::Customers(message) => {
+                if let Screen::Customers(state) = &mut self.screen {
+                    return state.update(message).map(Message::Customers);
+                }
+            }
+            Message::Financial(screens::financial::Message::Backup) => {
+                let backup_service = self.backup_service.clone();
+                return Task::perform(
+                    async move { backup_service.backup().await.map_err(|e| e.to_string()) },
+                    Message::BackedUp,
+                );
+            }
+            Message::Financial(message) => {
+                if let Screen::Financial(state) = &mut self.screen {
+                    return state.update(message).map(Message::Financial);
+                }
+            }
+            Message::Labels(message) => {
+                if let Screen::Labels(state) = &mut self.screen {
+                    return state.update(message).map(Message::Labels);
+                }
+            }
+            Message::Settings(screens::settings::Message::Saved(config)) => {
+                // Screens are built on navigation, so they pick up the new
+                // settings; the services holding them are rebuilt here and
+                // handed to the running API too
+                self.backup_service =
+                    Arc::new(self.backup_service.with_config(config.backup.clone()));
+                self.product_service = Arc::new(SqliteProductService::new(
+                    self.pool.clone(),
+                    config.scale_label,
+                ));
+                self.product_sale_service = Arc::new(SqliteProductSaleService::new(
+                    self.pool.clone(),
+                    config.loyalty.clone(),
+                    config.credit.clone(),
+                ));
+                self.scale = Scale::from_config(&config.scale).map(Arc::new);
+                if let Some(api_services) = &self.api_services {
+                    api_services.replace(api::Services {
+                        product_service: self.product_service.clone(),
+                        product_sale_service: self.product_sale_service.clone(),
+                        product_purchase_service: self.product_purchase_service.clone(),
+                    });
+                }
+                self.config = *config;
+            }
+            Message::Settings(screens::settings::Message::OpenBackups) => {
+                self.screen =
+                    Screen::Backup(screens::backup::State::new(self.backup_service.clone()));
+                return Task::done(Message::Backup(screens::backup::Message::Load));
+            }
+            Message::Settings(message) => {
+                if let Screen::Settings(state) = &mut self.screen {
+                    return state.update(message).map(Message::Settings);
+                }
+            }
+            Message::Backup(message) => {
+                if let Screen::Backup(state) = &mut self.screen {
+                    return state.update(message).map(Message::Backup);
+                }
+            }
+            Message::Catalog(message) => {
+                if let Screen::Catalog(state) = &mut self.screen {
+                    return state.update(message).map(Message::Catalog);
+                }
+            }
+            Message::BackupTick => {
+                let backup_service = self.backup_service.clone();
+                return Task::perform(
+                    async move {
+                        backup_service
+                            .backup_if_due()
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::BackupDone,
+                );
+            }
+            Message::BackupDone(result) => match result {
+                Ok(Some(path)) => self.notifications.push(Notification::success(format!(
+                    "Backup salvo em {}",
+                    path.display()
+                ))),
+                Ok(None) => {}
+                Err(e) => self.notifications.push(Notification::error(format!(
+                    "Erro no backup automático: {}",
+                    e
+                ))),
+            },
+            Message::BackedUp(result) => match result {
+                Ok(path) => self.notifications.push(Notification::success(format!(
+                    "Backup salvo em {}",
+                    path.display()
+                ))),
+                Err(e) => self.notifications.push(Notification::error(format!(
+                    "Erro ao fazer o backup: {}",
+                    e
+                ))),
+            },
+            Message::ApiStopped(result) => match result {
+                Ok(()) => self
+                    .notifications
+                    .push(Notification::warning("A API local foi encerrada")),
+                Err(e) => self
+                    .notifications
+                    .push(Notification::error(format!("A API local parou: {}", e))),
+            },
+            Message::Startup(_) => {}
+            Message::KeyPressed(key, status) => {
+                let message = match self.config.hotkeys.action(&key) {
+                    Some(HotkeyAction::Home) => Message::NavigateToHome,
+                    Some(HotkeyAction::Purchase) => Message::NavigateToAddPurchase,
+                    Some(HotkeyAction::Products) => Message::NavigateToProducts,
+                    Some(HotkeyAction::Sales) => Message::NavigateToSales,
+                    Some(HotkeyAction::Customers) => Message::NavigateToCustomers,
+                    Some(HotkeyAction::Settings) => Message::NavigateToSettings,
+                    Some(HotkeyAction::Search) => {
+                        Message::Home(screens::home::Message::FocusSearch)
+                    }
+                    Some(HotkeyAction::CancelSale) => {
+                        Message::Home(screens::home::Message::CancelSale)
+                    }
+                    Some(HotkeyAction::Payment) => {
+                        Message::Home(screens::home::Message::FinishSale)
+                    }
+                    Some(HotkeyAction::Financial) => Message::NavigateToFinancial,
+                    // Other keys belong to the screen on display
+                    None => match &self.screen {
+                        Screen::Home(_) => {
+                            Message::Home(screens::home::Message::KeyPressed(key, status))
+                        }
+                        _ => return Task::none(),
+                    },
+                };
+                return Task::done(message);
+            }
 
             Message::NavigateToHome => {
-                self.screen = Screen::Home(screens::home::State::new(
+                self.screen = Screen::Home(Box::new(screens::home::State::new(
                     self.product_service.clone(),
                     self.product_sale_service.clone(),
-                ));
+                    self.customer_service.clone(),
+                    self.scale.clone(),
+                    self.config.loyalty.clone(),
+                    self.config.hotkeys.clone(),
+                )));
+                return Task::done(Message::Home(screens::home::Message::FocusSearch));
             }
             Message::NavigateToAddPurchase => {
                 self.screen = Screen::AddPurchase(screens::add_purchase::State::new(
                     self.product_purchase_service.clone(),
                     self.product_service.clone(),
+                    self.config.purchase.markup(),
+                ));
+            }
+            Message::NavigateToProducts => {
+                self.screen =
+                    Screen::Products(screens::products::State::new(self.product_service.clone()));
+                return Task::done(Message::Products(
+                    screens::products::Message::SearchTextChange(String::new()),
+                ));
+            }
+            Message::NavigateToSales => {
+                self.screen = Screen::SalesHistory(screens::sales_history::State::new(
+                    self.product_sale_service.clone(),
+                    self.config.store.clone(),
+                    self.config.printer.receipt_target(),
+                ));
+                return Task::done(Message::SalesHistory(
+                    screens::sales_history::Message::Search,
                 ));
             }
+            Message::NavigateToCustomers => {
+                self.screen = Screen::Customers(screens::customers::State::new(
+                    self.customer_service.clone(),
+                ));
+                return Task::done(Message::Customers(screens::customers::Message::Search));
+            }
+            Message::NavigateToFinancial => {
+                self.screen = Screen::Financial(screens::financial::State::new(
+                    self.receivable_service.clone(),
+                    self.payable_service.clone(),
+                    self.expense_service.clone(),
+                    self.cash_flow_service.clone(),
+                    self.config.store.clone(),
+                    self.config.printer.receipt_target(),
+                ));
+                return Task::done(Message::Financial(screens::financial::Message::Load));
+            }
+            Message::NavigateToSettings => {
+                self.screen = Screen::Settings(Box::new(screens::settings::State::new(
+                    self.config_path.clone(),
+                    self.config.clone(),
+                )));
+            }
         }
         Task::none()
     }
 
-    fn theme(&self) -> Theme {
-        Theme::TokyoNightLight
+    fn title(&self) -> String {
+        format!("SS PDV LITE - {}", self.config.store.name)
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        on_key_press(|key, _| {
-            match key {
-                Key::Named(Named::F1) => {
-                    return Some(Message::NavigateToHome);
-                }
-                Key::Named(Named::F2) => {
-                    return Some(Message::NavigateToAddPurchase);
+        let expire_notifications = if self.notifications.has_expiring() {
+            iced::time::every(NOTIFICATION_CHECK_INTERVAL).map(Message::ExpireNotifications)
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([
+            event::listen_with(|event, status, _| match event {
+                Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
+                    Some(Message::KeyPressed(key, status))
                 }
-                Key::Named(Named::F3) => {}
-                Key::Named(Named::F4) => {}
-                _ => return None,
-            }
-
-            println!("{:?}", key);
-            None
-        })
+                _ => None,
+            }),
+            iced::time::every(BACKUP_CHECK_INTERVAL).map(|_| Message::BackupTick),
+            expire_notifications,
+        ])
     }
 }
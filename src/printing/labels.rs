@@ -0,0 +1,361 @@
+use crate::entities::product::Product;
+use crate::helpers::barcode::ean_modules;
+use crate::helpers::money::Money;
+use crate::helpers::{fold_accents, QUANTITY_SCALE};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Command language of the label printer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LabelFormat {
+    #[default]
+    Zpl,
+    Epl,
+}
+
+impl LabelFormat {
+    pub const ALL: [LabelFormat; 2] = [LabelFormat::Zpl, LabelFormat::Epl];
+}
+
+impl Display for LabelFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelFormat::Zpl => f.write_str("ZPL"),
+            LabelFormat::Epl => f.write_str("EPL"),
+        }
+    }
+}
+
+/// Price label of a product.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub name: String,
+    pub price: Money,
+    pub ean: Option<String>,
+    pub copies: u32,
+}
+
+impl Label {
+    pub fn new(product: &Product, copies: u32) -> Self {
+        Self {
+            name: product.name.clone(),
+            price: product.price_sale,
+            ean: product.ean.clone(),
+            copies,
+        }
+    }
+
+    /// One label per unit received; products sold by fractional units get a
+    /// single label.
+    pub fn for_received(product: &Product) -> Self {
+        let copies = if product.unit.is_fractional() {
+            1
+        } else {
+            (product.quantity / QUANTITY_SCALE).max(1) as u32
+        };
+        Self::new(product, copies)
+    }
+}
+
+pub fn render(labels: &[Label], format: LabelFormat) -> String {
+    match format {
+        LabelFormat::Zpl => to_zpl(labels),
+        LabelFormat::Epl => to_epl(labels),
+    }
+}
+
+/// ZPL II for 50x30 mm labels at 203 dpi.
+pub fn to_zpl(labels: &[Label]) -> String {
+    let mut zpl = String::new();
+    for label in labels.iter().filter(|label| label.copies > 0) {
+        let name = zpl_field(&label.name);
+        zpl.push_str("^XA^CI28\n");
+        zpl.push_str(&format!("^FO20,15^A0N,26,26^FB360,2,0,L^FD{}^FS\n", name));
+        zpl.push_str(&format!("^FO20,75^A0N,40,40^FD{}^FS\n", label.price));
+        if let Some(ean) = &label.ean {
+            let command = match ean.len() {
+                8 => Some(format!("^B8N,50,Y,N^FD{}^FS", &ean[..7])),
+                12 => Some(format!("^BUN,50,Y,N^FD{}^FS", &ean[..11])),
+                13 => Some(format!("^BEN,50,Y,N^FD{}^FS", &ean[..12])),
+                _ => None,
+            };
+            match command {
+                Some(command) => zpl.push_str(&format!("^FO60,125^BY2{}\n", command)),
+                None => zpl.push_str(&format!("^FO20,140^A0N,24,24^FD{}^FS\n", ean)),
+            }
+        }
+        zpl.push_str(&format!("^PQ{}\n^XZ\n", label.copies));
+    }
+    zpl
+}
+
+/// EPL2 for 50x30 mm labels at 203 dpi.
+pub fn to_epl(labels: &[Label]) -> String {
+    let mut epl = String::new();
+    for label in labels.iter().filter(|label| label.copies > 0) {
+        epl.push_str("\nN\nq400\nQ240,24\n");
+        epl.push_str(&format!(
+            "A20,15,0,3,1,1,N,\"{}\"\n",
+            epl_field(&label.name)
+        ));
+        epl.push_str(&format!("A20,60,0,4,1,1,N,\"{}\"\n", label.price));
+        if let Some(ean) = &label.ean {
+            let symbology = match ean.len() {
+                8 => Some("E80"),
+                12 => Some("UA0"),
+                13 => Some("E30"),
+                _ => None,
+            };
+            match symbology {
+                Some(symbology) => {
+                    epl.push_str(&format!("B60,110,0,{},2,4,60,B,\"{}\"\n", symbology, ean))
+                }
+                None => epl.push_str(&format!("A20,140,0,3,1,1,N,\"{}\"\n", ean)),
+            }
+        }
+        epl.push_str(&format!("P{}\n", label.copies));
+    }
+    epl
+}
+
+fn zpl_field(value: &str) -> String {
+    value.replace(['^', '~'], " ")
+}
+
+fn epl_field(value: &str) -> String {
+    fold_accents(value)
+        .chars()
+        .filter(|c| c.is_ascii())
+        .collect::<String>()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+}
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 20.0;
+const COLUMNS: usize = 3;
+const ROWS: usize = 8;
+const MODULE_WIDTH: f32 = 1.2;
+const BAR_HEIGHT: f32 = 32.0;
+const NAME_LENGTH: usize = 32;
+
+/// A4 sheet with a 3x8 grid of labels, one cell per copy.
+pub fn to_pdf(labels: &[Label]) -> Vec<u8> {
+    let cells: Vec<&Label> = labels
+        .iter()
+        .flat_map(|label| std::iter::repeat_n(label, label.copies as usize))
+        .collect();
+    let pages: Vec<&[&Label]> = if cells.is_empty() {
+        vec![&[]]
+    } else {
+        cells.chunks(COLUMNS * ROWS).collect()
+    };
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let bold_font_id = Ref::new(4);
+    let font_name = Name(b"F1");
+    let bold_font_name = Name(b"F2");
+    let page_ids: Vec<Ref> = (0..pages.len())
+        .map(|i| Ref::new(5 + 2 * i as i32))
+        .collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    pdf.type1_font(font_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_font_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    let cell_width = (PAGE_WIDTH - 2.0 * MARGIN) / COLUMNS as f32;
+    let cell_height = (PAGE_HEIGHT - 2.0 * MARGIN) / ROWS as f32;
+
+    for (page_index, cells) in pages.iter().enumerate() {
+        let page_id = page_ids[page_index];
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        fonts.pair(font_name, font_id);
+        fonts.pair(bold_font_name, bold_font_id);
+        fonts.finish();
+        resources.finish();
+        page.finish();
+
+        let mut content = Content::new();
+        for (index, label) in cells.iter().enumerate() {
+            let x = MARGIN + (index % COLUMNS) as f32 * cell_width + 8.0;
+            let top = PAGE_HEIGHT - MARGIN - (index / COLUMNS) as f32 * cell_height - 8.0;
+
+            let name: String = label.name.chars().take(NAME_LENGTH).collect();
+            write_text(&mut content, font_name, 9.0, x, top - 9.0, &name);
+            write_text(
+                &mut content,
+                bold_font_name,
+                16.0,
+                x,
+                top - 30.0,
+                &label.price.to_string(),
+            );
+            if let Some(ean) = &label.ean {
+                let bars_bottom = top - 36.0 - BAR_HEIGHT;
+                if let Some(modules) = ean_modules(ean) {
+                    for (position, black) in modules.iter().enumerate() {
+                        if *black {
+                            content.rect(
+                                x + position as f32 * MODULE_WIDTH,
+                                bars_bottom,
+                                MODULE_WIDTH,
+                                BAR_HEIGHT,
+                            );
+                        }
+                    }
+                    content.fill_nonzero();
+                }
+                write_text(&mut content, font_name, 8.0, x, bars_bottom - 9.0, ean);
+            }
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
+
+fn write_text(content: &mut Content, font: Name, size: f32, x: f32, y: f32, value: &str) {
+    let encoded: Vec<u8> = value
+        .chars()
+        .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+        .collect();
+    content
+        .begin_text()
+        .set_font(font, size)
+        .next_line(x, y)
+        .show(Str(&encoded))
+        .end_text();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str, cents: i64, ean: Option<&str>, copies: u32) -> Label {
+        Label {
+            name: name.to_string(),
+            price: Money::from_cents(cents),
+            ean: ean.map(str::to_string),
+            copies,
+        }
+    }
+
+    #[test]
+    fn zpl_per_barcode_length() {
+        let labels = [
+            label("CAFÉ PILÃO 500G", 1890, Some("7891000100103"), 2),
+            label("BALA", 10, Some("96385074"), 1),
+            label("PILHA AA", 123450, Some("036000291452"), 1),
+            label("ARROZ", 2590, Some("123"), 1),
+            label("SEM ETIQUETA", 100, None, 0),
+        ];
+
+        assert_eq!(
+            to_zpl(&labels),
+            "^XA^CI28\n\
+             ^FO20,15^A0N,26,26^FB360,2,0,L^FDCAFÉ PILÃO 500G^FS\n\
+             ^FO20,75^A0N,40,40^FDR$ 18,90^FS\n\
+             ^FO60,125^BY2^BEN,50,Y,N^FD789100010010^FS\n\
+             ^PQ2\n^XZ\n\
+             ^XA^CI28\n\
+             ^FO20,15^A0N,26,26^FB360,2,0,L^FDBALA^FS\n\
+             ^FO20,75^A0N,40,40^FDR$ 0,10^FS\n\
+             ^FO60,125^BY2^B8N,50,Y,N^FD9638507^FS\n\
+             ^PQ1\n^XZ\n\
+             ^XA^CI28\n\
+             ^FO20,15^A0N,26,26^FB360,2,0,L^FDPILHA AA^FS\n\
+             ^FO20,75^A0N,40,40^FDR$ 1.234,50^FS\n\
+             ^FO60,125^BY2^BUN,50,Y,N^FD03600029145^FS\n\
+             ^PQ1\n^XZ\n\
+             ^XA^CI28\n\
+             ^FO20,15^A0N,26,26^FB360,2,0,L^FDARROZ^FS\n\
+             ^FO20,75^A0N,40,40^FDR$ 25,90^FS\n\
+             ^FO20,140^A0N,24,24^FD123^FS\n\
+             ^PQ1\n^XZ\n"
+        );
+    }
+
+    #[test]
+    fn zpl_replaces_command_prefixes_in_the_name() {
+        let zpl = to_zpl(&[label("SUCO^FS~JA 1L", 599, None, 1)]);
+        assert_eq!(
+            zpl,
+            "^XA^CI28\n\
+             ^FO20,15^A0N,26,26^FB360,2,0,L^FDSUCO FS JA 1L^FS\n\
+             ^FO20,75^A0N,40,40^FDR$ 5,99^FS\n\
+             ^PQ1\n^XZ\n"
+        );
+    }
+
+    #[test]
+    fn epl_per_barcode_length() {
+        let labels = [
+            label("CAFÉ PILÃO 500G", 1890, Some("7891000100103"), 2),
+            label("BALA", 10, Some("96385074"), 1),
+            label("PILHA AA", 123450, Some("036000291452"), 1),
+            label("ARROZ", 2590, Some("123"), 1),
+            label("SEM ETIQUETA", 100, None, 0),
+        ];
+
+        assert_eq!(
+            to_epl(&labels),
+            "\nN\nq400\nQ240,24\n\
+             A20,15,0,3,1,1,N,\"CAFE PILAO 500G\"\n\
+             A20,60,0,4,1,1,N,\"R$ 18,90\"\n\
+             B60,110,0,E30,2,4,60,B,\"7891000100103\"\n\
+             P2\n\
+             \nN\nq400\nQ240,24\n\
+             A20,15,0,3,1,1,N,\"BALA\"\n\
+             A20,60,0,4,1,1,N,\"R$ 0,10\"\n\
+             B60,110,0,E80,2,4,60,B,\"96385074\"\n\
+             P1\n\
+             \nN\nq400\nQ240,24\n\
+             A20,15,0,3,1,1,N,\"PILHA AA\"\n\
+             A20,60,0,4,1,1,N,\"R$ 1.234,50\"\n\
+             B60,110,0,UA0,2,4,60,B,\"036000291452\"\n\
+             P1\n\
+             \nN\nq400\nQ240,24\n\
+             A20,15,0,3,1,1,N,\"ARROZ\"\n\
+             A20,60,0,4,1,1,N,\"R$ 25,90\"\n\
+             A20,140,0,3,1,1,N,\"123\"\n\
+             P1\n"
+        );
+    }
+
+    #[test]
+    fn epl_escapes_quotes_and_backslashes_and_drops_non_ascii() {
+        let epl = to_epl(&[label("TV 32\" \\ AÇÃO ★", 99900, None, 1)]);
+        assert_eq!(
+            epl,
+            "\nN\nq400\nQ240,24\n\
+             A20,15,0,3,1,1,N,\"TV 32\\\" \\\\ ACAO \"\n\
+             A20,60,0,4,1,1,N,\"R$ 999,00\"\n\
+             P1\n"
+        );
+    }
+
+    #[test]
+    fn render_picks_the_format() {
+        let labels = [label("BALA", 10, None, 1)];
+        assert_eq!(render(&labels, LabelFormat::Zpl), to_zpl(&labels));
+        assert_eq!(render(&labels, LabelFormat::Epl), to_epl(&labels));
+    }
+}
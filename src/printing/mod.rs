@@ -0,0 +1,55 @@
+pub mod labels;
+pub mod receipt;
+pub mod statement;
+
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::PathBuf;
+
+/// Where printer output goes: a file (or device path such as `/dev/usb/lp0`)
+/// or a raw TCP port, written as `tcp://host:9100`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrinterTarget {
+    File(PathBuf),
+    Tcp(String),
+}
+
+impl PrinterTarget {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            None
+        } else if let Some(address) = value.strip_prefix("tcp://") {
+            Some(PrinterTarget::Tcp(address.to_string()))
+        } else {
+            Some(PrinterTarget::File(PathBuf::from(value)))
+        }
+    }
+
+    pub async fn send(&self, data: Vec<u8>) -> Result<()> {
+        let target = self.clone();
+        tokio::task::spawn_blocking(move || match &target {
+            PrinterTarget::File(path) => std::fs::write(path, &data)
+                .map_err(|e| anyhow!("Não foi possível gravar em {}: {}", path.display(), e)),
+            PrinterTarget::Tcp(address) => {
+                let mut stream = TcpStream::connect(address)
+                    .map_err(|e| anyhow!("Impressora {} indisponível: {}", address, e))?;
+                stream.write_all(&data)?;
+                stream.flush()?;
+                Ok(())
+            }
+        })
+        .await?
+    }
+}
+
+impl Display for PrinterTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrinterTarget::File(path) => write!(f, "{}", path.display()),
+            PrinterTarget::Tcp(address) => write!(f, "tcp://{}", address),
+        }
+    }
+}
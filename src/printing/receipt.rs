@@ -0,0 +1,122 @@
+use crate::config::StoreConfig;
+use crate::entities::loyalty_entry::LoyaltyKind;
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use crate::helpers::{fold_accents, format_quantity, to_local};
+use crate::services::product_sale_service::SaleDetail;
+
+/// Characters per line of an 80 mm thermal printer in its default font.
+pub(super) const WIDTH: usize = 48;
+
+/// Plain text receipt of a sale, without accents so it prints on any code
+/// page. `copy` marks it as a reprint.
+pub fn render(store: &StoreConfig, detail: &SaleDetail, copy: bool) -> String {
+    let mut lines = store_header(store);
+    lines.push(columns(
+        &format!("VENDA {}", detail.sale.id),
+        &to_local(detail.sale.created_at)
+            .format("%d/%m/%Y %H:%M")
+            .to_string(),
+    ));
+    if copy {
+        lines.push(center("2a VIA"));
+    }
+    if let Some(voided_at) = detail.sale.voided_at {
+        lines.push(center(&format!(
+            "CANCELADA EM {}",
+            to_local(voided_at).format("%d/%m/%Y %H:%M")
+        )));
+    }
+    lines.push("-".repeat(WIDTH));
+    for item in &detail.items {
+        lines.push(item.name.chars().take(WIDTH).collect());
+        lines.push(columns(
+            &format!(
+                "  {} {} x {}",
+                format_quantity(item.quantity, item.unit),
+                item.unit,
+                item.price
+            ),
+            &item.total.to_string(),
+        ));
+        if item.returned_quantity > 0 {
+            lines.push(format!(
+                "  DEVOLVIDO {} {}",
+                format_quantity(item.returned_quantity, item.unit),
+                item.unit
+            ));
+        }
+    }
+    lines.push("-".repeat(WIDTH));
+    lines.push(columns("TOTAL", &detail.sale.total.to_string()));
+    if detail.net_total() != detail.sale.total {
+        lines.push(columns("TOTAL LIQUIDO", &detail.net_total().to_string()));
+    }
+    if detail.sale.loyalty_discount > Money::ZERO {
+        lines.push(columns(
+            "PAGO COM PONTOS",
+            &(-detail.sale.loyalty_discount).to_string(),
+        ));
+        lines.push(columns("A PAGAR", &detail.sale.amount_due().to_string()));
+    }
+    lines.push(columns("PAGAMENTO", detail.sale.payment_method.as_str()));
+    let points = [
+        (LoyaltyKind::Earn, "PONTOS GANHOS"),
+        (LoyaltyKind::Redeem, "PONTOS USADOS"),
+        (LoyaltyKind::Reversal, "PONTOS ESTORNADOS"),
+        (LoyaltyKind::Refund, "PONTOS DEVOLVIDOS"),
+    ];
+    if !detail.loyalty.is_empty() {
+        lines.push("-".repeat(WIDTH));
+        for (kind, label) in points {
+            let points = detail.points(kind);
+            if points > 0 {
+                lines.push(columns(label, &points.to_string()));
+            }
+        }
+    }
+    if detail.sale.payment_method == PaymentMethod::StoreCredit {
+        lines.push(String::new());
+        lines.push(String::new());
+        lines.push("_".repeat(WIDTH));
+        lines.push(center("ASSINATURA DO CLIENTE"));
+    }
+    lines.push(String::new());
+    lines.push(center("SEM VALOR FISCAL"));
+    finish(&lines)
+}
+
+/// Store name, CNPJ and address, centered, followed by a rule.
+pub(super) fn store_header(store: &StoreConfig) -> Vec<String> {
+    let mut lines = vec![center(&store.name)];
+    if !store.cnpj.trim().is_empty() {
+        lines.push(center(&format!("CNPJ {}", store.cnpj.trim())));
+    }
+    if !store.address.trim().is_empty() {
+        lines.push(center(store.address.trim()));
+    }
+    lines.push("-".repeat(WIDTH));
+    lines
+}
+
+/// Joins the lines without accents and feeds the paper past the cutter.
+pub(super) fn finish(lines: &[String]) -> String {
+    let mut text: String = lines
+        .iter()
+        .map(|line| fold_accents(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.push_str("\n\n\n\n");
+    text
+}
+
+pub(super) fn center(value: &str) -> String {
+    let value: String = value.chars().take(WIDTH).collect();
+    let padding = (WIDTH - value.chars().count()) / 2;
+    format!("{}{}", " ".repeat(padding), value)
+}
+
+pub(super) fn columns(left: &str, right: &str) -> String {
+    let space = WIDTH.saturating_sub(left.chars().count() + right.chars().count());
+    format!("{}{}{}", left, " ".repeat(space.max(1)), right)
+}
@@ -0,0 +1,62 @@
+use super::receipt::{center, columns, finish, store_header, WIDTH};
+use crate::config::StoreConfig;
+use crate::helpers::document::format_cpf;
+use crate::helpers::money::Money;
+use crate::helpers::to_local;
+use crate::services::receivable_service::Statement;
+use chrono::NaiveDateTime;
+
+/// Plain text statement of a customer's store credit account, with the
+/// balance after each entry, for the receipt printer.
+pub fn render(store: &StoreConfig, statement: &Statement, printed_at: NaiveDateTime) -> String {
+    let account = &statement.account;
+    let customer = &account.customer;
+    let mut lines = store_header(store);
+    lines.push(center("EXTRATO DO FIADO"));
+    lines.push(columns(
+        "EMITIDO EM",
+        &to_local(printed_at).format("%d/%m/%Y %H:%M").to_string(),
+    ));
+    lines.push(customer.name.chars().take(WIDTH).collect());
+    if let Some(cpf) = &customer.cpf {
+        lines.push(format!("CPF {}", format_cpf(cpf)));
+    }
+    lines.push("-".repeat(WIDTH));
+
+    let mut balance = Money::ZERO;
+    for entry in &statement.entries {
+        balance = balance.saturating_add(entry.amount);
+        let mut description = format!(
+            "{} {}",
+            to_local(entry.created_at).format("%d/%m/%y"),
+            entry.kind.as_str()
+        );
+        if let Some(sale_id) = entry.sale_id {
+            description.push_str(&format!(" VENDA {}", sale_id));
+        }
+        lines.push(description);
+        let due = entry
+            .due_date
+            .map(|date| format!("  VENCE {}", date.format("%d/%m/%y")))
+            .unwrap_or_default();
+        lines.push(columns(&due, &format!("{} | {}", entry.amount, balance)));
+    }
+    lines.push("-".repeat(WIDTH));
+    lines.push(columns("SALDO DEVEDOR", &account.balance.to_string()));
+    if account.overdue > Money::ZERO {
+        lines.push(columns("EM ATRASO", &account.overdue.to_string()));
+    }
+    if let Some(next_due) = account.next_due {
+        lines.push(columns(
+            "PROXIMO VENCIMENTO",
+            &next_due.format("%d/%m/%Y").to_string(),
+        ));
+    }
+    lines.push(columns(
+        "LIMITE DO FIADO",
+        &customer.credit_limit.to_string(),
+    ));
+    lines.push(String::new());
+    lines.push(center("SEM VALOR FISCAL"));
+    finish(&lines)
+}
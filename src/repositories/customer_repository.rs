@@ -0,0 +1,143 @@
+use crate::entities::customer::Customer;
+use crate::helpers::money::Money;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct CustomerRepository;
+
+impl CustomerRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer: &Customer,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_customer (name, cpf, phone, email, credit_limit, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+            customer.name,
+            customer.cpf,
+            customer.phone,
+            customer.email,
+            customer.credit_limit,
+            customer.created_at
+        )
+        .fetch_one(executor)
+        .await
+        .map_err(|e| duplicate_cpf_error(e, &customer.cpf))?;
+
+        Ok(rec.id)
+    }
+
+    pub async fn update<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer: &Customer,
+    ) -> Result<()> {
+        let updated_at = Utc::now().naive_local();
+        sqlx::query!(
+            r#"
+            UPDATE tb_customer
+            SET name = ?, cpf = ?, phone = ?, email = ?, credit_limit = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            customer.name,
+            customer.cpf,
+            customer.phone,
+            customer.email,
+            customer.credit_limit,
+            updated_at,
+            customer.id
+        )
+        .execute(executor)
+        .await
+        .map_err(|e| duplicate_cpf_error(e, &customer.cpf))?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+    ) -> Result<Option<Customer>> {
+        let customer = sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id, name, cpf, phone, email,
+                   credit_limit as "credit_limit: Money", created_at
+            FROM tb_customer
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(customer)
+    }
+
+    /// Customers whose name contains `name`, or whose CPF is `digits` or
+    /// phone contains `digits`, ordered by name. Empty values match nothing.
+    pub async fn search<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        name: &str,
+        digits: &str,
+        limit: i64,
+    ) -> Result<Vec<Customer>> {
+        let customers = sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id as "id!", name, cpf, phone, email,
+                   credit_limit as "credit_limit: Money", created_at
+            FROM tb_customer
+            WHERE (? <> '' AND name LIKE '%' || ? || '%')
+               OR (? <> '' AND (cpf = ? OR phone LIKE '%' || ? || '%'))
+            ORDER BY name
+            LIMIT ?
+            "#,
+            name,
+            name,
+            digits,
+            digits,
+            digits,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(customers)
+    }
+
+    pub async fn list<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        limit: i64,
+    ) -> Result<Vec<Customer>> {
+        let customers = sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id, name, cpf, phone, email,
+                   credit_limit as "credit_limit: Money", created_at
+            FROM tb_customer
+            ORDER BY name
+            LIMIT ?
+            "#,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(customers)
+    }
+}
+
+fn duplicate_cpf_error(error: sqlx::Error, cpf: &Option<String>) -> anyhow::Error {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => anyhow!(
+            "CPF {} já está cadastrado em outro cliente",
+            cpf.as_deref().unwrap_or_default()
+        ),
+        _ => error.into(),
+    }
+}
@@ -0,0 +1,71 @@
+use crate::entities::expense::{Expense, ExpenseCategory};
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct ExpenseRepository;
+
+impl ExpenseRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        entity: &Expense,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_expense (category, description, amount, paid_on, payment_method, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!"
+            "#,
+            entity.category,
+            entity.description,
+            entity.amount,
+            entity.paid_on,
+            entity.payment_method,
+            entity.created_at
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.id)
+    }
+
+    /// Expenses paid from `from` to `to`, both included, oldest first.
+    pub async fn list_between<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Expense>> {
+        let entities = sqlx::query_as!(
+            Expense,
+            r#"
+            SELECT id as "id!", category as "category: ExpenseCategory", description,
+                   amount as "amount: Money", paid_on,
+                   payment_method as "payment_method: PaymentMethod", created_at
+            FROM tb_expense
+            WHERE paid_on >= ? AND paid_on <= ?
+            ORDER BY paid_on, id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entities)
+    }
+
+    /// Returns whether the expense existed.
+    pub async fn delete<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query!("DELETE FROM tb_expense WHERE id = ?", id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
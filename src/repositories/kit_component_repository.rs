@@ -0,0 +1,80 @@
+use crate::entities::kit_component::KitComponent;
+use crate::entities::unit::Unit;
+use anyhow::Result;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct KitComponentRepository;
+
+impl KitComponentRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        kit_id: i64,
+        component_id: i64,
+        quantity: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO tb_kit_component (kit_id, component_id, quantity)
+            VALUES (?, ?, ?)
+            "#,
+            kit_id,
+            component_id,
+            quantity
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_kit<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        kit_id: i64,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM tb_kit_component WHERE kit_id = ?", kit_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Components of the kit, by name.
+    pub async fn list_by_kit<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        kit_id: i64,
+    ) -> Result<Vec<KitComponent>> {
+        let components = sqlx::query_as!(
+            KitComponent,
+            r#"
+            SELECT k.component_id, p.name, p.unit as "unit: Unit", k.quantity, p.quantity as stock
+            FROM tb_kit_component k
+            JOIN tb_product p ON p.id = k.component_id
+            WHERE k.kit_id = ?
+            ORDER BY p.name
+            "#,
+            kit_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(components)
+    }
+
+    /// Whether the product is part of some kit.
+    pub async fn is_component<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        product_id: i64,
+    ) -> Result<bool> {
+        let rec = sqlx::query!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM tb_kit_component WHERE component_id = ?) as "used!: bool"
+            "#,
+            product_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.used)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::entities::loyalty_entry::{LoyaltyEntry, LoyaltyKind};
+use anyhow::Result;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct LoyaltyRepository;
+
+impl LoyaltyRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        entity: &LoyaltyEntry,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_loyalty_entry (customer_id, sale_id, kind, points, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!"
+            "#,
+            entity.customer_id,
+            entity.sale_id,
+            entity.kind,
+            entity.points,
+            entity.expires_at,
+            entity.created_at
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.id)
+    }
+
+    /// Ledger of a customer, oldest first.
+    pub async fn list_by_customer<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer_id: i64,
+    ) -> Result<Vec<LoyaltyEntry>> {
+        let entries = sqlx::query_as!(
+            LoyaltyEntry,
+            r#"
+            SELECT customer_id, sale_id, kind as "kind: LoyaltyKind", points,
+                   expires_at, created_at
+            FROM tb_loyalty_entry
+            WHERE customer_id = ?
+            ORDER BY created_at, id
+            "#,
+            customer_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Entries of a sale, oldest first.
+    pub async fn list_by_sale<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        sale_id: i64,
+    ) -> Result<Vec<LoyaltyEntry>> {
+        let entries = sqlx::query_as!(
+            LoyaltyEntry,
+            r#"
+            SELECT customer_id, sale_id, kind as "kind: LoyaltyKind", points,
+                   expires_at, created_at
+            FROM tb_loyalty_entry
+            WHERE sale_id = ?
+            ORDER BY created_at, id
+            "#,
+            sale_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entries)
+    }
+}
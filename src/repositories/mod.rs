@@ -1,5 +1,13 @@
+pub mod customer_repository;
+pub mod expense_repository;
+pub mod kit_component_repository;
+pub mod loyalty_repository;
+pub mod payable_repository;
 pub mod product_purchase_repository;
 pub mod product_repository;
-pub mod purchase_repository;
 pub mod product_sale_repository;
+pub mod product_sale_return_repository;
+pub mod purchase_repository;
+pub mod receivable_repository;
 pub mod sale_repository;
+pub mod user_repository;
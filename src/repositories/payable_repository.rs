@@ -0,0 +1,131 @@
+use crate::entities::payable::Payable;
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct PayableRepository;
+
+impl PayableRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        entity: &Payable,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_payable (purchase_id, supplier, document, installment, installments,
+                                    amount, due_date, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!"
+            "#,
+            entity.purchase_id,
+            entity.supplier,
+            entity.document,
+            entity.installment,
+            entity.installments,
+            entity.amount,
+            entity.due_date,
+            entity.created_at
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.id)
+    }
+
+    pub async fn find_by_id<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+    ) -> Result<Option<Payable>> {
+        let entity = sqlx::query_as!(
+            Payable,
+            r#"
+            SELECT id, purchase_id, supplier, document, installment, installments,
+                   amount as "amount: Money", due_date, paid_at,
+                   created_at
+            FROM tb_payable
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(entity)
+    }
+
+    /// Titles not paid yet, falling due up to `until` (all of them without
+    /// it), soonest first.
+    pub async fn list_open<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        until: Option<NaiveDate>,
+    ) -> Result<Vec<Payable>> {
+        let entities = sqlx::query_as!(
+            Payable,
+            r#"
+            SELECT id as "id!", purchase_id, supplier, document, installment, installments,
+                   amount as "amount: Money", due_date, paid_at,
+                   created_at
+            FROM tb_payable
+            WHERE paid_at IS NULL AND (? IS NULL OR due_date <= ?)
+            ORDER BY due_date, id
+            "#,
+            until,
+            until
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entities)
+    }
+
+    /// Titles paid in `[from, to)`, oldest payment first.
+    pub async fn list_paid_between<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<Payable>> {
+        let entities = sqlx::query_as!(
+            Payable,
+            r#"
+            SELECT id as "id!", purchase_id, supplier, document, installment, installments,
+                   amount as "amount: Money", due_date, paid_at,
+                   created_at
+            FROM tb_payable
+            WHERE paid_at >= ? AND paid_at < ?
+            ORDER BY paid_at, id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entities)
+    }
+
+    /// Marks an open title as paid. Returns whether it was open.
+    pub async fn mark_paid<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        payment_method: PaymentMethod,
+        paid_at: NaiveDateTime,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE tb_payable
+            SET paid_at = ?, payment_method = ?
+            WHERE id = ? AND paid_at IS NULL
+            "#,
+            paid_at,
+            payment_method,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -6,10 +6,6 @@ use sqlx::Sqlite;
 pub struct ProductPurchaseRepository;
 
 impl ProductPurchaseRepository {
-    pub fn new() -> Self {
-        Self {}
-    }
-
     pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         product_purchase: &ProductPurchase,
@@ -1,35 +1,39 @@
+use crate::entities::abc_class::AbcClass;
 use crate::entities::product::Product;
-use anyhow::Result;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use anyhow::{anyhow, Result};
 use chrono::Utc;
-use sqlx::{Sqlite, SqlitePool};
+use sqlx::Sqlite;
 
 #[derive(Debug)]
 pub struct ProductRepository;
 
 impl ProductRepository {
-    pub fn new() -> Self {
-        Self {}
-    }
-
     pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         product: &Product,
     ) -> Result<i64> {
         let rec = sqlx::query!(
             r#"
-            INSERT INTO tb_product (name, price_sale, price_purchase, quantity, ean, created_at)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO tb_product (name, price_sale, price_purchase, quantity, unit, ean, category, min_stock, supplier, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING id
             "#,
             product.name,
             product.price_sale,
             product.price_purchase,
             product.quantity,
+            product.unit,
             product.ean,
+            product.category,
+            product.min_stock,
+            product.supplier,
             product.created_at
         )
         .fetch_one(executor)
-        .await?;
+        .await
+        .map_err(|e| duplicate_ean_error(e, &product.ean))?;
 
         Ok(rec.id)
     }
@@ -42,34 +46,141 @@ impl ProductRepository {
         sqlx::query!(
             r#"
             UPDATE tb_product
-            SET name = ?, price_sale = ?, price_purchase = ?, quantity = ?, ean = ?, updated_at = ?
+            SET name = ?, price_sale = ?, price_purchase = ?,
+                quantity = CASE WHEN is_kit THEN quantity ELSE ? END, unit = ?, ean = ?, category = ?, min_stock = ?, supplier = ?, updated_at = ?
             WHERE id = ?
             "#,
             product.name,
             product.price_sale,
             product.price_purchase,
             product.quantity,
+            product.unit,
             product.ean,
+            product.category,
+            product.min_stock,
+            product.supplier,
             updated_at,
             product.id
         )
         .execute(executor)
+        .await
+        .map_err(|e| duplicate_ean_error(e, &product.ean))?;
+
+        Ok(())
+    }
+
+    /// Turns the product into a kit or back. The stock of a kit follows its
+    /// components; a product that stops being a kit starts with none.
+    pub async fn set_kit<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        is_kit: bool,
+    ) -> Result<()> {
+        let updated_at = Utc::now().naive_local();
+        sqlx::query!(
+            r#"
+            UPDATE tb_product
+            SET is_kit = ?, quantity = CASE WHEN ? THEN quantity ELSE 0 END, updated_at = ?
+            WHERE id = ?
+            "#,
+            is_kit,
+            is_kit,
+            updated_at,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Adds `quantity` to the stock, or takes it out when negative, leaving
+    /// the rest of the product as it is.
+    pub async fn add_quantity<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        quantity: i64,
+    ) -> Result<()> {
+        let updated_at = Utc::now().naive_local();
+        sqlx::query!(
+            r#"
+            UPDATE tb_product
+            SET quantity = quantity + ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            quantity,
+            updated_at,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sets the ABC class of every product, before the classes of the
+    /// products in a curve are saved.
+    pub async fn set_all_abc_classes<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        abc_class: AbcClass,
+    ) -> Result<()> {
+        sqlx::query!("UPDATE tb_product SET abc_class = ?", abc_class)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_abc_class<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        abc_class: AbcClass,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE tb_product SET abc_class = ? WHERE id = ?",
+            abc_class,
+            id
+        )
+        .execute(executor)
         .await?;
 
         Ok(())
     }
 
+    pub async fn update_ean<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        ean: &str,
+    ) -> Result<()> {
+        let updated_at = Utc::now().naive_local();
+        sqlx::query!(
+            r#"
+            UPDATE tb_product
+            SET ean = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            ean,
+            updated_at,
+            id
+        )
+        .execute(executor)
+        .await
+        .map_err(|e| duplicate_ean_error(e, &Some(ean.to_string())))?;
+
+        Ok(())
+    }
+
     pub async fn find_by_id<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         id: i64,
     ) -> Result<Option<Product>> {
         let product = sqlx::query_as!(
             Product,
-            "
-            SELECT id, name, price_sale, price_purchase, quantity, ean, created_at, updated_at
+            r#"
+            SELECT id, name, price_sale as "price_sale: Money", price_purchase as "price_purchase: Money", quantity, unit as "unit: Unit", ean, category, abc_class as "abc_class: AbcClass", min_stock, supplier, is_kit as "is_kit: bool", created_at, updated_at
             FROM tb_product
             WHERE id = ?
-            ",
+            "#,
             id
         )
         .fetch_optional(executor)
@@ -78,20 +189,84 @@ impl ProductRepository {
         Ok(product)
     }
 
-    pub async fn search_by_name<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+    /// Products matching an FTS5 query over name, EAN and category, best
+    /// ranked first, optionally only the ones in stock.
+    pub async fn search<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
-        name: &str,
+        query: &str,
+        in_stock: bool,
+        limit: i64,
     ) -> Result<Vec<Product>> {
-        let name = format!("%{}%", name);
         let products = sqlx::query_as!(
             Product,
-            "
-            SELECT id, name, price_sale, price_purchase, quantity, ean, created_at, updated_at
+            r#"
+            SELECT p.id as "id!", p.name, p.price_sale as "price_sale: Money", p.price_purchase as "price_purchase: Money", p.quantity, p.unit as "unit: Unit", p.ean, p.category, p.abc_class as "abc_class: AbcClass", p.min_stock, p.supplier, p.is_kit as "is_kit: bool", p.created_at, p.updated_at
+            FROM tb_product_search s
+            JOIN tb_product p ON p.id = s.rowid
+            WHERE tb_product_search MATCH ? AND (p.quantity > 0 OR NOT ?)
+            ORDER BY bm25(tb_product_search, 10.0, 5.0, 1.0)
+            LIMIT ?
+            "#,
+            query,
+            in_stock,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(products)
+    }
+
+    /// Distinct words of the names and categories in the search index, with
+    /// at least `min_length` characters.
+    pub async fn search_vocabulary<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        min_length: i64,
+    ) -> Result<Vec<String>> {
+        let terms = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT term as "term!: String"
+            FROM tb_product_search_vocab
+            WHERE col IN ('name', 'category') AND length(term) >= ?
+            "#,
+            min_length
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(terms)
+    }
+
+    pub async fn list_all<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+    ) -> Result<Vec<Product>> {
+        let products = sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id, name, price_sale as "price_sale: Money", price_purchase as "price_purchase: Money", quantity, unit as "unit: Unit", ean, category, abc_class as "abc_class: AbcClass", min_stock, supplier, is_kit as "is_kit: bool", created_at, updated_at
             FROM tb_product
-            WHERE quantity > 0 AND name LIKE ?
-            LIMIT 10
-            ",
-            name
+            ORDER BY name
+            "#
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(products)
+    }
+
+    pub async fn list_first<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        limit: i64,
+    ) -> Result<Vec<Product>> {
+        let products = sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id, name, price_sale as "price_sale: Money", price_purchase as "price_purchase: Money", quantity, unit as "unit: Unit", ean, category, abc_class as "abc_class: AbcClass", min_stock, supplier, is_kit as "is_kit: bool", created_at, updated_at
+            FROM tb_product
+            ORDER BY name
+            LIMIT ?
+            "#,
+            limit
         )
         .fetch_all(executor)
         .await?;
@@ -105,12 +280,12 @@ impl ProductRepository {
     ) -> Result<Vec<Product>> {
         let product = sqlx::query_as!(
             Product,
-            "
-            SELECT id, name, price_sale, price_purchase, quantity, ean, created_at, updated_at
+            r#"
+            SELECT id as "id!", name, price_sale as "price_sale: Money", price_purchase as "price_purchase: Money", quantity, unit as "unit: Unit", ean, category, abc_class as "abc_class: AbcClass", min_stock, supplier, is_kit as "is_kit: bool", created_at, updated_at
             FROM tb_product
             WHERE ean = ?
             LIMIT 10
-            ",
+            "#,
             ean
         )
         .fetch_all(executor)
@@ -119,3 +294,13 @@ impl ProductRepository {
         Ok(product)
     }
 }
+
+fn duplicate_ean_error(error: sqlx::Error, ean: &Option<String>) -> anyhow::Error {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => anyhow!(
+            "EAN {} já está cadastrado em outro produto",
+            ean.as_deref().unwrap_or_default()
+        ),
+        _ => error.into(),
+    }
+}
@@ -1,16 +1,14 @@
-
+use crate::entities::product_sale::{ProductSale, ProductSaleLine, SoldItem};
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::Sqlite;
-use crate::entities::product_sale::ProductSale;
 
 #[derive(Debug)]
 pub struct ProductSaleRepository;
 
 impl ProductSaleRepository {
-    pub fn new() -> Self {
-        Self {}
-    }
-
     pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         entity: &ProductSale,
@@ -33,4 +31,66 @@ impl ProductSaleRepository {
 
         Ok(rec.id.unwrap_or(0))
     }
+
+    /// Lines of the sales made in `[from, to)`, oldest first. Voided sales
+    /// are left out and returned quantities are discounted.
+    pub async fn list_between<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<ProductSaleLine>> {
+        let lines = sqlx::query_as!(
+            ProductSaleLine,
+            r#"
+            SELECT ps.sale_id, s.created_at as sold_at, ps.product_id, p.name,
+                   p.unit as "unit: Unit", ps.price as "price: Money",
+                   ps.quantity - COALESCE(r.quantity, 0) as "quantity!: i64",
+                   ps.total - COALESCE(r.total, 0) as "total!: Money",
+                   p.price_purchase as "cost: Money"
+            FROM tb_product_sale ps
+            JOIN tb_sale s ON s.id = ps.sale_id
+            JOIN tb_product p ON p.id = ps.product_id
+            LEFT JOIN (SELECT product_sale_id, SUM(quantity) AS quantity, SUM(total) AS total
+                       FROM tb_product_sale_return
+                       GROUP BY product_sale_id) r ON r.product_sale_id = ps.id
+            WHERE s.created_at >= ? AND s.created_at < ?
+              AND s.voided_at IS NULL
+              AND ps.quantity > COALESCE(r.quantity, 0)
+            ORDER BY s.created_at, ps.id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(lines)
+    }
+
+    /// Lines of one sale, in the order they were sold.
+    pub async fn list_by_sale<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        sale_id: i64,
+    ) -> Result<Vec<SoldItem>> {
+        let items = sqlx::query_as!(
+            SoldItem,
+            r#"
+            SELECT ps.id as "id!", ps.product_id, p.name, p.unit as "unit: Unit",
+                   ps.price as "price: Money", ps.quantity,
+                   COALESCE((SELECT SUM(r.quantity)
+                             FROM tb_product_sale_return r
+                             WHERE r.product_sale_id = ps.id), 0) as "returned_quantity!: i64",
+                   ps.total as "total: Money"
+            FROM tb_product_sale ps
+            JOIN tb_product p ON p.id = ps.product_id
+            WHERE ps.sale_id = ?
+            ORDER BY ps.id
+            "#,
+            sale_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(items)
+    }
 }
@@ -0,0 +1,29 @@
+use crate::entities::product_sale_return::ProductSaleReturn;
+use anyhow::Result;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct ProductSaleReturnRepository;
+
+impl ProductSaleReturnRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        entity: &ProductSaleReturn,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_product_sale_return (product_sale_id, quantity, total, created_at)
+            VALUES (?, ?, ?, ?)
+            RETURNING id as "id!"
+            "#,
+            entity.product_sale_id,
+            entity.quantity,
+            entity.total,
+            entity.created_at
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.id)
+    }
+}
@@ -1,15 +1,13 @@
 use crate::entities::purchase::Purchase;
+use crate::helpers::money::Money;
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::Sqlite;
 
 #[derive(Debug)]
 pub struct PurchaseRepository;
 
 impl PurchaseRepository {
-    pub fn new() -> Self {
-        Self {}
-    }
-
     pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         purchase: &Purchase,
@@ -29,22 +27,28 @@ impl PurchaseRepository {
         Ok(rec.id)
     }
 
-    pub async fn find_by_id<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+    /// Purchases made in `[from, to)` without supplier titles, paid when the
+    /// goods were received.
+    pub async fn list_paid_on_receipt<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
-        id: i64,
-    ) -> Result<Option<Purchase>> {
-        let entity = sqlx::query_as!(
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<Purchase>> {
+        let entities = sqlx::query_as!(
             Purchase,
-            "
-            SELECT id, total, created_at, updated_at
-            FROM tb_purchase
-            WHERE id = ?
-            ",
-            id
+            r#"
+            SELECT total as "total: Money", created_at
+            FROM tb_purchase p
+            WHERE created_at >= ? AND created_at < ?
+              AND NOT EXISTS (SELECT 1 FROM tb_payable WHERE purchase_id = p.id)
+            ORDER BY created_at, id
+            "#,
+            from,
+            to
         )
-        .fetch_optional(executor)
+        .fetch_all(executor)
         .await?;
 
-        Ok(entity)
+        Ok(entities)
     }
 }
@@ -0,0 +1,119 @@
+use crate::entities::receivable_entry::{ReceivableEntry, ReceivableKind};
+use crate::helpers::money::Money;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct ReceivableRepository;
+
+impl ReceivableRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        entity: &ReceivableEntry,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_receivable_entry (customer_id, sale_id, kind, amount, due_date, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!"
+            "#,
+            entity.customer_id,
+            entity.sale_id,
+            entity.kind,
+            entity.amount,
+            entity.due_date,
+            entity.created_at
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(rec.id)
+    }
+
+    /// Account of a customer, oldest first.
+    pub async fn list_by_customer<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer_id: i64,
+    ) -> Result<Vec<ReceivableEntry>> {
+        let entries = sqlx::query_as!(
+            ReceivableEntry,
+            r#"
+            SELECT customer_id, sale_id, kind as "kind: ReceivableKind",
+                   amount as "amount: Money", due_date, created_at
+            FROM tb_receivable_entry
+            WHERE customer_id = ?
+            ORDER BY created_at, id
+            "#,
+            customer_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Entries of a sale, oldest first.
+    pub async fn list_by_sale<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        sale_id: i64,
+    ) -> Result<Vec<ReceivableEntry>> {
+        let entries = sqlx::query_as!(
+            ReceivableEntry,
+            r#"
+            SELECT customer_id, sale_id, kind as "kind: ReceivableKind",
+                   amount as "amount: Money", due_date, created_at
+            FROM tb_receivable_entry
+            WHERE sale_id = ?
+            ORDER BY created_at, id
+            "#,
+            sale_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Entries of every customer made in `[from, to)`, oldest first.
+    pub async fn list_between<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<ReceivableEntry>> {
+        let entries = sqlx::query_as!(
+            ReceivableEntry,
+            r#"
+            SELECT customer_id, sale_id, kind as "kind: ReceivableKind",
+                   amount as "amount: Money", due_date, created_at
+            FROM tb_receivable_entry
+            WHERE created_at >= ? AND created_at < ?
+            ORDER BY created_at, id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Customers whose account is not settled.
+    pub async fn list_open_customers<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+    ) -> Result<Vec<i64>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT customer_id
+            FROM tb_receivable_entry
+            GROUP BY customer_id
+            HAVING SUM(amount) <> 0
+            "#
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.customer_id).collect())
+    }
+}
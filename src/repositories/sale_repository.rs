@@ -1,27 +1,28 @@
-use crate::entities::purchase::Purchase;
+use crate::entities::payment_method::PaymentMethod;
+use crate::entities::sale::{CustomerSummary, Sale};
+use crate::helpers::money::Money;
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::Sqlite;
-use crate::entities::sale::Sale;
 
 #[derive(Debug)]
 pub struct SaleRepository;
 
 impl SaleRepository {
-    pub fn new() -> Self {
-        Self {}
-    }
-
     pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
         executor: E,
         entity: &Sale,
     ) -> Result<i64> {
         let rec = sqlx::query!(
             r#"
-            INSERT INTO tb_sale (total, created_at)
-            VALUES (?, ?)
+            INSERT INTO tb_sale (total, customer_id, loyalty_discount, payment_method, created_at)
+            VALUES (?, ?, ?, ?, ?)
             RETURNING id
             "#,
             entity.total,
+            entity.customer_id,
+            entity.loyalty_discount,
+            entity.payment_method,
             entity.created_at
         )
         .fetch_one(executor)
@@ -36,11 +37,13 @@ impl SaleRepository {
     ) -> Result<Option<Sale>> {
         let entity = sqlx::query_as!(
             Sale,
-            "
-            SELECT id, total, created_at, updated_at
-            FROM tb_purchase
+            r#"
+            SELECT id, total as "total: Money", created_at, voided_at, customer_id,
+                   loyalty_discount as "loyalty_discount: Money",
+                   payment_method as "payment_method: PaymentMethod"
+            FROM tb_sale
             WHERE id = ?
-            ",
+            "#,
             id
         )
         .fetch_optional(executor)
@@ -48,4 +51,134 @@ impl SaleRepository {
 
         Ok(entity)
     }
+
+    /// Sales made in `[from, to)`, newest first, voided ones included.
+    pub async fn list_between<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<Sale>> {
+        let sales = sqlx::query_as!(
+            Sale,
+            r#"
+            SELECT id as "id!", total as "total: Money", created_at, voided_at, customer_id,
+                   loyalty_discount as "loyalty_discount: Money",
+                   payment_method as "payment_method: PaymentMethod"
+            FROM tb_sale
+            WHERE created_at >= ? AND created_at < ?
+            ORDER BY created_at DESC, id DESC
+            "#,
+            from,
+            to
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(sales)
+    }
+
+    /// Sales made in `[from, to)` with a product whose name contains
+    /// `product` or whose EAN is `product`, newest first.
+    pub async fn list_with_product<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        product: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<Sale>> {
+        let sales = sqlx::query_as!(
+            Sale,
+            r#"
+            SELECT s.id as "id!", s.total as "total: Money", s.created_at, s.voided_at, s.customer_id,
+                   s.loyalty_discount as "loyalty_discount: Money",
+                   payment_method as "payment_method: PaymentMethod"
+            FROM tb_sale s
+            WHERE s.created_at >= ? AND s.created_at < ?
+              AND EXISTS (SELECT 1
+                          FROM tb_product_sale ps
+                          JOIN tb_product p ON p.id = ps.product_id
+                          WHERE ps.sale_id = s.id
+                            AND (p.name LIKE '%' || ? || '%' OR p.ean = ?))
+            ORDER BY s.created_at DESC, s.id DESC
+            "#,
+            from,
+            to,
+            product,
+            product
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(sales)
+    }
+
+    /// Sales of a customer, newest first, voided ones included.
+    pub async fn list_by_customer<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer_id: i64,
+    ) -> Result<Vec<Sale>> {
+        let sales = sqlx::query_as!(
+            Sale,
+            r#"
+            SELECT id as "id!", total as "total: Money", created_at, voided_at, customer_id,
+                   loyalty_discount as "loyalty_discount: Money",
+                   payment_method as "payment_method: PaymentMethod"
+            FROM tb_sale
+            WHERE customer_id = ?
+            ORDER BY created_at DESC, id DESC
+            "#,
+            customer_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(sales)
+    }
+
+    /// What a customer spent, net of returns, and when they last bought.
+    /// Voided sales are left out.
+    pub async fn customer_summary<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        customer_id: i64,
+    ) -> Result<CustomerSummary> {
+        let summary = sqlx::query_as!(
+            CustomerSummary,
+            r#"
+            SELECT COUNT(s.id) as "sales!: i64",
+                   COALESCE(SUM(s.total), 0) - COALESCE(SUM(r.total), 0) as "total_spent!: Money",
+                   MAX(s.created_at) as "last_visit: NaiveDateTime"
+            FROM tb_sale s
+            LEFT JOIN (SELECT ps.sale_id, SUM(ret.total) AS total
+                       FROM tb_product_sale_return ret
+                       JOIN tb_product_sale ps ON ps.id = ret.product_sale_id
+                       GROUP BY ps.sale_id) r ON r.sale_id = s.id
+            WHERE s.customer_id = ? AND s.voided_at IS NULL
+            "#,
+            customer_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(summary)
+    }
+
+    pub async fn set_voided<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        id: i64,
+        voided_at: NaiveDateTime,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE tb_sale
+            SET voided_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            voided_at,
+            voided_at,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
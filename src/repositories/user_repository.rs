@@ -0,0 +1,34 @@
+use crate::entities::user::User;
+use anyhow::{anyhow, Result};
+use sqlx::Sqlite;
+
+#[derive(Debug)]
+pub struct UserRepository;
+
+impl UserRepository {
+    pub async fn insert<'e, E: sqlx::Executor<'e, Database = Sqlite>>(
+        executor: E,
+        user: &User,
+    ) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO tb_user (username, password_hash, created_at)
+            VALUES (?, ?, ?)
+            RETURNING id
+            "#,
+            user.username,
+            user.password_hash,
+            user.created_at
+        )
+        .fetch_one(executor)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                anyhow!("Usuário {} já está cadastrado", user.username)
+            }
+            _ => e.into(),
+        })?;
+
+        Ok(rec.id)
+    }
+}
@@ -0,0 +1,210 @@
+use crate::components::notification::Notification;
+use crate::entities::abc_class::AbcClass;
+use crate::helpers::format_quantity;
+use crate::services::abc_service::{AbcCriterion, AbcItem, AbcService};
+use chrono::{Days, Local, NaiveDate};
+use iced::widget::{
+    button, column, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
+use iced::{Alignment, Element, Length, Task};
+use std::sync::Arc;
+
+const POSITION_WIDTH: f32 = 50.0;
+const DATE_WIDTH: f32 = 160.0;
+const CRITERION_WIDTH: f32 = 160.0;
+const UNIT_WIDTH: f32 = 40.0;
+const QUANTITY_WIDTH: f32 = 100.0;
+const PRICE_WIDTH: f32 = 120.0;
+const PERCENT_WIDTH: f32 = 90.0;
+const CLASS_WIDTH: f32 = 60.0;
+const DATE_FORMAT: &str = "%d/%m/%Y";
+/// Days covered by the curve when the screen opens.
+const DEFAULT_PERIOD_DAYS: u64 = 90;
+
+/// ABC curve of the products sold in a period, by revenue or by margin. The
+/// classes can be saved on the products.
+#[derive(Debug)]
+pub struct State {
+    abc_service: Arc<dyn AbcService>,
+    from: String,
+    to: String,
+    criterion: AbcCriterion,
+    items: Vec<AbcItem>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FromChange(String),
+    ToChange(String),
+    CriterionChange(AbcCriterion),
+    Search,
+    CurveLoaded(Result<Vec<AbcItem>, String>),
+    SaveClasses,
+    ClassesSaved(Result<(), String>),
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(abc_service: Arc<dyn AbcService>) -> Self {
+        let today = Local::now().date_naive();
+        let from = today - Days::new(DEFAULT_PERIOD_DAYS);
+        Self {
+            abc_service,
+            from: from.format(DATE_FORMAT).to_string(),
+            to: today.format(DATE_FORMAT).to_string(),
+            criterion: AbcCriterion::default(),
+            items: vec![],
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let filters = row![
+            text_input("DE (DD/MM/AAAA)", &self.from)
+                .on_input(Message::FromChange)
+                .on_submit(Message::Search)
+                .width(Length::Fixed(DATE_WIDTH)),
+            text_input("ATÉ (DD/MM/AAAA)", &self.to)
+                .on_input(Message::ToChange)
+                .on_submit(Message::Search)
+                .width(Length::Fixed(DATE_WIDTH)),
+            text("POR"),
+            pick_list(
+                AbcCriterion::ALL,
+                Some(self.criterion),
+                Message::CriterionChange
+            )
+            .width(Length::Fixed(CRITERION_WIDTH)),
+            button("BUSCAR").on_press_maybe((!self.busy).then_some(Message::Search)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let header = row![
+            text("#").width(Length::Fixed(POSITION_WIDTH)),
+            text("PRODUTO").width(Length::Fill),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
+            text("QTD").width(Length::Fixed(QUANTITY_WIDTH)),
+            text("FATURAMENTO").width(Length::Fixed(PRICE_WIDTH)),
+            text("MARGEM").width(Length::Fixed(PRICE_WIDTH)),
+            text("%").width(Length::Fixed(PERCENT_WIDTH)),
+            text("% ACUM.").width(Length::Fixed(PERCENT_WIDTH)),
+            text("CLASSE").width(Length::Fixed(CLASS_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (position, item) in self.items.iter().enumerate() {
+            list = list.push(
+                row![
+                    text(position + 1).width(Length::Fixed(POSITION_WIDTH)),
+                    text(&item.name).width(Length::Fill),
+                    text(item.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                    text(format_quantity(item.quantity, item.unit))
+                        .width(Length::Fixed(QUANTITY_WIDTH)),
+                    text(item.revenue.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(item.margin.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(format!("{}%", item.share)).width(Length::Fixed(PERCENT_WIDTH)),
+                    text(format!("{}%", item.cumulative)).width(Length::Fixed(PERCENT_WIDTH)),
+                    text(item.class.as_str()).width(Length::Fixed(CLASS_WIDTH)),
+                ]
+                .spacing(16),
+            );
+        }
+
+        let count = |class| self.items.iter().filter(|item| item.class == class).count();
+        column![
+            text("CURVA ABC").size(24),
+            filters,
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!(
+                    "A: {} - B: {} - C: {} PRODUTO(S)",
+                    count(AbcClass::A),
+                    count(AbcClass::B),
+                    count(AbcClass::C)
+                )),
+                horizontal_space(),
+                text("Produtos sem venda no período ficam como C"),
+                button("SALVAR CLASSIFICAÇÃO").on_press_maybe(
+                    (!self.busy && !self.items.is_empty()).then_some(Message::SaveClasses)
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::FromChange(value) => self.from = value,
+            Message::ToChange(value) => self.to = value,
+            Message::CriterionChange(criterion) => {
+                self.criterion = criterion;
+                return Task::done(Message::Search);
+            }
+            Message::Search => {
+                let (Some(from), Some(to)) = (parse_date(&self.from), parse_date(&self.to)) else {
+                    return notify_error("Informe as datas no formato DD/MM/AAAA".to_string());
+                };
+                let abc_service = self.abc_service.clone();
+                let criterion = self.criterion;
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        abc_service
+                            .curve(from, to, criterion)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::CurveLoaded,
+                );
+            }
+            Message::CurveLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(items) => self.items = items,
+                    Err(e) => return notify_error(format!("Erro ao calcular a curva: {}", e)),
+                }
+            }
+            Message::SaveClasses => {
+                let abc_service = self.abc_service.clone();
+                let items = self.items.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        abc_service
+                            .save_classes(&items)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ClassesSaved,
+                );
+            }
+            Message::ClassesSaved(result) => {
+                self.busy = false;
+                let notification = match result {
+                    Ok(()) => Notification::success("Classificação ABC salva nos produtos"),
+                    Err(e) => Notification::error(format!("Erro ao salvar a classificação: {}", e)),
+                };
+                return Task::done(Message::Notify(notification));
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+}
+
+fn notify_error(message: String) -> Task<Message> {
+    Task::done(Message::Notify(Notification::error(message)))
+}
@@ -1,12 +1,19 @@
+use crate::components::notification::Notification;
+use crate::config::MAX_MARKUP;
 use crate::entities::product::Product;
-use crate::helpers::{
-    f64_to_i64, format_int_to_decimal, i64_to_f64, validate_float, validate_float_range,
-    validate_int,
+use crate::entities::unit::Unit;
+use crate::helpers::barcode::{is_valid_gtin, validate_ean_input};
+use crate::helpers::money::{Money, Percent};
+use crate::helpers::{format_quantity, parse_quantity, payable, validate_quantity, QUANTITY_SCALE};
+use crate::printing::labels::Label;
+use crate::services::product_purchase_service::{
+    PaymentTerms, ProductPurchaseService, PurchaseSuggestion,
 };
-use crate::services::product_purchase_service::ProductPurchaseService;
 use crate::services::product_service::ProductService;
 use chrono::Local;
-use iced::widget::{button, column, horizontal_space, row, scrollable, text, text_input};
+use iced::widget::{
+    button, column, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
 use iced::{Alignment, Element, Length, Task};
 use std::sync::Arc;
 
@@ -15,22 +22,42 @@ const ID_WIDTH: f32 = 50.0;
 const EAN_WIDTH: f32 = 100.0;
 static NAME_WIDTH: Length = Length::Fill;
 const QNTD_WIDTH: f32 = 70.0;
+const UNIT_WIDTH: f32 = 70.0;
 const PRICE_UNIT_WIDTH: f32 = 100.0;
 const PRICE_SALE_WIDTH: f32 = 100.0;
 const PERCENTUAL_WIDTH: f32 = 50.0;
 const TOTAL_WIDTH: f32 = 120.0;
 const TOTAL_SALE_WIDTH: f32 = 120.0;
+const DOCUMENT_WIDTH: f32 = 160.0;
+const TERMS_WIDTH: f32 = 320.0;
+const DAYS_WIDTH: f32 = 180.0;
+const CLASS_WIDTH: f32 = 60.0;
+const STOCK_WIDTH: f32 = 100.0;
+/// Days of sales the suggestion looks at when the screen opens.
+const DEFAULT_HISTORY_DAYS: u32 = 30;
+/// Days the suggested order should last when the screen opens.
+const DEFAULT_COVERAGE_DAYS: u32 = 15;
 
 #[derive(Debug)]
 pub struct State {
-    product_purchase_service: Arc<ProductPurchaseService>,
-    product_service: Arc<ProductService>,
+    product_purchase_service: Arc<dyn ProductPurchaseService>,
+    product_service: Arc<dyn ProductService>,
     products: Vec<ProductItem>,
-    total: String,
     show_search: bool,
     search_index: Option<usize>,
     search_text: String,
     search_products: Vec<Product>,
+    received: Vec<Product>,
+    default_markup: Percent,
+    supplier: String,
+    document: String,
+    /// Due date or days of the installments; empty when paid on receipt.
+    terms: String,
+    show_suggestion: bool,
+    history_days: String,
+    coverage_days: String,
+    suggestions: Vec<PurchaseSuggestion>,
+    busy: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -38,54 +65,75 @@ pub enum Message {
     EanChange(usize, String),
     NameChange(usize, String),
     QuantityChange(usize, String),
+    UnitChange(usize, Unit),
     PriceUnitChange(usize, String),
     PriceSaleChange(usize, String),
     PercentualChange(usize, String),
+    SupplierChange(String),
+    DocumentChange(String),
+    TermsChange(String),
+    OpenSuggestion,
+    CloseSuggestion,
+    HistoryDaysChange(String),
+    CoverageDaysChange(String),
+    CalculateSuggestion,
+    SuggestionLoaded(Result<Vec<PurchaseSuggestion>, String>),
+    ApplySuggestion,
     AddProduct,
     FinishPurchase,
     RemoveProduct(usize),
     SearchProduct(usize),
     CloseSearch,
     SearchTextChange(String),
-    ChangeProductsSearch(Vec<Product>),
+    ChangeProductsSearch(Result<Vec<Product>, String>),
     ProductSelected(usize, Product),
+    PurchaseFinished(Result<Vec<Product>, String>),
+    PrintLabels(Vec<Label>),
+    Notify(Notification),
 }
 
 impl State {
     pub fn new(
-        product_purchase_service: Arc<ProductPurchaseService>,
-        product_service: Arc<ProductService>,
+        product_purchase_service: Arc<dyn ProductPurchaseService>,
+        product_service: Arc<dyn ProductService>,
+        default_markup: Percent,
     ) -> Self {
         Self {
             product_purchase_service,
             product_service,
-            products: vec![ProductItem {
-                id: None,
-                ean: None,
-                name: "".to_string(),
-                quantity: "1".to_string(),
-                price_unit: "0,00".to_string(),
-                price_sale: "0,00".to_string(),
-                percentual: "30,0".to_string(),
-                total: "R$ 0,00".to_string(),
-                total_sale: "R$ 0,00".to_string(),
-            }],
-            total: "R$ 0,00".to_string(),
+            products: vec![ProductItem::new(default_markup)],
             show_search: false,
             search_index: None,
             search_text: "".to_string(),
             search_products: vec![],
+            received: vec![],
+            default_markup,
+            supplier: String::new(),
+            document: String::new(),
+            terms: String::new(),
+            show_suggestion: false,
+            history_days: DEFAULT_HISTORY_DAYS.to_string(),
+            coverage_days: DEFAULT_COVERAGE_DAYS.to_string(),
+            suggestions: vec![],
+            busy: false,
         }
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        if (self.show_search) {
+        if self.show_search {
             self.search()
+        } else if self.show_suggestion {
+            self.suggestion()
         } else {
-            column![
-                self.product_list(),
-                row![
-                    button("ADICIONAR ITEM").on_press(Message::AddProduct),
+            column![self.product_list()]
+                .push_maybe(self.received_bar())
+                .push(self.payment_terms())
+                .push(row![
+                    row![
+                        button("ADICIONAR ITEM").on_press(Message::AddProduct),
+                        button("SUGERIR PEDIDO").on_press(Message::OpenSuggestion),
+                    ]
+                    .spacing(8),
                     horizontal_space(),
                     row![
                         text("TOTAL: "),
@@ -94,21 +142,20 @@ impl State {
                     ]
                     .spacing(8)
                     .align_y(Alignment::Center),
-                ]
-            ]
-            .spacing(16)
-            .align_x(Alignment::End)
-            .into()
+                ])
+                .spacing(16)
+                .align_x(Alignment::End)
+                .into()
         }
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::EanChange(index, value) => {
-                if validate_int(&value) {
-                    if let Some(product) = self.products.get_mut(index) {
-                        product.ean = Some(value);
-                    }
+                if validate_ean_input(&value)
+                    && let Some(product) = self.products.get_mut(index)
+                {
+                    product.ean = Some(value);
                 }
             }
             Message::NameChange(index, value) => {
@@ -117,57 +164,135 @@ impl State {
                 }
             }
             Message::QuantityChange(index, value) => {
-                if validate_int(&value) {
-                    if let Some(product) = self.products.get_mut(index) {
-                        product.quantity = value;
-                        product.total = calculate_total(product);
-                        product.total_sale = calculate_total_sale(product);
+                if let Some(product) = self.products.get_mut(index)
+                    && validate_quantity(&value, product.unit)
+                {
+                    product.quantity = value;
+                    product.total = calculate_total(product);
+                    product.total_sale = calculate_total_sale(product);
+                }
+            }
+            Message::UnitChange(index, unit) => {
+                if let Some(product) = self.products.get_mut(index) {
+                    product.unit = unit;
+                    if !validate_quantity(&product.quantity, unit) {
+                        product.quantity = "1".to_string();
                     }
+                    product.total = calculate_total(product);
+                    product.total_sale = calculate_total_sale(product);
                 }
             }
             Message::PriceUnitChange(index, value) => {
-                if validate_float(&value) {
-                    if let Some(product) = self.products.get_mut(index) {
-                        product.price_unit = value;
-                        product.total = calculate_total(product);
-                        product.price_sale =
-                            format!("{:.2}", calculate_price_sale(product)).replace(".", ",");
-                        product.total_sale = calculate_total_sale(product);
-                    }
+                if validate_price(&value)
+                    && let Some(product) = self.products.get_mut(index)
+                {
+                    product.price_unit = value;
+                    product.total = calculate_total(product);
+                    product.price_sale = calculate_price_sale(product).to_decimal_string();
+                    product.total_sale = calculate_total_sale(product);
                 }
             }
             Message::PriceSaleChange(index, value) => {
-                if validate_float(&value) {
-                    if let Some(product) = self.products.get_mut(index) {
-                        product.price_sale = value;
-                        let percentual = calculate_percentual(product);
-                        product.percentual = format!("{:.2}", percentual).replace(".", ",");
-                        product.total_sale = calculate_total_sale(product);
-                    }
+                if validate_price(&value)
+                    && let Some(product) = self.products.get_mut(index)
+                {
+                    product.price_sale = value;
+                    product.percentual = calculate_percentual(product).to_string();
+                    product.total_sale = calculate_total_sale(product);
                 }
             }
             Message::PercentualChange(index, value) => {
-                if validate_float_range(&value, 0.0, 100.0) {
-                    if let Some(product) = self.products.get_mut(index) {
-                        product.percentual = value;
-                        let price_sale = calculate_price_sale(product);
-                        product.price_sale = format!("{:.2}", price_sale).replace(".", ",");
-                        product.total_sale = calculate_total_sale(product);
+                if validate_percentual(&value)
+                    && let Some(product) = self.products.get_mut(index)
+                {
+                    product.percentual = value;
+                    product.price_sale = calculate_price_sale(product).to_decimal_string();
+                    product.total_sale = calculate_total_sale(product);
+                }
+            }
+            Message::SupplierChange(value) => self.supplier = value.to_uppercase(),
+            Message::DocumentChange(value) => self.document = value,
+            Message::TermsChange(value) => {
+                if value
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '/' || c == ' ')
+                {
+                    self.terms = value;
+                }
+            }
+            Message::OpenSuggestion => self.show_suggestion = true,
+            Message::CloseSuggestion => self.show_suggestion = false,
+            Message::HistoryDaysChange(value) => {
+                if value.chars().all(|c| c.is_ascii_digit()) {
+                    self.history_days = value;
+                }
+            }
+            Message::CoverageDaysChange(value) => {
+                if value.chars().all(|c| c.is_ascii_digit()) {
+                    self.coverage_days = value;
+                }
+            }
+            Message::CalculateSuggestion => {
+                let (Ok(history_days), Ok(coverage_days)) = (
+                    self.history_days.parse::<u32>(),
+                    self.coverage_days.parse::<u32>(),
+                ) else {
+                    return Task::done(Message::Notify(Notification::warning(
+                        "Informe os dias de venda e de cobertura",
+                    )));
+                };
+                let product_purchase_service = self.product_purchase_service.clone();
+                let supplier = Some(self.supplier.clone());
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        product_purchase_service
+                            .suggest_order(history_days, coverage_days, supplier)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::SuggestionLoaded,
+                );
+            }
+            Message::SuggestionLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(suggestions) => {
+                        self.suggestions = suggestions;
+                        if self.suggestions.is_empty() {
+                            return Task::done(Message::Notify(Notification::success(
+                                "O estoque atual cobre o período informado",
+                            )));
+                        }
+                    }
+                    Err(e) => {
+                        return Task::done(Message::Notify(Notification::error(format!(
+                            "Erro ao sugerir o pedido: {}",
+                            e
+                        ))));
                     }
                 }
             }
-            Message::AddProduct => {
-                self.products.push(ProductItem {
-                    id: None,
-                    ean: None,
-                    name: "".to_string(),
-                    quantity: "1".to_string(),
-                    price_unit: "0,00".to_string(),
-                    price_sale: "0,00".to_string(),
-                    percentual: "30,0".to_string(),
-                    total: "R$ 0,00".to_string(),
-                    total_sale: "R$ 0,00".to_string(),
+            Message::ApplySuggestion => {
+                // Blank lines and products in the suggestion are replaced
+                self.products.retain(|item| {
+                    !item.name.trim().is_empty()
+                        && !self
+                            .suggestions
+                            .iter()
+                            .any(|suggestion| item.id == Some(suggestion.product.id))
                 });
+                for suggestion in self.suggestions.drain(..) {
+                    let mut item = ProductItem::from_product(&suggestion.product);
+                    item.quantity = format_quantity(suggestion.quantity, suggestion.product.unit);
+                    item.total = calculate_total(&item);
+                    item.total_sale = calculate_total_sale(&item);
+                    self.products.push(item);
+                }
+                self.show_suggestion = false;
+            }
+            Message::AddProduct => {
+                self.products.push(ProductItem::new(self.default_markup));
             }
             Message::RemoveProduct(index) => {
                 if index < self.products.len() {
@@ -194,29 +319,80 @@ impl State {
                     let product_service = self.product_service.clone();
                     let search_text = self.search_text.clone();
                     return Task::perform(
-                        async move { product_service.search_products_by_name(&search_text).await },
+                        async move {
+                            product_service
+                                .search_products_by_name(&search_text)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
                         Message::ChangeProductsSearch,
                     );
                 }
             }
-            Message::ChangeProductsSearch(value) => {
-                self.search_products = value;
-            }
+            Message::ChangeProductsSearch(result) => match result {
+                Ok(products) => self.search_products = products,
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao buscar produtos: {}",
+                        e
+                    ))));
+                }
+            },
             Message::FinishPurchase => {
-                let products = self.products.iter().map(|x| x.to_product()).collect();
+                if let Err(e) = self.validate_prices().and_then(|()| self.validate_eans()) {
+                    return Task::done(Message::Notify(Notification::warning(e)));
+                }
+                let terms = match self.terms() {
+                    Ok(terms) => terms,
+                    Err(e) => return Task::done(Message::Notify(Notification::warning(e))),
+                };
+                let supplier = self.supplier.trim();
+                let products = self
+                    .products
+                    .iter()
+                    .map(|x| Product {
+                        supplier: (!supplier.is_empty()).then(|| supplier.to_string()),
+                        ..x.to_product()
+                    })
+                    .collect();
                 let product_purchase_service = self.product_purchase_service.clone();
                 return Task::perform(
-                    async move { product_purchase_service.add_purchase(products).await },
-                    |_| Message::CloseSearch,
+                    async move {
+                        product_purchase_service
+                            .add_purchase(products, terms)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::PurchaseFinished,
                 );
             }
+            Message::PurchaseFinished(result) => match result {
+                Ok(received) => {
+                    self.products = vec![ProductItem::new(self.default_markup)];
+                    self.received = received;
+                    self.supplier.clear();
+                    self.document.clear();
+                    self.terms.clear();
+                    return Task::done(Message::Notify(Notification::success(
+                        "Compra finalizada com sucesso!",
+                    )));
+                }
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao finalizar compra: {}",
+                        e
+                    ))));
+                }
+            },
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+            // Handled by the application, which opens the labels screen
+            Message::PrintLabels(_) => {}
             Message::ProductSelected(index, value) => {
-                if let Some(search_index) = self.search_index {
-                    if let Some(product_item) = self.products.get_mut(search_index) {
-                        *product_item = ProductItem::from_product(&value);
-                        product_item.total = calculate_total(product_item);
-                        product_item.total_sale = calculate_total_sale(product_item);
-                    }
+                if let Some(product_item) = self.products.get_mut(index) {
+                    *product_item = ProductItem::from_product(&value);
+                    product_item.total = calculate_total(product_item);
+                    product_item.total_sale = calculate_total_sale(product_item);
                 }
                 self.show_search = false;
                 self.search_index = None;
@@ -235,6 +411,7 @@ impl State {
             text("EAN").width(Length::Fixed(EAN_WIDTH)),
             text("PRODUTO").width(NAME_WIDTH),
             text("QNTD").width(Length::Fixed(QNTD_WIDTH)),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
             text("P. UNIT.").width(Length::Fixed(PRICE_UNIT_WIDTH)),
             text("P. VENDA").width(Length::Fixed(PRICE_SALE_WIDTH)),
             text("%").width(Length::Fixed(PERCENTUAL_WIDTH)),
@@ -264,6 +441,10 @@ impl State {
                     text_input("Quantidade", &product.quantity)
                         .width(Length::Fixed(QNTD_WIDTH))
                         .on_input(move |value| Message::QuantityChange(index, value)),
+                    pick_list(Unit::ALL, Some(product.unit), move |unit| {
+                        Message::UnitChange(index, unit)
+                    })
+                    .width(Length::Fixed(UNIT_WIDTH)),
                     text_input("Preço Unit.", &product.price_unit)
                         .width(Length::Fixed(PRICE_UNIT_WIDTH))
                         .on_input(move |value| Message::PriceUnitChange(index, value)),
@@ -300,7 +481,7 @@ impl State {
                             text(product.ean.clone().unwrap_or_default())
                                 .width(Length::Fixed(EAN_WIDTH)),
                             text(&product.name).width(NAME_WIDTH),
-                            text(format_int_to_decimal(product.price_sale))
+                            text(product.price_sale.to_string())
                                 .width(Length::Fixed(PRICE_UNIT_WIDTH)),
                         ]
                         .spacing(16)
@@ -325,18 +506,182 @@ impl State {
         .into()
     }
 
-    fn total(&self) -> String {
-        let mut total = 0;
+    fn suggestion(&self) -> Element<'_, Message> {
+        let filters = row![
+            button("FECHAR").on_press(Message::CloseSuggestion),
+            text_input("DIAS DE VENDA", &self.history_days)
+                .on_input(Message::HistoryDaysChange)
+                .on_submit(Message::CalculateSuggestion)
+                .width(Length::Fixed(DAYS_WIDTH)),
+            text_input("COBERTURA (DIAS)", &self.coverage_days)
+                .on_input(Message::CoverageDaysChange)
+                .on_submit(Message::CalculateSuggestion)
+                .width(Length::Fixed(DAYS_WIDTH)),
+            text_input("FORNECEDOR (TODOS)", &self.supplier)
+                .on_input(Message::SupplierChange)
+                .on_submit(Message::CalculateSuggestion),
+            button("CALCULAR").on_press_maybe((!self.busy).then_some(Message::CalculateSuggestion)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let header = row![
+            text("CLASSE").width(Length::Fixed(CLASS_WIDTH)),
+            text("PRODUTO").width(NAME_WIDTH),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
+            text("ESTOQUE").width(Length::Fixed(STOCK_WIDTH)),
+            text("MÍNIMO").width(Length::Fixed(STOCK_WIDTH)),
+            text("VENDIDO").width(Length::Fixed(STOCK_WIDTH)),
+            text("MÉDIA/DIA").width(Length::Fixed(STOCK_WIDTH)),
+            text("SUGESTÃO").width(Length::Fixed(STOCK_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for suggestion in &self.suggestions {
+            let product = &suggestion.product;
+            list = list.push(
+                row![
+                    text(product.abc_class.map_or("-", |class| class.as_str()))
+                        .width(Length::Fixed(CLASS_WIDTH)),
+                    text(&product.name).width(NAME_WIDTH),
+                    text(product.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                    text(format_quantity(product.quantity, product.unit))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                    text(format_quantity(product.min_stock, product.unit))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                    text(format_quantity(suggestion.sold, product.unit))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                    text(format_quantity(suggestion.daily_average, Unit::Kg))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                    text(format_quantity(suggestion.quantity, product.unit))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                ]
+                .spacing(16),
+            );
+        }
+
+        column![
+            text("SUGESTÃO DE COMPRA").size(24),
+            filters,
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("{} PRODUTO(S) A COMPRAR", self.suggestions.len())),
+                horizontal_space(),
+                text("Os itens podem ser ajustados antes de finalizar a compra"),
+                button("USAR SUGESTÃO").on_press_maybe(
+                    (!self.suggestions.is_empty()).then_some(Message::ApplySuggestion)
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn received_bar(&self) -> Option<Element<'_, Message>> {
+        if self.received.is_empty() {
+            return None;
+        }
+        let labels = self.received.iter().map(Label::for_received).collect();
+        Some(
+            row![
+                text(format!(
+                    "COMPRA FINALIZADA: {} PRODUTO(S) RECEBIDO(S)",
+                    self.received.len()
+                )),
+                button("IMPRIMIR ETIQUETAS DA COMPRA").on_press(Message::PrintLabels(labels)),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
+
+    fn payment_terms(&self) -> Element<'_, Message> {
+        let installments = payable::parse_terms(&self.terms, Local::now().date_naive())
+            .map(|due_dates| format!("{} PARCELA(S)", due_dates.len()))
+            .unwrap_or_else(|| "À VISTA".to_string());
+        row![
+            text_input("FORNECEDOR", &self.supplier).on_input(Message::SupplierChange),
+            text_input("NOTA FISCAL", &self.document)
+                .on_input(Message::DocumentChange)
+                .width(Length::Fixed(DOCUMENT_WIDTH)),
+            text_input("VENCIMENTO (DD/MM/AAAA) OU PRAZOS (30/60/90)", &self.terms)
+                .on_input(Message::TermsChange)
+                .width(Length::Fixed(TERMS_WIDTH)),
+            text(installments),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    /// Payment terms typed for the purchase, `None` when it was paid on
+    /// receipt.
+    fn terms(&self) -> Result<Option<PaymentTerms>, String> {
+        let terms = self.terms.trim();
+        if terms.is_empty() {
+            return Ok(None);
+        }
+        let due_dates = payable::parse_terms(terms, Local::now().date_naive()).ok_or_else(|| {
+            format!(
+                "Prazo \"{}\" inválido: informe o vencimento (DD/MM/AAAA) ou os dias das parcelas (30/60/90)",
+                terms
+            )
+        })?;
+        let document = self.document.trim();
+        Ok(Some(PaymentTerms {
+            supplier: self.supplier.trim().to_string(),
+            document: (!document.is_empty()).then(|| document.to_string()),
+            due_dates,
+        }))
+    }
+
+    /// Every product needs a cost and a sale price; a blank one is not zero.
+    fn validate_prices(&self) -> Result<(), String> {
+        for product in &self.products {
+            if Money::parse(&product.price_unit).is_none() {
+                return Err(format!("Informe o preço de custo de {}", product.name));
+            }
+            if Money::parse(&product.price_sale).is_none() {
+                return Err(format!("Informe o preço de venda de {}", product.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Barcodes are optional, but typed ones must be valid GTINs and unique
+    /// within the purchase.
+    fn validate_eans(&self) -> Result<(), String> {
+        let mut eans: Vec<&str> = vec![];
         for product in &self.products {
-            let quantity = product.quantity.parse::<i64>().unwrap_or(0);
-            let price_unit = product
-                .price_unit
-                .replace(",", ".")
-                .parse::<f64>()
-                .unwrap_or(0.0);
-            total += (quantity as f64 * price_unit * 100.0) as i64;
+            let Some(ean) = product.ean.as_deref().filter(|ean| !ean.is_empty()) else {
+                continue;
+            };
+            if !is_valid_gtin(ean) && product.stored_ean.as_deref() != Some(ean) {
+                return Err(format!("EAN {} inválido", ean));
+            }
+            if eans.contains(&ean) {
+                return Err(format!("EAN {} repetido na compra", ean));
+            }
+            eans.push(ean);
         }
-        format_int_to_decimal(total)
+        Ok(())
+    }
+
+    fn total(&self) -> String {
+        self.products
+            .iter()
+            .fold(Money::ZERO, |total, product| {
+                let quantity = parse_quantity(&product.quantity, product.unit).unwrap_or(0);
+                total.saturating_add(
+                    parse_price(&product.price_unit).saturating_times_quantity(quantity),
+                )
+            })
+            .to_string()
     }
 }
 
@@ -344,8 +689,13 @@ impl State {
 struct ProductItem {
     id: Option<i64>,
     ean: Option<String>,
+    /// EAN the product already had, accepted even if it predates the GTIN
+    /// validation.
+    stored_ean: Option<String>,
+    category: Option<String>,
     name: String,
     quantity: String,
+    unit: Unit,
     price_unit: String,
     price_sale: String,
     percentual: String,
@@ -354,101 +704,101 @@ struct ProductItem {
 }
 
 impl ProductItem {
+    fn new(markup: Percent) -> Self {
+        Self {
+            id: None,
+            ean: None,
+            stored_ean: None,
+            category: None,
+            name: "".to_string(),
+            quantity: "1".to_string(),
+            unit: Unit::Un,
+            price_unit: "0,00".to_string(),
+            price_sale: "0,00".to_string(),
+            percentual: markup.to_string(),
+            total: Money::ZERO.to_string(),
+            total_sale: Money::ZERO.to_string(),
+        }
+    }
+
     fn to_product(&self) -> Product {
-        let price_sale = self
-            .price_sale
-            .replace(",", ".")
-            .parse::<f64>()
-            .unwrap_or(0.0);
-        let price_purchase = self
-            .price_unit
-            .replace(",", ".")
-            .parse::<f64>()
-            .unwrap_or(0.0);
-        let quantity = self.quantity.parse::<i64>().unwrap_or(0);
-        Product::new(
-            self.id.unwrap_or(0),
-            self.name.clone(),
-            f64_to_i64(price_sale),
-            f64_to_i64(price_purchase),
-            quantity,
-            self.ean.clone(),
-            Local::now().naive_local(),
-        )
+        let quantity = parse_quantity(&self.quantity, self.unit).unwrap_or(0);
+        Product {
+            category: self.category.clone(),
+            ..Product::new(
+                self.id.unwrap_or(0),
+                self.name.clone(),
+                parse_price(&self.price_sale),
+                parse_price(&self.price_unit),
+                quantity,
+                self.unit,
+                self.ean.clone(),
+                Local::now().naive_local(),
+            )
+        }
     }
 
     fn from_product(product: &Product) -> Self {
-        let price_sale = i64_to_f64(product.price_sale);
-        let price_purchase = i64_to_f64(product.price_purchase);
-        let percentual = if price_purchase == 0.0 {
-            0.0
-        } else {
-            ((price_sale - price_purchase) / price_purchase) * 100.0
-        };
+        let percentual = product
+            .price_sale
+            .markup_over(product.price_purchase)
+            .unwrap_or_default();
         Self {
             id: Some(product.id),
             ean: product.ean.clone(),
+            stored_ean: product.ean.clone(),
+            category: product.category.clone(),
             name: product.name.clone(),
-            quantity: "1".to_string(),
-            price_unit: format!("{:.2}", price_purchase).replace(".", ","),
-            price_sale: format!("{:.2}", price_sale).replace(".", ","),
-            percentual: format!("{:.2}", percentual).replace(".", ","),
-            total: format_int_to_decimal(product.price_sale * product.quantity),
-            total_sale: format_int_to_decimal(product.price_sale * product.quantity),
+            quantity: format_quantity(QUANTITY_SCALE, product.unit),
+            unit: product.unit,
+            price_unit: product.price_purchase.to_decimal_string(),
+            price_sale: product.price_sale.to_decimal_string(),
+            percentual: percentual.to_string(),
+            total: product.price_purchase.to_string(),
+            total_sale: product.price_sale.to_string(),
         }
     }
 }
 
+/// Prices typed on the purchase can't be negative.
+fn validate_price(value: &str) -> bool {
+    !value.contains('-') && Money::validate_input(value)
+}
+
+fn validate_percentual(value: &str) -> bool {
+    !value.contains('-')
+        && Percent::validate_input(value)
+        && Percent::parse(value).is_none_or(|percent| percent <= Percent::from_f64(MAX_MARKUP))
+}
+
+fn parse_price(value: &str) -> Money {
+    Money::parse(value).unwrap_or_default()
+}
+
 fn calculate_total(product: &ProductItem) -> String {
-    let quantity = product.quantity.parse::<f64>().unwrap_or(0.0);
-    let price_unit = product
-        .price_unit
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let total = f64_to_i64(quantity * price_unit);
-    format_int_to_decimal(total)
+    let quantity = parse_quantity(&product.quantity, product.unit).unwrap_or(0);
+    parse_price(&product.price_unit)
+        .saturating_times_quantity(quantity)
+        .to_string()
 }
 
-fn calculate_price_sale(product: &ProductItem) -> f64 {
-    let price_unit = product
-        .price_unit
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let percentual = product
-        .percentual
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    price_unit + (price_unit * percentual / 100.0)
+fn calculate_price_sale(product: &ProductItem) -> Money {
+    let price_unit = parse_price(&product.price_unit);
+    let percentual = Percent::parse(&product.percentual).unwrap_or_default();
+    price_unit
+        .checked_with_markup(percentual)
+        .unwrap_or(price_unit)
 }
 
-fn calculate_percentual(product: &ProductItem) -> f64 {
-    let price_unit = product
-        .price_unit
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let price_sale = product
-        .price_sale
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    if price_unit == 0.0 {
-        0.0
-    } else {
-        ((price_sale - price_unit) / price_unit) * 100.0
-    }
+fn calculate_percentual(product: &ProductItem) -> Percent {
+    parse_price(&product.price_sale)
+        .markup_over(parse_price(&product.price_unit))
+        .unwrap_or_default()
 }
 
 fn calculate_total_sale(product: &ProductItem) -> String {
-    let quantity = product.quantity.parse::<f64>().unwrap_or(0.0);
-    let price_sale = product
-        .price_sale
-        .replace(",", ".")
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let total_sale = f64_to_i64(quantity * price_sale);
-    format_int_to_decimal(total_sale)
+    let quantity = parse_quantity(&product.quantity, product.unit).unwrap_or(0);
+    parse_price(&product.price_sale)
+        .saturating_times_quantity(quantity)
+        .to_string()
 }
@@ -0,0 +1,145 @@
+use crate::components::notification::Notification;
+use crate::services::backup_service::{Backup, BackupService};
+use iced::widget::{button, column, horizontal_space, row, scrollable, text};
+use iced::{Alignment, Element, Length, Task};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const DATE_WIDTH: f32 = 180.0;
+const SIZE_WIDTH: f32 = 100.0;
+const ACTION_WIDTH: f32 = 120.0;
+
+#[derive(Debug)]
+pub struct State {
+    backup_service: Arc<BackupService>,
+    backups: Vec<Backup>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Load,
+    Loaded(Result<Vec<Backup>, String>),
+    BackupNow,
+    BackupFinished(Result<PathBuf, String>),
+    Restore(usize),
+    RestoreStaged(Result<PathBuf, String>),
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(backup_service: Arc<BackupService>) -> Self {
+        Self {
+            backup_service,
+            backups: vec![],
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("ARQUIVO").width(Length::Fill),
+            text("TAMANHO").width(Length::Fixed(SIZE_WIDTH)),
+            text("").width(Length::Fixed(ACTION_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (index, backup) in self.backups.iter().enumerate() {
+            list = list.push(
+                row![
+                    text(backup.created_at.format("%d/%m/%Y %H:%M:%S").to_string())
+                        .width(Length::Fixed(DATE_WIDTH)),
+                    text(backup.path.display().to_string()).width(Length::Fill),
+                    text(format!("{} KB", backup.size.div_ceil(1024)))
+                        .width(Length::Fixed(SIZE_WIDTH)),
+                    button("RESTAURAR")
+                        .width(Length::Fixed(ACTION_WIDTH))
+                        .on_press_maybe((!self.busy).then_some(Message::Restore(index))),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![
+            text("BACKUPS").size(24),
+            header,
+            scrollable(list).height(Length::Fill),
+        ]
+        .push(row![
+            horizontal_space(),
+            button("FAZER BACKUP AGORA").on_press_maybe((!self.busy).then_some(Message::BackupNow)),
+        ])
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Load => {
+                let backup_service = self.backup_service.clone();
+                return Task::perform(
+                    async move { backup_service.list().map_err(|e| e.to_string()) },
+                    Message::Loaded,
+                );
+            }
+            Message::Loaded(result) => match result {
+                Ok(backups) => self.backups = backups,
+                Err(e) => return Task::done(Message::Notify(Notification::error(e))),
+            },
+            Message::BackupNow => {
+                self.busy = true;
+                let backup_service = self.backup_service.clone();
+                return Task::perform(
+                    async move { backup_service.backup().await.map_err(|e| e.to_string()) },
+                    Message::BackupFinished,
+                );
+            }
+            Message::BackupFinished(result) => {
+                self.busy = false;
+                let notification = match result {
+                    Ok(path) => {
+                        Notification::success(format!("Backup salvo em {}", path.display()))
+                    }
+                    Err(e) => Notification::error(e),
+                };
+                return Task::batch([
+                    Task::done(Message::Notify(notification)),
+                    Task::done(Message::Load),
+                ]);
+            }
+            Message::Restore(index) => {
+                if let Some(backup) = self.backups.get(index) {
+                    self.busy = true;
+                    let backup_service = self.backup_service.clone();
+                    let path = backup.path.clone();
+                    return Task::perform(
+                        async move {
+                            backup_service
+                                .stage_restore(&path)
+                                .await
+                                .map(|_| path)
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::RestoreStaged,
+                    );
+                }
+            }
+            Message::RestoreStaged(result) => {
+                self.busy = false;
+                return Task::done(Message::Notify(match result {
+                    Ok(path) => Notification::warning(format!(
+                        "Backup {} verificado. Reinicie o sistema para concluir a restauração.",
+                        path.display()
+                    )),
+                    Err(e) => Notification::error(e),
+                }));
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+        Task::none()
+    }
+}
@@ -0,0 +1,342 @@
+use crate::catalog::{self, ColumnMapping, Field, Sheet};
+use crate::components::notification::Notification;
+use crate::services::product_service::{ImportAction, ImportReport, ProductService};
+use iced::widget::{
+    button, column, horizontal_rule, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
+use iced::{Alignment, Element, Length, Task};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const FIELD_WIDTH: f32 = 180.0;
+const COLUMN_WIDTH: f32 = 260.0;
+const LINE_WIDTH: f32 = 70.0;
+const ACTION_WIDTH: f32 = 140.0;
+
+/// Imports the product catalog from a CSV or XLSX file, previewing the changes
+/// first, and exports it in the same format.
+#[derive(Debug)]
+pub struct State {
+    product_service: Arc<dyn ProductService>,
+    import_path: String,
+    export_path: String,
+    sheet: Option<Sheet>,
+    mapping: ColumnMapping,
+    preview: Option<ImportReport>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ImportPathChange(String),
+    LoadFile,
+    FileLoaded(Result<Sheet, String>),
+    MappingChange(Field, Column),
+    Preview,
+    PreviewLoaded(Result<ImportReport, String>),
+    Import,
+    Imported(Result<ImportReport, String>),
+    ExportPathChange(String),
+    Export,
+    Exported(Result<usize, String>),
+    Notify(Notification),
+}
+
+/// A column of the loaded file offered for a field, or none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    index: Option<usize>,
+    header: String,
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(_) => f.write_str(&self.header),
+            None => f.write_str("(não importar)"),
+        }
+    }
+}
+
+impl State {
+    pub fn new(product_service: Arc<dyn ProductService>) -> Self {
+        Self {
+            product_service,
+            import_path: String::new(),
+            export_path: "produtos.xlsx".to_string(),
+            sheet: None,
+            mapping: ColumnMapping::default(),
+            preview: None,
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let enabled = |message: Message| (!self.busy).then_some(message);
+
+        let mut content = column![
+            text("IMPORTAR PRODUTOS").size(24),
+            row![
+                text_input("Arquivo CSV ou XLSX", &self.import_path)
+                    .on_input(Message::ImportPathChange)
+                    .on_submit(Message::LoadFile),
+                button("ABRIR").on_press_maybe(enabled(Message::LoadFile)),
+            ]
+            .spacing(8),
+        ]
+        .spacing(16);
+
+        if let Some(sheet) = &self.sheet {
+            content = content
+                .push(text(format!(
+                    "{} LINHA(S) NO ARQUIVO. ASSOCIE AS COLUNAS AOS CAMPOS:",
+                    sheet.rows.len()
+                )))
+                .push(self.mapping_view(sheet))
+                .push(row![
+                    button("PRÉ-VISUALIZAR").on_press_maybe(enabled(Message::Preview)),
+                    horizontal_space(),
+                ]);
+        }
+        if let Some(preview) = &self.preview {
+            content = content.push(self.preview_view(preview));
+        }
+
+        content = content
+            .push(horizontal_rule(1))
+            .push(text("EXPORTAR PRODUTOS").size(24))
+            .push(
+                row![
+                    text_input("Arquivo .csv ou .xlsx", &self.export_path)
+                        .on_input(Message::ExportPathChange)
+                        .on_submit(Message::Export),
+                    button("EXPORTAR").on_press_maybe(enabled(Message::Export)),
+                ]
+                .spacing(8),
+            );
+
+        scrollable(content).height(Length::Fill).into()
+    }
+
+    fn mapping_view(&self, sheet: &Sheet) -> Element<'_, Message> {
+        let columns: Vec<Column> = std::iter::once(Column {
+            index: None,
+            header: String::new(),
+        })
+        .chain(
+            sheet
+                .headers
+                .iter()
+                .enumerate()
+                .map(|(index, header)| Column {
+                    index: Some(index),
+                    header: header.clone(),
+                }),
+        )
+        .collect();
+
+        let mut mapping = column![].spacing(8);
+        for field in Field::ALL {
+            let selected = columns
+                .iter()
+                .find(|column| column.index == self.mapping.get(field))
+                .cloned();
+            mapping = mapping.push(
+                row![
+                    text(field.to_string()).width(Length::Fixed(FIELD_WIDTH)),
+                    pick_list(columns.clone(), selected, move |column| {
+                        Message::MappingChange(field, column)
+                    })
+                    .width(Length::Fixed(COLUMN_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+        mapping.into()
+    }
+
+    fn preview_view<'a>(&self, preview: &'a ImportReport) -> Element<'a, Message> {
+        let header = row![
+            text("LINHA").width(Length::Fixed(LINE_WIDTH)),
+            text("AÇÃO").width(Length::Fixed(ACTION_WIDTH)),
+            text("PRODUTO").width(Length::Fill),
+        ]
+        .spacing(16);
+
+        let mut entries = column![].spacing(8);
+        for entry in &preview.entries {
+            let (action, detail) = match &entry.action {
+                ImportAction::Create => ("CADASTRAR".to_string(), entry.name.clone()),
+                ImportAction::Update(id) => (format!("ATUALIZAR #{}", id), entry.name.clone()),
+                ImportAction::Reject(reason) => (
+                    "REJEITADA".to_string(),
+                    if entry.name.is_empty() {
+                        reason.clone()
+                    } else {
+                        format!("{}: {}", entry.name, reason)
+                    },
+                ),
+            };
+            entries = entries.push(
+                row![
+                    text(entry.line).width(Length::Fixed(LINE_WIDTH)),
+                    text(action).width(Length::Fixed(ACTION_WIDTH)),
+                    text(detail).width(Length::Fill),
+                ]
+                .spacing(16),
+            );
+        }
+
+        let can_import = !self.busy && preview.created() + preview.updated() > 0;
+        column![
+            text(format!(
+                "{} A CADASTRAR, {} A ATUALIZAR, {} REJEITADA(S)",
+                preview.created(),
+                preview.updated(),
+                preview.rejected()
+            )),
+            header,
+            entries,
+            row![
+                horizontal_space(),
+                button("IMPORTAR").on_press_maybe(can_import.then_some(Message::Import)),
+            ],
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ImportPathChange(value) => {
+                self.import_path = value;
+            }
+            Message::LoadFile => {
+                let path = PathBuf::from(self.import_path.trim());
+                if path.as_os_str().is_empty() {
+                    return Task::none();
+                }
+                self.busy = true;
+                return Task::perform(
+                    async move { Sheet::read(&path).map_err(|e| format!("{:#}", e)) },
+                    Message::FileLoaded,
+                );
+            }
+            Message::FileLoaded(result) => {
+                self.busy = false;
+                self.preview = None;
+                match result {
+                    Ok(sheet) => {
+                        self.mapping = ColumnMapping::detect(&sheet.headers);
+                        self.sheet = Some(sheet);
+                    }
+                    Err(e) => {
+                        self.sheet = None;
+                        return Task::done(Message::Notify(Notification::error(e)));
+                    }
+                }
+            }
+            Message::MappingChange(field, column) => {
+                self.mapping.set(field, column.index);
+                self.preview = None;
+            }
+            Message::Preview => return self.import(true),
+            Message::Import => return self.import(false),
+            Message::PreviewLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(report) => self.preview = Some(report),
+                    Err(e) => return Task::done(Message::Notify(Notification::error(e))),
+                }
+            }
+            Message::Imported(result) => {
+                self.busy = false;
+                match result {
+                    Ok(report) => {
+                        self.preview = None;
+                        self.sheet = None;
+                        self.import_path.clear();
+                        return Task::done(Message::Notify(Notification::success(format!(
+                            "Importação concluída: {} produto(s) cadastrado(s), {} atualizado(s), {} linha(s) rejeitada(s)",
+                            report.created(),
+                            report.updated(),
+                            report.rejected()
+                        ))));
+                    }
+                    Err(e) => {
+                        return Task::done(Message::Notify(Notification::error(format!(
+                            "Erro ao importar produtos, nada foi alterado: {}",
+                            e
+                        ))));
+                    }
+                }
+            }
+            Message::ExportPathChange(value) => {
+                self.export_path = value;
+            }
+            Message::Export => {
+                let path = PathBuf::from(self.export_path.trim());
+                if path.as_os_str().is_empty() {
+                    return Task::none();
+                }
+                self.busy = true;
+                let product_service = self.product_service.clone();
+                return Task::perform(
+                    async move {
+                        let products = product_service.list_catalog().await?;
+                        catalog::to_sheet(&products).write(&path)?;
+                        Ok(products.len())
+                    },
+                    |result: anyhow::Result<usize>| {
+                        Message::Exported(result.map_err(|e| format!("{:#}", e)))
+                    },
+                );
+            }
+            Message::Exported(result) => {
+                self.busy = false;
+                return Task::done(Message::Notify(match result {
+                    Ok(count) => Notification::success(format!(
+                        "{} produto(s) exportado(s) para {}",
+                        count,
+                        self.export_path.trim()
+                    )),
+                    Err(e) => Notification::error(e),
+                }));
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    /// Runs the import of the loaded file, as a preview on a dry run.
+    fn import(&mut self, dry_run: bool) -> Task<Message> {
+        let Some(sheet) = &self.sheet else {
+            return Task::none();
+        };
+        if let Err(e) = self.mapping.validate() {
+            return Task::done(Message::Notify(Notification::warning(e.to_string())));
+        }
+        self.busy = true;
+        let rows = catalog::parse_rows(sheet, &self.mapping);
+        let product_service = self.product_service.clone();
+        Task::perform(
+            async move {
+                product_service
+                    .import_products(rows, dry_run)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            move |result| {
+                if dry_run {
+                    Message::PreviewLoaded(result)
+                } else {
+                    Message::Imported(result)
+                }
+            },
+        )
+    }
+}
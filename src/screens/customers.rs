@@ -0,0 +1,435 @@
+use crate::components::notification::Notification;
+use crate::entities::customer::Customer;
+use crate::helpers::document::format_cpf;
+use crate::helpers::money::Money;
+use crate::helpers::to_local;
+use crate::services::customer_service::{CustomerHistory, CustomerService};
+use chrono::{NaiveDateTime, Utc};
+use iced::widget::{button, column, horizontal_space, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task};
+use std::sync::Arc;
+
+const ID_WIDTH: f32 = 60.0;
+const DATE_WIDTH: f32 = 160.0;
+const DOCUMENT_WIDTH: f32 = 140.0;
+const STATUS_WIDTH: f32 = 120.0;
+const BUTTON_WIDTH: f32 = 100.0;
+
+/// Customer registry: customers are searched by name, CPF or phone, and an
+/// opened customer shows their purchase history.
+#[derive(Debug)]
+pub struct State {
+    customer_service: Arc<dyn CustomerService>,
+    search: String,
+    customers: Vec<Customer>,
+    form: Option<Form>,
+    history: Option<CustomerHistory>,
+    busy: bool,
+}
+
+/// Customer being registered (id 0) or edited.
+#[derive(Debug, Default)]
+struct Form {
+    id: i64,
+    name: String,
+    cpf: String,
+    phone: String,
+    email: String,
+    credit_limit: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SearchChange(String),
+    Search,
+    CustomersLoaded(Result<Vec<Customer>, String>),
+    Open(i64),
+    HistoryLoaded(Result<Option<CustomerHistory>, String>),
+    New,
+    Edit,
+    NameChange(String),
+    CpfChange(String),
+    PhoneChange(String),
+    EmailChange(String),
+    CreditLimitChange(String),
+    Save,
+    Saved(Result<i64, String>),
+    CancelForm,
+    Back,
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(customer_service: Arc<dyn CustomerService>) -> Self {
+        Self {
+            customer_service,
+            search: String::new(),
+            customers: vec![],
+            form: None,
+            history: None,
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        match (&self.form, &self.history) {
+            (Some(form), _) => self.form_view(form),
+            (None, Some(history)) => self.history_view(history),
+            (None, None) => self.list_view(),
+        }
+    }
+
+    fn list_view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("NOME").width(Length::Fill),
+            text("CPF").width(Length::Fixed(DOCUMENT_WIDTH)),
+            text("TELEFONE").width(Length::Fixed(DOCUMENT_WIDTH)),
+            text("E-MAIL").width(Length::Fill),
+            text("").width(Length::Fixed(BUTTON_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for customer in &self.customers {
+            list = list.push(
+                row![
+                    text(&customer.name).width(Length::Fill),
+                    text(customer.cpf.as_deref().map(format_cpf).unwrap_or_default())
+                        .width(Length::Fixed(DOCUMENT_WIDTH)),
+                    text(customer.phone.as_deref().unwrap_or_default())
+                        .width(Length::Fixed(DOCUMENT_WIDTH)),
+                    text(customer.email.as_deref().unwrap_or_default()).width(Length::Fill),
+                    button("ABRIR")
+                        .on_press(Message::Open(customer.id))
+                        .width(Length::Fixed(BUTTON_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![
+            text("CLIENTES").size(24),
+            row![
+                text_input("NOME, CPF OU TELEFONE", &self.search)
+                    .on_input(Message::SearchChange)
+                    .on_submit(Message::Search),
+                button("BUSCAR").on_press_maybe((!self.busy).then_some(Message::Search)),
+                button("NOVO CLIENTE").on_press(Message::New),
+            ]
+            .spacing(8),
+            header,
+            scrollable(list).height(Length::Fill),
+            text(format!("{} CLIENTE(S)", self.customers.len())),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn form_view<'a>(&'a self, form: &'a Form) -> Element<'a, Message> {
+        let title = if form.id == 0 {
+            "NOVO CLIENTE".to_string()
+        } else {
+            format!("CLIENTE {}", form.id)
+        };
+        column![
+            text(title).size(24),
+            text("NOME"),
+            text_input("NOME", &form.name).on_input(Message::NameChange),
+            text("CPF"),
+            text_input("000.000.000-00", &form.cpf).on_input(Message::CpfChange),
+            text("TELEFONE"),
+            text_input("(00) 00000-0000", &form.phone).on_input(Message::PhoneChange),
+            text("E-MAIL"),
+            text_input("cliente@exemplo.com", &form.email).on_input(Message::EmailChange),
+            text("LIMITE DO FIADO"),
+            text_input("0,00", &form.credit_limit)
+                .on_input(Message::CreditLimitChange)
+                .on_submit(Message::Save),
+            row![
+                button("CANCELAR").on_press(Message::CancelForm),
+                horizontal_space(),
+                button("SALVAR").on_press_maybe((!self.busy).then_some(Message::Save)),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn history_view<'a>(&'a self, history: &'a CustomerHistory) -> Element<'a, Message> {
+        let customer = &history.customer;
+        let summary = &history.summary;
+        let last_visit = summary
+            .last_visit
+            .map(|date| to_local(date).format("%d/%m/%Y %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        let header = row![
+            text("VENDA").width(Length::Fixed(ID_WIDTH)),
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("TOTAL").width(Length::Fill),
+            text("SITUAÇÃO").width(Length::Fixed(STATUS_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for sale in &history.sales {
+            list = list.push(
+                row![
+                    text(sale.id).width(Length::Fixed(ID_WIDTH)),
+                    text(
+                        to_local(sale.created_at)
+                            .format("%d/%m/%Y %H:%M")
+                            .to_string()
+                    )
+                    .width(Length::Fixed(DATE_WIDTH)),
+                    text(sale.total.to_string()).width(Length::Fill),
+                    text(if sale.is_voided() { "CANCELADA" } else { "" })
+                        .width(Length::Fixed(STATUS_WIDTH)),
+                ]
+                .spacing(16),
+            );
+        }
+
+        let points_header = row![
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("PONTOS").width(Length::Fill),
+            text("VENDA").width(Length::Fixed(ID_WIDTH)),
+        ]
+        .spacing(16);
+        let mut ledger = column![].spacing(8);
+        for entry in &history.loyalty {
+            ledger = ledger.push(
+                row![
+                    text(
+                        to_local(entry.created_at)
+                            .format("%d/%m/%Y %H:%M")
+                            .to_string()
+                    )
+                    .width(Length::Fixed(DATE_WIDTH)),
+                    text(format!("{:+} {}", entry.points, entry.kind.as_str())).width(Length::Fill),
+                    text(entry.sale_id.map(|id| id.to_string()).unwrap_or_default())
+                        .width(Length::Fixed(ID_WIDTH)),
+                ]
+                .spacing(16),
+            );
+        }
+
+        column![
+            text(&customer.name).size(24),
+            row![
+                text(format!(
+                    "CPF: {}",
+                    customer.cpf.as_deref().map(format_cpf).unwrap_or_default()
+                )),
+                text(format!(
+                    "TELEFONE: {}",
+                    customer.phone.as_deref().unwrap_or_default()
+                )),
+                text(format!(
+                    "E-MAIL: {}",
+                    customer.email.as_deref().unwrap_or_default()
+                )),
+            ]
+            .spacing(32),
+            row![
+                text(format!("TOTAL GASTO: {}", summary.total_spent)),
+                text(format!("COMPRAS: {}", summary.sales)),
+                text(format!("ÚLTIMA VISITA: {}", last_visit)),
+                text(format!("PONTOS: {}", history.points)),
+                text(format!("LIMITE DO FIADO: {}", customer.credit_limit)),
+            ]
+            .spacing(32),
+            row![
+                column![header, scrollable(list).height(Length::Fill)]
+                    .spacing(16)
+                    .width(Length::FillPortion(2)),
+                column![points_header, scrollable(ledger).height(Length::Fill)]
+                    .spacing(16)
+                    .width(Length::FillPortion(1)),
+            ]
+            .spacing(32)
+            .height(Length::Fill),
+            row![
+                button("VOLTAR").on_press(Message::Back),
+                horizontal_space(),
+                button("EDITAR").on_press(Message::Edit),
+            ]
+            .spacing(8),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SearchChange(value) => self.search = value,
+            Message::Search => {
+                let customer_service = self.customer_service.clone();
+                let search = self.search.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        customer_service
+                            .search_customers(&search)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::CustomersLoaded,
+                );
+            }
+            Message::CustomersLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(customers) => self.customers = customers,
+                    Err(e) => return notify_error(format!("Erro ao buscar clientes: {}", e)),
+                }
+            }
+            Message::Open(id) => return self.load_history(id),
+            Message::HistoryLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(Some(history)) => self.history = Some(history),
+                    Ok(None) => return notify_error("Cliente não encontrado".to_string()),
+                    Err(e) => return notify_error(format!("Erro ao carregar o cliente: {}", e)),
+                }
+            }
+            Message::New => self.form = Some(Form::default()),
+            Message::Edit => {
+                if let Some(history) = &self.history {
+                    self.form = Some(Form::from_customer(&history.customer));
+                }
+            }
+            Message::NameChange(value) => {
+                if let Some(form) = &mut self.form {
+                    form.name = value.to_uppercase();
+                }
+            }
+            Message::CpfChange(value) => {
+                if let Some(form) = &mut self.form {
+                    form.cpf = value;
+                }
+            }
+            Message::PhoneChange(value) => {
+                if let Some(form) = &mut self.form {
+                    form.phone = value;
+                }
+            }
+            Message::EmailChange(value) => {
+                if let Some(form) = &mut self.form {
+                    form.email = value;
+                }
+            }
+            Message::CreditLimitChange(value) => {
+                if let Some(form) = &mut self.form
+                    && Money::validate_input(&value)
+                {
+                    form.credit_limit = value;
+                }
+            }
+            Message::Save => {
+                let Some(form) = &self.form else {
+                    return Task::none();
+                };
+                let created_at = match &self.history {
+                    Some(history) if history.customer.id == form.id => history.customer.created_at,
+                    _ => Utc::now().naive_local(),
+                };
+                let Some(customer) = form.to_customer(created_at) else {
+                    return notify_error(format!(
+                        "Limite do fiado \"{}\" inválido",
+                        form.credit_limit
+                    ));
+                };
+                let customer_service = self.customer_service.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        customer_service
+                            .save_customer(customer)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Saved,
+                );
+            }
+            Message::Saved(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(id) => {
+                        self.form = None;
+                        Task::batch([
+                            Task::done(Message::Notify(Notification::success("Cliente salvo"))),
+                            self.load_history(id),
+                        ])
+                    }
+                    Err(e) => notify_error(format!("Erro ao salvar o cliente: {}", e)),
+                };
+            }
+            Message::CancelForm => self.form = None,
+            Message::Back => {
+                self.history = None;
+                return Task::done(Message::Search);
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn load_history(&mut self, id: i64) -> Task<Message> {
+        let customer_service = self.customer_service.clone();
+        self.busy = true;
+        Task::perform(
+            async move {
+                customer_service
+                    .customer_history(id)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::HistoryLoaded,
+        )
+    }
+}
+
+impl Form {
+    fn from_customer(customer: &Customer) -> Self {
+        Self {
+            id: customer.id,
+            name: customer.name.clone(),
+            cpf: customer.cpf.as_deref().map(format_cpf).unwrap_or_default(),
+            phone: customer.phone.clone().unwrap_or_default(),
+            email: customer.email.clone().unwrap_or_default(),
+            credit_limit: customer.credit_limit.to_decimal_string(),
+        }
+    }
+
+    /// The customer typed, `None` when the credit limit is not a value.
+    fn to_customer(&self, created_at: NaiveDateTime) -> Option<Customer> {
+        let optional = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let credit_limit = match self.credit_limit.trim() {
+            "" => Money::ZERO,
+            value => Money::parse(value)?,
+        };
+        Some(Customer {
+            credit_limit,
+            ..Customer::new(
+                self.id,
+                self.name.clone(),
+                optional(&self.cpf),
+                optional(&self.phone),
+                optional(&self.email),
+                created_at,
+            )
+        })
+    }
+}
+
+fn notify_error(message: String) -> Task<Message> {
+    Task::done(Message::Notify(Notification::error(message)))
+}
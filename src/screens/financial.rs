@@ -0,0 +1,903 @@
+use crate::components::notification::Notification;
+use crate::config::StoreConfig;
+use crate::entities::expense::{Expense, ExpenseCategory};
+use crate::entities::payable::Payable;
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use crate::helpers::to_local;
+use crate::printing::statement;
+use crate::printing::PrinterTarget;
+use crate::services::cash_flow_service::{CashFlowService, MonthResult};
+use crate::services::expense_service::ExpenseService;
+use crate::services::payable_service::PayableService;
+use crate::services::receivable_service::{CustomerAccount, ReceivableService, Statement};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Utc};
+use iced::widget::{
+    button, checkbox, column, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
+use iced::{Alignment, Element, Length, Task};
+use std::sync::Arc;
+
+const DATE_WIDTH: f32 = 120.0;
+const KIND_WIDTH: f32 = 120.0;
+const SALE_WIDTH: f32 = 80.0;
+const PRICE_WIDTH: f32 = 120.0;
+const AMOUNT_WIDTH: f32 = 160.0;
+const DOCUMENT_WIDTH: f32 = 120.0;
+const METHOD_WIDTH: f32 = 160.0;
+const CATEGORY_WIDTH: f32 = 220.0;
+const MONTH_WIDTH: f32 = 100.0;
+const RESULT_WIDTH: f32 = 110.0;
+const DATE_FORMAT: &str = "%d/%m/%Y";
+const MONTH_FORMAT: &str = "%m/%Y";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Receivables,
+    Payables,
+    Expenses,
+    Results,
+}
+
+/// Pending store credit ("fiado") balances, supplier titles, expenses and
+/// the monthly result. An opened account shows its statement, takes partial
+/// or full payments and can be printed; titles are marked as paid with the
+/// payment method.
+#[derive(Debug)]
+pub struct State {
+    receivable_service: Arc<dyn ReceivableService>,
+    payable_service: Arc<dyn PayableService>,
+    expense_service: Arc<dyn ExpenseService>,
+    cash_flow_service: Arc<dyn CashFlowService>,
+    store: StoreConfig,
+    printer: Option<PrinterTarget>,
+    tab: Tab,
+    accounts: Vec<CustomerAccount>,
+    statement: Option<Statement>,
+    amount: String,
+    payables: Vec<Payable>,
+    /// Shows only titles due up to the end of the week, overdue included.
+    this_week: bool,
+    payment_method: PaymentMethod,
+    /// Month of the listed expenses, `MM/AAAA`.
+    expense_month: String,
+    expenses: Vec<Expense>,
+    expense: ExpenseForm,
+    year: String,
+    months: Vec<MonthResult>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ExpenseForm {
+    paid_on: String,
+    category: ExpenseCategory,
+    description: String,
+    amount: String,
+    payment_method: PaymentMethod,
+}
+
+impl ExpenseForm {
+    fn new() -> Self {
+        Self {
+            paid_on: Local::now().date_naive().format(DATE_FORMAT).to_string(),
+            category: ExpenseCategory::default(),
+            description: String::new(),
+            amount: String::new(),
+            payment_method: PaymentMethod::Cash,
+        }
+    }
+
+    fn to_expense(&self) -> Result<Expense, String> {
+        let paid_on = NaiveDate::parse_from_str(self.paid_on.trim(), DATE_FORMAT)
+            .map_err(|_| "Informe a data da despesa no formato DD/MM/AAAA".to_string())?;
+        let amount =
+            Money::parse(&self.amount).ok_or_else(|| "Informe o valor da despesa".to_string())?;
+        Ok(Expense::new(
+            0,
+            self.category,
+            self.description.clone(),
+            amount,
+            paid_on,
+            self.payment_method,
+            Utc::now().naive_local(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Load,
+    ShowTab(Tab),
+    ThisWeekToggle(bool),
+    PayablesLoaded(Result<Vec<Payable>, String>),
+    PaymentMethodChange(PaymentMethod),
+    Pay(i64),
+    Paid(Result<(), String>),
+    ExpenseMonthChange(String),
+    LoadExpenses,
+    ExpensesLoaded(Result<Vec<Expense>, String>),
+    ExpenseDateChange(String),
+    ExpenseCategoryChange(ExpenseCategory),
+    ExpenseDescriptionChange(String),
+    ExpenseAmountChange(String),
+    ExpenseMethodChange(PaymentMethod),
+    AddExpense,
+    ExpenseAdded(Result<i64, String>),
+    DeleteExpense(i64),
+    ExpenseDeleted(Result<(), String>),
+    YearChange(String),
+    LoadResults,
+    ResultsLoaded(Result<Vec<MonthResult>, String>),
+    AccountsLoaded(Result<Vec<CustomerAccount>, String>),
+    Open(i64),
+    StatementLoaded(Result<Option<Statement>, String>),
+    Back,
+    AmountChange(String),
+    ReceiveAll,
+    Receive,
+    Received(Result<(), String>),
+    Print,
+    Printed(Result<(), String>),
+    Backup,
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(
+        receivable_service: Arc<dyn ReceivableService>,
+        payable_service: Arc<dyn PayableService>,
+        expense_service: Arc<dyn ExpenseService>,
+        cash_flow_service: Arc<dyn CashFlowService>,
+        store: StoreConfig,
+        printer: Option<PrinterTarget>,
+    ) -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            receivable_service,
+            payable_service,
+            expense_service,
+            cash_flow_service,
+            store,
+            printer,
+            tab: Tab::Receivables,
+            accounts: vec![],
+            statement: None,
+            amount: String::new(),
+            payables: vec![],
+            this_week: true,
+            payment_method: PaymentMethod::BankSlip,
+            expense_month: today.format(MONTH_FORMAT).to_string(),
+            expenses: vec![],
+            expense: ExpenseForm::new(),
+            year: today.year().to_string(),
+            months: vec![],
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some(statement) = &self.statement {
+            return self.statement_view(statement);
+        }
+        let tab_button = |label, tab| {
+            button(label).on_press_maybe((self.tab != tab).then_some(Message::ShowTab(tab)))
+        };
+        let content = match self.tab {
+            Tab::Receivables => self.receivables_view(),
+            Tab::Payables => self.payables_view(),
+            Tab::Expenses => self.expenses_view(),
+            Tab::Results => self.results_view(),
+        };
+        column![
+            row![
+                text("FINANCEIRO").size(24),
+                horizontal_space(),
+                tab_button("A RECEBER (FIADO)", Tab::Receivables),
+                tab_button("A PAGAR (FORNECEDORES)", Tab::Payables),
+                tab_button("DESPESAS", Tab::Expenses),
+                tab_button("RESULTADO MENSAL", Tab::Results),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            content,
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn receivables_view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("CLIENTE").width(Length::Fill),
+            text("SALDO").width(Length::Fixed(PRICE_WIDTH)),
+            text("EM ATRASO").width(Length::Fixed(PRICE_WIDTH)),
+            text("VENCIMENTO").width(Length::Fixed(DATE_WIDTH)),
+            text("").width(Length::Fixed(SALE_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        let mut total = Money::ZERO;
+        let mut overdue = Money::ZERO;
+        for account in &self.accounts {
+            total = total.saturating_add(account.balance);
+            overdue = overdue.saturating_add(account.overdue);
+            list = list.push(
+                row![
+                    text(&account.customer.name).width(Length::Fill),
+                    text(account.balance.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(if account.overdue > Money::ZERO {
+                        account.overdue.to_string()
+                    } else {
+                        String::new()
+                    })
+                    .width(Length::Fixed(PRICE_WIDTH)),
+                    text(
+                        account
+                            .next_due
+                            .map(|date| date.format(DATE_FORMAT).to_string())
+                            .unwrap_or_default()
+                    )
+                    .width(Length::Fixed(DATE_WIDTH)),
+                    button("ABRIR")
+                        .on_press(Message::Open(account.customer.id))
+                        .width(Length::Fixed(SALE_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("{} CLIENTE(S)", self.accounts.len())),
+                text(format!("A RECEBER: {}", total)),
+                text(format!("EM ATRASO: {}", overdue)),
+            ]
+            .spacing(32),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn payables_view(&self) -> Element<'_, Message> {
+        let today = Local::now().date_naive();
+        let header = row![
+            text("VENCIMENTO").width(Length::Fixed(DATE_WIDTH)),
+            text("FORNECEDOR").width(Length::Fill),
+            text("NOTA FISCAL").width(Length::Fixed(DOCUMENT_WIDTH)),
+            text("PARCELA").width(Length::Fixed(SALE_WIDTH)),
+            text("VALOR").width(Length::Fixed(PRICE_WIDTH)),
+            text("SITUAÇÃO").width(Length::Fixed(KIND_WIDTH)),
+            text("").width(Length::Fixed(SALE_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        let mut total = Money::ZERO;
+        let mut overdue = Money::ZERO;
+        for payable in &self.payables {
+            total = total.saturating_add(payable.amount);
+            let status = if payable.is_overdue(today) {
+                overdue = overdue.saturating_add(payable.amount);
+                "VENCIDO"
+            } else if payable.due_date == today {
+                "VENCE HOJE"
+            } else {
+                ""
+            };
+            list = list.push(
+                row![
+                    text(payable.due_date.format(DATE_FORMAT).to_string())
+                        .width(Length::Fixed(DATE_WIDTH)),
+                    text(&payable.supplier).width(Length::Fill),
+                    text(payable.document.clone().unwrap_or_default())
+                        .width(Length::Fixed(DOCUMENT_WIDTH)),
+                    text(format!("{}/{}", payable.installment, payable.installments))
+                        .width(Length::Fixed(SALE_WIDTH)),
+                    text(payable.amount.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(status).width(Length::Fixed(KIND_WIDTH)),
+                    button("PAGAR")
+                        .on_press_maybe((!self.busy).then_some(Message::Pay(payable.id)))
+                        .width(Length::Fixed(SALE_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![
+            row![
+                checkbox("SÓ ATÉ O FIM DA SEMANA", self.this_week)
+                    .on_toggle(Message::ThisWeekToggle),
+                horizontal_space(),
+                text("PAGAR COM"),
+                pick_list(
+                    PaymentMethod::PAYABLE,
+                    Some(self.payment_method),
+                    Message::PaymentMethodChange
+                )
+                .width(Length::Fixed(METHOD_WIDTH)),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("{} TÍTULO(S)", self.payables.len())),
+                text(format!("A PAGAR: {}", total)),
+                text(format!("VENCIDO: {}", overdue)),
+            ]
+            .spacing(32),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn expenses_view(&self) -> Element<'_, Message> {
+        let form = row![
+            text_input("DATA (DD/MM/AAAA)", &self.expense.paid_on)
+                .on_input(Message::ExpenseDateChange)
+                .width(Length::Fixed(DATE_WIDTH)),
+            pick_list(
+                ExpenseCategory::ALL,
+                Some(self.expense.category),
+                Message::ExpenseCategoryChange
+            )
+            .width(Length::Fixed(CATEGORY_WIDTH)),
+            text_input("DESCRIÇÃO", &self.expense.description)
+                .on_input(Message::ExpenseDescriptionChange)
+                .on_submit(Message::AddExpense),
+            text_input("VALOR", &self.expense.amount)
+                .on_input(Message::ExpenseAmountChange)
+                .on_submit(Message::AddExpense)
+                .width(Length::Fixed(PRICE_WIDTH)),
+            pick_list(
+                PaymentMethod::PAYABLE,
+                Some(self.expense.payment_method),
+                Message::ExpenseMethodChange
+            )
+            .width(Length::Fixed(METHOD_WIDTH)),
+            button("LANÇAR").on_press_maybe((!self.busy).then_some(Message::AddExpense)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let header = row![
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("CATEGORIA").width(Length::Fixed(CATEGORY_WIDTH)),
+            text("DESCRIÇÃO").width(Length::Fill),
+            text("VALOR").width(Length::Fixed(PRICE_WIDTH)),
+            text("PAGAMENTO").width(Length::Fixed(METHOD_WIDTH)),
+            text("").width(Length::Fixed(SALE_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for expense in &self.expenses {
+            list = list.push(
+                row![
+                    text(expense.paid_on.format(DATE_FORMAT).to_string())
+                        .width(Length::Fixed(DATE_WIDTH)),
+                    text(expense.category.as_str()).width(Length::Fixed(CATEGORY_WIDTH)),
+                    text(&expense.description).width(Length::Fill),
+                    text(expense.amount.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(expense.payment_method.as_str()).width(Length::Fixed(METHOD_WIDTH)),
+                    button("EXCLUIR")
+                        .on_press_maybe((!self.busy).then_some(Message::DeleteExpense(expense.id)))
+                        .width(Length::Fixed(SALE_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+        let total = Money::checked_sum(self.expenses.iter().map(|expense| expense.amount))
+            .map(|total| total.to_string())
+            .unwrap_or_else(|| "EXCEDE O LIMITE".to_string());
+
+        column![
+            form,
+            row![
+                text("MÊS"),
+                text_input("MM/AAAA", &self.expense_month)
+                    .on_input(Message::ExpenseMonthChange)
+                    .on_submit(Message::LoadExpenses)
+                    .width(Length::Fixed(MONTH_WIDTH)),
+                button("BUSCAR").on_press_maybe((!self.busy).then_some(Message::LoadExpenses)),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("{} DESPESA(S)", self.expenses.len())),
+                text(format!("TOTAL: {}", total)),
+            ]
+            .spacing(32),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn results_view(&self) -> Element<'_, Message> {
+        let cell = |value: String| text(value).width(Length::Fixed(RESULT_WIDTH));
+        let result_row = |label: String, result: &MonthResult| {
+            row![
+                text(label).width(Length::Fixed(MONTH_WIDTH)),
+                cell(result.revenue.to_string()),
+                cell(result.cost_of_goods.to_string()),
+                cell(result.gross_margin().to_string()),
+                cell(
+                    result
+                        .gross_margin_percent()
+                        .map(|percent| format!("{}%", percent))
+                        .unwrap_or_default()
+                ),
+                cell(result.expenses.to_string()),
+                cell(result.net_result().to_string()),
+                cell(result.cash_in.to_string()),
+                cell(result.cash_out().to_string()),
+                cell(result.cash_balance().to_string()),
+            ]
+            .spacing(8)
+        };
+
+        let header = row![
+            text("MÊS").width(Length::Fixed(MONTH_WIDTH)),
+            cell("RECEITA".to_string()),
+            cell("CMV".to_string()),
+            cell("MARGEM BRUTA".to_string()),
+            cell("MARGEM %".to_string()),
+            cell("DESPESAS".to_string()),
+            cell("RESULTADO".to_string()),
+            cell("ENTRADAS".to_string()),
+            cell("SAÍDAS".to_string()),
+            cell("SALDO CAIXA".to_string()),
+        ]
+        .spacing(8);
+
+        let mut list = column![].spacing(8);
+        for month in &self.months {
+            list = list.push(result_row(
+                month.month.format(MONTH_FORMAT).to_string(),
+                month,
+            ));
+        }
+
+        column![
+            row![
+                text("ANO"),
+                text_input("AAAA", &self.year)
+                    .on_input(Message::YearChange)
+                    .on_submit(Message::LoadResults)
+                    .width(Length::Fixed(MONTH_WIDTH)),
+                button("CALCULAR").on_press_maybe((!self.busy).then_some(Message::LoadResults)),
+                horizontal_space(),
+                button("FAZER BACKUP").on_press(Message::Backup),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            header,
+            scrollable(list).height(Length::Fill),
+        ]
+        .push_maybe(
+            MonthResult::total(&self.months).map(|total| result_row("TOTAL".to_string(), &total)),
+        )
+        .push(text(
+            "CMV pelo custo atual dos produtos. Saídas: despesas e pagamentos de compras.",
+        ))
+        .spacing(16)
+        .into()
+    }
+
+    fn statement_view<'a>(&'a self, statement: &'a Statement) -> Element<'a, Message> {
+        let account = &statement.account;
+        let open = account.balance > Money::ZERO && !self.busy;
+
+        let header = row![
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("LANÇAMENTO").width(Length::Fixed(KIND_WIDTH)),
+            text("VENDA").width(Length::Fixed(SALE_WIDTH)),
+            text("VENCIMENTO").width(Length::Fill),
+            text("VALOR").width(Length::Fixed(PRICE_WIDTH)),
+            text("SALDO").width(Length::Fixed(PRICE_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        let mut balance = Money::ZERO;
+        for entry in &statement.entries {
+            balance = balance.saturating_add(entry.amount);
+            list = list.push(
+                row![
+                    text(to_local(entry.created_at).format(DATE_FORMAT).to_string())
+                        .width(Length::Fixed(DATE_WIDTH)),
+                    text(entry.kind.as_str()).width(Length::Fixed(KIND_WIDTH)),
+                    text(entry.sale_id.map(|id| id.to_string()).unwrap_or_default())
+                        .width(Length::Fixed(SALE_WIDTH)),
+                    text(
+                        entry
+                            .due_date
+                            .map(|date| date.format(DATE_FORMAT).to_string())
+                            .unwrap_or_default()
+                    )
+                    .width(Length::Fill),
+                    text(entry.amount.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(balance.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                ]
+                .spacing(16),
+            );
+        }
+
+        let mut amount =
+            text_input("VALOR RECEBIDO", &self.amount).width(Length::Fixed(AMOUNT_WIDTH));
+        if open {
+            amount = amount
+                .on_input(Message::AmountChange)
+                .on_submit(Message::Receive);
+        }
+
+        column![
+            text(format!("EXTRATO - {}", account.customer.name)).size(24),
+            row![
+                text(format!("SALDO DEVEDOR: {}", account.balance)),
+                text(format!("EM ATRASO: {}", account.overdue)),
+                text(format!(
+                    "LIMITE DO FIADO: {}",
+                    account.customer.credit_limit
+                )),
+            ]
+            .spacing(32),
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                button("VOLTAR").on_press(Message::Back),
+                horizontal_space(),
+                button("IMPRIMIR EXTRATO").on_press_maybe(
+                    (self.printer.is_some() && !self.busy).then_some(Message::Print)
+                ),
+                amount,
+                button("TUDO").on_press_maybe(open.then_some(Message::ReceiveAll)),
+                button("RECEBER").on_press_maybe(open.then_some(Message::Receive)),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Load => {
+                let receivable_service = self.receivable_service.clone();
+                self.busy = true;
+                return Task::batch([
+                    Task::perform(
+                        async move {
+                            receivable_service
+                                .list_open_accounts()
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::AccountsLoaded,
+                    ),
+                    self.load_payables(),
+                ]);
+            }
+            Message::ShowTab(tab) => {
+                self.tab = tab;
+                match tab {
+                    Tab::Expenses => return Task::done(Message::LoadExpenses),
+                    Tab::Results => return Task::done(Message::LoadResults),
+                    Tab::Receivables | Tab::Payables => {}
+                }
+            }
+            Message::ThisWeekToggle(this_week) => {
+                self.this_week = this_week;
+                return self.load_payables();
+            }
+            Message::PayablesLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(payables) => self.payables = payables,
+                    Err(e) => {
+                        return notify_error(format!("Erro ao buscar as contas a pagar: {}", e))
+                    }
+                }
+            }
+            Message::PaymentMethodChange(payment_method) => self.payment_method = payment_method,
+            Message::Pay(id) => {
+                let payable_service = self.payable_service.clone();
+                let payment_method = self.payment_method;
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        payable_service
+                            .pay(id, payment_method)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Paid,
+                );
+            }
+            Message::Paid(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::Notify(Notification::success("Título pago"))),
+                        self.load_payables(),
+                    ]),
+                    Err(e) => notify_error(format!("Erro ao pagar o título: {}", e)),
+                };
+            }
+            Message::ExpenseMonthChange(value) => self.expense_month = value,
+            Message::LoadExpenses => {
+                let Some((from, to)) = parse_month(&self.expense_month) else {
+                    return notify_error("Informe o mês no formato MM/AAAA".to_string());
+                };
+                let expense_service = self.expense_service.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        expense_service
+                            .list_expenses(from, to)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ExpensesLoaded,
+                );
+            }
+            Message::ExpensesLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(expenses) => self.expenses = expenses,
+                    Err(e) => return notify_error(format!("Erro ao buscar as despesas: {}", e)),
+                }
+            }
+            Message::ExpenseDateChange(value) => self.expense.paid_on = value,
+            Message::ExpenseCategoryChange(category) => self.expense.category = category,
+            Message::ExpenseDescriptionChange(value) => {
+                self.expense.description = value.to_uppercase()
+            }
+            Message::ExpenseAmountChange(value) => {
+                if !value.contains('-') && Money::validate_input(&value) {
+                    self.expense.amount = value;
+                }
+            }
+            Message::ExpenseMethodChange(payment_method) => {
+                self.expense.payment_method = payment_method
+            }
+            Message::AddExpense => {
+                let expense = match self.expense.to_expense() {
+                    Ok(expense) => expense,
+                    Err(e) => return notify_error(e),
+                };
+                let expense_service = self.expense_service.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        expense_service
+                            .add_expense(expense)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ExpenseAdded,
+                );
+            }
+            Message::ExpenseAdded(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(_) => {
+                        self.expense = ExpenseForm {
+                            paid_on: self.expense.paid_on.clone(),
+                            ..ExpenseForm::new()
+                        };
+                        Task::batch([
+                            Task::done(Message::Notify(Notification::success("Despesa lançada"))),
+                            Task::done(Message::LoadExpenses),
+                        ])
+                    }
+                    Err(e) => notify_error(format!("Erro ao lançar a despesa: {}", e)),
+                };
+            }
+            Message::DeleteExpense(id) => {
+                let expense_service = self.expense_service.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        expense_service
+                            .delete_expense(id)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ExpenseDeleted,
+                );
+            }
+            Message::ExpenseDeleted(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::Notify(Notification::success("Despesa excluída"))),
+                        Task::done(Message::LoadExpenses),
+                    ]),
+                    Err(e) => notify_error(format!("Erro ao excluir a despesa: {}", e)),
+                };
+            }
+            Message::YearChange(value) => {
+                if value.len() <= 4 && value.chars().all(|c| c.is_ascii_digit()) {
+                    self.year = value;
+                }
+            }
+            Message::LoadResults => {
+                let Some(year) = self.year.parse::<i32>().ok().filter(|year| *year >= 2000) else {
+                    return notify_error("Informe o ano com quatro dígitos".to_string());
+                };
+                let cash_flow_service = self.cash_flow_service.clone();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        cash_flow_service
+                            .monthly_results(year)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ResultsLoaded,
+                );
+            }
+            Message::ResultsLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(months) => self.months = months,
+                    Err(e) => return notify_error(format!("Erro ao calcular o resultado: {}", e)),
+                }
+            }
+            Message::AccountsLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(accounts) => self.accounts = accounts,
+                    Err(e) => return notify_error(format!("Erro ao buscar o fiado: {}", e)),
+                }
+            }
+            Message::Open(customer_id) => return self.load_statement(customer_id),
+            Message::StatementLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(Some(statement)) => self.statement = Some(statement),
+                    Ok(None) => return notify_error("Cliente não encontrado".to_string()),
+                    Err(e) => return notify_error(format!("Erro ao carregar o extrato: {}", e)),
+                }
+            }
+            Message::Back => {
+                self.statement = None;
+                self.amount.clear();
+                return Task::done(Message::Load);
+            }
+            Message::AmountChange(value) => {
+                if Money::validate_input(&value) {
+                    self.amount = value;
+                }
+            }
+            Message::ReceiveAll => {
+                if let Some(statement) = &self.statement {
+                    self.amount = statement.account.balance.to_string();
+                }
+            }
+            Message::Receive => {
+                let Some(statement) = &self.statement else {
+                    return Task::none();
+                };
+                let Some(amount) = Money::parse(&self.amount) else {
+                    return notify_error("Informe o valor recebido".to_string());
+                };
+                let receivable_service = self.receivable_service.clone();
+                let customer_id = statement.account.customer.id;
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        receivable_service
+                            .receive_payment(customer_id, amount)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Received,
+                );
+            }
+            Message::Received(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(()) => {
+                        self.amount.clear();
+                        let reload = match &self.statement {
+                            Some(statement) => self.load_statement(statement.account.customer.id),
+                            None => Task::none(),
+                        };
+                        Task::batch([
+                            Task::done(Message::Notify(Notification::success(
+                                "Pagamento registrado",
+                            ))),
+                            reload,
+                        ])
+                    }
+                    Err(e) => notify_error(format!("Erro ao receber: {}", e)),
+                };
+            }
+            Message::Print => {
+                let (Some(statement), Some(printer)) = (&self.statement, self.printer.clone())
+                else {
+                    return Task::none();
+                };
+                let data = statement::render(&self.store, statement, Utc::now().naive_local())
+                    .into_bytes();
+                self.busy = true;
+                return Task::perform(
+                    async move { printer.send(data).await.map_err(|e| e.to_string()) },
+                    Message::Printed,
+                );
+            }
+            Message::Printed(result) => {
+                self.busy = false;
+                let notification = match result {
+                    Ok(()) => Notification::success("Extrato enviado para a impressora"),
+                    Err(e) => Notification::error(format!("Erro ao imprimir: {}", e)),
+                };
+                return Task::done(Message::Notify(notification));
+            }
+            Message::Backup => {
+                // Handled by the application, which backs up the database
+            }
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn load_payables(&mut self) -> Task<Message> {
+        let payable_service = self.payable_service.clone();
+        let until = self
+            .this_week
+            .then(|| end_of_week(Local::now().date_naive()));
+        self.busy = true;
+        Task::perform(
+            async move {
+                payable_service
+                    .list_open(until)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::PayablesLoaded,
+        )
+    }
+
+    fn load_statement(&mut self, customer_id: i64) -> Task<Message> {
+        let receivable_service = self.receivable_service.clone();
+        self.busy = true;
+        Task::perform(
+            async move {
+                receivable_service
+                    .statement(customer_id)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::StatementLoaded,
+        )
+    }
+}
+
+/// First and last day of a month typed as `MM/AAAA`.
+fn parse_month(value: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::parse_from_str(&format!("01/{}", value.trim()), DATE_FORMAT).ok()?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((first, last))
+}
+
+/// Sunday of the week of `day`.
+fn end_of_week(day: NaiveDate) -> NaiveDate {
+    day + Days::new(6 - u64::from(day.weekday().num_days_from_monday()))
+}
+
+fn notify_error(message: String) -> Task<Message> {
+    Task::done(Message::Notify(Notification::error(message)))
+}
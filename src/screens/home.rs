@@ -1,111 +1,344 @@
 use crate::components::combo_box;
 use crate::components::combo_box::combo_box;
+use crate::components::notification::Notification;
+use crate::config::{HotkeysConfig, LoyaltyConfig};
+use crate::devices::scale::Scale;
+use crate::entities::customer::Customer;
+use crate::entities::payment_method::PaymentMethod;
 use crate::entities::product::Product;
-use crate::helpers::format_int_to_decimal;
+use crate::entities::product_sale::SaleItem;
+use crate::entities::unit::Unit;
+use crate::helpers::document::format_cpf;
+use crate::helpers::loyalty;
+use crate::helpers::money::Money;
+use crate::helpers::{format_quantity, parse_quantity, validate_quantity, QUANTITY_SCALE};
+use crate::services::customer_service::CustomerService;
 use crate::services::product_sale_service::ProductSaleService;
-use crate::services::product_service::ProductService;
-use iced::widget::{button, column, horizontal_rule, row, text, vertical_space};
+use crate::services::product_service::{search_text, BarcodeMatch, ProductService};
+use iced::event;
+use iced::keyboard::key::Named;
+use iced::keyboard::Key;
+use iced::widget::{
+    button, column, container, horizontal_rule, pick_list, row, text, text_input, vertical_space,
+};
 use iced::{Alignment, Element, Length, Task};
 use std::fmt::Display;
 use std::sync::Arc;
 
+/// Letters typed before the customers are searched.
+const CUSTOMER_SEARCH_MIN_LENGTH: usize = 3;
+/// Customers offered below the customer search.
+const CUSTOMER_RESULTS: usize = 5;
+
 #[derive(Debug)]
 pub struct State {
-    product_service: Arc<ProductService>,
-    product_sale_service: Arc<ProductSaleService>,
+    product_service: Arc<dyn ProductService>,
+    product_sale_service: Arc<dyn ProductSaleService>,
+    customer_service: Arc<dyn CustomerService>,
+    scale: Option<Arc<Scale>>,
+    status_message: Option<String>,
     search_bar: String,
     search_bar_products: combo_box::State<ProductItem>,
+    search_focused: bool,
     products: Vec<ProductItem>,
+    /// Id given to the next line added to the cart.
+    next_line: u64,
+    selected: Option<usize>,
+    confirm_cancel: bool,
+    customer: Option<Customer>,
+    customer_search: String,
+    customers_found: Vec<Customer>,
+    /// Loyalty points balance of the customer, once loaded.
+    points: Option<i64>,
+    points_input: String,
+    payment_method: PaymentMethod,
+    loyalty: LoyaltyConfig,
+    hotkeys: HotkeysConfig,
 }
 
 impl State {
     pub fn new(
-        product_service: Arc<ProductService>,
-        product_sale_service: Arc<ProductSaleService>,
+        product_service: Arc<dyn ProductService>,
+        product_sale_service: Arc<dyn ProductSaleService>,
+        customer_service: Arc<dyn CustomerService>,
+        scale: Option<Arc<Scale>>,
+        loyalty: LoyaltyConfig,
+        hotkeys: HotkeysConfig,
     ) -> Self {
         State {
             product_service,
             product_sale_service,
+            customer_service,
+            scale,
+            status_message: None,
             search_bar: String::new(),
-            search_bar_products: combo_box::State::default(),
+            search_bar_products: combo_box::State::default()
+                .search_by(ProductItem::search_text)
+                .query_by(|value| split_quantity(value).1),
+            search_focused: false,
             products: vec![],
+            next_line: 0,
+            selected: None,
+            confirm_cancel: false,
+            customer: None,
+            customer_search: String::new(),
+            customers_found: vec![],
+            points: None,
+            points_input: String::new(),
+            payment_method: PaymentMethod::default(),
+            loyalty,
+            hotkeys,
         }
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        column![
-            combo_box(
-                &self.search_bar_products,
-                "DIGITE O NOME DO PRODUTO",
-                None,
-                Message::SelectProduct
-            )
-            .on_input(Message::OnSearchBarChange),
-            row![
+        let search_bar = combo_box(
+            &self.search_bar_products,
+            "DIGITE O NOME OU O CÓDIGO DO PRODUTO",
+            None,
+            Message::SelectProduct,
+        )
+        .id(search_id())
+        .on_input(Message::OnSearchBarChange)
+        .on_submit(Message::SubmitSearch)
+        .on_open(Message::SearchFocused(true))
+        .on_close(Message::SearchFocused(false));
+
+        column![search_bar]
+            .push_maybe(self.status_message.as_ref().map(text))
+            .push(row![
                 self.product_list(),
                 column![
+                    self.customer_picker(),
+                    self.points_picker(),
                     text("TOTAL").size(64),
-                    text(format_int_to_decimal(self.total_value())).size(64),
+                    text(self.amount_due().to_string()).size(64),
                     vertical_space(),
-                    button(text("FINALIZAR VENDA").align_x(Alignment::Center))
-                        .padding(16)
-                        .width(Length::Fill)
-                        .on_press(Message::FinishSale),
+                    row![
+                        text("PAGAMENTO"),
+                        pick_list(
+                            PaymentMethod::ALL,
+                            Some(self.payment_method),
+                            Message::PaymentMethodChange
+                        )
+                        .width(Length::Fill),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                    button(
+                        text(format!("({}) FINALIZAR VENDA", self.hotkeys.payment))
+                            .align_x(Alignment::Center)
+                    )
+                    .padding(16)
+                    .width(Length::Fill)
+                    .on_press(Message::FinishSale),
                 ]
                 .align_x(Alignment::Center)
                 .width(Length::FillPortion(1))
-            ]
-        ]
-        .spacing(16)
-        .into()
+            ])
+            .push(vertical_space())
+            .push(self.legend())
+            .spacing(16)
+            .into()
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        // Cancelling takes two presses in a row, without touching the sale
+        // in between
+        if self.confirm_cancel && message.changes_sale() {
+            self.confirm_cancel = false;
+            self.status_message = None;
+        }
         match message {
             Message::OnSearchBarChange(value) => {
-                self.search_bar = value.clone();
-                if value.is_empty() {
+                self.search_bar = value;
+                let (_, query) = split_quantity(&self.search_bar);
+                if query.is_empty() || is_barcode(query) {
                     self.search_bar_products.change_options(vec![]);
                 } else {
                     let product_service = self.product_service.clone();
-                    let search_text = self.search_bar.clone();
+                    let search_text = query.to_string();
                     return Task::perform(
-                        async move { product_service.search_products_by_name(&search_text).await },
+                        async move {
+                            product_service
+                                .search_products_by_name(&search_text)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
                         Message::SearchedProducts,
                     );
                 }
             }
             Message::SelectProduct(product) => {
-                self.products.push(product);
-                self.search_bar = String::new();
-                self.search_bar_products.clear_text();
-                return Task::done(Message::OnSearchBarChange(String::new()));
+                return self.add_product(product);
             }
-            Message::SearchedProducts(products) => {
-                let items: Vec<ProductItem> = products
-                    .into_iter()
-                    .map(ProductItem::from_product)
-                    .collect();
-                self.search_bar_products.change_options(items);
+            Message::SubmitSearch(value) => {
+                let (_, code) = split_quantity(&value);
+                if is_barcode(code) {
+                    let product_service = self.product_service.clone();
+                    let code = code.to_string();
+                    return Task::perform(
+                        async move {
+                            product_service
+                                .search_by_barcode(&code)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::BarcodeSearched,
+                    );
+                }
             }
+            Message::BarcodeSearched(result) => match result {
+                Ok(Some(found)) => {
+                    let mut product = ProductItem::from_product(found.product);
+                    if let Some(quantity) = found.quantity {
+                        if quantity <= 0 || quantity > product.stock {
+                            return Task::done(Message::Notify(Notification::warning(format!(
+                                "Estoque insuficiente para {}",
+                                format_quantity(quantity, product.unit)
+                            ))));
+                        }
+                        product.set_quantity(quantity);
+                        product.weighed_by_label = true;
+                        product.label_total = found.total;
+                    }
+                    return self.add_product(product);
+                }
+                Ok(None) => {
+                    return Task::done(Message::Notify(Notification::warning(format!(
+                        "Produto não encontrado para o código {}",
+                        split_quantity(&self.search_bar).1
+                    ))));
+                }
+                Err(e) => return Task::done(Message::Notify(Notification::error(e))),
+            },
+            Message::ReadWeight(line) => {
+                if let Some(scale) = self.scale.clone() {
+                    self.status_message = Some("Lendo peso da balança...".to_string());
+                    return Task::perform(
+                        async move { scale.read_weight().await.map_err(|e| e.to_string()) },
+                        move |result| Message::WeightRead(line, result),
+                    );
+                }
+            }
+            Message::WeightRead(line, result) => {
+                self.status_message = None;
+                match result {
+                    Ok(weight) => {
+                        // The line may have been removed, or the sale
+                        // cancelled, while the scale was read
+                        if let Some(product) = self.products.iter_mut().find(|p| p.line == line) {
+                            if weight > product.stock {
+                                return Task::done(Message::Notify(Notification::warning(
+                                    format!(
+                                        "Estoque insuficiente para {}",
+                                        format_quantity(weight, product.unit)
+                                    ),
+                                )));
+                            }
+                            product.set_quantity(weight);
+                        }
+                    }
+                    Err(e) => return Task::done(Message::Notify(Notification::error(e))),
+                }
+            }
+            Message::SearchedProducts(result) => match result {
+                Ok(products) => {
+                    let items: Vec<ProductItem> = products
+                        .into_iter()
+                        .map(ProductItem::from_product)
+                        .collect();
+                    self.search_bar_products.change_options(items);
+                }
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao buscar produtos: {}",
+                        e
+                    ))));
+                }
+            },
             Message::RemoveProduct(index) => {
                 if index < self.products.len() {
                     self.products.remove(index);
+                    self.selected = match self.products.len() {
+                        0 => None,
+                        len => self.selected.map(|selected| selected.min(len - 1)),
+                    };
                 }
             }
-            Message::DecreaseProductQuantity(index) => {
-                if index < self.products.len() {
-                    let product = &mut self.products[index];
-                    if product.quantity > 1 {
-                        product.quantity -= 1;
+            Message::SearchFocused(focused) => self.search_focused = focused,
+            Message::FocusSearch => {
+                self.search_focused = true;
+                return text_input::focus(search_id());
+            }
+            Message::KeyPressed(key, status) => {
+                // Keys typed in the search bar only move through the cart
+                // while it is empty, and the ones of other inputs never do
+                let cart_keys = if self.search_focused {
+                    self.search_bar.is_empty()
+                } else {
+                    status == event::Status::Ignored
+                };
+                if !cart_keys || self.products.is_empty() {
+                    return Task::none();
+                }
+                let last = self.products.len() - 1;
+                match key.as_ref() {
+                    Key::Named(Named::ArrowUp) => {
+                        self.selected = Some(self.selected.map_or(last, |i| i.saturating_sub(1)));
                     }
+                    Key::Named(Named::ArrowDown) => {
+                        self.selected = Some(self.selected.map_or(0, |i| (i + 1).min(last)));
+                    }
+                    Key::Named(Named::Delete) => {
+                        if let Some(index) = self.selected {
+                            return Task::done(Message::RemoveProduct(index));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Message::CancelSale => {
+                if self.products.is_empty() {
+                    return Task::none();
+                }
+                if !self.confirm_cancel {
+                    self.confirm_cancel = true;
+                    self.status_message = Some(format!(
+                        "Pressione {} novamente para cancelar a venda",
+                        self.hotkeys.cancel_sale
+                    ));
+                    return Task::none();
+                }
+                self.clear_sale();
+                return Task::batch([
+                    Task::done(Message::Notify(Notification::warning("Venda cancelada"))),
+                    Task::done(Message::FocusSearch),
+                ]);
+            }
+            Message::DecreaseProductQuantity(index) => {
+                if let Some(product) = self.products.get_mut(index)
+                    && product.quantity > QUANTITY_SCALE
+                {
+                    product.set_quantity(product.quantity - QUANTITY_SCALE);
                 }
             }
             Message::IncreaseProductQuantity(index) => {
-                if index < self.products.len() {
-                    let product = &mut self.products[index];
-                    if product.quantity < product.stock {
-                        product.quantity += 1;
+                if let Some(product) = self.products.get_mut(index)
+                    && product.quantity + QUANTITY_SCALE <= product.stock
+                {
+                    product.set_quantity(product.quantity + QUANTITY_SCALE);
+                }
+            }
+            Message::QuantityChange(index, value) => {
+                if let Some(product) = self.products.get_mut(index)
+                    && validate_quantity(&value, product.unit)
+                {
+                    let quantity = parse_quantity(&value, product.unit).unwrap_or(0);
+                    if quantity <= product.stock {
+                        product.quantity = quantity;
+                        product.quantity_input = value;
+                        product.label_total = None;
                     }
                 }
             }
@@ -114,39 +347,282 @@ impl State {
                     return Task::none();
                 }
 
-                let sale_products: Vec<Product> =
-                    self.products.iter().map(|p| p.to_product()).collect();
+                if self.products.iter().any(|p| p.quantity <= 0) {
+                    return Task::done(Message::Notify(Notification::warning(
+                        "Informe a quantidade de todos os itens",
+                    )));
+                }
+
+                let Some(points) = self.points_to_use() else {
+                    return Task::done(Message::Notify(Notification::warning(
+                        "Quantidade de pontos inválida",
+                    )));
+                };
+                let sale_items: Vec<SaleItem> =
+                    self.products.iter().map(|p| p.to_sale_item()).collect();
+                let customer_id = self.customer.as_ref().map(|customer| customer.id);
+                let payment_method = self.payment_method;
                 let product_sale_service = self.product_sale_service.clone();
                 return Task::perform(
                     async move {
-                        match product_sale_service.add_sale(sale_products).await {
-                            Ok(_) => Message::OnSaleFinished(Some(
-                                "Venda finalizada com sucesso!".to_string(),
-                            )),
-                            Err(e) => Message::OnSaleFinished(Some(format!(
-                                "Erro ao finalizar venda: {}",
-                                e
-                            ))),
-                        }
+                        product_sale_service
+                            .add_sale(sale_items, customer_id, points, payment_method)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::OnSaleFinished,
+                );
+            }
+            Message::OnSaleFinished(result) => match result {
+                Ok(_) => {
+                    self.clear_sale();
+                    return Task::batch([
+                        Task::done(Message::Notify(Notification::success(
+                            "Venda finalizada com sucesso!",
+                        ))),
+                        Task::done(Message::FocusSearch),
+                    ]);
+                }
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao finalizar venda: {}",
+                        e
+                    ))));
+                }
+            },
+            Message::CustomerSearchChange(value) => {
+                self.customer_search = value;
+                let text = self.customer_search.trim().to_string();
+                if text.chars().count() < CUSTOMER_SEARCH_MIN_LENGTH {
+                    self.customers_found.clear();
+                    return Task::none();
+                }
+                let customer_service = self.customer_service.clone();
+                return Task::perform(
+                    async move {
+                        customer_service
+                            .search_customers(&text)
+                            .await
+                            .map_err(|e| e.to_string())
                     },
-                    |msg| msg,
+                    Message::CustomersFound,
                 );
             }
-            Message::OnSaleFinished(value) => {
-                self.products.clear();
-                if let Some(msg) = value {
-                    println!("{}", msg);
+            Message::CustomersFound(result) => match result {
+                Ok(customers) => self.customers_found = customers,
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao buscar clientes: {}",
+                        e
+                    ))));
+                }
+            },
+            Message::SelectCustomer(customer) => {
+                let id = customer.id;
+                self.customer = Some(customer);
+                self.customer_search.clear();
+                self.customers_found.clear();
+                if !self.loyalty.enabled {
+                    return Task::done(Message::FocusSearch);
+                }
+                let customer_service = self.customer_service.clone();
+                return Task::batch([
+                    Task::perform(
+                        async move {
+                            customer_service
+                                .points_balance(id)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PointsLoaded,
+                    ),
+                    Task::done(Message::FocusSearch),
+                ]);
+            }
+            Message::RemoveCustomer => {
+                self.customer = None;
+                self.points = None;
+                self.points_input.clear();
+            }
+            Message::PointsLoaded(result) => match result {
+                Ok(points) => self.points = Some(points),
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao carregar os pontos do cliente: {}",
+                        e
+                    ))));
+                }
+            },
+            Message::PointsChange(value) => {
+                if value.chars().all(|c| c.is_ascii_digit()) {
+                    self.points_input = value;
                 }
             }
+            Message::PaymentMethodChange(method) => self.payment_method = method,
+            Message::UseAllPoints => {
+                let points = self
+                    .points
+                    .unwrap_or(0)
+                    .min(loyalty::points_for(&self.loyalty, self.total_value()));
+                self.points_input = if points > 0 {
+                    points.to_string()
+                } else {
+                    String::new()
+                };
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
         }
 
         Task::none()
     }
 
+    fn add_product(&mut self, mut product: ProductItem) -> Task<Message> {
+        let (quantity, _) = split_quantity(&self.search_bar);
+        if let Some(quantity) = quantity
+            && !product.weighed_by_label
+        {
+            match parse_quantity(quantity, product.unit).filter(|q| *q > 0) {
+                Some(quantity) if quantity > product.stock => {
+                    return Task::done(Message::Notify(Notification::warning(format!(
+                        "Estoque insuficiente para {}",
+                        format_quantity(quantity, product.unit)
+                    ))));
+                }
+                Some(quantity) => product.set_quantity(quantity),
+                None => {
+                    return Task::done(Message::Notify(Notification::warning(format!(
+                        "Quantidade \"{}\" inválida para {}",
+                        quantity, product.name
+                    ))));
+                }
+            }
+        }
+        let read_weight = product.unit.is_weighed()
+            && !product.weighed_by_label
+            && quantity.is_none()
+            && self.scale.is_some();
+        product.line = self.next_line;
+        self.next_line += 1;
+        let line = product.line;
+        self.products.push(product);
+        self.selected = Some(self.products.len() - 1);
+        self.confirm_cancel = false;
+        self.search_bar = String::new();
+        self.search_bar_products.clear_text();
+        self.status_message = None;
+        let clear_search = Task::done(Message::OnSearchBarChange(String::new()));
+        if read_weight {
+            return Task::batch([clear_search, Task::done(Message::ReadWeight(line))]);
+        }
+        clear_search
+    }
+
+    fn clear_sale(&mut self) {
+        self.products.clear();
+        self.customer = None;
+        self.points = None;
+        self.points_input.clear();
+        self.payment_method = PaymentMethod::default();
+        self.selected = None;
+        self.confirm_cancel = false;
+        self.status_message = None;
+    }
+
+    /// Optional customer of the sale, searched by name, CPF or phone.
+    fn customer_picker(&self) -> Element<'_, Message> {
+        if let Some(customer) = &self.customer {
+            return row![
+                text(format!("CLIENTE: {}", customer.name)).width(Length::Fill),
+                button("X").on_press(Message::RemoveCustomer),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into();
+        }
+        let mut picker =
+            column![
+                text_input("CLIENTE (NOME, CPF OU TELEFONE)", &self.customer_search)
+                    .on_input(Message::CustomerSearchChange)
+            ]
+            .spacing(4);
+        for customer in self.customers_found.iter().take(CUSTOMER_RESULTS) {
+            let document = customer.cpf.as_deref().map(format_cpf).unwrap_or_default();
+            picker = picker.push(
+                button(text(format!("{} {}", customer.name, document)))
+                    .width(Length::Fill)
+                    .on_press(Message::SelectCustomer(customer.clone())),
+            );
+        }
+        picker.into()
+    }
+
+    /// Loyalty points of the customer, which can pay part of the sale.
+    fn points_picker(&self) -> Element<'_, Message> {
+        let Some(points) = self.points else {
+            return column![].into();
+        };
+        let mut picker = column![
+            text(format!(
+                "PONTOS: {} ({})",
+                points,
+                loyalty::value(&self.loyalty, points.max(0))
+            )),
+            row![
+                text_input("PONTOS A USAR", &self.points_input).on_input(Message::PointsChange),
+                button("USAR TODOS").on_press(Message::UseAllPoints),
+            ]
+            .spacing(8),
+        ]
+        .spacing(4);
+        let discount = self.points_discount();
+        if discount > Money::ZERO {
+            picker = picker.push(text(format!(
+                "SUBTOTAL {} - PONTOS {}",
+                self.total_value(),
+                discount
+            )));
+        }
+        picker.into()
+    }
+
+    /// Points typed to pay the sale, `None` when not a number.
+    fn points_to_use(&self) -> Option<i64> {
+        match self.points_input.trim() {
+            "" => Some(0),
+            value => value.parse().ok(),
+        }
+    }
+
+    fn points_discount(&self) -> Money {
+        let points = self.points_to_use().unwrap_or(0);
+        loyalty::value(&self.loyalty, points).min(self.total_value())
+    }
+
+    fn amount_due(&self) -> Money {
+        self.total_value() - self.points_discount()
+    }
+
+    fn legend(&self) -> Element<'_, Message> {
+        let keys = [
+            ("↑ ↓".to_string(), "SELECIONAR ITEM"),
+            ("DEL".to_string(), "REMOVER ITEM"),
+            ("QTD*CÓDIGO".to_string(), "QUANTIDADE"),
+            (self.hotkeys.search.clone(), "BUSCAR"),
+            (self.hotkeys.cancel_sale.clone(), "CANCELAR VENDA"),
+            (self.hotkeys.payment.clone(), "PAGAMENTO"),
+        ];
+        row(keys
+            .into_iter()
+            .map(|(key, action)| text(format!("({}) {}", key, action)).size(14).into()))
+        .spacing(24)
+        .into()
+    }
+
     fn product_list(&self) -> Element<'_, Message> {
         let quantity_width = 100;
         let total_width = 100;
-        let action_width = 120;
+        let action_width = 180;
         let mut list = column![
             row![
                 text("PRODUTO\nPRECO UNIT.").width(Length::FillPortion(4)),
@@ -159,46 +635,53 @@ impl State {
         ];
 
         for (index, product) in self.products.iter().enumerate() {
-            let button_more = if product.quantity < product.stock {
+            let button_more = if product.quantity + QUANTITY_SCALE <= product.stock {
                 button("+").on_press(Message::IncreaseProductQuantity(index))
             } else {
                 button("+")
             };
-            let button_less = if product.quantity > 1 {
+            let button_less = if product.quantity > QUANTITY_SCALE {
                 button("-").on_press(Message::DecreaseProductQuantity(index))
             } else {
                 button("-")
             };
-            let buttons_row = row![
+            let mut buttons_row = row![
                 button("X").on_press(Message::RemoveProduct(index)),
                 button_less,
                 button_more,
             ]
             .spacing(4);
+            if product.unit.is_weighed() && self.scale.is_some() {
+                buttons_row =
+                    buttons_row.push(button("PESO").on_press(Message::ReadWeight(product.line)));
+            }
 
-            list = list
-                .push(
-                    row![
-                        column![
-                            text(&product.name),
-                            text(format_int_to_decimal(product.value)),
-                        ]
-                        .width(Length::FillPortion(4)),
-                        text(product.quantity).width(quantity_width),
-                        text(format_int_to_decimal(product.total_value())).width(total_width),
-                        buttons_row.width(action_width),
-                    ]
-                    .spacing(16)
-                    .align_y(Alignment::Center),
-                )
-                .push(horizontal_rule(1));
+            let line = row![
+                column![text(&product.name), text(product.value.to_string()),]
+                    .width(Length::FillPortion(4)),
+                text_input(product.unit.as_str(), &product.quantity_input)
+                    .on_input(move |value| Message::QuantityChange(index, value))
+                    .width(quantity_width),
+                text(product.total_value().to_string()).width(total_width),
+                buttons_row.width(action_width),
+            ]
+            .spacing(16)
+            .align_y(Alignment::Center);
+            let mut line = container(line).padding(4);
+            if self.selected == Some(index) {
+                line = line.style(container::bordered_box);
+            }
+
+            list = list.push(line).push(horizontal_rule(1));
         }
 
         list.into()
     }
 
-    fn total_value(&self) -> i64 {
-        self.products.iter().map(|x| x.total_value()).sum()
+    fn total_value(&self) -> Money {
+        self.products.iter().fold(Money::ZERO, |total, x| {
+            total.saturating_add(x.total_value())
+        })
     }
 }
 
@@ -206,71 +689,178 @@ impl State {
 pub enum Message {
     OnSearchBarChange(String),
     SelectProduct(ProductItem),
-    SearchedProducts(Vec<Product>),
+    SubmitSearch(String),
+    BarcodeSearched(Result<Option<BarcodeMatch>, String>),
+    SearchedProducts(Result<Vec<Product>, String>),
     RemoveProduct(usize),
     DecreaseProductQuantity(usize),
     IncreaseProductQuantity(usize),
+    QuantityChange(usize, String),
+    ReadWeight(u64),
+    WeightRead(u64, Result<i64, String>),
     FinishSale,
-    OnSaleFinished(Option<String>),
+    OnSaleFinished(Result<i64, String>),
+    KeyPressed(Key, event::Status),
+    SearchFocused(bool),
+    FocusSearch,
+    CancelSale,
+    CustomerSearchChange(String),
+    CustomersFound(Result<Vec<Customer>, String>),
+    SelectCustomer(Customer),
+    RemoveCustomer,
+    PointsLoaded(Result<i64, String>),
+    PointsChange(String),
+    UseAllPoints,
+    PaymentMethodChange(PaymentMethod),
+    Notify(Notification),
+}
+
+impl Message {
+    /// Whether the operator is working on the sale, rather than the message
+    /// being a result or the cancel command itself.
+    fn changes_sale(&self) -> bool {
+        matches!(
+            self,
+            Message::OnSearchBarChange(_)
+                | Message::SelectProduct(_)
+                | Message::SubmitSearch(_)
+                | Message::RemoveProduct(_)
+                | Message::DecreaseProductQuantity(_)
+                | Message::IncreaseProductQuantity(_)
+                | Message::QuantityChange(_, _)
+                | Message::ReadWeight(_)
+                | Message::FinishSale
+                | Message::KeyPressed(_, _)
+                | Message::CustomerSearchChange(_)
+                | Message::SelectCustomer(_)
+                | Message::RemoveCustomer
+                | Message::PointsChange(_)
+                | Message::UseAllPoints
+                | Message::PaymentMethodChange(_)
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct ProductItem {
+    /// Identifies the line in the cart, as its index changes on removals.
+    line: u64,
     id: i64,
     ean: Option<String>,
+    category: Option<String>,
     name: String,
     quantity: i64,
-    value: i64,
-    value_purchase: i64,
+    quantity_input: String,
+    unit: Unit,
+    value: Money,
+    value_purchase: Money,
     stock: i64,
+    weighed_by_label: bool,
+    /// Total printed on a price label, charged until the quantity changes.
+    label_total: Option<Money>,
 }
 
 impl ProductItem {
-    fn total_value(&self) -> i64 {
-        (self.quantity * self.value)
+    fn total_value(&self) -> Money {
+        self.label_total
+            .unwrap_or_else(|| self.value.saturating_times_quantity(self.quantity))
+    }
+
+    fn set_quantity(&mut self, quantity: i64) {
+        self.quantity = quantity;
+        self.quantity_input = format_quantity(quantity, self.unit);
+        self.label_total = None;
     }
 
     fn from_product(product: Product) -> Self {
         ProductItem {
+            line: 0,
             id: product.id,
             ean: product.ean,
+            category: product.category,
             name: product.name,
-            quantity: 1,
+            quantity: QUANTITY_SCALE,
+            quantity_input: format_quantity(QUANTITY_SCALE, product.unit),
+            unit: product.unit,
             value: product.price_sale,
             value_purchase: product.price_purchase,
             stock: product.quantity,
+            weighed_by_label: false,
+            label_total: None,
         }
     }
 
     fn to_product(&self) -> Product {
-        Product::new(
-            self.id,
-            self.name.clone(),
-            self.value,
-            self.value_purchase,
-            self.quantity,
-            self.ean.clone(),
-            chrono::Local::now().naive_local(),
-        )
+        Product {
+            category: self.category.clone(),
+            ..Product::new(
+                self.id,
+                self.name.clone(),
+                self.value,
+                self.value_purchase,
+                self.quantity,
+                self.unit,
+                self.ean.clone(),
+                chrono::Local::now().naive_local(),
+            )
+        }
+    }
+
+    fn to_sale_item(&self) -> SaleItem {
+        SaleItem {
+            product: self.to_product(),
+            total: self.label_total,
+        }
+    }
+
+    /// Lets the search bar also find the product by EAN and category.
+    fn search_text(&self) -> String {
+        search_text(&self.to_product())
     }
 }
 
 impl Default for ProductItem {
     fn default() -> Self {
         ProductItem {
+            line: 0,
             id: 0,
             ean: None,
+            category: None,
             name: "Produto".to_string(),
-            quantity: 2,
-            value: 1050,
-            value_purchase: 800,
-            stock: 10,
+            quantity: 2 * QUANTITY_SCALE,
+            quantity_input: "2".to_string(),
+            unit: Unit::Un,
+            value: Money::from_cents(1050),
+            value_purchase: Money::from_cents(800),
+            stock: 10 * QUANTITY_SCALE,
+            weighed_by_label: false,
+            label_total: None,
         }
     }
 }
 
 impl Display for ProductItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} - {}", self.name, format_int_to_decimal(self.value))
+        write!(f, "{} - {}", self.name, self.value)
     }
 }
+
+fn search_id() -> text_input::Id {
+    text_input::Id::new("home-search")
+}
+
+/// Splits a quantity typed before `*` from the product, so `5*7891234567890`
+/// adds 5 units of the scanned product.
+fn split_quantity(value: &str) -> (Option<&str>, &str) {
+    match value.split_once('*') {
+        Some((quantity, product)) if !quantity.trim().is_empty() => {
+            (Some(quantity.trim()), product.trim())
+        }
+        _ => (None, value),
+    }
+}
+
+/// Scanned codes are all digits, with at least the 8 digits of an EAN-8.
+fn is_barcode(value: &str) -> bool {
+    value.len() >= 8 && value.chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,177 @@
+use crate::components::notification::Notification;
+use crate::config::LabelPrinterConfig;
+use crate::printing::labels::{render, to_pdf, Label, LabelFormat};
+use crate::printing::PrinterTarget;
+use iced::widget::{
+    button, column, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
+use iced::{Alignment, Element, Length, Task};
+
+const REMOVE_BUTTON_WIDTH: f32 = 30.0;
+const EAN_WIDTH: f32 = 140.0;
+const PRICE_WIDTH: f32 = 100.0;
+const COPIES_WIDTH: f32 = 80.0;
+
+#[derive(Debug)]
+pub struct State {
+    config: LabelPrinterConfig,
+    items: Vec<LabelItem>,
+    format: LabelFormat,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    CopiesChange(usize, String),
+    RemoveItem(usize),
+    FormatChange(LabelFormat),
+    Print,
+    SavePdf,
+    Printed(Result<String, String>),
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(config: LabelPrinterConfig, labels: Vec<Label>) -> Self {
+        Self {
+            format: config.format,
+            config,
+            items: labels.into_iter().map(LabelItem::new).collect(),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("").width(Length::Fixed(REMOVE_BUTTON_WIDTH)),
+            text("PRODUTO").width(Length::Fill),
+            text("EAN").width(Length::Fixed(EAN_WIDTH)),
+            text("PREÇO").width(Length::Fixed(PRICE_WIDTH)),
+            text("CÓPIAS").width(Length::Fixed(COPIES_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (index, item) in self.items.iter().enumerate() {
+            list = list.push(
+                row![
+                    button("X")
+                        .width(Length::Fixed(REMOVE_BUTTON_WIDTH))
+                        .on_press(Message::RemoveItem(index)),
+                    text(&item.label.name).width(Length::Fill),
+                    text(item.label.ean.clone().unwrap_or_default())
+                        .width(Length::Fixed(EAN_WIDTH)),
+                    text(item.label.price.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text_input("Cópias", &item.copies)
+                        .width(Length::Fixed(COPIES_WIDTH))
+                        .on_input(move |value| Message::CopiesChange(index, value)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let printer = match self.config.target() {
+            Some(target) => format!("IMPRESSORA: {}", target),
+            None => "IMPRESSORA NÃO CONFIGURADA".to_string(),
+        };
+        let print_button = button("IMPRIMIR ETIQUETAS")
+            .on_press_maybe(self.config.target().map(|_| Message::Print));
+
+        column![
+            text("ETIQUETAS").size(24),
+            header,
+            scrollable(list).height(Length::Fill),
+        ]
+        .push(
+            row![
+                text(printer),
+                horizontal_space(),
+                pick_list(LabelFormat::ALL, Some(self.format), Message::FormatChange),
+                print_button,
+                button("GERAR PDF").on_press(Message::SavePdf),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        )
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::CopiesChange(index, value) => {
+                if value.chars().all(|c| c.is_ascii_digit())
+                    && let Some(item) = self.items.get_mut(index)
+                {
+                    item.label.copies = value.parse().unwrap_or(0);
+                    item.copies = value;
+                }
+            }
+            Message::RemoveItem(index) => {
+                if index < self.items.len() {
+                    self.items.remove(index);
+                }
+            }
+            Message::FormatChange(format) => {
+                self.format = format;
+            }
+            Message::Print => {
+                if let Some(target) = self.config.target() {
+                    let data = render(&self.labels(), self.format).into_bytes();
+                    return Task::perform(
+                        async move {
+                            target
+                                .send(data)
+                                .await
+                                .map(|_| format!("Etiquetas enviadas para {}", target))
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::Printed,
+                    );
+                }
+            }
+            Message::SavePdf => {
+                let target = PrinterTarget::File(self.config.pdf_path.clone());
+                let data = to_pdf(&self.labels());
+                return Task::perform(
+                    async move {
+                        target
+                            .send(data)
+                            .await
+                            .map(|_| format!("PDF salvo em {}", target))
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Printed,
+                );
+            }
+            Message::Printed(result) => {
+                return Task::done(Message::Notify(match result {
+                    Ok(message) => Notification::success(message),
+                    Err(e) => Notification::error(e),
+                }));
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        self.items.iter().map(|item| item.label.clone()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LabelItem {
+    label: Label,
+    copies: String,
+}
+
+impl LabelItem {
+    fn new(label: Label) -> Self {
+        Self {
+            copies: label.copies.to_string(),
+            label,
+        }
+    }
+}
@@ -1,2 +1,15 @@
+pub mod abc_curve;
+pub mod add_purchase;
+pub mod catalog;
+pub mod customers;
+pub mod financial;
 pub mod home;
-pub mod add_purchase;
\ No newline at end of file
+pub mod labels;
+pub mod products;
+pub mod sales_history;
+
+pub mod settings;
+
+pub mod backup;
+
+pub mod startup;
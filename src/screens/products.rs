@@ -0,0 +1,469 @@
+use crate::components::notification::Notification;
+use crate::entities::kit_component::KitComponent;
+use crate::entities::product::Product;
+use crate::entities::unit::Unit;
+use crate::helpers::{format_quantity, parse_quantity, validate_quantity};
+use crate::printing::labels::Label;
+use crate::services::product_service::ProductService;
+use iced::widget::{button, checkbox, column, horizontal_space, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task};
+use std::sync::Arc;
+
+const SELECT_WIDTH: f32 = 30.0;
+const ID_WIDTH: f32 = 50.0;
+const EAN_WIDTH: f32 = 140.0;
+const UNIT_WIDTH: f32 = 40.0;
+const STOCK_WIDTH: f32 = 100.0;
+const PRICE_WIDTH: f32 = 100.0;
+const CLASS_WIDTH: f32 = 40.0;
+const KIT_WIDTH: f32 = 110.0;
+const REMOVE_BUTTON_WIDTH: f32 = 30.0;
+const QUANTITY_WIDTH: f32 = 100.0;
+
+#[derive(Debug)]
+pub struct State {
+    product_service: Arc<dyn ProductService>,
+    search_text: String,
+    products: Vec<Product>,
+    selected: Vec<Product>,
+    kit: Option<KitEditor>,
+}
+
+/// Composition of the kit being edited, with the quantities as typed.
+#[derive(Debug)]
+struct KitEditor {
+    kit: Product,
+    components: Vec<ComponentItem>,
+    search_text: String,
+    search_products: Vec<Product>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ComponentItem {
+    id: i64,
+    name: String,
+    unit: Unit,
+    quantity: String,
+    stock: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SearchTextChange(String),
+    ProductsLoaded(Result<Vec<Product>, String>),
+    ToggleSelected(usize, bool),
+    PrintLabels(Vec<Label>),
+    OpenCatalog,
+    OpenAbcCurve,
+    EditKit(usize),
+    KitLoaded(Result<Vec<KitComponent>, String>),
+    CloseKit,
+    ComponentSearchChange(String),
+    ComponentsFound(Result<Vec<Product>, String>),
+    AddComponent(Product),
+    ComponentQuantityChange(usize, String),
+    RemoveComponent(usize),
+    SaveKit,
+    UndoKit,
+    KitSaved(Result<(), String>),
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(product_service: Arc<dyn ProductService>) -> Self {
+        Self {
+            product_service,
+            search_text: String::new(),
+            products: vec![],
+            selected: vec![],
+            kit: None,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some(editor) = &self.kit {
+            return editor.view();
+        }
+
+        let header = row![
+            text("").width(Length::Fixed(SELECT_WIDTH)),
+            text("ID").width(Length::Fixed(ID_WIDTH)),
+            text("EAN").width(Length::Fixed(EAN_WIDTH)),
+            text("PRODUTO").width(Length::Fill),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
+            text("ESTOQUE").width(Length::Fixed(STOCK_WIDTH)),
+            text("P. VENDA").width(Length::Fixed(PRICE_WIDTH)),
+            text("ABC").width(Length::Fixed(CLASS_WIDTH)),
+            text("").width(Length::Fixed(KIT_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (index, product) in self.products.iter().enumerate() {
+            list = list.push(
+                row![
+                    checkbox("", self.is_selected(product.id))
+                        .on_toggle(move |value| Message::ToggleSelected(index, value))
+                        .width(Length::Fixed(SELECT_WIDTH)),
+                    text(product.id).width(Length::Fixed(ID_WIDTH)),
+                    text(product.ean.clone().unwrap_or_default()).width(Length::Fixed(EAN_WIDTH)),
+                    text(&product.name).width(Length::Fill),
+                    text(product.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                    text(format_quantity(product.quantity, product.unit))
+                        .width(Length::Fixed(STOCK_WIDTH)),
+                    text(product.price_sale.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(
+                        product
+                            .abc_class
+                            .map(|class| class.as_str())
+                            .unwrap_or_default()
+                    )
+                    .width(Length::Fixed(CLASS_WIDTH)),
+                    button(if product.is_kit { "EDITAR KIT" } else { "KIT" })
+                        .on_press(Message::EditKit(index))
+                        .width(Length::Fixed(KIT_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let labels_button = button("IMPRIMIR ETIQUETAS").on_press_maybe(
+            (!self.selected.is_empty()).then(|| Message::PrintLabels(self.selected_labels())),
+        );
+
+        column![
+            text_input("DIGITE O NOME DO PRODUTO", &self.search_text)
+                .on_input(Message::SearchTextChange),
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("{} SELECIONADO(S)", self.selected.len())),
+                horizontal_space(),
+                button("CURVA ABC").on_press(Message::OpenAbcCurve),
+                button("IMPORTAR / EXPORTAR").on_press(Message::OpenCatalog),
+                labels_button,
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SearchTextChange(value) => {
+                self.search_text = value.to_uppercase();
+                let product_service = self.product_service.clone();
+                let search_text = self.search_text.clone();
+                return Task::perform(
+                    async move {
+                        product_service
+                            .list_products(&search_text)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ProductsLoaded,
+                );
+            }
+            Message::ProductsLoaded(result) => match result {
+                Ok(products) => self.products = products,
+                Err(e) => {
+                    return Task::done(Message::Notify(Notification::error(format!(
+                        "Erro ao carregar produtos: {}",
+                        e
+                    ))));
+                }
+            },
+            Message::ToggleSelected(index, selected) => {
+                if let Some(product) = self.products.get(index) {
+                    let id = product.id;
+                    if selected && !self.is_selected(id) {
+                        self.selected.push(product.clone());
+                    } else if !selected {
+                        self.selected.retain(|product| product.id != id);
+                    }
+                }
+            }
+            // Handled by the application, which opens the labels screen
+            Message::PrintLabels(_) => {}
+            // Handled by the application, which opens the catalog screen
+            Message::OpenCatalog => {}
+            // Handled by the application, which opens the ABC curve screen
+            Message::OpenAbcCurve => {}
+            Message::EditKit(index) => {
+                if let Some(product) = self.products.get(index) {
+                    let kit_id = product.id;
+                    self.kit = Some(KitEditor {
+                        kit: product.clone(),
+                        components: vec![],
+                        search_text: String::new(),
+                        search_products: vec![],
+                        busy: true,
+                    });
+                    let product_service = self.product_service.clone();
+                    return Task::perform(
+                        async move {
+                            product_service
+                                .kit_components(kit_id)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::KitLoaded,
+                    );
+                }
+            }
+            Message::KitLoaded(result) => {
+                if let Some(editor) = &mut self.kit {
+                    editor.busy = false;
+                    match result {
+                        Ok(components) => {
+                            editor.components = components
+                                .iter()
+                                .map(ComponentItem::from_component)
+                                .collect();
+                        }
+                        Err(e) => {
+                            return notify_error(format!("Erro ao carregar o kit: {}", e));
+                        }
+                    }
+                }
+            }
+            Message::CloseKit => self.kit = None,
+            Message::ComponentSearchChange(value) => {
+                if let Some(editor) = &mut self.kit {
+                    editor.search_text = value.to_uppercase();
+                    if editor.search_text.is_empty() {
+                        editor.search_products = vec![];
+                    } else {
+                        let product_service = self.product_service.clone();
+                        let search_text = editor.search_text.clone();
+                        return Task::perform(
+                            async move {
+                                product_service
+                                    .list_products(&search_text)
+                                    .await
+                                    .map_err(|e| e.to_string())
+                            },
+                            Message::ComponentsFound,
+                        );
+                    }
+                }
+            }
+            Message::ComponentsFound(result) => match result {
+                Ok(products) => {
+                    if let Some(editor) = &mut self.kit {
+                        editor.search_products = products;
+                    }
+                }
+                Err(e) => return notify_error(format!("Erro ao buscar produtos: {}", e)),
+            },
+            Message::AddComponent(product) => {
+                if let Some(editor) = &mut self.kit {
+                    if product.is_kit || product.id == editor.kit.id {
+                        return Task::done(Message::Notify(Notification::warning(
+                            "Um kit não pode fazer parte de outro kit",
+                        )));
+                    }
+                    if !editor.components.iter().any(|item| item.id == product.id) {
+                        editor
+                            .components
+                            .push(ComponentItem::from_product(&product));
+                    }
+                    editor.search_text.clear();
+                    editor.search_products = vec![];
+                }
+            }
+            Message::ComponentQuantityChange(index, value) => {
+                if let Some(item) = self
+                    .kit
+                    .as_mut()
+                    .and_then(|editor| editor.components.get_mut(index))
+                    && validate_quantity(&value, item.unit)
+                {
+                    item.quantity = value;
+                }
+            }
+            Message::RemoveComponent(index) => {
+                if let Some(editor) = &mut self.kit
+                    && index < editor.components.len()
+                {
+                    editor.components.remove(index);
+                }
+            }
+            Message::SaveKit => {
+                if let Some(editor) = &mut self.kit {
+                    let mut components = Vec::with_capacity(editor.components.len());
+                    for item in &editor.components {
+                        match parse_quantity(&item.quantity, item.unit).filter(|q| *q > 0) {
+                            Some(quantity) => components.push((item.id, quantity)),
+                            None => {
+                                return Task::done(Message::Notify(Notification::warning(
+                                    format!("Informe a quantidade de {} no kit", item.name),
+                                )));
+                            }
+                        }
+                    }
+                    let product_service = self.product_service.clone();
+                    let kit_id = editor.kit.id;
+                    editor.busy = true;
+                    return Task::perform(
+                        async move {
+                            product_service
+                                .save_kit(kit_id, components)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::KitSaved,
+                    );
+                }
+            }
+            // Without components the product goes back to a regular one
+            Message::UndoKit => {
+                if let Some(editor) = &mut self.kit {
+                    editor.components.clear();
+                    return Task::done(Message::SaveKit);
+                }
+            }
+            Message::KitSaved(result) => {
+                if let Some(editor) = &mut self.kit {
+                    editor.busy = false;
+                }
+                match result {
+                    Ok(()) => {
+                        self.kit = None;
+                        return Task::batch([
+                            Task::done(Message::Notify(Notification::success(
+                                "Composição do kit salva",
+                            ))),
+                            Task::done(Message::SearchTextChange(self.search_text.clone())),
+                        ]);
+                    }
+                    Err(e) => return notify_error(format!("Erro ao salvar o kit: {}", e)),
+                }
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn is_selected(&self, id: i64) -> bool {
+        self.selected.iter().any(|product| product.id == id)
+    }
+
+    fn selected_labels(&self) -> Vec<Label> {
+        self.selected
+            .iter()
+            .map(|product| Label::new(product, 1))
+            .collect()
+    }
+}
+
+impl KitEditor {
+    fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("").width(Length::Fixed(REMOVE_BUTTON_WIDTH)),
+            text("COMPONENTE").width(Length::Fill),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
+            text("QTD NO KIT").width(Length::Fixed(QUANTITY_WIDTH)),
+            text("ESTOQUE").width(Length::Fixed(STOCK_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (index, item) in self.components.iter().enumerate() {
+            list = list.push(
+                row![
+                    button("X")
+                        .on_press(Message::RemoveComponent(index))
+                        .width(Length::Fixed(REMOVE_BUTTON_WIDTH)),
+                    text(&item.name).width(Length::Fill),
+                    text(item.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                    text_input("Quantidade", &item.quantity)
+                        .on_input(move |value| Message::ComponentQuantityChange(index, value))
+                        .width(Length::Fixed(QUANTITY_WIDTH)),
+                    text(format_quantity(item.stock, item.unit)).width(Length::Fixed(STOCK_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let mut found = column![].spacing(8);
+        for product in &self.search_products {
+            found = found.push(
+                button(
+                    row![
+                        text(product.id).width(Length::Fixed(ID_WIDTH)),
+                        text(&product.name).width(Length::Fill),
+                        text(product.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                        text(format_quantity(product.quantity, product.unit))
+                            .width(Length::Fixed(STOCK_WIDTH)),
+                    ]
+                    .spacing(16),
+                )
+                .on_press(Message::AddComponent(product.clone())),
+            );
+        }
+
+        column![
+            row![
+                button("FECHAR").on_press(Message::CloseKit),
+                text(format!("COMPOSIÇÃO DO KIT: {}", self.kit.name)).size(24),
+            ]
+            .spacing(16)
+            .align_y(Alignment::Center),
+            header,
+            scrollable(list).height(Length::FillPortion(2)),
+            text_input(
+                "ADICIONAR COMPONENTE: DIGITE O NOME DO PRODUTO",
+                &self.search_text
+            )
+            .on_input(Message::ComponentSearchChange),
+            scrollable(found).height(Length::FillPortion(1)),
+            row![
+                text("O estoque do kit vem dos componentes, que saem do estoque a cada venda"),
+                horizontal_space(),
+                button("DESFAZER KIT")
+                    .on_press_maybe((!self.busy && self.kit.is_kit).then_some(Message::UndoKit)),
+                button("SALVAR KIT").on_press_maybe(
+                    (!self.busy && !self.components.is_empty()).then_some(Message::SaveKit)
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+}
+
+impl ComponentItem {
+    fn from_component(component: &KitComponent) -> Self {
+        Self {
+            id: component.component_id,
+            name: component.name.clone(),
+            unit: component.unit,
+            quantity: format_quantity(component.quantity, component.unit),
+            stock: component.stock,
+        }
+    }
+
+    fn from_product(product: &Product) -> Self {
+        Self {
+            id: product.id,
+            name: product.name.clone(),
+            unit: product.unit,
+            quantity: "1".to_string(),
+            stock: product.quantity,
+        }
+    }
+}
+
+fn notify_error(message: String) -> Task<Message> {
+    Task::done(Message::Notify(Notification::error(message)))
+}
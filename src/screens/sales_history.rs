@@ -0,0 +1,427 @@
+use crate::components::notification::Notification;
+use crate::config::StoreConfig;
+use crate::entities::sale::Sale;
+use crate::helpers::money::Money;
+use crate::helpers::{format_quantity, parse_quantity, to_local};
+use crate::printing::receipt;
+use crate::printing::PrinterTarget;
+use crate::services::product_sale_service::{ProductSaleService, SaleDetail};
+use chrono::{Local, NaiveDate};
+use iced::widget::{button, column, horizontal_space, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task};
+use std::sync::Arc;
+
+const ID_WIDTH: f32 = 60.0;
+const DATE_WIDTH: f32 = 160.0;
+const STATUS_WIDTH: f32 = 120.0;
+const UNIT_WIDTH: f32 = 40.0;
+const QUANTITY_WIDTH: f32 = 100.0;
+const PRICE_WIDTH: f32 = 100.0;
+const DATE_FORMAT: &str = "%d/%m/%Y";
+
+/// Past sales, searched by period and product. An opened sale can be
+/// reprinted, have items returned or be voided.
+#[derive(Debug)]
+pub struct State {
+    product_sale_service: Arc<dyn ProductSaleService>,
+    store: StoreConfig,
+    printer: Option<PrinterTarget>,
+    from: String,
+    to: String,
+    product: String,
+    sales: Vec<Sale>,
+    detail: Option<SaleDetail>,
+    returns: Vec<String>,
+    confirm_void: bool,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FromChange(String),
+    ToChange(String),
+    ProductChange(String),
+    Search,
+    SalesLoaded(Result<Vec<Sale>, String>),
+    Open(i64),
+    SaleLoaded(Result<Option<SaleDetail>, String>),
+    Back,
+    ReturnQuantityChange(usize, String),
+    ReturnItems,
+    Returned(Result<(), String>),
+    VoidSale,
+    CancelVoid,
+    ConfirmVoid,
+    Voided(Result<(), String>),
+    Reprint,
+    Printed(Result<(), String>),
+    Notify(Notification),
+}
+
+impl State {
+    pub fn new(
+        product_sale_service: Arc<dyn ProductSaleService>,
+        store: StoreConfig,
+        printer: Option<PrinterTarget>,
+    ) -> Self {
+        let today = Local::now().date_naive().format(DATE_FORMAT).to_string();
+        Self {
+            product_sale_service,
+            store,
+            printer,
+            from: today.clone(),
+            to: today,
+            product: String::new(),
+            sales: vec![],
+            detail: None,
+            returns: vec![],
+            confirm_void: false,
+            busy: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        match &self.detail {
+            Some(detail) => self.detail_view(detail),
+            None => self.list_view(),
+        }
+    }
+
+    fn list_view(&self) -> Element<'_, Message> {
+        let filters = row![
+            text_input("DE (DD/MM/AAAA)", &self.from)
+                .on_input(Message::FromChange)
+                .on_submit(Message::Search)
+                .width(Length::Fixed(DATE_WIDTH)),
+            text_input("ATÉ (DD/MM/AAAA)", &self.to)
+                .on_input(Message::ToChange)
+                .on_submit(Message::Search)
+                .width(Length::Fixed(DATE_WIDTH)),
+            text_input("PRODUTO OU CÓDIGO DE BARRAS", &self.product)
+                .on_input(Message::ProductChange)
+                .on_submit(Message::Search),
+            button("BUSCAR").on_press_maybe((!self.busy).then_some(Message::Search)),
+        ]
+        .spacing(8);
+
+        let header = row![
+            text("VENDA").width(Length::Fixed(ID_WIDTH)),
+            text("DATA").width(Length::Fixed(DATE_WIDTH)),
+            text("TOTAL").width(Length::Fill),
+            text("SITUAÇÃO").width(Length::Fixed(STATUS_WIDTH)),
+            text("").width(Length::Fixed(PRICE_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for sale in &self.sales {
+            list = list.push(
+                row![
+                    text(sale.id).width(Length::Fixed(ID_WIDTH)),
+                    text(
+                        to_local(sale.created_at)
+                            .format("%d/%m/%Y %H:%M")
+                            .to_string()
+                    )
+                    .width(Length::Fixed(DATE_WIDTH)),
+                    text(sale.total.to_string()).width(Length::Fill),
+                    text(if sale.is_voided() { "CANCELADA" } else { "" })
+                        .width(Length::Fixed(STATUS_WIDTH)),
+                    button("ABRIR")
+                        .on_press(Message::Open(sale.id))
+                        .width(Length::Fixed(PRICE_WIDTH)),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![
+            text("VENDAS").size(24),
+            filters,
+            header,
+            scrollable(list).height(Length::Fill),
+            text(format!("{} VENDA(S)", self.sales.len())),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    fn detail_view<'a>(&'a self, detail: &'a SaleDetail) -> Element<'a, Message> {
+        let sale = &detail.sale;
+        let open = !sale.is_voided() && !self.busy;
+        let mut title = format!(
+            "VENDA {} - {}",
+            sale.id,
+            to_local(sale.created_at).format("%d/%m/%Y %H:%M")
+        );
+        if let Some(voided_at) = sale.voided_at {
+            title.push_str(&format!(
+                " - CANCELADA EM {}",
+                to_local(voided_at).format("%d/%m/%Y %H:%M")
+            ));
+        }
+
+        let header = row![
+            text("PRODUTO").width(Length::Fill),
+            text("UN").width(Length::Fixed(UNIT_WIDTH)),
+            text("PREÇO").width(Length::Fixed(PRICE_WIDTH)),
+            text("QTD").width(Length::Fixed(QUANTITY_WIDTH)),
+            text("DEVOLVIDO").width(Length::Fixed(QUANTITY_WIDTH)),
+            text("TOTAL").width(Length::Fixed(PRICE_WIDTH)),
+            text("DEVOLVER").width(Length::Fixed(QUANTITY_WIDTH)),
+        ]
+        .spacing(16);
+
+        let mut list = column![].spacing(8);
+        for (index, item) in detail.items.iter().enumerate() {
+            let value = self.returns.get(index).map(String::as_str).unwrap_or("");
+            let mut input = text_input("0", value).width(Length::Fixed(QUANTITY_WIDTH));
+            if open && item.remaining() > 0 {
+                input = input.on_input(move |value| Message::ReturnQuantityChange(index, value));
+            }
+            list = list.push(
+                row![
+                    text(&item.name).width(Length::Fill),
+                    text(item.unit.as_str()).width(Length::Fixed(UNIT_WIDTH)),
+                    text(item.price.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    text(format_quantity(item.quantity, item.unit))
+                        .width(Length::Fixed(QUANTITY_WIDTH)),
+                    text(format_quantity(item.returned_quantity, item.unit))
+                        .width(Length::Fixed(QUANTITY_WIDTH)),
+                    text(item.total.to_string()).width(Length::Fixed(PRICE_WIDTH)),
+                    input,
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let void_buttons = if self.confirm_void {
+            row![
+                text("CONFIRMA O CANCELAMENTO?"),
+                button("SIM, CANCELAR").on_press_maybe(open.then_some(Message::ConfirmVoid)),
+                button("NÃO").on_press(Message::CancelVoid),
+            ]
+        } else {
+            row![button("CANCELAR VENDA").on_press_maybe(open.then_some(Message::VoidSale))]
+        };
+
+        column![
+            text(title).size(24),
+            header,
+            scrollable(list).height(Length::Fill),
+            row![
+                text(format!("TOTAL: {}", sale.total)),
+                text(format!("LÍQUIDO: {}", detail.net_total())),
+                text(format!("PAGAMENTO: {}", sale.payment_method)),
+            ]
+            .push_maybe((sale.loyalty_discount > Money::ZERO).then(|| {
+                text(format!(
+                    "PAGO COM PONTOS: {} - A PAGAR: {}",
+                    sale.loyalty_discount,
+                    sale.amount_due()
+                ))
+            }))
+            .spacing(32),
+            row![
+                button("VOLTAR").on_press(Message::Back),
+                horizontal_space(),
+                button("REIMPRIMIR").on_press_maybe(
+                    (self.printer.is_some() && !self.busy).then_some(Message::Reprint)
+                ),
+                button("DEVOLVER ITENS").on_press_maybe(open.then_some(Message::ReturnItems)),
+                void_buttons.spacing(8).align_y(Alignment::Center),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::FromChange(value) => self.from = value,
+            Message::ToChange(value) => self.to = value,
+            Message::ProductChange(value) => self.product = value.to_uppercase(),
+            Message::Search => {
+                let (Some(from), Some(to)) = (parse_date(&self.from), parse_date(&self.to)) else {
+                    return notify_error("Informe as datas no formato DD/MM/AAAA".to_string());
+                };
+                let product_sale_service = self.product_sale_service.clone();
+                let product = self.product.trim().to_string();
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        let sales = if product.is_empty() {
+                            product_sale_service.list_sales(from, to).await
+                        } else {
+                            product_sale_service
+                                .search_sales_by_product(&product, from, to)
+                                .await
+                        };
+                        sales.map_err(|e| e.to_string())
+                    },
+                    Message::SalesLoaded,
+                );
+            }
+            Message::SalesLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(sales) => self.sales = sales,
+                    Err(e) => return notify_error(format!("Erro ao buscar vendas: {}", e)),
+                }
+            }
+            Message::Open(id) => return self.load_sale(id),
+            Message::SaleLoaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(Some(detail)) => {
+                        self.returns = vec![String::new(); detail.items.len()];
+                        self.detail = Some(detail);
+                    }
+                    Ok(None) => return notify_error("Venda não encontrada".to_string()),
+                    Err(e) => return notify_error(format!("Erro ao carregar a venda: {}", e)),
+                }
+            }
+            Message::Back => {
+                self.detail = None;
+                self.confirm_void = false;
+                return Task::done(Message::Search);
+            }
+            Message::ReturnQuantityChange(index, value) => {
+                if let Some(input) = self.returns.get_mut(index) {
+                    *input = value;
+                }
+            }
+            Message::ReturnItems => {
+                let Some(detail) = &self.detail else {
+                    return Task::none();
+                };
+                let mut items = vec![];
+                for (item, value) in detail.items.iter().zip(&self.returns) {
+                    let value = value.trim();
+                    if value.is_empty() {
+                        continue;
+                    }
+                    match parse_quantity(value, item.unit) {
+                        Some(quantity) => items.push((item.id, quantity)),
+                        None => {
+                            return notify_error(format!(
+                                "Quantidade \"{}\" inválida para {}",
+                                value, item.name
+                            ));
+                        }
+                    }
+                }
+                let product_sale_service = self.product_sale_service.clone();
+                let id = detail.sale.id;
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        product_sale_service
+                            .return_items(id, items)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Returned,
+                );
+            }
+            Message::Returned(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::Notify(Notification::success(
+                            "Devolução registrada",
+                        ))),
+                        self.reload_sale(),
+                    ]),
+                    Err(e) => notify_error(format!("Erro na devolução: {}", e)),
+                };
+            }
+            Message::VoidSale => self.confirm_void = true,
+            Message::CancelVoid => self.confirm_void = false,
+            Message::ConfirmVoid => {
+                let Some(detail) = &self.detail else {
+                    return Task::none();
+                };
+                let product_sale_service = self.product_sale_service.clone();
+                let id = detail.sale.id;
+                self.confirm_void = false;
+                self.busy = true;
+                return Task::perform(
+                    async move {
+                        product_sale_service
+                            .void_sale(id)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::Voided,
+                );
+            }
+            Message::Voided(result) => {
+                self.busy = false;
+                return match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::Notify(Notification::success("Venda cancelada"))),
+                        self.reload_sale(),
+                    ]),
+                    Err(e) => notify_error(format!("Erro ao cancelar a venda: {}", e)),
+                };
+            }
+            Message::Reprint => {
+                let (Some(detail), Some(printer)) = (&self.detail, self.printer.clone()) else {
+                    return Task::none();
+                };
+                let data = receipt::render(&self.store, detail, true).into_bytes();
+                self.busy = true;
+                return Task::perform(
+                    async move { printer.send(data).await.map_err(|e| e.to_string()) },
+                    Message::Printed,
+                );
+            }
+            Message::Printed(result) => {
+                self.busy = false;
+                let notification = match result {
+                    Ok(()) => Notification::success("Comprovante enviado para a impressora"),
+                    Err(e) => Notification::error(format!("Erro ao imprimir: {}", e)),
+                };
+                return Task::done(Message::Notify(notification));
+            }
+            // Handled by the application, which shows the toast
+            Message::Notify(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn load_sale(&mut self, id: i64) -> Task<Message> {
+        let product_sale_service = self.product_sale_service.clone();
+        self.busy = true;
+        Task::perform(
+            async move {
+                product_sale_service
+                    .find_sale(id)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::SaleLoaded,
+        )
+    }
+
+    fn reload_sale(&mut self) -> Task<Message> {
+        match self.detail.as_ref().map(|detail| detail.sale.id) {
+            Some(id) => self.load_sale(id),
+            None => Task::none(),
+        }
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+}
+
+fn notify_error(message: String) -> Task<Message> {
+    Task::done(Message::Notify(Notification::error(message)))
+}
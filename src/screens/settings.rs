@@ -0,0 +1,592 @@
+use crate::components::notification::Notification;
+use crate::config::{Config, ScaleLabelValue};
+use crate::helpers::money::Percent;
+use crate::printing::labels::LabelFormat;
+use iced::widget::{button, checkbox, column, pick_list, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task, Theme};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const LABEL_WIDTH: f32 = 220.0;
+
+#[derive(Debug)]
+pub struct State {
+    path: PathBuf,
+    config: Config,
+    form: Form,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FieldChange(Field, String),
+    ThemeChange(Theme),
+    LabelFormatChange(LabelFormat),
+    ScaleLabelValueChange(ScaleLabelValue),
+    ValueCheckDigitToggle(bool),
+    ApiEnabledToggle(bool),
+    LoyaltyEnabledToggle(bool),
+    Save,
+    Saved(Box<Config>),
+    OpenBackups,
+    Notify(Notification),
+}
+
+/// Settings edited as free text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    DatabaseUrl,
+    BackupFolder,
+    BackupInterval,
+    BackupKeepDaily,
+    BackupKeepWeekly,
+    StoreName,
+    StoreCnpj,
+    StoreAddress,
+    ReceiptPrinter,
+    LabelPrinter,
+    LabelPdfPath,
+    DefaultMarkup,
+    LoyaltyPointsPerReal,
+    LoyaltyCategoryBonus,
+    LoyaltyExpiration,
+    LoyaltyPointValue,
+    CreditDueDays,
+    HotkeyHome,
+    HotkeyPurchase,
+    HotkeyProducts,
+    HotkeySales,
+    HotkeyCustomers,
+    HotkeyFinancial,
+    HotkeySettings,
+    HotkeySearch,
+    HotkeyCancelSale,
+    HotkeyPayment,
+    ScaleDevice,
+    ScaleBaudRate,
+    ScaleTimeout,
+    ScaleLabelCodeLength,
+    ApiPort,
+    ApiToken,
+}
+
+impl State {
+    pub fn new(path: PathBuf, config: Config) -> Self {
+        Self {
+            path,
+            form: Form::from_config(&config),
+            config,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let form = &self.form;
+        let content = column![
+            section("BANCO DE DADOS"),
+            field(
+                "Banco (sqlite:caminho)",
+                Field::DatabaseUrl,
+                &form.database_url
+            ),
+            section("BACKUP"),
+            field("Pasta de backups", Field::BackupFolder, &form.backup_folder),
+            field(
+                "Intervalo (horas)",
+                Field::BackupInterval,
+                &form.backup_interval
+            ),
+            field(
+                "Cópias diárias",
+                Field::BackupKeepDaily,
+                &form.backup_keep_daily
+            ),
+            field(
+                "Cópias semanais",
+                Field::BackupKeepWeekly,
+                &form.backup_keep_weekly
+            ),
+            section("LOJA"),
+            field("Nome", Field::StoreName, &form.store_name),
+            field("CNPJ", Field::StoreCnpj, &form.store_cnpj),
+            field("Endereço", Field::StoreAddress, &form.store_address),
+            section("IMPRESSORAS"),
+            field(
+                "Impressora de cupom",
+                Field::ReceiptPrinter,
+                &form.receipt_printer
+            ),
+            field(
+                "Impressora de etiquetas",
+                Field::LabelPrinter,
+                &form.label_printer
+            ),
+            labeled(
+                "Formato das etiquetas",
+                pick_list(
+                    LabelFormat::ALL,
+                    Some(form.label_format),
+                    Message::LabelFormatChange
+                )
+                .into()
+            ),
+            field(
+                "PDF de etiquetas",
+                Field::LabelPdfPath,
+                &form.label_pdf_path
+            ),
+            section("COMPRAS"),
+            field(
+                "Margem padrão (%)",
+                Field::DefaultMarkup,
+                &form.default_markup
+            ),
+            section("FIDELIDADE"),
+            checkbox("Ativar programa de pontos", form.loyalty_enabled)
+                .on_toggle(Message::LoyaltyEnabledToggle),
+            field(
+                "Pontos por real",
+                Field::LoyaltyPointsPerReal,
+                &form.loyalty_points_per_real
+            ),
+            field(
+                "Bônus (BEBIDAS=2; ...)",
+                Field::LoyaltyCategoryBonus,
+                &form.loyalty_category_bonus
+            ),
+            field(
+                "Validade (meses, 0 = sem)",
+                Field::LoyaltyExpiration,
+                &form.loyalty_expiration
+            ),
+            field(
+                "Valor do ponto (centavos)",
+                Field::LoyaltyPointValue,
+                &form.loyalty_point_value
+            ),
+            section("FIADO"),
+            field(
+                "Prazo de pagamento (dias)",
+                Field::CreditDueDays,
+                &form.credit_due_days
+            ),
+            section("APARÊNCIA"),
+            labeled(
+                "Tema",
+                pick_list(Theme::ALL, form.theme.clone(), Message::ThemeChange).into()
+            ),
+            section("ATALHOS"),
+            field("Início", Field::HotkeyHome, &form.hotkey_home),
+            field("Produtos", Field::HotkeyPurchase, &form.hotkey_purchase),
+            field("Estoque", Field::HotkeyProducts, &form.hotkey_products),
+            field("Vendas", Field::HotkeySales, &form.hotkey_sales),
+            field("Clientes", Field::HotkeyCustomers, &form.hotkey_customers),
+            field("Financeiro", Field::HotkeyFinancial, &form.hotkey_financial),
+            field(
+                "Configurações",
+                Field::HotkeySettings,
+                &form.hotkey_settings
+            ),
+            field("Buscar produto", Field::HotkeySearch, &form.hotkey_search),
+            field(
+                "Cancelar venda",
+                Field::HotkeyCancelSale,
+                &form.hotkey_cancel_sale
+            ),
+            field("Pagamento", Field::HotkeyPayment, &form.hotkey_payment),
+            section("BALANÇA"),
+            field("Porta serial", Field::ScaleDevice, &form.scale_device),
+            field(
+                "Velocidade (baud)",
+                Field::ScaleBaudRate,
+                &form.scale_baud_rate
+            ),
+            field(
+                "Tempo limite (ms)",
+                Field::ScaleTimeout,
+                &form.scale_timeout
+            ),
+            field(
+                "Dígitos do código na etiqueta",
+                Field::ScaleLabelCodeLength,
+                &form.scale_label_code_length
+            ),
+            labeled(
+                "Valor da etiqueta",
+                pick_list(
+                    ScaleLabelValue::ALL,
+                    Some(form.scale_label_value),
+                    Message::ScaleLabelValueChange
+                )
+                .into()
+            ),
+            checkbox(
+                "Etiqueta com dígito verificador do valor",
+                form.value_check_digit
+            )
+            .on_toggle(Message::ValueCheckDigitToggle),
+            section("API LOCAL"),
+            checkbox("Ativar a API em 127.0.0.1", form.api_enabled)
+                .on_toggle(Message::ApiEnabledToggle),
+            field("Porta", Field::ApiPort, &form.api_port),
+            labeled(
+                "Token de acesso",
+                text_input("Token de acesso", &form.api_token)
+                    .on_input(|value| Message::FieldChange(Field::ApiToken, value))
+                    .secure(true)
+                    .into()
+            ),
+        ]
+        .spacing(8);
+
+        column![
+            text("CONFIGURAÇÕES").size(24),
+            text(format!("Arquivo: {}", self.path.display())),
+            scrollable(content).height(Length::Fill),
+        ]
+        .push(
+            row![
+                button("SALVAR").padding(8).on_press(Message::Save),
+                button("BACKUPS").padding(8).on_press(Message::OpenBackups),
+            ]
+            .spacing(8),
+        )
+        .spacing(16)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::FieldChange(field, value) => {
+                *self.form.field_mut(field) = value;
+            }
+            Message::ThemeChange(theme) => {
+                self.form.theme = Some(theme);
+            }
+            Message::LabelFormatChange(format) => {
+                self.form.label_format = format;
+            }
+            Message::ScaleLabelValueChange(value) => {
+                self.form.scale_label_value = value;
+            }
+            Message::ValueCheckDigitToggle(value) => {
+                self.form.value_check_digit = value;
+            }
+            Message::ApiEnabledToggle(value) => {
+                self.form.api_enabled = value;
+            }
+            Message::LoyaltyEnabledToggle(value) => {
+                self.form.loyalty_enabled = value;
+            }
+            Message::Save => {
+                let config = match self.form.to_config() {
+                    Ok(config) => config,
+                    Err(e) => return Task::done(Message::Notify(Notification::error(e))),
+                };
+                if let Err(e) = config.save(&self.path) {
+                    return Task::done(Message::Notify(Notification::error(e.to_string())));
+                }
+                let needs_restart =
+                    config.database != self.config.database || config.api != self.config.api;
+                let notification = if needs_restart {
+                    Notification::warning(
+                        "Configurações salvas. Reinicie o sistema para aplicar as alterações de banco de dados e da API.",
+                    )
+                } else {
+                    Notification::success("Configurações salvas.")
+                };
+                self.config = config.clone();
+                return Task::batch([
+                    Task::done(Message::Saved(Box::new(config))),
+                    Task::done(Message::Notify(notification)),
+                ]);
+            }
+            Message::Saved(_) => {
+                // Handled by the application, which applies the new settings
+            }
+            Message::OpenBackups => {
+                // Handled by the application, which opens the backups screen
+            }
+            Message::Notify(_) => {
+                // Handled by the application, which shows the toast
+            }
+        }
+        Task::none()
+    }
+}
+
+fn section(title: &str) -> Element<'_, Message> {
+    text(title).size(18).into()
+}
+
+fn labeled<'a>(label: &'a str, input: Element<'a, Message>) -> Element<'a, Message> {
+    row![text(label).width(Length::Fixed(LABEL_WIDTH)), input]
+        .spacing(16)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+fn field<'a>(label: &'a str, field: Field, value: &'a str) -> Element<'a, Message> {
+    labeled(
+        label,
+        text_input(label, value)
+            .on_input(move |value| Message::FieldChange(field, value))
+            .into(),
+    )
+}
+
+/// Settings as typed on the screen, converted back to a [`Config`] on save.
+#[derive(Debug)]
+struct Form {
+    database_url: String,
+    backup_folder: String,
+    backup_interval: String,
+    backup_keep_daily: String,
+    backup_keep_weekly: String,
+    store_name: String,
+    store_cnpj: String,
+    store_address: String,
+    receipt_printer: String,
+    label_printer: String,
+    label_format: LabelFormat,
+    label_pdf_path: String,
+    default_markup: String,
+    loyalty_enabled: bool,
+    loyalty_points_per_real: String,
+    loyalty_category_bonus: String,
+    loyalty_expiration: String,
+    loyalty_point_value: String,
+    credit_due_days: String,
+    theme: Option<Theme>,
+    hotkey_home: String,
+    hotkey_purchase: String,
+    hotkey_products: String,
+    hotkey_sales: String,
+    hotkey_customers: String,
+    hotkey_financial: String,
+    hotkey_settings: String,
+    hotkey_search: String,
+    hotkey_cancel_sale: String,
+    hotkey_payment: String,
+    scale_device: String,
+    scale_baud_rate: String,
+    scale_timeout: String,
+    scale_label_code_length: String,
+    scale_label_value: ScaleLabelValue,
+    value_check_digit: bool,
+    api_enabled: bool,
+    api_port: String,
+    api_token: String,
+}
+
+impl Form {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            database_url: config.database.url.clone(),
+            backup_folder: config.backup.folder.display().to_string(),
+            backup_interval: config.backup.interval_hours.to_string(),
+            backup_keep_daily: config.backup.keep_daily.to_string(),
+            backup_keep_weekly: config.backup.keep_weekly.to_string(),
+            store_name: config.store.name.clone(),
+            store_cnpj: config.store.cnpj.clone(),
+            store_address: config.store.address.clone(),
+            receipt_printer: config.printer.receipt.clone(),
+            label_printer: config.labels.target.clone(),
+            label_format: config.labels.format,
+            label_pdf_path: config.labels.pdf_path.display().to_string(),
+            default_markup: config.purchase.markup().to_string(),
+            loyalty_enabled: config.loyalty.enabled,
+            loyalty_points_per_real: config.loyalty.points_per_real.to_string(),
+            loyalty_category_bonus: config
+                .loyalty
+                .category_bonus
+                .iter()
+                .map(|(category, bonus)| format!("{}={}", category, bonus))
+                .collect::<Vec<_>>()
+                .join("; "),
+            loyalty_expiration: config.loyalty.expiration_months.to_string(),
+            loyalty_point_value: config.loyalty.point_value.to_string(),
+            credit_due_days: config.credit.due_days.to_string(),
+            theme: config.appearance.theme(),
+            hotkey_home: config.hotkeys.home.clone(),
+            hotkey_purchase: config.hotkeys.purchase.clone(),
+            hotkey_products: config.hotkeys.products.clone(),
+            hotkey_sales: config.hotkeys.sales.clone(),
+            hotkey_customers: config.hotkeys.customers.clone(),
+            hotkey_financial: config.hotkeys.financial.clone(),
+            hotkey_settings: config.hotkeys.settings.clone(),
+            hotkey_search: config.hotkeys.search.clone(),
+            hotkey_cancel_sale: config.hotkeys.cancel_sale.clone(),
+            hotkey_payment: config.hotkeys.payment.clone(),
+            scale_device: config.scale.device.clone().unwrap_or_default(),
+            scale_baud_rate: config.scale.baud_rate.to_string(),
+            scale_timeout: config.scale.timeout_ms.to_string(),
+            scale_label_code_length: config.scale_label.code_length.to_string(),
+            scale_label_value: config.scale_label.value,
+            value_check_digit: config.scale_label.value_check_digit,
+            api_enabled: config.api.enabled,
+            api_port: config.api.port.to_string(),
+            api_token: config.api.token.clone(),
+        }
+    }
+
+    fn field_mut(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::DatabaseUrl => &mut self.database_url,
+            Field::BackupFolder => &mut self.backup_folder,
+            Field::BackupInterval => &mut self.backup_interval,
+            Field::BackupKeepDaily => &mut self.backup_keep_daily,
+            Field::BackupKeepWeekly => &mut self.backup_keep_weekly,
+            Field::StoreName => &mut self.store_name,
+            Field::StoreCnpj => &mut self.store_cnpj,
+            Field::StoreAddress => &mut self.store_address,
+            Field::ReceiptPrinter => &mut self.receipt_printer,
+            Field::LabelPrinter => &mut self.label_printer,
+            Field::LabelPdfPath => &mut self.label_pdf_path,
+            Field::DefaultMarkup => &mut self.default_markup,
+            Field::LoyaltyPointsPerReal => &mut self.loyalty_points_per_real,
+            Field::LoyaltyCategoryBonus => &mut self.loyalty_category_bonus,
+            Field::LoyaltyExpiration => &mut self.loyalty_expiration,
+            Field::LoyaltyPointValue => &mut self.loyalty_point_value,
+            Field::CreditDueDays => &mut self.credit_due_days,
+            Field::HotkeyHome => &mut self.hotkey_home,
+            Field::HotkeyPurchase => &mut self.hotkey_purchase,
+            Field::HotkeyProducts => &mut self.hotkey_products,
+            Field::HotkeySales => &mut self.hotkey_sales,
+            Field::HotkeyCustomers => &mut self.hotkey_customers,
+            Field::HotkeyFinancial => &mut self.hotkey_financial,
+            Field::HotkeySettings => &mut self.hotkey_settings,
+            Field::HotkeySearch => &mut self.hotkey_search,
+            Field::HotkeyCancelSale => &mut self.hotkey_cancel_sale,
+            Field::HotkeyPayment => &mut self.hotkey_payment,
+            Field::ScaleDevice => &mut self.scale_device,
+            Field::ScaleBaudRate => &mut self.scale_baud_rate,
+            Field::ScaleTimeout => &mut self.scale_timeout,
+            Field::ScaleLabelCodeLength => &mut self.scale_label_code_length,
+            Field::ApiPort => &mut self.api_port,
+            Field::ApiToken => &mut self.api_token,
+        }
+    }
+
+    /// Parses the numeric fields; everything else is checked by
+    /// [`Config::validate`] when saving.
+    fn to_config(&self) -> Result<Config, String> {
+        let mut config = Config::default();
+        config.database.url = self.database_url.trim().to_string();
+        config.backup.folder = PathBuf::from(self.backup_folder.trim());
+        config.backup.interval_hours =
+            self.backup_interval.trim().parse().map_err(|_| {
+                format!("Intervalo de backup \"{}\" inválido", self.backup_interval)
+            })?;
+        config.backup.keep_daily = self
+            .backup_keep_daily
+            .trim()
+            .parse()
+            .map_err(|_| format!("Cópias diárias \"{}\" inválido", self.backup_keep_daily))?;
+        config.backup.keep_weekly = self
+            .backup_keep_weekly
+            .trim()
+            .parse()
+            .map_err(|_| format!("Cópias semanais \"{}\" inválido", self.backup_keep_weekly))?;
+        config.store.name = self.store_name.trim().to_string();
+        config.store.cnpj = self.store_cnpj.trim().to_string();
+        config.store.address = self.store_address.trim().to_string();
+        config.printer.receipt = self.receipt_printer.trim().to_string();
+        config.labels.target = self.label_printer.trim().to_string();
+        config.labels.format = self.label_format;
+        config.labels.pdf_path = PathBuf::from(self.label_pdf_path.trim());
+        config.purchase.default_markup = Percent::parse(&self.default_markup)
+            .map(|markup| markup.hundredths() as f64 / 100.0)
+            .ok_or_else(|| format!("Margem padrão \"{}\" inválida", self.default_markup))?;
+        config.loyalty.enabled = self.loyalty_enabled;
+        config.loyalty.points_per_real =
+            self.loyalty_points_per_real.trim().parse().map_err(|_| {
+                format!(
+                    "Pontos por real \"{}\" inválido",
+                    self.loyalty_points_per_real
+                )
+            })?;
+        config.loyalty.category_bonus = parse_category_bonus(&self.loyalty_category_bonus)?;
+        config.loyalty.expiration_months =
+            self.loyalty_expiration.trim().parse().map_err(|_| {
+                format!(
+                    "Validade dos pontos \"{}\" inválida",
+                    self.loyalty_expiration
+                )
+            })?;
+        config.loyalty.point_value = self
+            .loyalty_point_value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Valor do ponto \"{}\" inválido", self.loyalty_point_value))?;
+        config.credit.due_days = self
+            .credit_due_days
+            .trim()
+            .parse()
+            .map_err(|_| format!("Prazo de pagamento \"{}\" inválido", self.credit_due_days))?;
+        if let Some(theme) = &self.theme {
+            config.appearance.theme = theme.to_string();
+        }
+        config.hotkeys.home = self.hotkey_home.trim().to_uppercase();
+        config.hotkeys.purchase = self.hotkey_purchase.trim().to_uppercase();
+        config.hotkeys.products = self.hotkey_products.trim().to_uppercase();
+        config.hotkeys.sales = self.hotkey_sales.trim().to_uppercase();
+        config.hotkeys.customers = self.hotkey_customers.trim().to_uppercase();
+        config.hotkeys.financial = self.hotkey_financial.trim().to_uppercase();
+        config.hotkeys.settings = self.hotkey_settings.trim().to_uppercase();
+        config.hotkeys.search = self.hotkey_search.trim().to_uppercase();
+        config.hotkeys.cancel_sale = self.hotkey_cancel_sale.trim().to_uppercase();
+        config.hotkeys.payment = self.hotkey_payment.trim().to_uppercase();
+        config.scale.device = Some(self.scale_device.trim().to_string()).filter(|d| !d.is_empty());
+        config.scale.baud_rate = self.scale_baud_rate.trim().parse().map_err(|_| {
+            format!(
+                "Velocidade da balança \"{}\" inválida",
+                self.scale_baud_rate
+            )
+        })?;
+        config.scale.timeout_ms = self.scale_timeout.trim().parse().map_err(|_| {
+            format!(
+                "Tempo limite da balança \"{}\" inválido",
+                self.scale_timeout
+            )
+        })?;
+        config.scale_label.code_length =
+            self.scale_label_code_length.trim().parse().map_err(|_| {
+                format!(
+                    "Dígitos do código na etiqueta \"{}\" inválido",
+                    self.scale_label_code_length
+                )
+            })?;
+        config.scale_label.value = self.scale_label_value;
+        config.scale_label.value_check_digit = self.value_check_digit;
+        config.api.enabled = self.api_enabled;
+        config.api.port = self
+            .api_port
+            .trim()
+            .parse()
+            .map_err(|_| format!("Porta da API \"{}\" inválida", self.api_port))?;
+        config.api.token = self.api_token.trim().to_string();
+        Ok(config)
+    }
+}
+
+/// Reads bonuses written as `CATEGORIA=PONTOS`, separated by `;`.
+fn parse_category_bonus(value: &str) -> Result<BTreeMap<String, u32>, String> {
+    let mut bonus = BTreeMap::new();
+    for item in value
+        .split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let parsed = item.split_once('=').and_then(|(category, points)| {
+            let category = category.trim().to_uppercase();
+            let points = points.trim().parse().ok()?;
+            (!category.is_empty()).then_some((category, points))
+        });
+        match parsed {
+            Some((category, points)) => {
+                bonus.insert(category, points);
+            }
+            None => return Err(format!("Bônus por categoria \"{}\" inválido", item)),
+        }
+    }
+    Ok(bonus)
+}
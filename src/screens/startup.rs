@@ -0,0 +1,255 @@
+use crate::config::Config;
+use crate::database;
+use crate::services::backup_service::{Backup, BackupService};
+use iced::widget::{button, column, container, horizontal_rule, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+/// Startup sequence: loads the configuration and opens the database. When
+/// either fails, the error is shown with the actions that can fix it.
+#[derive(Debug)]
+pub struct State {
+    config_path: PathBuf,
+    config: Option<Config>,
+    error: Option<String>,
+    status: Option<String>,
+    database_input: String,
+    backups: Vec<Backup>,
+    busy: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Start,
+    UseDefaultConfig,
+    CreateDatabase,
+    DatabasePathChange(String),
+    OpenDatabase,
+    RestoreBackup(usize),
+    Opened(Result<SqlitePool, String>),
+    Started(SqlitePool, Box<Config>),
+    Exit,
+}
+
+impl State {
+    pub fn new(config_path: PathBuf) -> Self {
+        Self {
+            config_path,
+            config: None,
+            error: None,
+            status: None,
+            database_input: String::new(),
+            backups: vec![],
+            busy: true,
+        }
+    }
+
+    pub fn config(&self) -> Option<&Config> {
+        self.config.as_ref()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(error) = &self.error else {
+            return container(text("Abrindo o banco de dados...").size(24))
+                .center(Length::Fill)
+                .into();
+        };
+
+        let mut content = column![
+            text("NÃO FOI POSSÍVEL INICIAR O SISTEMA").size(24),
+            text(error),
+        ]
+        .spacing(16);
+        if let Some(status) = &self.status {
+            content = content.push(text(status));
+        }
+        content = content.push(horizontal_rule(1));
+
+        let enabled = |message: Message| (!self.busy).then_some(message);
+        match &self.config {
+            None => {
+                content = content.push(
+                    row![
+                        button("TENTAR NOVAMENTE").on_press_maybe(enabled(Message::Start)),
+                        button("USAR CONFIGURAÇÃO PADRÃO")
+                            .on_press_maybe(enabled(Message::UseDefaultConfig)),
+                    ]
+                    .spacing(8),
+                );
+            }
+            Some(_) => {
+                let mut backups = column![].spacing(8);
+                for (index, backup) in self.backups.iter().enumerate() {
+                    backups = backups.push(
+                        row![
+                            text(backup.created_at.format("%d/%m/%Y %H:%M:%S").to_string())
+                                .width(Length::Fill),
+                            button("RESTAURAR")
+                                .on_press_maybe(enabled(Message::RestoreBackup(index))),
+                        ]
+                        .spacing(16)
+                        .align_y(Alignment::Center),
+                    );
+                }
+                if self.backups.is_empty() {
+                    backups = backups.push(text("Nenhum backup encontrado."));
+                }
+
+                content = content
+                    .push(
+                        row![
+                            button("TENTAR NOVAMENTE").on_press_maybe(enabled(Message::Start)),
+                            button("CRIAR NOVO BANCO")
+                                .on_press_maybe(enabled(Message::CreateDatabase)),
+                        ]
+                        .spacing(8),
+                    )
+                    .push(text("ABRIR OUTRO ARQUIVO").size(18))
+                    .push(
+                        row![
+                            text_input("Caminho do banco de dados", &self.database_input)
+                                .on_input(Message::DatabasePathChange)
+                                .on_submit(Message::OpenDatabase),
+                            button("ABRIR").on_press_maybe(enabled(Message::OpenDatabase)),
+                        ]
+                        .spacing(8),
+                    )
+                    .push(text("RESTAURAR BACKUP").size(18))
+                    .push(backups);
+            }
+        }
+        content = content.push(button("SAIR").on_press(Message::Exit));
+
+        container(scrollable(content.padding(32)))
+            .center_x(Length::Fill)
+            .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Start => match Config::load(&self.config_path) {
+                Ok(config) => {
+                    self.database_input = config.database.path().display().to_string();
+                    self.config = Some(config);
+                    return self.open();
+                }
+                Err(e) => {
+                    self.config = None;
+                    self.busy = false;
+                    self.error = Some(e.to_string());
+                }
+            },
+            Message::UseDefaultConfig => {
+                let mut backup = self.config_path.as_os_str().to_owned();
+                backup.push(".bak");
+                let backup = PathBuf::from(backup);
+                if let Err(e) = std::fs::rename(&self.config_path, &backup) {
+                    self.status = Some(format!(
+                        "Não foi possível mover {}: {}",
+                        self.config_path.display(),
+                        e
+                    ));
+                    return Task::none();
+                }
+                self.status = Some(format!(
+                    "A configuração anterior foi guardada em {}",
+                    backup.display()
+                ));
+                return Task::done(Message::Start);
+            }
+            Message::CreateDatabase => {
+                if let Some(config) = &self.config {
+                    match database::set_aside(&config.database) {
+                        Ok(moved) => {
+                            self.status = moved.map(|path| {
+                                format!("O banco anterior foi guardado em {}", path.display())
+                            });
+                            return self.open();
+                        }
+                        Err(e) => self.status = Some(e.to_string()),
+                    }
+                }
+            }
+            Message::DatabasePathChange(value) => {
+                self.database_input = value;
+            }
+            Message::OpenDatabase => {
+                let path = self.database_input.trim();
+                if let Some(config) = &mut self.config
+                    && !path.is_empty()
+                {
+                    config.database.url = format!("sqlite:{}", path);
+                    return self.open();
+                }
+            }
+            Message::RestoreBackup(index) => {
+                if let (Some(config), Some(backup)) = (&self.config, self.backups.get(index)) {
+                    self.busy = true;
+                    self.status = Some("Verificando o backup...".to_string());
+                    let backup = backup.path.clone();
+                    let database = config.database.clone();
+                    return Task::perform(
+                        async move {
+                            BackupService::restore(&backup, &database.path()).await?;
+                            database::open(&database).await
+                        },
+                        |result| Message::Opened(result.map_err(|e| e.to_string())),
+                    );
+                }
+            }
+            Message::Opened(result) => {
+                self.busy = false;
+                match result {
+                    Ok(pool) => {
+                        if let Some(config) = self.config.take() {
+                            if let Err(e) = self.save_database_url(&config) {
+                                self.error = Some(e);
+                                self.config = Some(config);
+                                return Task::none();
+                            }
+                            return Task::done(Message::Started(pool, Box::new(config)));
+                        }
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                        if let Some(config) = &self.config {
+                            self.backups = BackupService::list_folder(&config.backup.folder)
+                                .unwrap_or_default();
+                        }
+                    }
+                }
+            }
+            Message::Started(..) | Message::Exit => {
+                // Handled by the application, which opens the main screen or closes the window
+            }
+        }
+        Task::none()
+    }
+
+    fn open(&mut self) -> Task<Message> {
+        let Some(config) = &self.config else {
+            return Task::none();
+        };
+        self.busy = true;
+        let database = config.database.clone();
+        Task::perform(
+            async move { database::open(&database).await.map_err(|e| e.to_string()) },
+            Message::Opened,
+        )
+    }
+
+    /// Keeps the file chosen on this screen as the database for the next
+    /// starts.
+    fn save_database_url(&self, config: &Config) -> Result<(), String> {
+        let Ok(saved) = Config::load(&self.config_path) else {
+            return Ok(());
+        };
+        if saved.database == config.database {
+            return Ok(());
+        }
+        let mut saved = saved;
+        saved.database = config.database.clone();
+        saved.save(&self.config_path).map_err(|e| e.to_string())
+    }
+}
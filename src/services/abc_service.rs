@@ -0,0 +1,235 @@
+use crate::entities::abc_class::AbcClass;
+use crate::entities::unit::Unit;
+use crate::helpers::money::{Money, Percent};
+use crate::helpers::{abc, local_day_start};
+use crate::repositories::product_repository::ProductRepository;
+use crate::repositories::product_sale_repository::ProductSaleRepository;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate};
+use sqlx::SqlitePool;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+/// What the ABC curve ranks products by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AbcCriterion {
+    #[default]
+    Revenue,
+    /// Revenue less the current cost of the sold quantity.
+    Margin,
+}
+
+impl AbcCriterion {
+    pub const ALL: [AbcCriterion; 2] = [AbcCriterion::Revenue, AbcCriterion::Margin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AbcCriterion::Revenue => "FATURAMENTO",
+            AbcCriterion::Margin => "MARGEM",
+        }
+    }
+}
+
+impl Display for AbcCriterion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A product in the ABC curve, with its share of the criterion ranked by.
+#[derive(Debug, Clone)]
+pub struct AbcItem {
+    pub product_id: i64,
+    pub name: String,
+    pub unit: Unit,
+    pub quantity: i64,
+    pub revenue: Money,
+    pub margin: Money,
+    pub share: Percent,
+    pub cumulative: Percent,
+    pub class: AbcClass,
+}
+
+#[derive(Debug)]
+pub struct SqliteAbcService {
+    pool: SqlitePool,
+}
+
+/// ABC classification of the products sold in a period.
+#[async_trait]
+pub trait AbcService: Debug + Send + Sync {
+    /// Products sold from `from` to `to`, returns discounted, ranked by
+    /// `criterion`.
+    async fn curve(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        criterion: AbcCriterion,
+    ) -> Result<Vec<AbcItem>>;
+
+    /// Stores the class of each product of the curve. Products not in it
+    /// weren't sold and become C.
+    async fn save_classes(&self, items: &[AbcItem]) -> Result<()>;
+}
+
+impl SqliteAbcService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AbcService for SqliteAbcService {
+    async fn curve(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        criterion: AbcCriterion,
+    ) -> Result<Vec<AbcItem>> {
+        if to < from {
+            bail!("A data final deve ser igual ou posterior à data inicial");
+        }
+        let end = to
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| anyhow!("Data {} inválida", to))?;
+        let lines = ProductSaleRepository::list_between(
+            &self.pool,
+            local_day_start(from),
+            local_day_start(end),
+        )
+        .await?;
+
+        let mut items: Vec<AbcItem> = vec![];
+        for line in lines {
+            let cost = line.cost.saturating_times_quantity(line.quantity);
+            let item = match items
+                .iter_mut()
+                .find(|item| item.product_id == line.product_id)
+            {
+                Some(item) => item,
+                None => {
+                    items.push(AbcItem {
+                        product_id: line.product_id,
+                        name: line.name,
+                        unit: line.unit,
+                        quantity: 0,
+                        revenue: Money::ZERO,
+                        margin: Money::ZERO,
+                        share: Percent::default(),
+                        cumulative: Percent::default(),
+                        class: AbcClass::C,
+                    });
+                    items.last_mut().expect("item just pushed")
+                }
+            };
+            item.quantity += line.quantity;
+            item.revenue = item.revenue.saturating_add(line.total);
+            item.margin = item.margin.saturating_add(line.total - cost);
+        }
+
+        let values: Vec<Money> = items
+            .iter()
+            .map(|item| match criterion {
+                AbcCriterion::Revenue => item.revenue,
+                AbcCriterion::Margin => item.margin,
+            })
+            .collect();
+        let ranked = abc::rank(&values)
+            .ok_or_else(|| anyhow!("Valor total da curva ABC excede o limite permitido"))?;
+        Ok(ranked
+            .into_iter()
+            .map(|ranked| AbcItem {
+                share: ranked.share,
+                cumulative: ranked.cumulative,
+                class: ranked.class,
+                ..items[ranked.index].clone()
+            })
+            .collect())
+    }
+
+    async fn save_classes(&self, items: &[AbcItem]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        ProductRepository::set_all_abc_classes(&mut *tx, AbcClass::C).await?;
+        for item in items {
+            ProductRepository::update_abc_class(&mut *tx, item.product_id, item.class).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, insert_product, sold, units};
+    use crate::entities::payment_method::PaymentMethod;
+    use crate::services::product_sale_service::ProductSaleService;
+    use chrono::Local;
+
+    #[tokio::test]
+    async fn curve_ranks_sold_products_and_saves_their_classes() {
+        let pool = testing::pool().await;
+        let rice = insert_product(&pool, "ARROZ", Unit::Un, units(100), 2000, 2500).await;
+        let wine = insert_product(&pool, "VINHO", Unit::Un, units(10), 3000, 9000).await;
+        let salt = insert_product(&pool, "SAL", Unit::Un, units(10), 200, 300).await;
+        let soap = insert_product(&pool, "SABÃO", Unit::Un, units(10), 100, 200).await;
+        let sales = testing::sale_service(&pool);
+        sales
+            .add_sale(
+                vec![
+                    sold(&rice, units(20)),
+                    sold(&wine, units(1)),
+                    sold(&salt, units(2)),
+                ],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        let service = SqliteAbcService::new(pool.clone());
+        let today = Local::now().date_naive();
+
+        let by_revenue = service
+            .curve(today, today, AbcCriterion::Revenue)
+            .await
+            .unwrap();
+        let ranking: Vec<(&str, AbcClass)> = by_revenue
+            .iter()
+            .map(|item| (item.name.as_str(), item.class))
+            .collect();
+        assert_eq!(
+            ranking,
+            vec![
+                ("ARROZ", AbcClass::A),
+                ("VINHO", AbcClass::B),
+                ("SAL", AbcClass::C)
+            ]
+        );
+        assert_eq!(by_revenue[0].revenue, Money::from_cents(50_000));
+        assert_eq!(by_revenue[2].cumulative, Percent::parse("100").unwrap());
+
+        let by_margin = service
+            .curve(today, today, AbcCriterion::Margin)
+            .await
+            .unwrap();
+        assert_eq!(by_margin[0].name, "ARROZ");
+        assert_eq!(by_margin[1].margin, Money::from_cents(6000));
+
+        service.save_classes(&by_revenue).await.unwrap();
+        let class_of = |id| {
+            let pool = pool.clone();
+            async move {
+                ProductRepository::find_by_id(&pool, id)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .abc_class
+            }
+        };
+        assert_eq!(class_of(rice.id).await, Some(AbcClass::A));
+        assert_eq!(class_of(wine.id).await, Some(AbcClass::B));
+        assert_eq!(class_of(soap.id).await, Some(AbcClass::C));
+    }
+}
@@ -0,0 +1,361 @@
+use crate::config::BackupConfig;
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Local, NaiveDateTime};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqlitePool};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+const BACKUP_PREFIX: &str = "backup-";
+const BACKUP_EXTENSION: &str = "db";
+const BACKUP_TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+const RESTORE_SUFFIX: &str = "restore";
+const PRE_RESTORE_SUFFIX: &str = "pre-restore";
+
+/// A database copy in the backup folder.
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created_at: NaiveDateTime,
+    pub size: u64,
+}
+
+#[derive(Debug)]
+pub struct BackupService {
+    pool: SqlitePool,
+    database: PathBuf,
+    config: BackupConfig,
+}
+
+impl BackupService {
+    pub fn new(pool: SqlitePool, database: PathBuf, config: BackupConfig) -> Self {
+        Self {
+            pool,
+            database,
+            config,
+        }
+    }
+
+    pub fn with_config(&self, config: BackupConfig) -> Self {
+        Self::new(self.pool.clone(), self.database.clone(), config)
+    }
+
+    /// Copies the open database into the backup folder with `VACUUM INTO`,
+    /// which is safe while the application is using it, then applies the
+    /// retention policy.
+    pub async fn backup(&self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.folder).map_err(|e| {
+            anyhow!(
+                "Não foi possível criar a pasta de backups {}: {}",
+                self.config.folder.display(),
+                e
+            )
+        })?;
+        let file_name = format!(
+            "{}{}.{}",
+            BACKUP_PREFIX,
+            Local::now().format(BACKUP_TIMESTAMP),
+            BACKUP_EXTENSION
+        );
+        let path = self.config.folder.join(file_name);
+        if path.exists() {
+            bail!("O backup {} já existe", path.display());
+        }
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Falha ao gerar o backup {}: {}", path.display(), e))?;
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// Runs a backup when the newest one is older than the configured
+    /// interval.
+    pub async fn backup_if_due(&self) -> Result<Option<PathBuf>> {
+        let now = Local::now().naive_local();
+        let due = match self.list()?.first() {
+            Some(latest) => (now - latest.created_at)
+                .to_std()
+                .map(|elapsed| elapsed >= self.config.interval())
+                .unwrap_or(false),
+            None => true,
+        };
+        if !due {
+            return Ok(None);
+        }
+        self.backup().await.map(Some)
+    }
+
+    /// Backups in the configured folder, newest first.
+    pub fn list(&self) -> Result<Vec<Backup>> {
+        Self::list_folder(&self.config.folder)
+    }
+
+    /// Backups in `folder`, newest first.
+    pub fn list_folder(folder: &Path) -> Result<Vec<Backup>> {
+        let entries = match std::fs::read_dir(folder) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => bail!(
+                "Não foi possível ler a pasta de backups {}: {}",
+                folder.display(),
+                e
+            ),
+        };
+
+        let mut backups = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if let Some(created_at) = backup_timestamp(&path) {
+                backups.push(Backup {
+                    path,
+                    created_at,
+                    size: entry.metadata()?.len(),
+                });
+            }
+        }
+        backups.sort_by_key(|backup| Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// Keeps the newest backup of each of the last `keep_daily` days and
+    /// `keep_weekly` ISO weeks and deletes the others.
+    fn prune(&self) -> Result<()> {
+        let backups = self.list()?;
+        let keep = backups_to_keep(&backups, self.config.keep_daily, self.config.keep_weekly);
+        for (backup, keep) in backups.iter().zip(keep) {
+            if !keep {
+                std::fs::remove_file(&backup.path).map_err(|e| {
+                    anyhow!(
+                        "Não foi possível remover o backup antigo {}: {}",
+                        backup.path.display(),
+                        e
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the backup with `PRAGMA integrity_check` and stages it to
+    /// replace the database on the next start, since the current file is in
+    /// use by the open pool.
+    pub async fn stage_restore(&self, backup: &Path) -> Result<()> {
+        Self::stage(backup, &self.database).await
+    }
+
+    /// Stages a restore of `database`, for when there is no open service.
+    pub async fn stage(backup: &Path, database: &Path) -> Result<()> {
+        check_integrity(backup).await?;
+        let staged = with_suffix(database, RESTORE_SUFFIX);
+        std::fs::copy(backup, &staged).map_err(|e| {
+            anyhow!(
+                "Não foi possível preparar a restauração em {}: {}",
+                staged.display(),
+                e
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Restores a backup right away. Only for when the database is not open,
+    /// such as from the startup error screen.
+    pub async fn restore(backup: &Path, database: &Path) -> Result<()> {
+        Self::stage(backup, database).await?;
+        Self::apply_pending_restore(database)?;
+        Ok(())
+    }
+
+    /// Swaps in a backup staged by [`BackupService::stage_restore`]. Must run
+    /// before the database is opened. The replaced file is kept with a
+    /// `.pre-restore` suffix.
+    pub fn apply_pending_restore(database: &Path) -> Result<bool> {
+        let staged = with_suffix(database, RESTORE_SUFFIX);
+        if !staged.exists() {
+            return Ok(false);
+        }
+
+        if database.exists() {
+            std::fs::rename(database, with_suffix(database, PRE_RESTORE_SUFFIX)).map_err(|e| {
+                anyhow!(
+                    "Não foi possível mover o banco atual {}: {}",
+                    database.display(),
+                    e
+                )
+            })?;
+        }
+        for suffix in ["-wal", "-shm"] {
+            let mut journal = database.as_os_str().to_owned();
+            journal.push(suffix);
+            let journal = PathBuf::from(journal);
+            if journal.exists() {
+                std::fs::remove_file(&journal)?;
+            }
+        }
+        std::fs::rename(&staged, database).map_err(|e| {
+            anyhow!(
+                "Não foi possível restaurar o backup em {}: {}",
+                database.display(),
+                e
+            )
+        })?;
+        Ok(true)
+    }
+}
+
+/// Opens the file read-only and requires `PRAGMA integrity_check` to report
+/// `ok` and the product table to exist.
+async fn check_integrity(path: &Path) -> Result<()> {
+    let mut connection = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .map_err(|e| anyhow!("Não foi possível abrir o backup {}: {}", path.display(), e))?;
+
+    let result: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut connection)
+        .await?;
+    if result != ["ok"] {
+        bail!(
+            "O backup {} está corrompido: {}",
+            path.display(),
+            result.join("; ")
+        );
+    }
+
+    let tables: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'tb_product'",
+    )
+    .fetch_one(&mut connection)
+    .await?;
+    if tables == 0 {
+        bail!(
+            "O arquivo {} não é um backup do SS PDV LITE",
+            path.display()
+        );
+    }
+
+    connection.close().await?;
+    Ok(())
+}
+
+fn backup_timestamp(path: &Path) -> Option<NaiveDateTime> {
+    if path.extension()? != BACKUP_EXTENSION {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?.strip_prefix(BACKUP_PREFIX)?;
+    NaiveDateTime::parse_from_str(stem, BACKUP_TIMESTAMP).ok()
+}
+
+/// For backups sorted newest first, marks the ones retained by the daily and
+/// weekly policy. The newest backup is always kept.
+fn backups_to_keep(backups: &[Backup], keep_daily: usize, keep_weekly: usize) -> Vec<bool> {
+    let mut days = Vec::new();
+    let mut weeks = Vec::new();
+    backups
+        .iter()
+        .enumerate()
+        .map(|(index, backup)| {
+            let date = backup.created_at.date();
+            let week = date.iso_week();
+            let mut keep = index == 0;
+            if !days.contains(&date) && days.len() < keep_daily {
+                days.push(date);
+                keep = true;
+            }
+            if !weeks.contains(&week) && weeks.len() < keep_weekly {
+                weeks.push(week);
+                keep = true;
+            }
+            keep
+        })
+        .collect()
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Days, NaiveDate};
+
+    /// One backup at noon of each day, newest first. 2026-01-05 is a Monday.
+    fn daily_backups(days: u64) -> Vec<Backup> {
+        let last = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        (0..days)
+            .map(|day| Backup {
+                path: PathBuf::new(),
+                created_at: (last - Days::new(day)).and_hms_opt(12, 0, 0).unwrap(),
+                size: 0,
+            })
+            .collect()
+    }
+
+    fn kept(backups: &[Backup], keep: &[bool]) -> Vec<String> {
+        backups
+            .iter()
+            .zip(keep)
+            .filter(|(_, keep)| **keep)
+            .map(|(backup, _)| backup.created_at.format("%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_last_days_and_the_newest_of_each_week() {
+        let backups = daily_backups(31);
+        let keep = backups_to_keep(&backups, 3, 3);
+        // Jan 31 is a Saturday: the last 3 days, then the newest backup of
+        // the 2 weeks before (Sundays 25 and 18)
+        assert_eq!(
+            kept(&backups, &keep),
+            ["01-31", "01-30", "01-29", "01-25", "01-18"]
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_newest_of_each_day() {
+        let mut backups = daily_backups(3);
+        let mut earlier = backups[0].clone();
+        earlier.created_at -= chrono::Duration::hours(2);
+        backups.insert(1, earlier);
+        let keep = backups_to_keep(&backups, 7, 0);
+        assert_eq!(keep, [true, false, true, true]);
+    }
+
+    #[test]
+    fn always_keeps_the_newest_backup() {
+        let backups = daily_backups(2);
+        assert_eq!(backups_to_keep(&backups, 0, 0), [true, false]);
+        assert!(backups_to_keep(&[], 1, 1).is_empty());
+    }
+
+    #[test]
+    fn apply_pending_restore_swaps_the_file_and_keeps_the_replaced_one() {
+        let dir = crate::database::testing::temp_dir("apply-restore");
+        let database = dir.join("pdv.db");
+        assert!(!BackupService::apply_pending_restore(&database).unwrap());
+
+        std::fs::write(&database, "atual").unwrap();
+        std::fs::write(dir.join("pdv.db-wal"), "diário").unwrap();
+        std::fs::write(dir.join("pdv.db.restore"), "backup").unwrap();
+        assert!(BackupService::apply_pending_restore(&database).unwrap());
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("pdv.db"), "backup");
+        assert_eq!(read("pdv.db.pre-restore"), "atual");
+        // The journal of the replaced file must not be applied to the backup
+        assert!(!dir.join("pdv.db-wal").exists());
+        assert!(!dir.join("pdv.db.restore").exists());
+        assert!(!BackupService::apply_pending_restore(&database).unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
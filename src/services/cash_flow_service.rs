@@ -0,0 +1,279 @@
+use crate::helpers::money::{Money, Percent};
+use crate::helpers::{local_day_start, to_local};
+use crate::repositories::expense_repository::ExpenseRepository;
+use crate::repositories::payable_repository::PayableRepository;
+use crate::repositories::product_sale_repository::ProductSaleRepository;
+use crate::repositories::purchase_repository::PurchaseRepository;
+use crate::repositories::receivable_repository::ReceivableRepository;
+use crate::repositories::sale_repository::SaleRepository;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+/// Result and cash flow of one month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthResult {
+    /// First day of the month.
+    pub month: NaiveDate,
+    /// Sales less returns and what was paid with loyalty points.
+    pub revenue: Money,
+    /// Sold quantities, less returns, at the current cost of each product.
+    pub cost_of_goods: Money,
+    pub expenses: Money,
+    /// Supplier titles paid and purchases paid on receipt.
+    pub purchase_payments: Money,
+    /// Sales paid at the checkout plus store credit received.
+    pub cash_in: Money,
+}
+
+impl MonthResult {
+    fn new(month: NaiveDate) -> Self {
+        Self {
+            month,
+            revenue: Money::ZERO,
+            cost_of_goods: Money::ZERO,
+            expenses: Money::ZERO,
+            purchase_payments: Money::ZERO,
+            cash_in: Money::ZERO,
+        }
+    }
+
+    pub fn gross_margin(&self) -> Money {
+        self.revenue - self.cost_of_goods
+    }
+
+    /// Gross margin over revenue, `None` without sales.
+    pub fn gross_margin_percent(&self) -> Option<Percent> {
+        self.gross_margin().share_of(self.revenue)
+    }
+
+    pub fn net_result(&self) -> Money {
+        self.gross_margin() - self.expenses
+    }
+
+    pub fn cash_out(&self) -> Money {
+        self.expenses + self.purchase_payments
+    }
+
+    pub fn cash_balance(&self) -> Money {
+        self.cash_in - self.cash_out()
+    }
+
+    /// Sum of several months, dated on the first of them.
+    pub fn total(months: &[MonthResult]) -> Option<MonthResult> {
+        let first = months.first()?;
+        Some(
+            months
+                .iter()
+                .fold(MonthResult::new(first.month), |total, month| MonthResult {
+                    month: total.month,
+                    revenue: total.revenue + month.revenue,
+                    cost_of_goods: total.cost_of_goods + month.cost_of_goods,
+                    expenses: total.expenses + month.expenses,
+                    purchase_payments: total.purchase_payments + month.purchase_payments,
+                    cash_in: total.cash_in + month.cash_in,
+                }),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct SqliteCashFlowService {
+    pool: SqlitePool,
+}
+
+/// Monthly profit and cash flow, combining sales, cost of goods, expenses
+/// and purchase payments.
+#[async_trait]
+pub trait CashFlowService: Debug + Send + Sync {
+    /// The twelve months of `year`, January first.
+    async fn monthly_results(&self, year: i32) -> Result<Vec<MonthResult>>;
+}
+
+impl SqliteCashFlowService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CashFlowService for SqliteCashFlowService {
+    async fn monthly_results(&self, year: i32) -> Result<Vec<MonthResult>> {
+        let first_day = |year| {
+            NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| anyhow!("Ano {} inválido", year))
+        };
+        let (first, last) = (first_day(year)?, first_day(year + 1)?);
+        let (start, end) = (local_day_start(first), local_day_start(last));
+        let mut months: Vec<MonthResult> = (1..=12)
+            .filter_map(|month| NaiveDate::from_ymd_opt(year, month, 1))
+            .map(MonthResult::new)
+            .collect();
+
+        for line in ProductSaleRepository::list_between(&self.pool, start, end).await? {
+            if let Some(result) = month_of(&mut months, local_date(line.sold_at)) {
+                result.revenue += line.total;
+                result.cash_in += line.total;
+                result.cost_of_goods += line.cost.saturating_times_quantity(line.quantity);
+            }
+        }
+        for sale in SaleRepository::list_between(&self.pool, start, end).await? {
+            if !sale.is_voided()
+                && let Some(result) = month_of(&mut months, local_date(sale.created_at))
+            {
+                result.revenue = result.revenue - sale.loyalty_discount;
+                result.cash_in = result.cash_in - sale.loyalty_discount;
+            }
+        }
+        for entry in ReceivableRepository::list_between(&self.pool, start, end).await? {
+            // Store credit sales bring no money until the customer pays:
+            // charges come out of the cash, payments (negative) go in
+            if let Some(result) = month_of(&mut months, local_date(entry.created_at)) {
+                result.cash_in = result.cash_in - entry.amount;
+            }
+        }
+        let year_end = last.pred_opt().unwrap_or(last);
+        for expense in ExpenseRepository::list_between(&self.pool, first, year_end).await? {
+            if let Some(result) = month_of(&mut months, expense.paid_on) {
+                result.expenses += expense.amount;
+            }
+        }
+        for payable in PayableRepository::list_paid_between(&self.pool, start, end).await? {
+            if let Some(paid_at) = payable.paid_at
+                && let Some(result) = month_of(&mut months, local_date(paid_at))
+            {
+                result.purchase_payments += payable.amount;
+            }
+        }
+        for purchase in PurchaseRepository::list_paid_on_receipt(&self.pool, start, end).await? {
+            if let Some(result) = month_of(&mut months, local_date(purchase.created_at)) {
+                result.purchase_payments += purchase.total;
+            }
+        }
+        Ok(months)
+    }
+}
+
+/// The month of `date` among the twelve of the year being computed.
+fn month_of(months: &mut [MonthResult], date: NaiveDate) -> Option<&mut MonthResult> {
+    months
+        .get_mut(date.month0() as usize)
+        .filter(|result| result.month.year() == date.year())
+}
+
+fn local_date(value: NaiveDateTime) -> NaiveDate {
+    to_local(value).date()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, units};
+    use crate::entities::customer::Customer;
+    use crate::entities::expense::{Expense, ExpenseCategory};
+    use crate::entities::payment_method::PaymentMethod;
+    use crate::entities::product::Product;
+    use crate::repositories::customer_repository::CustomerRepository;
+    use crate::services::expense_service::{ExpenseService, SqliteExpenseService};
+    use crate::services::payable_service::{PayableService, SqlitePayableService};
+    use crate::services::product_purchase_service::{
+        PaymentTerms, ProductPurchaseService, SqliteProductPurchaseService,
+    };
+    use crate::services::product_sale_service::ProductSaleService;
+    use crate::services::receivable_service::{ReceivableService, SqliteReceivableService};
+    use chrono::{Days, Utc};
+
+    #[tokio::test]
+    async fn month_combines_sales_costs_expenses_and_payments() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let sales = testing::sale_service(&pool);
+        let purchases = SqliteProductPurchaseService::new(pool.clone());
+        let now = Utc::now().naive_local();
+        let today = local_date(now);
+        let customer = CustomerRepository::insert(
+            &pool,
+            &Customer {
+                credit_limit: Money::from_cents(10_000),
+                ..Customer::new(0, "MARIA".to_string(), None, None, None, now)
+            },
+        )
+        .await
+        .unwrap();
+        let rice_with = |quantity| Product {
+            quantity,
+            ..rice.clone()
+        };
+
+        sales
+            .add_sale(
+                vec![rice_with(units(2)).into()],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        sales
+            .add_sale(
+                vec![rice_with(units(1)).into()],
+                Some(customer),
+                0,
+                PaymentMethod::StoreCredit,
+            )
+            .await
+            .unwrap();
+        SqliteReceivableService::new(pool.clone())
+            .receive_payment(customer, Money::from_cents(1000))
+            .await
+            .unwrap();
+        SqliteExpenseService::new(pool.clone())
+            .add_expense(Expense::new(
+                0,
+                ExpenseCategory::Utilities,
+                "luz".to_string(),
+                Money::from_cents(1500),
+                today,
+                PaymentMethod::Pix,
+                now,
+            ))
+            .await
+            .unwrap();
+        purchases
+            .add_purchase(vec![rice_with(units(1))], None)
+            .await
+            .unwrap();
+        purchases
+            .add_purchase(
+                vec![rice_with(units(2))],
+                Some(PaymentTerms {
+                    supplier: "ATACADO".to_string(),
+                    document: None,
+                    due_dates: vec![today + Days::new(30), today + Days::new(60)],
+                }),
+            )
+            .await
+            .unwrap();
+        let payables = SqlitePayableService::new(pool.clone());
+        let first = payables.list_open(None).await.unwrap()[0].id;
+        payables.pay(first, PaymentMethod::BankSlip).await.unwrap();
+
+        let months = SqliteCashFlowService::new(pool.clone())
+            .monthly_results(today.year())
+            .await
+            .unwrap();
+
+        assert_eq!(months.len(), 12);
+        let month = &months[today.month0() as usize];
+        assert_eq!(month.revenue, Money::from_cents(7770));
+        assert_eq!(month.cost_of_goods, Money::from_cents(6000));
+        assert_eq!(month.gross_margin_percent(), Percent::parse("22,78"));
+        assert_eq!(month.expenses, Money::from_cents(1500));
+        assert_eq!(month.net_result(), Money::from_cents(270));
+        assert_eq!(month.cash_in, Money::from_cents(6180));
+        assert_eq!(month.purchase_payments, Money::from_cents(4000));
+        assert_eq!(month.cash_balance(), Money::from_cents(680));
+        assert_eq!(MonthResult::total(&months).unwrap().revenue, month.revenue);
+    }
+}
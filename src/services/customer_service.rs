@@ -0,0 +1,316 @@
+use crate::entities::customer::Customer;
+use crate::entities::loyalty_entry::LoyaltyEntry;
+use crate::entities::sale::{CustomerSummary, Sale};
+use crate::helpers::document::{is_valid_cpf, only_digits};
+use crate::helpers::loyalty;
+use crate::repositories::customer_repository::CustomerRepository;
+use crate::repositories::loyalty_repository::LoyaltyRepository;
+use crate::repositories::sale_repository::SaleRepository;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+const CUSTOMER_LIST_LIMIT: i64 = 200;
+
+/// A customer with every sale made to them and their loyalty points, for
+/// the customer screen.
+#[derive(Debug, Clone)]
+pub struct CustomerHistory {
+    pub customer: Customer,
+    pub summary: CustomerSummary,
+    pub sales: Vec<Sale>,
+    pub points: i64,
+    /// Loyalty ledger, newest first.
+    pub loyalty: Vec<LoyaltyEntry>,
+}
+
+#[derive(Debug)]
+pub struct SqliteCustomerService {
+    pool: SqlitePool,
+}
+
+/// Customer registry and purchase history.
+#[async_trait]
+pub trait CustomerService: Debug + Send + Sync {
+    /// Customers by part of the name, the CPF or part of the phone, or the
+    /// first ones by name when `text` is empty.
+    async fn search_customers(&self, text: &str) -> Result<Vec<Customer>>;
+
+    /// Registers the customer, or updates it when it has an id, and returns
+    /// its id. CPF and phone are kept as digits only.
+    async fn save_customer(&self, customer: Customer) -> Result<i64>;
+
+    async fn customer_history(&self, id: i64) -> Result<Option<CustomerHistory>>;
+
+    /// Loyalty points the customer can use now.
+    async fn points_balance(&self, id: i64) -> Result<i64>;
+}
+
+impl SqliteCustomerService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomerService for SqliteCustomerService {
+    async fn search_customers(&self, text: &str) -> Result<Vec<Customer>> {
+        let text = text.trim();
+        if text.is_empty() {
+            return CustomerRepository::list(&self.pool, CUSTOMER_LIST_LIMIT).await;
+        }
+        let name = if text.chars().any(char::is_alphabetic) {
+            text.to_uppercase()
+        } else {
+            String::new()
+        };
+        let digits = only_digits(text);
+        CustomerRepository::search(&self.pool, &name, &digits, CUSTOMER_LIST_LIMIT).await
+    }
+
+    async fn save_customer(&self, customer: Customer) -> Result<i64> {
+        let customer = validate(customer)?;
+        if customer.id == 0 {
+            return CustomerRepository::insert(&self.pool, &customer).await;
+        }
+        if CustomerRepository::find_by_id(&self.pool, customer.id)
+            .await?
+            .is_none()
+        {
+            bail!("Cliente {} não encontrado", customer.id);
+        }
+        CustomerRepository::update(&self.pool, &customer).await?;
+        Ok(customer.id)
+    }
+
+    async fn customer_history(&self, id: i64) -> Result<Option<CustomerHistory>> {
+        let Some(customer) = CustomerRepository::find_by_id(&self.pool, id).await? else {
+            return Ok(None);
+        };
+        let summary = SaleRepository::customer_summary(&self.pool, id).await?;
+        let sales = SaleRepository::list_by_customer(&self.pool, id).await?;
+        let mut entries = LoyaltyRepository::list_by_customer(&self.pool, id).await?;
+        let points = loyalty::balance(&entries, Utc::now().naive_local());
+        entries.reverse();
+        Ok(Some(CustomerHistory {
+            customer,
+            summary,
+            sales,
+            points,
+            loyalty: entries,
+        }))
+    }
+
+    async fn points_balance(&self, id: i64) -> Result<i64> {
+        let entries = LoyaltyRepository::list_by_customer(&self.pool, id).await?;
+        Ok(loyalty::balance(&entries, Utc::now().naive_local()))
+    }
+}
+
+/// Trims and normalizes the fields, rejecting invalid ones.
+fn validate(customer: Customer) -> Result<Customer> {
+    let name = customer.name.trim().to_uppercase();
+    if name.is_empty() {
+        bail!("Informe o nome do cliente");
+    }
+    let cpf = customer.cpf.as_deref().map(str::trim).unwrap_or_default();
+    if !cpf.is_empty() && !is_valid_cpf(cpf) {
+        bail!("CPF {} inválido", cpf);
+    }
+    let phone = only_digits(customer.phone.as_deref().unwrap_or_default());
+    if !phone.is_empty() && !(10..=11).contains(&phone.len()) {
+        bail!("Telefone deve ter DDD e 8 ou 9 dígitos");
+    }
+    if customer.credit_limit.is_negative() {
+        bail!("O limite do fiado não pode ser negativo");
+    }
+    let email = customer
+        .email
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_lowercase();
+    if !email.is_empty() && !is_valid_email(&email) {
+        bail!("E-mail {} inválido", email);
+    }
+
+    let optional = |value: String| (!value.is_empty()).then_some(value);
+    Ok(Customer {
+        name,
+        cpf: optional(only_digits(cpf)),
+        phone: optional(phone),
+        email: optional(email),
+        created_at: if customer.id == 0 {
+            Utc::now().naive_local()
+        } else {
+            customer.created_at
+        },
+        ..customer
+    })
+}
+
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && !domain.contains('@')
+                && !email.chars().any(char::is_whitespace)
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(host, rest)| !host.is_empty() && !rest.is_empty())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, units};
+    use crate::entities::payment_method::PaymentMethod;
+    use crate::helpers::money::Money;
+    use crate::services::product_sale_service::ProductSaleService;
+
+    fn customer(name: &str, cpf: &str, phone: &str, email: &str) -> Customer {
+        let optional = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        Customer::new(
+            0,
+            name.to_string(),
+            optional(cpf),
+            optional(phone),
+            optional(email),
+            Utc::now().naive_local(),
+        )
+    }
+
+    #[tokio::test]
+    async fn save_customer_normalizes_and_validates_fields() {
+        let service = SqliteCustomerService::new(testing::pool().await);
+
+        let id = service
+            .save_customer(customer(
+                " maria souza ",
+                "529.982.247-25",
+                "(11) 98765-4321",
+                "Maria@Exemplo.com",
+            ))
+            .await
+            .unwrap();
+        let saved = service
+            .customer_history(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .customer;
+        assert_eq!(saved.name, "MARIA SOUZA");
+        assert_eq!(saved.cpf.as_deref(), Some("52998224725"));
+        assert_eq!(saved.phone.as_deref(), Some("11987654321"));
+        assert_eq!(saved.email.as_deref(), Some("maria@exemplo.com"));
+
+        let invalid = [
+            customer("", "", "", ""),
+            customer("JOÃO", "529.982.247-24", "", ""),
+            customer("JOÃO", "111.111.111-11", "", ""),
+            customer("JOÃO", "", "98765-4321", ""),
+            customer("JOÃO", "", "", "joao@exemplo"),
+        ];
+        for customer in invalid {
+            assert!(service.save_customer(customer).await.is_err());
+        }
+
+        let error = service
+            .save_customer(customer("OUTRA MARIA", "52998224725", "", ""))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "CPF 52998224725 já está cadastrado em outro cliente"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_customers_by_name_cpf_or_phone() {
+        let service = SqliteCustomerService::new(testing::pool().await);
+        for customer in [
+            customer("MARIA SOUZA", "52998224725", "11987654321", ""),
+            customer("JOSÉ MARIA", "", "2133334444", ""),
+            customer("ANA LIMA", "", "", ""),
+        ] {
+            service.save_customer(customer).await.unwrap();
+        }
+        let names = |customers: Vec<Customer>| {
+            customers
+                .into_iter()
+                .map(|customer| customer.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(service.search_customers("maria").await.unwrap()),
+            vec!["JOSÉ MARIA", "MARIA SOUZA"]
+        );
+        assert_eq!(
+            names(service.search_customers("529.982.247-25").await.unwrap()),
+            vec!["MARIA SOUZA"]
+        );
+        assert_eq!(
+            names(service.search_customers("3333-4444").await.unwrap()),
+            vec!["JOSÉ MARIA"]
+        );
+        assert_eq!(service.search_customers("").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn customer_history_sums_sales_net_of_returns_and_voids() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let customers = SqliteCustomerService::new(pool.clone());
+        let sales = testing::sale_service(&pool);
+        let id = customers
+            .save_customer(customer("MARIA SOUZA", "", "", ""))
+            .await
+            .unwrap();
+        let sold = |quantity| vec![testing::sold(&rice, quantity)];
+
+        let first = sales
+            .add_sale(sold(units(2)), Some(id), 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let second = sales
+            .add_sale(sold(units(1)), Some(id), 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let voided = sales
+            .add_sale(sold(units(3)), Some(id), 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        sales
+            .add_sale(sold(units(1)), None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let item = sales.find_sale(first).await.unwrap().unwrap().items[0].id;
+        sales
+            .return_items(first, vec![(item, units(1))])
+            .await
+            .unwrap();
+        sales.void_sale(voided).await.unwrap();
+
+        let history = customers.customer_history(id).await.unwrap().unwrap();
+        assert_eq!(history.summary.sales, 2);
+        assert_eq!(history.summary.total_spent, Money::from_cents(2 * 2590));
+        assert!(history.summary.last_visit.is_some());
+        let ids: Vec<i64> = history.sales.iter().map(|sale| sale.id).collect();
+        assert_eq!(ids, vec![voided, second, first]);
+
+        let error = sales
+            .add_sale(sold(units(1)), Some(id + 100), 0, PaymentMethod::Cash)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Cliente {} não encontrado", id + 100)
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::entities::expense::Expense;
+use crate::entities::payment_method::PaymentMethod;
+use crate::helpers::money::Money;
+use crate::repositories::expense_repository::ExpenseRepository;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+#[derive(Debug)]
+pub struct SqliteExpenseService {
+    pool: SqlitePool,
+}
+
+/// Register of operating expenses.
+#[async_trait]
+pub trait ExpenseService: Debug + Send + Sync {
+    async fn add_expense(&self, expense: Expense) -> Result<i64>;
+
+    /// Expenses paid from `from` to `to`, both included.
+    async fn list_expenses(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Expense>>;
+
+    async fn delete_expense(&self, id: i64) -> Result<()>;
+}
+
+impl SqliteExpenseService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ExpenseService for SqliteExpenseService {
+    async fn add_expense(&self, mut expense: Expense) -> Result<i64> {
+        expense.description = expense.description.trim().to_uppercase();
+        if expense.description.is_empty() {
+            bail!("Informe a descrição da despesa");
+        }
+        if expense.amount <= Money::ZERO {
+            bail!("Informe o valor da despesa");
+        }
+        if expense.payment_method == PaymentMethod::StoreCredit {
+            bail!("Despesas não podem ser pagas no fiado");
+        }
+        expense.created_at = Utc::now().naive_local();
+        ExpenseRepository::insert(&self.pool, &expense).await
+    }
+
+    async fn list_expenses(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Expense>> {
+        if to < from {
+            bail!("A data final deve ser igual ou posterior à data inicial");
+        }
+        ExpenseRepository::list_between(&self.pool, from, to).await
+    }
+
+    async fn delete_expense(&self, id: i64) -> Result<()> {
+        if !ExpenseRepository::delete(&self.pool, id).await? {
+            bail!("Despesa {} não encontrada", id);
+        }
+        Ok(())
+    }
+}
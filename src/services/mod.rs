@@ -1,3 +1,11 @@
+pub mod abc_service;
+pub mod backup_service;
+pub mod cash_flow_service;
+pub mod customer_service;
+pub mod expense_service;
+pub mod payable_service;
 pub mod product_purchase_service;
+pub mod product_sale_service;
 pub mod product_service;
-pub mod product_sale_service;
\ No newline at end of file
+pub mod receivable_service;
+pub mod user_service;
@@ -0,0 +1,125 @@
+use crate::entities::payable::Payable;
+use crate::entities::payment_method::PaymentMethod;
+use crate::repositories::payable_repository::PayableRepository;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+#[derive(Debug)]
+pub struct SqlitePayableService {
+    pool: SqlitePool,
+}
+
+/// Supplier titles created by purchases on terms, and their payment.
+#[async_trait]
+pub trait PayableService: Debug + Send + Sync {
+    /// Open titles falling due up to `until`, overdue ones included, or all
+    /// open titles without it. Soonest first.
+    async fn list_open(&self, until: Option<NaiveDate>) -> Result<Vec<Payable>>;
+
+    async fn pay(&self, id: i64, payment_method: PaymentMethod) -> Result<()>;
+}
+
+impl SqlitePayableService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PayableService for SqlitePayableService {
+    async fn list_open(&self, until: Option<NaiveDate>) -> Result<Vec<Payable>> {
+        PayableRepository::list_open(&self.pool, until).await
+    }
+
+    async fn pay(&self, id: i64, payment_method: PaymentMethod) -> Result<()> {
+        if payment_method == PaymentMethod::StoreCredit {
+            bail!("Títulos de fornecedor não podem ser pagos no fiado");
+        }
+        let mut tx = self.pool.begin().await?;
+        let Some(payable) = PayableRepository::find_by_id(&mut *tx, id).await? else {
+            bail!("Título {} não encontrado", id);
+        };
+        if !PayableRepository::mark_paid(&mut *tx, id, payment_method, Utc::now().naive_local())
+            .await?
+        {
+            bail!(
+                "A parcela {}/{} de {} já foi paga",
+                payable.installment,
+                payable.installments,
+                payable.supplier
+            );
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, units};
+    use crate::entities::product::Product;
+    use crate::services::product_purchase_service::{
+        PaymentTerms, ProductPurchaseService, SqliteProductPurchaseService,
+    };
+    use chrono::Days;
+
+    #[tokio::test]
+    async fn paid_titles_leave_the_open_list() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let purchases = SqliteProductPurchaseService::new(pool.clone());
+        let service = SqlitePayableService::new(pool.clone());
+        let today = Utc::now().date_naive();
+        purchases
+            .add_purchase(
+                vec![Product {
+                    quantity: units(3),
+                    ..rice.clone()
+                }],
+                Some(PaymentTerms {
+                    supplier: "ATACADO".to_string(),
+                    document: None,
+                    due_dates: vec![today, today + Days::new(30)],
+                }),
+            )
+            .await
+            .unwrap();
+
+        let this_week = service.list_open(Some(today + Days::new(6))).await.unwrap();
+        assert_eq!(this_week.len(), 1);
+        assert_eq!(this_week[0].installment, 1);
+        assert_eq!(service.list_open(None).await.unwrap().len(), 2);
+
+        service
+            .pay(this_week[0].id, PaymentMethod::BankSlip)
+            .await
+            .unwrap();
+        let paid = PayableRepository::find_by_id(&pool, this_week[0].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(paid.is_paid());
+        let (method,): (String,) =
+            sqlx::query_as("SELECT payment_method FROM tb_payable WHERE id = ?")
+                .bind(paid.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(method, "BANK_SLIP");
+        assert!(service
+            .list_open(Some(today + Days::new(6)))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let error = service
+            .pay(this_week[0].id, PaymentMethod::Pix)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "A parcela 1/2 de ATACADO já foi paga");
+    }
+}
@@ -1,57 +1,556 @@
+use crate::entities::payable::Payable;
 use crate::entities::product::Product;
 use crate::entities::product_purchase::ProductPurchase;
 use crate::entities::purchase::Purchase;
+use crate::helpers::barcode::{internal_ean13, is_valid_gtin};
+use crate::helpers::local_day_start;
+use crate::helpers::money::Money;
+use crate::helpers::payable;
+use crate::helpers::replenishment::{daily_average, suggested_quantity};
+use crate::repositories::payable_repository::PayableRepository;
 use crate::repositories::product_purchase_repository::ProductPurchaseRepository;
 use crate::repositories::product_repository::ProductRepository;
+use crate::repositories::product_sale_repository::ProductSaleRepository;
 use crate::repositories::purchase_repository::PurchaseRepository;
-use anyhow::{anyhow, Result};
-use chrono::Utc;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{Days, Local, NaiveDate, Utc};
 use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+/// How a purchase will be paid: one supplier title per due date, splitting
+/// the total.
+#[derive(Debug, Clone)]
+pub struct PaymentTerms {
+    pub supplier: String,
+    /// Invoice number.
+    pub document: Option<String>,
+    pub due_dates: Vec<NaiveDate>,
+}
+
+/// A product to buy again, with the sales pace behind the suggestion.
+#[derive(Debug, Clone)]
+pub struct PurchaseSuggestion {
+    pub product: Product,
+    /// Quantity sold in the period, returns discounted.
+    pub sold: i64,
+    pub daily_average: i64,
+    pub quantity: i64,
+}
 
 #[derive(Debug)]
-pub struct ProductPurchaseService {
+pub struct SqliteProductPurchaseService {
     pool: SqlitePool,
 }
 
-impl ProductPurchaseService {
+/// Registers purchases, adding the received quantities to stock.
+#[async_trait]
+pub trait ProductPurchaseService: Debug + Send + Sync {
+    /// Registers the purchase and returns the received products, with their
+    /// final id and EAN and the purchased quantity. With `terms` the total is
+    /// owed to the supplier; without them it was paid on receipt.
+    async fn add_purchase(
+        &self,
+        mut products: Vec<Product>,
+        terms: Option<PaymentTerms>,
+    ) -> Result<Vec<Product>>;
+
+    /// Products whose stock doesn't cover `coverage_days` of the sales of
+    /// the last `history_days` plus their minimum stock, A products first.
+    /// Kits are left out, their components are bought instead.
+    /// With `supplier` only the products last bought from it.
+    async fn suggest_order(
+        &self,
+        history_days: u32,
+        coverage_days: u32,
+        supplier: Option<String>,
+    ) -> Result<Vec<PurchaseSuggestion>>;
+}
+
+impl SqliteProductPurchaseService {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
+}
+
+#[async_trait]
+impl ProductPurchaseService for SqliteProductPurchaseService {
+    async fn add_purchase(
+        &self,
+        mut products: Vec<Product>,
+        terms: Option<PaymentTerms>,
+    ) -> Result<Vec<Product>> {
+        if let Some(terms) = &terms {
+            if terms.supplier.trim().is_empty() {
+                bail!("Informe o fornecedor da compra a prazo");
+            }
+            if terms.due_dates.is_empty() {
+                bail!("Informe o vencimento da compra a prazo");
+            }
+        }
+        for product in products.iter_mut() {
+            product.ean = product.ean.take().filter(|ean| !ean.trim().is_empty());
+            product.supplier = product
+                .supplier
+                .take()
+                .map(|supplier| supplier.trim().to_uppercase())
+                .filter(|supplier| !supplier.is_empty())
+                .or_else(|| {
+                    terms
+                        .as_ref()
+                        .map(|terms| terms.supplier.trim().to_uppercase())
+                });
+        }
 
-    pub async fn add_purchase(&self, products: Vec<Product>) -> Result<()> {
         let mut tx = self.pool.begin().await?;
         let now = Utc::now().naive_local();
-        let total = products.iter().map(|p| p.price_purchase * p.quantity).sum();
-        let purchase_id =
-            PurchaseRepository::insert(&mut *tx, &Purchase::new(0, total, now)).await?;
+        let line_totals = products
+            .iter()
+            .map(|p| {
+                p.price_purchase
+                    .checked_times_quantity(p.quantity)
+                    .ok_or_else(|| anyhow!("Valor de {} excede o limite permitido", p.name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let total = Money::checked_sum(line_totals.iter().copied())
+            .ok_or_else(|| anyhow!("Valor total da compra excede o limite permitido"))?;
+        let purchase_id = PurchaseRepository::insert(&mut *tx, &Purchase::new(total, now)).await?;
+        if let Some(terms) = terms {
+            let installments = terms.due_dates.len();
+            let amounts = payable::split(total, installments);
+            for (index, (due_date, amount)) in terms.due_dates.into_iter().zip(amounts).enumerate()
+            {
+                PayableRepository::insert(
+                    &mut *tx,
+                    &Payable::new(
+                        0,
+                        Some(purchase_id),
+                        terms.supplier.trim().to_uppercase(),
+                        terms.document.clone(),
+                        index as i64 + 1,
+                        installments as i64,
+                        amount,
+                        due_date,
+                        now,
+                    ),
+                )
+                .await?;
+            }
+        }
 
-        for product in products.into_iter().as_mut_slice() {
+        let mut received = Vec::with_capacity(products.len());
+        for (product, line_total) in products.iter_mut().zip(line_totals) {
+            let quantity = product.quantity;
             let product_id = if product.id == 0 {
+                check_ean(product, None)?;
                 ProductRepository::insert(&mut *tx, product).await?
             } else if let Some(existing_product) =
                 ProductRepository::find_by_id(&mut *tx, product.id).await?
             {
+                check_ean(product, existing_product.ean.as_deref())?;
+                if existing_product.is_kit {
+                    bail!("{} é um kit: compre os componentes", existing_product.name);
+                }
                 product.quantity += existing_product.quantity;
+                product.min_stock = existing_product.min_stock;
+                if product.supplier.is_none() {
+                    product.supplier = existing_product.supplier;
+                }
                 ProductRepository::update(&mut *tx, product).await?;
                 product.id
             } else {
-                return Err(anyhow!("Product with ID {} not found", product.id));
+                bail!("Produto {} não encontrado", product.id);
             };
+            if product.ean.is_none() {
+                let ean = internal_ean13(product_id);
+                ProductRepository::update_ean(&mut *tx, product_id, &ean).await?;
+                product.ean = Some(ean);
+            }
             ProductPurchaseRepository::insert(
                 &mut *tx,
                 &ProductPurchase::new(
-                    0,
                     product_id,
                     purchase_id,
                     product.price_purchase,
-                    product.quantity,
-                    product.quantity * product.price_purchase,
+                    quantity,
+                    line_total,
                     now,
                 ),
             )
             .await?;
+            received.push(Product {
+                id: product_id,
+                quantity,
+                ..product.clone()
+            });
         }
         tx.commit().await?;
-        Ok(())
+        Ok(received)
+    }
+
+    async fn suggest_order(
+        &self,
+        history_days: u32,
+        coverage_days: u32,
+        supplier: Option<String>,
+    ) -> Result<Vec<PurchaseSuggestion>> {
+        if history_days == 0 {
+            bail!("Informe quantos dias de venda considerar");
+        }
+        let tomorrow = Local::now().date_naive() + Days::new(1);
+        let from = tomorrow
+            .checked_sub_days(Days::new(u64::from(history_days)))
+            .ok_or_else(|| anyhow!("Período de {} dias inválido", history_days))?;
+        let lines = ProductSaleRepository::list_between(
+            &self.pool,
+            local_day_start(from),
+            local_day_start(tomorrow),
+        )
+        .await?;
+        let supplier = supplier
+            .map(|supplier| supplier.trim().to_uppercase())
+            .filter(|supplier| !supplier.is_empty());
+
+        let mut suggestions: Vec<PurchaseSuggestion> = vec![];
+        for product in ProductRepository::list_all(&self.pool).await? {
+            if product.is_kit || (supplier.is_some() && product.supplier != supplier) {
+                continue;
+            }
+            let sold = lines
+                .iter()
+                .filter(|line| line.product_id == product.id)
+                .try_fold(0_i64, |total, line| total.checked_add(line.quantity))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Quantidade vendida de {} excede o limite permitido",
+                        product.name
+                    )
+                })?;
+            let quantity = suggested_quantity(
+                sold,
+                history_days,
+                coverage_days,
+                product.quantity,
+                product.min_stock,
+                product.unit,
+            );
+            if quantity > 0 {
+                suggestions.push(PurchaseSuggestion {
+                    daily_average: daily_average(sold, history_days),
+                    product,
+                    sold,
+                    quantity,
+                });
+            }
+        }
+        // Unclassified products go after C, already sorted by name
+        suggestions.sort_by_key(|suggestion| {
+            let class = suggestion.product.abc_class;
+            (class.is_none(), class)
+        });
+        Ok(suggestions)
+    }
+}
+
+/// Rejects a typed EAN that is not a valid GTIN, unless it is the one
+/// already stored for the product, which may predate the validation.
+fn check_ean(product: &Product, stored: Option<&str>) -> Result<()> {
+    match product.ean.as_deref() {
+        Some(ean) if !is_valid_gtin(ean) && stored != Some(ean) => bail!("EAN {} inválido", ean),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, insert_product, stock, units};
+    use crate::entities::payment_method::PaymentMethod;
+    use crate::entities::unit::Unit;
+    use crate::services::product_sale_service::ProductSaleService;
+
+    async fn purchased_quantities(pool: &SqlitePool) -> Vec<(i64, i64)> {
+        sqlx::query_as("SELECT product_id, quantity FROM tb_product_purchase ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn add_purchase_adds_received_quantities_to_stock() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 500, 3000, 4990).await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+
+        let received = service
+            .add_purchase(
+                vec![
+                    Product {
+                        quantity: units(3),
+                        price_purchase: Money::from_cents(2100),
+                        ..rice.clone()
+                    },
+                    Product {
+                        quantity: 1250,
+                        ..cheese.clone()
+                    },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, rice.id).await, units(8));
+        assert_eq!(stock(&pool, cheese.id).await, 1750);
+        let quantities: Vec<i64> = received.iter().map(|p| p.quantity).collect();
+        assert_eq!(quantities, vec![units(3), 1250]);
+        assert_eq!(
+            purchased_quantities(&pool).await,
+            vec![(rice.id, units(3)), (cheese.id, 1250)]
+        );
+        let price = ProductRepository::find_by_id(&pool, rice.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .price_purchase;
+        assert_eq!(price, Money::from_cents(2100));
+    }
+
+    #[tokio::test]
+    async fn add_purchase_registers_new_products_with_an_internal_ean() {
+        let pool = testing::pool().await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+        let coffee = Product::new(
+            0,
+            "CAFÉ".to_string(),
+            Money::from_cents(1550),
+            Money::from_cents(1000),
+            units(4),
+            Unit::Un,
+            None,
+            Utc::now().naive_local(),
+        );
+
+        let received = service.add_purchase(vec![coffee], None).await.unwrap();
+
+        let id = received[0].id;
+        assert!(id > 0);
+        assert_eq!(received[0].ean, Some(internal_ean13(id)));
+        assert_eq!(stock(&pool, id).await, units(4));
+    }
+
+    #[tokio::test]
+    async fn add_purchase_rejects_unknown_products() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+        let unknown = Product {
+            id: rice.id + 100,
+            quantity: units(1),
+            ..rice.clone()
+        };
+
+        let error = service
+            .add_purchase(
+                vec![
+                    Product {
+                        quantity: units(3),
+                        ..rice.clone()
+                    },
+                    unknown,
+                ],
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("Produto {} não encontrado", rice.id + 100)
+        );
+        assert_eq!(stock(&pool, rice.id).await, units(5));
+        assert!(purchased_quantities(&pool).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn add_purchase_rejects_invalid_eans() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+
+        let error = service
+            .add_purchase(
+                vec![Product {
+                    quantity: units(1),
+                    ean: Some("7891000100104".to_string()),
+                    ..rice.clone()
+                }],
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "EAN 7891000100104 inválido");
+        assert_eq!(stock(&pool, rice.id).await, units(5));
+    }
+
+    #[tokio::test]
+    async fn add_purchase_keeps_an_invalid_ean_stored_before_the_validation() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        ProductRepository::update_ean(&pool, rice.id, "7891000100104")
+            .await
+            .unwrap();
+        let service = SqliteProductPurchaseService::new(pool.clone());
+
+        service
+            .add_purchase(
+                vec![Product {
+                    quantity: units(1),
+                    ean: Some("7891000100104".to_string()),
+                    ..rice.clone()
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, rice.id).await, units(6));
+    }
+
+    #[tokio::test]
+    async fn add_purchase_with_terms_owes_the_total_in_installments() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+        let today = Utc::now().date_naive();
+        let due_dates = payable::parse_terms("30/60/90", today).unwrap();
+
+        service
+            .add_purchase(
+                vec![Product {
+                    quantity: units(5),
+                    ..rice.clone()
+                }],
+                Some(PaymentTerms {
+                    supplier: "distribuidora sul ".to_string(),
+                    document: Some("1234".to_string()),
+                    due_dates: due_dates.clone(),
+                }),
+            )
+            .await
+            .unwrap();
+
+        let payables = PayableRepository::list_open(&pool, None).await.unwrap();
+        let amounts: Vec<i64> = payables.iter().map(|p| p.amount.cents()).collect();
+        assert_eq!(amounts, vec![3334, 3333, 3333]);
+        let dates: Vec<NaiveDate> = payables.iter().map(|p| p.due_date).collect();
+        assert_eq!(dates, due_dates);
+        assert_eq!(payables[2].supplier, "DISTRIBUIDORA SUL");
+        assert_eq!(payables[2].installment, 3);
+        assert_eq!(payables[2].installments, 3);
+    }
+
+    #[tokio::test]
+    async fn add_purchase_with_terms_requires_the_supplier() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(5)).await;
+        let service = SqliteProductPurchaseService::new(pool.clone());
+
+        let error = service
+            .add_purchase(
+                vec![Product {
+                    quantity: units(1),
+                    ..rice.clone()
+                }],
+                Some(PaymentTerms {
+                    supplier: " ".to_string(),
+                    document: None,
+                    due_dates: vec![Utc::now().date_naive()],
+                }),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Informe o fornecedor da compra a prazo");
+        assert_eq!(stock(&pool, rice.id).await, units(5));
+    }
+
+    #[tokio::test]
+    async fn suggest_order_covers_the_sales_pace_and_the_minimum_stock() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(40)).await;
+        let salt = insert_product(&pool, "SAL", Unit::Un, units(10), 200, 300).await;
+        let soap = insert_product(&pool, "SABÃO", Unit::Un, units(1), 100, 200).await;
+        for product in [
+            Product {
+                min_stock: units(5),
+                supplier: Some("ATACADO NORTE".to_string()),
+                ..rice.clone()
+            },
+            Product {
+                min_stock: units(3),
+                supplier: Some("LIMPEZA SUL".to_string()),
+                ..soap.clone()
+            },
+        ] {
+            ProductRepository::update(&pool, &product).await.unwrap();
+        }
+        testing::sale_service(&pool)
+            .add_sale(
+                vec![testing::sold(&rice, units(30))],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        let service = SqliteProductPurchaseService::new(pool.clone());
+
+        // 1 a day for 15 days plus 5 minimum, with 10 in stock
+        let suggestions = service.suggest_order(30, 15, None).await.unwrap();
+        let quantities: Vec<(i64, i64)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.product.id, suggestion.quantity))
+            .collect();
+        assert_eq!(quantities, vec![(rice.id, units(10)), (soap.id, units(2))]);
+        assert_eq!(suggestions[0].daily_average, units(1));
+        assert!(!quantities.iter().any(|(id, _)| *id == salt.id));
+
+        let from_supplier = service
+            .suggest_order(30, 15, Some("atacado norte".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(from_supplier.len(), 1);
+        assert_eq!(from_supplier[0].product.id, rice.id);
+
+        // Buying keeps the minimum stock and records the new supplier
+        service
+            .add_purchase(
+                vec![Product {
+                    quantity: units(10),
+                    supplier: Some("atacado leste".to_string()),
+                    ..Product::new(
+                        rice.id,
+                        rice.name.clone(),
+                        rice.price_sale,
+                        rice.price_purchase,
+                        0,
+                        Unit::Un,
+                        rice.ean.clone(),
+                        Utc::now().naive_local(),
+                    )
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+        let rice = ProductRepository::find_by_id(&pool, rice.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rice.min_stock, units(5));
+        assert_eq!(rice.supplier.as_deref(), Some("ATACADO LESTE"));
     }
 }
@@ -1,59 +1,1200 @@
+use crate::config::{CreditConfig, LoyaltyConfig};
+use crate::entities::loyalty_entry::{LoyaltyEntry, LoyaltyKind};
+use crate::entities::payment_method::PaymentMethod;
 use crate::entities::product::Product;
-use crate::entities::product_sale::ProductSale;
+use crate::entities::product_sale::{ProductSale, ProductSaleLine, SaleItem, SoldItem};
+use crate::entities::product_sale_return::ProductSaleReturn;
+use crate::entities::receivable_entry::{ReceivableEntry, ReceivableKind};
 use crate::entities::sale::Sale;
+use crate::entities::unit::Unit;
+use crate::helpers::money::Money;
+use crate::helpers::{local_day_start, QUANTITY_SCALE};
+use crate::helpers::{loyalty, receivable, to_local};
+use crate::repositories::customer_repository::CustomerRepository;
+use crate::repositories::kit_component_repository::KitComponentRepository;
+use crate::repositories::loyalty_repository::LoyaltyRepository;
 use crate::repositories::product_repository::ProductRepository;
 use crate::repositories::product_sale_repository::ProductSaleRepository;
+use crate::repositories::product_sale_return_repository::ProductSaleReturnRepository;
+use crate::repositories::receivable_repository::ReceivableRepository;
 use crate::repositories::sale_repository::SaleRepository;
-use anyhow::{anyhow, Result};
-use chrono::Utc;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate, NaiveDateTime, Utc};
 use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// Sales of one day, with the sold quantity and value of each product.
+#[derive(Debug, Clone)]
+pub struct DailyReport {
+    pub date: NaiveDate,
+    pub sales: usize,
+    pub total: Money,
+    pub products: Vec<ProductSummary>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductSummary {
+    pub product_id: i64,
+    pub name: String,
+    pub unit: Unit,
+    pub quantity: i64,
+    pub total: Money,
+}
+
+/// A past sale with its lines and loyalty points, for the sales history.
+#[derive(Debug, Clone)]
+pub struct SaleDetail {
+    pub sale: Sale,
+    pub items: Vec<SoldItem>,
+    pub loyalty: Vec<LoyaltyEntry>,
+}
+
+impl SaleDetail {
+    /// Value of the lines the customer still holds.
+    pub fn net_total(&self) -> Money {
+        if self.sale.is_voided() {
+            return Money::ZERO;
+        }
+        self.items
+            .iter()
+            .map(|item| item.value_of(item.remaining()).unwrap_or(item.total))
+            .fold(Money::ZERO, Money::saturating_add)
+    }
+
+    /// Points of the sale of one kind, as a positive number.
+    pub fn points(&self, kind: LoyaltyKind) -> i64 {
+        points_of(&self.loyalty, kind).unwrap_or(i64::MAX)
+    }
+}
+
+/// `None` when the points overflow.
+fn points_of(entries: &[LoyaltyEntry], kind: LoyaltyKind) -> Option<i64> {
+    entries
+        .iter()
+        .filter(|entry| entry.kind == kind)
+        .try_fold(0_i64, |total, entry| {
+            total.checked_add(entry.points.checked_abs()?)
+        })
+}
 
 #[derive(Debug)]
-pub struct ProductSaleService {
+pub struct SqliteProductSaleService {
     pool: SqlitePool,
+    loyalty: LoyaltyConfig,
+    credit: CreditConfig,
+}
+
+/// Registers sales and reports on them.
+#[async_trait]
+pub trait ProductSaleService: Debug + Send + Sync {
+    /// Registers the sale of registered products, optionally to a customer,
+    /// taking the sold quantities from stock, and returns its id. The customer earns loyalty
+    /// points and may pay part of the sale with `points` of their balance.
+    /// Store credit sales are charged to the customer's account, within
+    /// their credit limit and only when nothing is overdue.
+    async fn add_sale(
+        &self,
+        items: Vec<SaleItem>,
+        customer_id: Option<i64>,
+        points: i64,
+        payment_method: PaymentMethod,
+    ) -> Result<i64>;
+
+    /// Sold lines from the start of `from` to the end of `to`, in local days.
+    async fn list_lines(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<ProductSaleLine>>;
+
+    /// Totals of the sales made on `date`, with products ordered by value.
+    async fn daily_report(&self, date: NaiveDate) -> Result<DailyReport>;
+
+    /// Sales from the start of `from` to the end of `to`, newest first.
+    async fn list_sales(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Sale>>;
+
+    /// Sales of the period with a product whose name contains `product` or
+    /// whose EAN is `product`.
+    async fn search_sales_by_product(
+        &self,
+        product: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Sale>>;
+
+    async fn find_sale(&self, id: i64) -> Result<Option<SaleDetail>>;
+
+    /// Registers the return of `(line id, quantity)` pairs of the sale, with
+    /// the pairs of one line added up, and puts the quantities back in
+    /// stock. The loyalty points of the returned part are reversed, the
+    /// points used on it given back and, on store credit, its value credited
+    /// to the customer's account.
+    async fn return_items(&self, sale_id: i64, items: Vec<(i64, i64)>) -> Result<()>;
+
+    /// Voids the sale, putting back in stock whatever was not returned yet and
+    /// undoing its loyalty points and store credit charge.
+    async fn void_sale(&self, id: i64) -> Result<()>;
+}
+
+impl SqliteProductSaleService {
+    pub fn new(pool: SqlitePool, loyalty: LoyaltyConfig, credit: CreditConfig) -> Self {
+        Self {
+            pool,
+            loyalty,
+            credit,
+        }
+    }
+
+    /// Loads a sale that can still be changed.
+    async fn open_sale(tx: &mut sqlx::SqliteConnection, id: i64) -> Result<Sale> {
+        let sale = SaleRepository::find_by_id(&mut *tx, id)
+            .await?
+            .ok_or_else(|| anyhow!("Venda {} não encontrada", id))?;
+        if sale.is_voided() {
+            bail!("Venda {} já foi cancelada", id);
+        }
+        Ok(sale)
+    }
+
+    /// Takes the components of `quantity` kits from stock. The kit's own
+    /// stock follows them.
+    async fn take_components(
+        tx: &mut sqlx::SqliteConnection,
+        kit: &Product,
+        quantity: i64,
+    ) -> Result<()> {
+        if quantity % QUANTITY_SCALE != 0 {
+            bail!("O kit {} é vendido por unidade", kit.name);
+        }
+        let components = KitComponentRepository::list_by_kit(&mut *tx, kit.id).await?;
+        if components.is_empty() {
+            bail!("O kit {} não tem componentes", kit.name);
+        }
+        for component in components {
+            let taken = component
+                .quantity_for(quantity)
+                .ok_or_else(|| anyhow!("Quantidade de {} excede o limite permitido", kit.name))?;
+            if component.stock < taken {
+                bail!(
+                    "Estoque insuficiente de {} para o kit {}",
+                    component.name,
+                    kit.name
+                );
+            }
+            ProductRepository::add_quantity(&mut *tx, component.component_id, -taken).await?;
+        }
+        Ok(())
+    }
+
+    /// Puts `quantity` of a sold product back in stock. A kit puts back its
+    /// current components.
+    async fn restock(
+        tx: &mut sqlx::SqliteConnection,
+        product_id: i64,
+        quantity: i64,
+    ) -> Result<()> {
+        let components = KitComponentRepository::list_by_kit(&mut *tx, product_id).await?;
+        if components.is_empty() {
+            return ProductRepository::add_quantity(&mut *tx, product_id, quantity).await;
+        }
+        for component in components {
+            let restocked = component.quantity_for(quantity).ok_or_else(|| {
+                anyhow!("Quantidade de {} excede o limite permitido", component.name)
+            })?;
+            ProductRepository::add_quantity(&mut *tx, component.component_id, restocked).await?;
+        }
+        Ok(())
+    }
+
+    /// Brings the points reversed and given back on a sale up to the share
+    /// of `returned` in its total.
+    async fn adjust_loyalty(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        sale: &Sale,
+        returned: Money,
+        now: NaiveDateTime,
+    ) -> Result<()> {
+        let Some(customer_id) = sale.customer_id else {
+            return Ok(());
+        };
+        let entries = LoyaltyRepository::list_by_sale(&mut *tx, sale.id).await?;
+        let total = |kind| {
+            points_of(&entries, kind)
+                .ok_or_else(|| anyhow!("Pontos da venda {} excedem o limite permitido", sale.id))
+        };
+        let reversal = loyalty::share(total(LoyaltyKind::Earn)?, returned, sale.total)
+            - total(LoyaltyKind::Reversal)?;
+        let refund = loyalty::share(total(LoyaltyKind::Redeem)?, returned, sale.total)
+            - total(LoyaltyKind::Refund)?;
+        if reversal > 0 {
+            LoyaltyRepository::insert(
+                &mut *tx,
+                &LoyaltyEntry::new(
+                    customer_id,
+                    Some(sale.id),
+                    LoyaltyKind::Reversal,
+                    -reversal,
+                    None,
+                    now,
+                ),
+            )
+            .await?;
+        }
+        if refund > 0 {
+            LoyaltyRepository::insert(
+                &mut *tx,
+                &LoyaltyEntry::new(
+                    customer_id,
+                    Some(sale.id),
+                    LoyaltyKind::Refund,
+                    refund,
+                    loyalty::expires_at(&self.loyalty, now),
+                    now,
+                ),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Brings the credits of a store credit sale up to the share of
+    /// `returned` in its total, taken from what was charged.
+    async fn adjust_receivable(
+        tx: &mut sqlx::SqliteConnection,
+        sale: &Sale,
+        returned: Money,
+        now: NaiveDateTime,
+    ) -> Result<()> {
+        let (Some(customer_id), PaymentMethod::StoreCredit) =
+            (sale.customer_id, sale.payment_method)
+        else {
+            return Ok(());
+        };
+        let entries = ReceivableRepository::list_by_sale(&mut *tx, sale.id).await?;
+        let total = |kind| {
+            entries
+                .iter()
+                .filter(|entry| entry.kind == kind)
+                .fold(Money::ZERO, |total, entry| {
+                    total.saturating_add(entry.amount)
+                })
+        };
+        let charged = total(ReceivableKind::Charge);
+        let credit = Money::from_cents(loyalty::share(charged.cents(), returned, sale.total))
+            + total(ReceivableKind::Credit);
+        if credit > Money::ZERO {
+            ReceivableRepository::insert(
+                &mut *tx,
+                &ReceivableEntry::new(
+                    customer_id,
+                    Some(sale.id),
+                    ReceivableKind::Credit,
+                    -credit,
+                    None,
+                    now,
+                ),
+            )
+            .await?;
+        }
+        Ok(())
+    }
 }
 
-impl ProductSaleService {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+/// Local days `[from, to]` as the UTC bounds stored in the database.
+fn period(from: NaiveDate, to: NaiveDate) -> Result<(NaiveDateTime, NaiveDateTime)> {
+    if to < from {
+        bail!("A data final deve ser igual ou posterior à data inicial");
     }
+    let end = to
+        .checked_add_days(Days::new(1))
+        .ok_or_else(|| anyhow!("Data {} inválida", to))?;
+    Ok((local_day_start(from), local_day_start(end)))
+}
 
-    pub async fn add_sale(&self, products: Vec<Product>) -> Result<()> {
+#[async_trait]
+impl ProductSaleService for SqliteProductSaleService {
+    async fn add_sale(
+        &self,
+        items: Vec<SaleItem>,
+        customer_id: Option<i64>,
+        points: i64,
+        payment_method: PaymentMethod,
+    ) -> Result<i64> {
         let mut tx = self.pool.begin().await?;
+        let customer = match customer_id {
+            Some(id) => Some(
+                CustomerRepository::find_by_id(&mut *tx, id)
+                    .await?
+                    .ok_or_else(|| anyhow!("Cliente {} não encontrado", id))?,
+            ),
+            None => None,
+        };
+        if points < 0 {
+            bail!("Quantidade de pontos inválida");
+        }
+        for SaleItem { product, .. } in &items {
+            if product.id == 0 {
+                bail!("{} não está cadastrado", product.name);
+            }
+            if product.quantity <= 0 {
+                bail!("Quantidade de {} inválida", product.name);
+            }
+        }
         let now = Utc::now().naive_local();
-        let total = products.iter().map(|p| p.price_sale * p.quantity).sum();
-        let sale_id = SaleRepository::insert(&mut *tx, &Sale::new(0, total, now)).await?;
-
-        for product in products.into_iter().as_mut_slice() {
-            let product_id = if product.id == 0 {
-                ProductRepository::insert(&mut *tx, product).await?
-            } else if let Some(existing_product) =
-                ProductRepository::find_by_id(&mut *tx, product.id).await?
-            {
-                product.quantity = existing_product.quantity - product.quantity;
-                if product.quantity < 0 {
-                    return Err(anyhow!("Insufficient stock for product ID {}", product.id));
+        let line_totals = items
+            .iter()
+            .map(|item| {
+                item.total().ok_or_else(|| {
+                    anyhow!("Valor de {} excede o limite permitido", item.product.name)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let total = Money::checked_sum(line_totals.iter().copied())
+            .ok_or_else(|| anyhow!("Valor total da venda excede o limite permitido"))?;
+        let loyalty_discount = match customer_id {
+            Some(id) if points > 0 => {
+                if !self.loyalty.enabled {
+                    bail!("O programa de pontos está desativado");
+                }
+                let entries = LoyaltyRepository::list_by_customer(&mut *tx, id).await?;
+                let balance = loyalty::balance(&entries, now);
+                if points > balance {
+                    bail!(
+                        "Saldo insuficiente: o cliente tem {} pontos",
+                        balance.max(0)
+                    );
+                }
+                if points > loyalty::points_for(&self.loyalty, total) {
+                    bail!("Os pontos usados passam do valor da venda");
                 }
-                ProductRepository::update(&mut *tx, product).await?;
-                product.id
+                loyalty::value(&self.loyalty, points)
+            }
+            None if points > 0 => bail!("Informe o cliente para usar pontos"),
+            _ => Money::ZERO,
+        };
+        let sale = Sale {
+            customer_id,
+            loyalty_discount,
+            payment_method,
+            ..Sale::new(0, total, now)
+        };
+        let due_date = match (&customer, payment_method) {
+            (Some(customer), PaymentMethod::StoreCredit) => {
+                let entries = ReceivableRepository::list_by_customer(&mut *tx, customer.id).await?;
+                let today = to_local(now).date();
+                let overdue = receivable::overdue(&entries, today);
+                if overdue > Money::ZERO {
+                    bail!("{} tem {} em atraso no fiado", customer.name, overdue);
+                }
+                let available = customer
+                    .credit_limit
+                    .checked_sub(receivable::balance(&entries))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Saldo do fiado de {} excede o limite permitido",
+                            customer.name
+                        )
+                    })?;
+                if sale.amount_due() > available {
+                    bail!(
+                        "Limite do fiado insuficiente: {} disponível para {}",
+                        available.max(Money::ZERO),
+                        customer.name
+                    );
+                }
+                Some(
+                    receivable::due_date(&self.credit, today)
+                        .ok_or_else(|| anyhow!("Data de vencimento inválida"))?,
+                )
+            }
+            (None, PaymentMethod::StoreCredit) => bail!("Informe o cliente para vender fiado"),
+            _ => None,
+        };
+        let sale_id = SaleRepository::insert(&mut *tx, &sale).await?;
+        if let (Some(customer_id), Some(due_date)) = (customer_id, due_date)
+            && sale.amount_due() > Money::ZERO
+        {
+            ReceivableRepository::insert(
+                &mut *tx,
+                &ReceivableEntry::new(
+                    customer_id,
+                    Some(sale_id),
+                    ReceivableKind::Charge,
+                    sale.amount_due(),
+                    Some(due_date),
+                    now,
+                ),
+            )
+            .await?;
+        }
+
+        let mut categories = Vec::with_capacity(items.len());
+        for (product, line_total) in items
+            .iter()
+            .map(|item| &item.product)
+            .zip(line_totals.iter().copied())
+        {
+            let existing_product = ProductRepository::find_by_id(&mut *tx, product.id)
+                .await?
+                .ok_or_else(|| anyhow!("Produto {} não encontrado", product.id))?;
+            categories.push(existing_product.category.clone());
+            if existing_product.is_kit {
+                Self::take_components(&mut tx, &existing_product, product.quantity).await?;
             } else {
-                return Err(anyhow!("Product with ID {} not found", product.id));
-            };
+                if existing_product.quantity < product.quantity {
+                    bail!("Estoque insuficiente para {}", existing_product.name);
+                }
+                ProductRepository::add_quantity(&mut *tx, product.id, -product.quantity).await?;
+            }
             ProductSaleRepository::insert(
                 &mut *tx,
                 &ProductSale::new(
-                    0,
-                    product_id,
+                    product.id,
                     sale_id,
                     product.price_sale,
                     product.quantity,
-                    product.quantity * product.price_sale,
+                    line_total,
                     now,
                 ),
             )
             .await?;
         }
+
+        if let Some(customer_id) = customer_id {
+            if points > 0 {
+                LoyaltyRepository::insert(
+                    &mut *tx,
+                    &LoyaltyEntry::new(
+                        customer_id,
+                        Some(sale_id),
+                        LoyaltyKind::Redeem,
+                        -points,
+                        None,
+                        now,
+                    ),
+                )
+                .await?;
+            }
+            let earned = loyalty::points_earned(
+                &self.loyalty,
+                sale.amount_due(),
+                categories.iter().map(Option::as_deref).zip(line_totals),
+            );
+            if earned > 0 {
+                LoyaltyRepository::insert(
+                    &mut *tx,
+                    &LoyaltyEntry::new(
+                        customer_id,
+                        Some(sale_id),
+                        LoyaltyKind::Earn,
+                        earned,
+                        loyalty::expires_at(&self.loyalty, now),
+                        now,
+                    ),
+                )
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(sale_id)
+    }
+
+    async fn list_lines(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<ProductSaleLine>> {
+        let (start, end) = period(from, to)?;
+        ProductSaleRepository::list_between(&self.pool, start, end).await
+    }
+
+    async fn daily_report(&self, date: NaiveDate) -> Result<DailyReport> {
+        let lines = self.list_lines(date, date).await?;
+        let mut sales: Vec<i64> = lines.iter().map(|line| line.sale_id).collect();
+        sales.sort_unstable();
+        sales.dedup();
+        let total = Money::checked_sum(lines.iter().map(|line| line.total))
+            .ok_or_else(|| anyhow!("Valor total do dia excede o limite permitido"))?;
+
+        let mut products: Vec<ProductSummary> = vec![];
+        for line in lines {
+            match products
+                .iter_mut()
+                .find(|p| p.product_id == line.product_id)
+            {
+                Some(summary) => {
+                    summary.quantity += line.quantity;
+                    summary.total = summary.total.saturating_add(line.total);
+                }
+                None => products.push(ProductSummary {
+                    product_id: line.product_id,
+                    name: line.name,
+                    unit: line.unit,
+                    quantity: line.quantity,
+                    total: line.total,
+                }),
+            }
+        }
+        products.sort_by_key(|p| Reverse(p.total));
+
+        Ok(DailyReport {
+            date,
+            sales: sales.len(),
+            total,
+            products,
+        })
+    }
+
+    async fn list_sales(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Sale>> {
+        let (start, end) = period(from, to)?;
+        SaleRepository::list_between(&self.pool, start, end).await
+    }
+
+    async fn search_sales_by_product(
+        &self,
+        product: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Sale>> {
+        let (start, end) = period(from, to)?;
+        SaleRepository::list_with_product(&self.pool, product, start, end).await
+    }
+
+    async fn find_sale(&self, id: i64) -> Result<Option<SaleDetail>> {
+        let Some(sale) = SaleRepository::find_by_id(&self.pool, id).await? else {
+            return Ok(None);
+        };
+        let items = ProductSaleRepository::list_by_sale(&self.pool, id).await?;
+        let loyalty = LoyaltyRepository::list_by_sale(&self.pool, id).await?;
+        Ok(Some(SaleDetail {
+            sale,
+            items,
+            loyalty,
+        }))
+    }
+
+    async fn return_items(&self, sale_id: i64, items: Vec<(i64, i64)>) -> Result<()> {
+        if items.iter().all(|(_, quantity)| *quantity == 0) {
+            bail!("Informe a quantidade a devolver");
+        }
+        let mut tx = self.pool.begin().await?;
+        let sale = Self::open_sale(&mut tx, sale_id).await?;
+        let sold = ProductSaleRepository::list_by_sale(&mut *tx, sale_id).await?;
+        let now = Utc::now().naive_local();
+        let mut returned: BTreeMap<i64, i64> = BTreeMap::new();
+        for (item_id, quantity) in items {
+            let total = returned.entry(item_id).or_default();
+            *total = total
+                .checked_add(quantity)
+                .ok_or_else(|| anyhow!("Quantidade a devolver excede o limite permitido"))?;
+        }
+        for (item_id, quantity) in returned {
+            if quantity == 0 {
+                continue;
+            }
+            let item = sold
+                .iter()
+                .find(|item| item.id == item_id)
+                .ok_or_else(|| anyhow!("Item {} não pertence à venda {}", item_id, sale_id))?;
+            if quantity < 0 || quantity > item.remaining() {
+                bail!("Quantidade a devolver de {} inválida", item.name);
+            }
+            let total = item
+                .value_of(item.remaining())
+                .zip(item.value_of(item.remaining() - quantity))
+                .and_then(|(before, after)| before.checked_sub(after))
+                .ok_or_else(|| anyhow!("Valor de {} excede o limite permitido", item.name))?;
+            ProductSaleReturnRepository::insert(
+                &mut *tx,
+                &ProductSaleReturn::new(item.id, quantity, total, now),
+            )
+            .await?;
+            Self::restock(&mut tx, item.product_id, quantity).await?;
+        }
+        let detail = SaleDetail {
+            items: ProductSaleRepository::list_by_sale(&mut *tx, sale_id).await?,
+            sale,
+            loyalty: vec![],
+        };
+        let returned = detail.sale.total - detail.net_total();
+        self.adjust_loyalty(&mut tx, &detail.sale, returned, now)
+            .await?;
+        Self::adjust_receivable(&mut tx, &detail.sale, returned, now).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn void_sale(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let sale = Self::open_sale(&mut tx, id).await?;
+        let now = Utc::now().naive_local();
+        for item in ProductSaleRepository::list_by_sale(&mut *tx, id).await? {
+            if item.remaining() > 0 {
+                Self::restock(&mut tx, item.product_id, item.remaining()).await?;
+            }
+        }
+        self.adjust_loyalty(&mut tx, &sale, sale.total, now).await?;
+        Self::adjust_receivable(&mut tx, &sale, sale.total, now).await?;
+        SaleRepository::set_voided(&mut *tx, id, now).await?;
         tx.commit().await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, insert_product, sold, stock, units};
+    use crate::entities::customer::Customer;
+    use chrono::Local;
+
+    #[tokio::test]
+    async fn add_sale_takes_sold_quantities_from_stock() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 2500, 3000, 4990).await;
+        let service = testing::sale_service(&pool);
+
+        service
+            .add_sale(
+                vec![sold(&rice, units(3)), sold(&cheese, 750)],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, rice.id).await, units(7));
+        assert_eq!(stock(&pool, cheese.id).await, 1750);
+
+        let today = Local::now().date_naive();
+        let report = service.daily_report(today).await.unwrap();
+        assert_eq!(report.sales, 1);
+        // 3 x 25,90 + 0,750 x 49,90 = 77,70 + 37,43 (rounded half up)
+        assert_eq!(report.total, Money::from_cents(7770 + 3743));
+        let quantities: Vec<(i64, i64)> = report
+            .products
+            .iter()
+            .map(|p| (p.product_id, p.quantity))
+            .collect();
+        assert_eq!(quantities, vec![(rice.id, units(3)), (cheese.id, 750)]);
+    }
+
+    #[tokio::test]
+    async fn add_sale_charges_the_total_of_a_price_label() {
+        let pool = testing::pool().await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 2000, 3000, 3999).await;
+        let service = testing::sale_service(&pool);
+
+        // 0,309 kg at 39,99 is 12,36, but the label says 12,34
+        let id = service
+            .add_sale(
+                vec![SaleItem {
+                    total: Some(Money::from_cents(1234)),
+                    ..sold(&cheese, 309)
+                }],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(detail.sale.total, Money::from_cents(1234));
+        assert_eq!(detail.items[0].total, Money::from_cents(1234));
+        assert_eq!(detail.items[0].quantity, 309);
+        assert_eq!(stock(&pool, cheese.id).await, 2000 - 309);
+    }
+
+    #[tokio::test]
+    async fn add_sale_can_sell_the_whole_stock() {
+        let pool = testing::pool().await;
+        let beans = insert_product(&pool, "FEIJÃO", Unit::Un, units(2), 500, 850).await;
+        let service = testing::sale_service(&pool);
+
+        service
+            .add_sale(vec![sold(&beans, units(2))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, beans.id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn add_sale_keeps_product_changes_made_during_the_sale() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let in_cart = sold(&rice, units(2));
+        ProductRepository::update(
+            &pool,
+            &Product {
+                name: "ARROZ TIPO 1".to_string(),
+                price_sale: Money::from_cents(2790),
+                price_purchase: Money::from_cents(2150),
+                ..rice.clone()
+            },
+        )
+        .await
+        .unwrap();
+
+        service
+            .add_sale(vec![in_cart], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+
+        let product = ProductRepository::find_by_id(&pool, rice.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(product.name, "ARROZ TIPO 1");
+        assert_eq!(product.price_sale, Money::from_cents(2790));
+        assert_eq!(product.price_purchase, Money::from_cents(2150));
+        assert_eq!(product.quantity, units(8));
+    }
+
+    #[tokio::test]
+    async fn add_sale_with_insufficient_stock_changes_nothing() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let beans = insert_product(&pool, "FEIJÃO", Unit::Un, units(1), 500, 850).await;
+        let service = testing::sale_service(&pool);
+
+        let error = service
+            .add_sale(
+                vec![sold(&rice, units(2)), sold(&beans, units(5))],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Estoque insuficiente para FEIJÃO");
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+        assert_eq!(stock(&pool, beans.id).await, units(1));
+        let today = Local::now().date_naive();
+        assert_eq!(service.daily_report(today).await.unwrap().sales, 0);
+    }
+
+    #[tokio::test]
+    async fn add_sale_rejects_unknown_products() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let unknown = sold(
+            &Product {
+                id: rice.id + 100,
+                ..rice.clone()
+            },
+            units(1),
+        );
+
+        let error = service
+            .add_sale(
+                vec![sold(&rice, units(1)), unknown],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("Produto {} não encontrado", rice.id + 100)
+        );
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+    }
+
+    #[tokio::test]
+    async fn add_sale_rejects_unregistered_products_and_quantities_below_one() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let unregistered = sold(
+            &Product {
+                id: 0,
+                name: "AVULSO".to_string(),
+                ..rice.clone()
+            },
+            units(1),
+        );
+
+        for (items, message) in [
+            (vec![unregistered], "AVULSO não está cadastrado"),
+            (vec![sold(&rice, 0)], "Quantidade de ARROZ inválida"),
+            (vec![sold(&rice, -units(2))], "Quantidade de ARROZ inválida"),
+        ] {
+            let error = service
+                .add_sale(items, None, 0, PaymentMethod::Cash)
+                .await
+                .unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+        let products = ProductRepository::list_all(&pool).await.unwrap();
+        assert_eq!(products.len(), 1);
+        let today = Local::now().date_naive();
+        assert_eq!(service.daily_report(today).await.unwrap().sales, 0);
+    }
+
+    #[tokio::test]
+    async fn find_sale_loads_lines_and_finds_by_product() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let beans = insert_product(&pool, "FEIJÃO", Unit::Un, units(10), 500, 850).await;
+        let service = testing::sale_service(&pool);
+        let first = service
+            .add_sale(vec![sold(&rice, units(1))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let second = service
+            .add_sale(
+                vec![sold(&rice, units(2)), sold(&beans, units(1))],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+
+        let detail = service.find_sale(second).await.unwrap().unwrap();
+        assert_eq!(detail.sale.total, Money::from_cents(2 * 2590 + 850));
+        let names: Vec<&str> = detail.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["ARROZ", "FEIJÃO"]);
+        assert!(service.find_sale(second + 100).await.unwrap().is_none());
+
+        let today = Local::now().date_naive();
+        let all: Vec<i64> = service
+            .list_sales(today, today)
+            .await
+            .unwrap()
+            .iter()
+            .map(|sale| sale.id)
+            .collect();
+        assert_eq!(all, vec![second, first]);
+        let with_beans: Vec<i64> = service
+            .search_sales_by_product("FEIJ", today, today)
+            .await
+            .unwrap()
+            .iter()
+            .map(|sale| sale.id)
+            .collect();
+        assert_eq!(with_beans, vec![second]);
+    }
+
+    #[tokio::test]
+    async fn return_items_puts_stock_back_and_leaves_the_report() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let id = service
+            .add_sale(vec![sold(&rice, units(3))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let item = service.find_sale(id).await.unwrap().unwrap().items[0].id;
+
+        service
+            .return_items(id, vec![(item, units(2))])
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, rice.id).await, units(9));
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(detail.items[0].remaining(), units(1));
+        assert_eq!(detail.net_total(), Money::from_cents(2590));
+        let today = Local::now().date_naive();
+        assert_eq!(
+            service.daily_report(today).await.unwrap().total,
+            Money::from_cents(2590)
+        );
+
+        let error = service
+            .return_items(id, vec![(item, units(2))])
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Quantidade a devolver de ARROZ inválida");
+        assert_eq!(stock(&pool, rice.id).await, units(9));
+    }
+
+    #[tokio::test]
+    async fn return_items_adds_up_repeated_lines_before_checking_them() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let id = service
+            .add_sale(vec![sold(&rice, units(5))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let item = service.find_sale(id).await.unwrap().unwrap().items[0].id;
+
+        let error = service
+            .return_items(id, vec![(item, units(3)), (item, units(3))])
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Quantidade a devolver de ARROZ inválida");
+        assert_eq!(stock(&pool, rice.id).await, units(5));
+
+        service
+            .return_items(id, vec![(item, units(2)), (item, units(3))])
+            .await
+            .unwrap();
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(detail.items[0].remaining(), 0);
+        assert_eq!(detail.net_total(), Money::ZERO);
+    }
+
+    #[tokio::test]
+    async fn returns_of_a_label_line_refund_what_was_charged() {
+        let pool = testing::pool().await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 2000, 3000, 3999).await;
+        let service = testing::sale_service(&pool);
+        // 0,300 kg at 39,99 is 12,00, but the label says 10,00
+        let id = service
+            .add_sale(
+                vec![SaleItem {
+                    total: Some(Money::from_cents(1000)),
+                    ..sold(&cheese, 300)
+                }],
+                None,
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        let item = service.find_sale(id).await.unwrap().unwrap().items[0].id;
+        let refunded = || async {
+            sqlx::query_scalar::<_, i64>("SELECT SUM(total) FROM tb_product_sale_return")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        service.return_items(id, vec![(item, 100)]).await.unwrap();
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(refunded().await, 333);
+        assert_eq!(detail.net_total(), Money::from_cents(667));
+
+        service.return_items(id, vec![(item, 200)]).await.unwrap();
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(refunded().await, 1000);
+        assert_eq!(detail.net_total(), Money::ZERO);
+    }
+
+    #[tokio::test]
+    async fn void_sale_puts_back_what_was_not_returned() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service = testing::sale_service(&pool);
+        let id = service
+            .add_sale(vec![sold(&rice, units(3))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+        let item = service.find_sale(id).await.unwrap().unwrap().items[0].id;
+        service
+            .return_items(id, vec![(item, units(1))])
+            .await
+            .unwrap();
+
+        service.void_sale(id).await.unwrap();
+
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+        assert!(service
+            .find_sale(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .sale
+            .is_voided());
+        let today = Local::now().date_naive();
+        assert_eq!(service.daily_report(today).await.unwrap().sales, 0);
+        let error = service.void_sale(id).await.unwrap_err();
+        assert_eq!(error.to_string(), format!("Venda {} já foi cancelada", id));
+        assert_eq!(stock(&pool, rice.id).await, units(10));
+    }
+
+    #[tokio::test]
+    async fn kits_take_their_components_from_stock_and_put_them_back() {
+        let pool = testing::pool().await;
+        let coffee = insert_product(&pool, "CAFÉ", Unit::Un, units(10), 1200, 1890).await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 2500, 3000, 4990).await;
+        let basket = insert_product(&pool, "CESTA", Unit::Un, 0, 0, 9990).await;
+        ProductRepository::set_kit(&pool, basket.id, true)
+            .await
+            .unwrap();
+        KitComponentRepository::insert(&pool, basket.id, coffee.id, units(2))
+            .await
+            .unwrap();
+        KitComponentRepository::insert(&pool, basket.id, cheese.id, 500)
+            .await
+            .unwrap();
+        let service = testing::sale_service(&pool);
+
+        let id = service
+            .add_sale(vec![sold(&basket, units(2))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap();
+
+        assert_eq!(stock(&pool, coffee.id).await, units(6));
+        assert_eq!(stock(&pool, cheese.id).await, 1500);
+        assert_eq!(stock(&pool, basket.id).await, units(3));
+        let items = service.find_sale(id).await.unwrap().unwrap().items;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "CESTA");
+
+        let error = service
+            .add_sale(vec![sold(&basket, units(4))], None, 0, PaymentMethod::Cash)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Estoque insuficiente de CAFÉ para o kit CESTA"
+        );
+        assert_eq!(stock(&pool, coffee.id).await, units(6));
+
+        service
+            .return_items(id, vec![(items[0].id, units(1))])
+            .await
+            .unwrap();
+        service.void_sale(id).await.unwrap();
+        assert_eq!(stock(&pool, coffee.id).await, units(10));
+        assert_eq!(stock(&pool, cheese.id).await, 2500);
+        assert_eq!(stock(&pool, basket.id).await, units(5));
+    }
+
+    fn loyalty() -> LoyaltyConfig {
+        LoyaltyConfig {
+            enabled: true,
+            points_per_real: 1,
+            category_bonus: BTreeMap::from([("Bebidas".to_string(), 2)]),
+            expiration_months: 12,
+            point_value: 1,
+        }
+    }
+
+    async fn customer(pool: &SqlitePool) -> i64 {
+        let customer = Customer::new(
+            0,
+            "MARIA SOUZA".to_string(),
+            None,
+            None,
+            None,
+            Utc::now().naive_local(),
+        );
+        CustomerRepository::insert(pool, &customer).await.unwrap()
+    }
+
+    async fn points(pool: &SqlitePool, customer_id: i64) -> i64 {
+        let entries = LoyaltyRepository::list_by_customer(pool, customer_id)
+            .await
+            .unwrap();
+        loyalty::balance(&entries, Utc::now().naive_local())
+    }
+
+    #[tokio::test]
+    async fn add_sale_earns_points_with_category_bonus_and_redeems_them() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let mut soda = insert_product(&pool, "REFRIGERANTE", Unit::Un, units(10), 500, 800).await;
+        soda.category = Some("BEBIDAS".to_string());
+        ProductRepository::update(&pool, &soda).await.unwrap();
+        let service =
+            SqliteProductSaleService::new(pool.clone(), loyalty(), CreditConfig::default());
+        let maria = customer(&pool).await;
+
+        // 59,80 paid gives 59 points, plus 2 per real on the 8,00 of soda
+        service
+            .add_sale(
+                vec![sold(&rice, units(2)), sold(&soda, units(1))],
+                Some(maria),
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        assert_eq!(points(&pool, maria).await, 75);
+
+        // 50 points pay 0,50 and the remaining 25,40 gives 25 points
+        let id = service
+            .add_sale(
+                vec![sold(&rice, units(1))],
+                Some(maria),
+                50,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(detail.sale.loyalty_discount, Money::from_cents(50));
+        assert_eq!(detail.sale.amount_due(), Money::from_cents(2540));
+        assert_eq!(detail.points(LoyaltyKind::Redeem), 50);
+        assert_eq!(detail.points(LoyaltyKind::Earn), 25);
+        assert_eq!(points(&pool, maria).await, 50);
+
+        let error = service
+            .add_sale(
+                vec![sold(&rice, units(1))],
+                Some(maria),
+                51,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Saldo insuficiente: o cliente tem 50 pontos"
+        );
+        let error = service
+            .add_sale(vec![sold(&rice, units(1))], None, 10, PaymentMethod::Cash)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Informe o cliente para usar pontos");
+        assert_eq!(stock(&pool, rice.id).await, units(7));
+    }
+
+    #[tokio::test]
+    async fn returns_and_voids_undo_the_points_of_the_sale() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let service =
+            SqliteProductSaleService::new(pool.clone(), loyalty(), CreditConfig::default());
+        let maria = customer(&pool).await;
+        service
+            .add_sale(
+                vec![sold(&rice, units(4))],
+                Some(maria),
+                0,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        assert_eq!(points(&pool, maria).await, 103);
+
+        // 100 points pay 1,00 of 51,80 and the remaining 50,80 gives 50
+        let id = service
+            .add_sale(
+                vec![sold(&rice, units(2))],
+                Some(maria),
+                100,
+                PaymentMethod::Cash,
+            )
+            .await
+            .unwrap();
+        assert_eq!(points(&pool, maria).await, 53);
+
+        // Returning half the sale takes back half of each
+        let item = service.find_sale(id).await.unwrap().unwrap().items[0].id;
+        service
+            .return_items(id, vec![(item, units(1))])
+            .await
+            .unwrap();
+        assert_eq!(points(&pool, maria).await, 53 - 25 + 50);
+
+        service.void_sale(id).await.unwrap();
+        assert_eq!(points(&pool, maria).await, 103);
+        let detail = service.find_sale(id).await.unwrap().unwrap();
+        assert_eq!(detail.points(LoyaltyKind::Reversal), 50);
+        assert_eq!(detail.points(LoyaltyKind::Refund), 100);
+    }
+}
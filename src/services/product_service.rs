@@ -1,20 +1,766 @@
+use crate::config::{ScaleLabelConfig, ScaleLabelValue};
+use crate::entities::kit_component::KitComponent;
 use crate::entities::product::Product;
+use crate::entities::unit::Unit;
+use crate::helpers::barcode::{decode_scale_label, internal_ean13, is_valid_gtin};
+use crate::helpers::money::Money;
+use crate::helpers::search;
+use crate::helpers::{quantity_for_total, QUANTITY_SCALE};
+use crate::repositories::kit_component_repository::KitComponentRepository;
 use crate::repositories::product_repository::ProductRepository;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
 use sqlx::SqlitePool;
+use std::fmt::Debug;
+
+const PRODUCT_LIST_LIMIT: i64 = 200;
+const PRODUCT_SEARCH_LIMIT: usize = 10;
+/// Products read from the full-text index before ranking by closeness.
+const SEARCH_CANDIDATES: i64 = 500;
 
 #[derive(Debug)]
-pub struct ProductService {
+pub struct SqliteProductService {
     pool: SqlitePool,
+    scale_label: ScaleLabelConfig,
+}
+
+/// Product found by a scanned barcode. Scale labels also carry the quantity
+/// of the line, and price labels the total to charge for it, which may differ
+/// from price × quantity by the rounding of the weight.
+#[derive(Debug, Clone)]
+pub struct BarcodeMatch {
+    pub product: Product,
+    pub quantity: Option<i64>,
+    pub total: Option<Money>,
+}
+
+/// Product fields read from one line of an import file. Missing fields keep
+/// the current value of an existing product.
+#[derive(Debug, Clone, Default)]
+pub struct ProductRow {
+    pub id: Option<i64>,
+    pub ean: Option<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub unit: Option<Unit>,
+    pub quantity: Option<i64>,
+    pub price_purchase: Option<Money>,
+    pub price_sale: Option<Money>,
+    pub min_stock: Option<i64>,
+    pub supplier: Option<String>,
+}
+
+/// A line of an import file, or the reason it could not be read.
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    pub line: usize,
+    pub row: Result<ProductRow, String>,
 }
 
-impl ProductService {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportAction {
+    Create,
+    Update(i64),
+    Reject(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub line: usize,
+    pub name: String,
+    pub action: ImportAction,
+}
+
+/// What an import did, or would do on a dry run, with each line of the file.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub entries: Vec<ImportEntry>,
+    pub dry_run: bool,
+}
+
+impl ImportReport {
+    pub fn created(&self) -> usize {
+        self.count(|action| *action == ImportAction::Create)
     }
 
-    pub async fn search_products_by_name(&self, name: &str) -> Vec<Product> {
-        ProductRepository::search_by_name(&self.pool, name)
+    pub fn updated(&self) -> usize {
+        self.count(|action| matches!(action, ImportAction::Update(_)))
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.count(|action| matches!(action, ImportAction::Reject(_)))
+    }
+
+    fn count(&self, filter: impl Fn(&ImportAction) -> bool) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| filter(&entry.action))
+            .count()
+    }
+}
+
+/// Product lookups for the screens and the catalog import and export.
+#[async_trait]
+pub trait ProductService: Debug + Send + Sync {
+    /// Products in stock matching the words typed, by name, EAN or category,
+    /// ignoring accents and case and forgiving small typos.
+    async fn search_products_by_name(&self, name: &str) -> Result<Vec<Product>>;
+
+    async fn find_product(&self, id: i64) -> Result<Option<Product>>;
+
+    /// Catalog listing for the stock screen, including products out of stock.
+    async fn list_products(&self, name: &str) -> Result<Vec<Product>>;
+
+    /// Looks the code up as a regular EAN first and falls back to decoding it
+    /// as a scale label, which refers to the product by its internal code.
+    async fn search_by_barcode(&self, code: &str) -> Result<Option<BarcodeMatch>>;
+
+    /// All products, ordered by name, for the catalog export.
+    async fn list_catalog(&self) -> Result<Vec<Product>>;
+
+    /// Creates or updates the products in a single transaction, matching them
+    /// by id and then by EAN. Rejected lines are reported and skipped. On a
+    /// dry run the transaction is rolled back, so the report previews exactly
+    /// what the import would do.
+    async fn import_products(&self, rows: Vec<ImportRow>, dry_run: bool) -> Result<ImportReport>;
+
+    async fn kit_components(&self, kit_id: i64) -> Result<Vec<KitComponent>>;
+
+    /// Replaces the composition of the kit, given as component ids and the
+    /// quantity of each in one kit, and sets its cost to the cost of the
+    /// components. Without components the product stops being a kit.
+    async fn save_kit(&self, kit_id: i64, components: Vec<(i64, i64)>) -> Result<()>;
+}
+
+impl SqliteProductService {
+    pub fn new(pool: SqlitePool, scale_label: ScaleLabelConfig) -> Self {
+        Self { pool, scale_label }
+    }
+
+    /// Full-text search ranked by how close each product is to the typed
+    /// words. When a typo leaves no product with the words as prefixes, the
+    /// candidates come from the first letters of each word instead.
+    async fn search(&self, text: &str, in_stock: bool, limit: usize) -> Result<Vec<Product>> {
+        let terms = search::terms(text);
+        let Some(query) = search::prefix_query(&terms) else {
+            return Ok(vec![]);
+        };
+        let mut products =
+            ProductRepository::search(&self.pool, &query, in_stock, SEARCH_CANDIDATES).await?;
+        if products.is_empty() {
+            let min_length = search::typo_min_length(&terms) as i64;
+            let vocabulary = ProductRepository::search_vocabulary(&self.pool, min_length).await?;
+            if let Some(query) = search::typo_query(&terms, &vocabulary) {
+                products =
+                    ProductRepository::search(&self.pool, &query, in_stock, SEARCH_CANDIDATES)
+                        .await?;
+            }
+        }
+
+        let mut ranked: Vec<(u32, Product)> = products
+            .into_iter()
+            .filter_map(|product| {
+                search::cost(&terms, &search::terms(&search_text(&product)))
+                    .map(|cost| (cost, product))
+            })
+            .collect();
+        ranked.sort_by_key(|(cost, _)| *cost);
+        Ok(ranked
+            .into_iter()
+            .take(limit)
+            .map(|(_, product)| product)
+            .collect())
+    }
+
+    /// Applies one line. The outer error aborts the import, the inner one
+    /// rejects only this line.
+    async fn import_row(
+        tx: &mut sqlx::SqliteConnection,
+        row: ProductRow,
+        now: NaiveDateTime,
+        seen_ids: &mut Vec<(i64, usize)>,
+        seen_eans: &mut Vec<(String, usize)>,
+        line: usize,
+    ) -> Result<Result<ImportAction, String>> {
+        let ean = row.ean.clone().filter(|ean| !ean.trim().is_empty());
+        if let Some(ean) = &ean
+            && let Some((_, other)) = seen_eans.iter().find(|(seen, _)| seen == ean)
+        {
+            return Ok(Err(format!("EAN {} repetido na linha {}", ean, other)));
+        }
+
+        let existing = match row.id {
+            Some(id) => match ProductRepository::find_by_id(&mut *tx, id).await? {
+                Some(product) => Some(product),
+                None => return Ok(Err(format!("Produto com ID {} não encontrado", id))),
+            },
+            None => match &ean {
+                Some(ean) => ProductRepository::search_by_ean(&mut *tx, ean)
+                    .await?
+                    .into_iter()
+                    .next(),
+                None => None,
+            },
+        };
+        // An EAN the product already has may predate the GTIN validation
+        if let Some(ean) = &ean
+            && !is_valid_gtin(ean)
+            && existing.as_ref().and_then(|p| p.ean.as_ref()) != Some(ean)
+        {
+            return Ok(Err(format!("EAN {} inválido", ean)));
+        }
+        if let Some(existing) = &existing {
+            if let Some((_, other)) = seen_ids.iter().find(|(id, _)| *id == existing.id) {
+                return Ok(Err(format!("Produto repetido na linha {}", other)));
+            }
+            if let Some(ean) = &ean
+                && let Some(owner) = ProductRepository::search_by_ean(&mut *tx, ean)
+                    .await?
+                    .into_iter()
+                    .find(|owner| owner.id != existing.id)
+            {
+                return Ok(Err(format!("EAN {} já pertence a {}", ean, owner.name)));
+            }
+        }
+
+        let action = match existing {
+            Some(mut product) => {
+                if let Some(name) = row.name {
+                    product.name = name;
+                }
+                if row.category.is_some() {
+                    product.category = row.category;
+                }
+                if ean.is_some() {
+                    product.ean = ean.clone();
+                }
+                if let Some(unit) = row.unit {
+                    product.unit = unit;
+                }
+                if let Some(quantity) = row.quantity {
+                    product.quantity = quantity;
+                }
+                if let Some(price) = row.price_purchase {
+                    product.price_purchase = price;
+                }
+                if let Some(price) = row.price_sale {
+                    product.price_sale = price;
+                }
+                if let Some(min_stock) = row.min_stock {
+                    product.min_stock = min_stock;
+                }
+                if row.supplier.is_some() {
+                    product.supplier = row.supplier;
+                }
+                if let Err(reason) = check_whole_quantity(&product) {
+                    return Ok(Err(reason));
+                }
+                ProductRepository::update(&mut *tx, &product).await?;
+                seen_ids.push((product.id, line));
+                ImportAction::Update(product.id)
+            }
+            None => {
+                let (Some(name), Some(price_sale)) = (row.name, row.price_sale) else {
+                    return Ok(Err(
+                        "Nome e preço de venda são obrigatórios para cadastrar".to_string()
+                    ));
+                };
+                let product = Product {
+                    category: row.category,
+                    min_stock: row.min_stock.unwrap_or_default(),
+                    supplier: row.supplier,
+                    ..Product::new(
+                        0,
+                        name,
+                        price_sale,
+                        row.price_purchase.unwrap_or_default(),
+                        row.quantity.unwrap_or_default(),
+                        row.unit.unwrap_or_default(),
+                        ean.clone(),
+                        now,
+                    )
+                };
+                if let Err(reason) = check_whole_quantity(&product) {
+                    return Ok(Err(reason));
+                }
+                let id = ProductRepository::insert(&mut *tx, &product).await?;
+                if product.ean.is_none() {
+                    ProductRepository::update_ean(&mut *tx, id, &internal_ean13(id)).await?;
+                }
+                seen_ids.push((id, line));
+                ImportAction::Create
+            }
+        };
+        if let Some(ean) = ean {
+            seen_eans.push((ean, line));
+        }
+        Ok(Ok(action))
+    }
+}
+
+#[async_trait]
+impl ProductService for SqliteProductService {
+    async fn search_products_by_name(&self, name: &str) -> Result<Vec<Product>> {
+        self.search(name, true, PRODUCT_SEARCH_LIMIT).await
+    }
+
+    async fn find_product(&self, id: i64) -> Result<Option<Product>> {
+        ProductRepository::find_by_id(&self.pool, id).await
+    }
+
+    async fn list_products(&self, name: &str) -> Result<Vec<Product>> {
+        if search::terms(name).is_empty() {
+            return ProductRepository::list_first(&self.pool, PRODUCT_LIST_LIMIT).await;
+        }
+        self.search(name, false, PRODUCT_LIST_LIMIT as usize).await
+    }
+
+    async fn search_by_barcode(&self, code: &str) -> Result<Option<BarcodeMatch>> {
+        // Stored codes are looked up first, as some predate the validation
+        if let Some(product) = ProductRepository::search_by_ean(&self.pool, code)
+            .await?
+            .into_iter()
+            .next()
+        {
+            return Ok(Some(BarcodeMatch {
+                product,
+                quantity: None,
+                total: None,
+            }));
+        }
+        if !is_valid_gtin(code) {
+            bail!("Código de barras {} inválido", code);
+        }
+
+        let Some(label) = decode_scale_label(code, &self.scale_label) else {
+            return Ok(None);
+        };
+        let Some(product) = ProductRepository::find_by_id(&self.pool, label.code).await? else {
+            return Ok(None);
+        };
+        let quantity = match label.value {
+            ScaleLabelValue::Weight if !product.unit.is_fractional() => {
+                return Err(anyhow!(
+                    "Etiqueta de peso para {} que é vendido por unidade",
+                    product.name
+                ));
+            }
+            ScaleLabelValue::Weight => label.amount,
+            ScaleLabelValue::Price if product.unit.is_fractional() => {
+                quantity_for_total(product.price_sale, Money::from_cents(label.amount))
+            }
+            ScaleLabelValue::Price => {
+                let price = product.price_sale.cents();
+                if price <= 0 || label.amount % price != 0 {
+                    return Err(anyhow!(
+                        "Valor da etiqueta não corresponde ao preço de {}",
+                        product.name
+                    ));
+                }
+                label.amount / price * QUANTITY_SCALE
+            }
+        };
+        let total = match label.value {
+            ScaleLabelValue::Price => Some(Money::from_cents(label.amount)),
+            ScaleLabelValue::Weight => None,
+        };
+        Ok(Some(BarcodeMatch {
+            product,
+            quantity: Some(quantity),
+            total,
+        }))
+    }
+
+    async fn list_catalog(&self) -> Result<Vec<Product>> {
+        ProductRepository::list_all(&self.pool).await
+    }
+
+    async fn import_products(&self, rows: Vec<ImportRow>, dry_run: bool) -> Result<ImportReport> {
+        let mut report = ImportReport {
+            entries: Vec::with_capacity(rows.len()),
+            dry_run,
+        };
+        let mut seen_ids: Vec<(i64, usize)> = vec![];
+        let mut seen_eans: Vec<(String, usize)> = vec![];
+        let now = Utc::now().naive_local();
+        let mut tx = self.pool.begin().await?;
+        for ImportRow { line, row } in rows {
+            let name = row
+                .as_ref()
+                .ok()
+                .and_then(|row| row.name.clone())
+                .unwrap_or_default();
+            let action = match row {
+                Err(reason) => ImportAction::Reject(reason),
+                Ok(row) => {
+                    match Self::import_row(&mut tx, row, now, &mut seen_ids, &mut seen_eans, line)
+                        .await?
+                    {
+                        Ok(action) => action,
+                        Err(reason) => ImportAction::Reject(reason),
+                    }
+                }
+            };
+            report.entries.push(ImportEntry { line, name, action });
+        }
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(report)
+    }
+
+    async fn kit_components(&self, kit_id: i64) -> Result<Vec<KitComponent>> {
+        KitComponentRepository::list_by_kit(&self.pool, kit_id).await
+    }
+
+    async fn save_kit(&self, kit_id: i64, components: Vec<(i64, i64)>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let kit = ProductRepository::find_by_id(&mut *tx, kit_id)
+            .await?
+            .ok_or_else(|| anyhow!("Produto {} não encontrado", kit_id))?;
+        KitComponentRepository::delete_by_kit(&mut *tx, kit_id).await?;
+        if components.is_empty() {
+            ProductRepository::set_kit(&mut *tx, kit_id, false).await?;
+            tx.commit().await?;
+            return Ok(());
+        }
+        if kit.unit.is_fractional() {
+            bail!("Kits são vendidos por unidade");
+        }
+        if KitComponentRepository::is_component(&mut *tx, kit_id).await? {
+            bail!("{} faz parte de outro kit", kit.name);
+        }
+        ProductRepository::set_kit(&mut *tx, kit_id, true).await?;
+
+        let mut cost = Money::ZERO;
+        let mut seen = Vec::with_capacity(components.len());
+        for (component_id, quantity) in components {
+            let component = ProductRepository::find_by_id(&mut *tx, component_id)
+                .await?
+                .ok_or_else(|| anyhow!("Produto {} não encontrado", component_id))?;
+            if component_id == kit_id {
+                bail!("O kit não pode conter ele mesmo");
+            }
+            if component.is_kit {
+                bail!(
+                    "{} é um kit e não pode fazer parte de outro",
+                    component.name
+                );
+            }
+            if seen.contains(&component_id) {
+                bail!("{} repetido no kit", component.name);
+            }
+            if quantity <= 0 || (!component.unit.is_fractional() && quantity % QUANTITY_SCALE != 0)
+            {
+                bail!("Quantidade de {} no kit inválida", component.name);
+            }
+            KitComponentRepository::insert(&mut *tx, kit_id, component_id, quantity).await?;
+            cost =
+                cost.saturating_add(component.price_purchase.saturating_times_quantity(quantity));
+            seen.push(component_id);
+        }
+        ProductRepository::update(
+            &mut *tx,
+            &Product {
+                price_purchase: cost,
+                ..kit
+            },
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Text a product is found by: its name, EAN and category.
+pub fn search_text(product: &Product) -> String {
+    [
+        Some(product.name.as_str()),
+        product.ean.as_deref(),
+        product.category.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn check_whole_quantity(product: &Product) -> Result<(), String> {
+    if !product.unit.is_fractional() && product.quantity % QUANTITY_SCALE != 0 {
+        return Err(format!(
+            "Estoque fracionado para {} que é vendido por unidade",
+            product.name
+        ));
+    }
+    if !product.unit.is_fractional() && product.min_stock % QUANTITY_SCALE != 0 {
+        return Err(format!(
+            "Estoque mínimo fracionado para {} que é vendido por unidade",
+            product.name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, insert_product, units};
+
+    async fn names(service: &SqliteProductService, text: &str) -> Vec<String> {
+        service
+            .search_products_by_name(text)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|product| product.name)
+            .collect()
+    }
+
+    async fn service() -> SqliteProductService {
+        let pool = testing::pool().await;
+        insert_product(&pool, "CAFÉ PILÃO 500G", Unit::Un, units(5), 1200, 1890).await;
+        insert_product(&pool, "CAFETEIRA ELÉTRICA", Unit::Un, units(1), 9000, 12990).await;
+        insert_product(&pool, "AÇÚCAR UNIÃO 1KG", Unit::Un, units(9), 350, 549).await;
+        insert_product(&pool, "FEIJÃO CARIOCA", Unit::Un, 0, 500, 850).await;
+        testing::product_service(&pool)
+    }
+
+    #[tokio::test]
+    async fn search_ignores_accents_and_case_and_ranks_whole_words_first() {
+        let service = service().await;
+
+        assert_eq!(
+            names(&service, "cafe").await,
+            vec!["CAFÉ PILÃO 500G", "CAFETEIRA ELÉTRICA"]
+        );
+        assert_eq!(
+            names(&service, "acucar uni").await,
+            vec!["AÇÚCAR UNIÃO 1KG"]
+        );
+        assert_eq!(names(&service, "pilão café").await, vec!["CAFÉ PILÃO 500G"]);
+    }
+
+    #[tokio::test]
+    async fn search_forgives_typos() {
+        let service = service().await;
+
+        assert_eq!(names(&service, "acucra").await, vec!["AÇÚCAR UNIÃO 1KG"]);
+        assert_eq!(
+            names(&service, "cafeteria").await,
+            vec!["CAFETEIRA ELÉTRICA"]
+        );
+        assert!(names(&service, "xyz").await.is_empty());
+        // Also in the first letters
+        assert_eq!(names(&service, "scucar").await, vec!["AÇÚCAR UNIÃO 1KG"]);
+    }
+
+    #[tokio::test]
+    async fn search_stays_under_a_second_with_50k_products() {
+        let pool = testing::pool().await;
+        sqlx::query(
+            r#"
+            WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 50000)
+            INSERT INTO tb_product (name, price_sale, price_purchase, quantity, created_at)
+            SELECT CASE n % 5
+                       WHEN 0 THEN 'BISCOITO'
+                       WHEN 1 THEN 'ARROZ'
+                       WHEN 2 THEN 'REFRIGERANTE'
+                       WHEN 3 THEN 'SABONETE'
+                       ELSE 'CAFE'
+                   END || ' MARCA' || (n % 997) || ' ' || n,
+                   500, 300, 1000, CURRENT_TIMESTAMP
+            FROM seq
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        insert_product(
+            &pool,
+            "BISCOITO RECHEADO MORANGO",
+            Unit::Un,
+            units(3),
+            150,
+            299,
+        )
+        .await;
+        let service = testing::product_service(&pool);
+
+        for (text, first) in [
+            ("biscoito rech", "BISCOITO RECHEADO MORANGO"),
+            ("bsicoito rechaedo", "BISCOITO RECHEADO MORANGO"),
+            ("sabonete 49998", "SABONETE MARCA148 49998"),
+        ] {
+            let started = std::time::Instant::now();
+            let found = names(&service, text).await;
+            assert!(
+                started.elapsed() < std::time::Duration::from_secs(1),
+                "{} took {:?}",
+                text,
+                started.elapsed()
+            );
+            assert_eq!(found.first().map(String::as_str), Some(first), "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn search_for_sale_skips_products_out_of_stock() {
+        let service = service().await;
+
+        assert!(names(&service, "feijao").await.is_empty());
+        let listed = service.list_products("feijao").await.unwrap();
+        assert_eq!(listed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn search_follows_changes_to_name_ean_and_category() {
+        let service = service().await;
+        let mut product = service.search_products_by_name("acucar").await.unwrap()[0].clone();
+        product.name = "AÇÚCAR REFINADO".to_string();
+        product.ean = Some("7891000100103".to_string());
+        product.category = Some("MERCEARIA".to_string());
+        ProductRepository::update(&service.pool, &product)
+            .await
+            .unwrap();
+
+        assert_eq!(names(&service, "refinado").await, vec!["AÇÚCAR REFINADO"]);
+        assert!(names(&service, "uniao").await.is_empty());
+        assert_eq!(names(&service, "7891000").await, vec!["AÇÚCAR REFINADO"]);
+        assert_eq!(names(&service, "mercearia").await, vec!["AÇÚCAR REFINADO"]);
+    }
+
+    #[tokio::test]
+    async fn barcodes_stored_before_the_validation_still_scan() {
+        let service = service().await;
+        let coffee = service.search_products_by_name("cafe pilao").await.unwrap()[0].clone();
+        ProductRepository::update_ean(&service.pool, coffee.id, "7891000315508")
+            .await
+            .unwrap();
+
+        let found = service.search_by_barcode("7891000315508").await.unwrap();
+        assert_eq!(found.map(|found| found.product.id), Some(coffee.id));
+        let error = service
+            .search_by_barcode("7891000315509")
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Código de barras 7891000315509 inválido");
+    }
+
+    #[tokio::test]
+    async fn save_kit_derives_stock_and_cost_from_the_components() {
+        let pool = testing::pool().await;
+        let coffee = insert_product(&pool, "CAFÉ", Unit::Un, units(10), 1200, 1890).await;
+        let cheese = insert_product(&pool, "QUEIJO", Unit::Kg, 1600, 3000, 4990).await;
+        let basket = insert_product(&pool, "CESTA", Unit::Un, 0, 0, 9990).await;
+        let service = testing::product_service(&pool);
+
+        service
+            .save_kit(basket.id, vec![(coffee.id, units(2)), (cheese.id, 500)])
             .await
-            .unwrap_or(vec![])
+            .unwrap();
+
+        let kit = service.find_product(basket.id).await.unwrap().unwrap();
+        assert!(kit.is_kit);
+        // 5 by the coffee, 3 by the cheese
+        assert_eq!(kit.quantity, units(3));
+        assert_eq!(kit.price_purchase, Money::from_cents(3900));
+        assert_eq!(service.kit_components(basket.id).await.unwrap().len(), 2);
+        ProductRepository::add_quantity(&pool, cheese.id, 1000)
+            .await
+            .unwrap();
+        let kit = service.find_product(basket.id).await.unwrap().unwrap();
+        assert_eq!(kit.quantity, units(5));
+
+        let error = service
+            .save_kit(coffee.id, vec![(basket.id, units(1))])
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "CAFÉ faz parte de outro kit");
+        let error = service
+            .save_kit(basket.id, vec![(coffee.id, 1500)])
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Quantidade de CAFÉ no kit inválida");
+
+        service.save_kit(basket.id, vec![]).await.unwrap();
+        let product = service.find_product(basket.id).await.unwrap().unwrap();
+        assert!(!product.is_kit);
+        assert_eq!(product.quantity, 0);
+    }
+
+    #[tokio::test]
+    async fn import_dry_run_reports_the_changes_and_rolls_them_back() {
+        let service = service().await;
+        let coffee = service.search_products_by_name("cafe pilao").await.unwrap()[0].clone();
+        let rows = vec![
+            ImportRow {
+                line: 2,
+                row: Ok(ProductRow {
+                    id: Some(coffee.id),
+                    price_sale: Some(Money::from_cents(1990)),
+                    ..Default::default()
+                }),
+            },
+            ImportRow {
+                line: 3,
+                row: Ok(ProductRow {
+                    name: Some("SAL 1KG".to_string()),
+                    unit: Some(Unit::Un),
+                    price_sale: Some(Money::from_cents(299)),
+                    ..Default::default()
+                }),
+            },
+            ImportRow {
+                line: 4,
+                row: Err("ID \"x\" inválido".to_string()),
+            },
+        ];
+
+        let preview = service.import_products(rows.clone(), true).await.unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(
+            (preview.created(), preview.updated(), preview.rejected()),
+            (1, 1, 1)
+        );
+        let unchanged = service.find_product(coffee.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.price_sale, Money::from_cents(1890));
+        assert!(service.list_products("sal").await.unwrap().is_empty());
+
+        let report = service.import_products(rows, false).await.unwrap();
+        let actions = |report: &ImportReport| -> Vec<ImportAction> {
+            report
+                .entries
+                .iter()
+                .map(|entry| entry.action.clone())
+                .collect()
+        };
+        assert_eq!(actions(&report), actions(&preview));
+        let updated = service.find_product(coffee.id).await.unwrap().unwrap();
+        assert_eq!(updated.price_sale, Money::from_cents(1990));
+        assert_eq!(service.list_products("sal").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn exported_catalog_imports_back_as_updates() {
+        let service = service().await;
+        let catalog = service.list_catalog().await.unwrap();
+        let sheet = crate::catalog::to_sheet(&catalog);
+        let rows = crate::catalog::parse_rows(
+            &sheet,
+            &crate::catalog::ColumnMapping::detect(&sheet.headers),
+        );
+
+        let report = service.import_products(rows, false).await.unwrap();
+        assert_eq!(report.updated(), catalog.len());
+        assert_eq!(report.created() + report.rejected(), 0);
+        for (before, after) in catalog.iter().zip(service.list_catalog().await.unwrap()) {
+            assert_eq!(
+                (&before.name, before.quantity, before.price_sale),
+                (&after.name, after.quantity, after.price_sale)
+            );
+        }
     }
 }
@@ -0,0 +1,243 @@
+use crate::entities::customer::Customer;
+use crate::entities::receivable_entry::{ReceivableEntry, ReceivableKind};
+use crate::helpers::money::Money;
+use crate::helpers::{receivable, to_local};
+use crate::repositories::customer_repository::CustomerRepository;
+use crate::repositories::receivable_repository::ReceivableRepository;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::fmt::Debug;
+
+/// What a customer owes on store credit.
+#[derive(Debug, Clone)]
+pub struct CustomerAccount {
+    pub customer: Customer,
+    pub balance: Money,
+    pub overdue: Money,
+    /// Due date of the oldest charge still open.
+    pub next_due: Option<NaiveDate>,
+}
+
+impl CustomerAccount {
+    fn new(customer: Customer, entries: &[ReceivableEntry], today: NaiveDate) -> Self {
+        Self {
+            customer,
+            balance: receivable::balance(entries),
+            overdue: receivable::overdue(entries, today),
+            next_due: receivable::open_charges(entries)
+                .iter()
+                .filter_map(|charge| charge.due_date)
+                .min(),
+        }
+    }
+}
+
+/// A customer's account with every entry, oldest first.
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub account: CustomerAccount,
+    pub entries: Vec<ReceivableEntry>,
+}
+
+#[derive(Debug)]
+pub struct SqliteReceivableService {
+    pool: SqlitePool,
+}
+
+/// Store credit accounts: what customers owe and the payments they make.
+#[async_trait]
+pub trait ReceivableService: Debug + Send + Sync {
+    /// Customers owing something, overdue ones first.
+    async fn list_open_accounts(&self) -> Result<Vec<CustomerAccount>>;
+
+    async fn statement(&self, customer_id: i64) -> Result<Option<Statement>>;
+
+    /// Registers money received from the customer, which pays their oldest
+    /// charges first.
+    async fn receive_payment(&self, customer_id: i64, amount: Money) -> Result<()>;
+}
+
+impl SqliteReceivableService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ReceivableService for SqliteReceivableService {
+    async fn list_open_accounts(&self) -> Result<Vec<CustomerAccount>> {
+        let today = to_local(Utc::now().naive_local()).date();
+        let mut accounts = vec![];
+        for id in ReceivableRepository::list_open_customers(&self.pool).await? {
+            let Some(customer) = CustomerRepository::find_by_id(&self.pool, id).await? else {
+                continue;
+            };
+            let entries = ReceivableRepository::list_by_customer(&self.pool, id).await?;
+            accounts.push(CustomerAccount::new(customer, &entries, today));
+        }
+        accounts.sort_by(|a, b| {
+            (Reverse(a.overdue), &a.customer.name).cmp(&(Reverse(b.overdue), &b.customer.name))
+        });
+        Ok(accounts)
+    }
+
+    async fn statement(&self, customer_id: i64) -> Result<Option<Statement>> {
+        let Some(customer) = CustomerRepository::find_by_id(&self.pool, customer_id).await? else {
+            return Ok(None);
+        };
+        let today = to_local(Utc::now().naive_local()).date();
+        let entries = ReceivableRepository::list_by_customer(&self.pool, customer_id).await?;
+        Ok(Some(Statement {
+            account: CustomerAccount::new(customer, &entries, today),
+            entries,
+        }))
+    }
+
+    async fn receive_payment(&self, customer_id: i64, amount: Money) -> Result<()> {
+        if amount <= Money::ZERO {
+            bail!("Informe o valor recebido");
+        }
+        let mut tx = self.pool.begin().await?;
+        let customer = CustomerRepository::find_by_id(&mut *tx, customer_id)
+            .await?
+            .ok_or_else(|| anyhow!("Cliente {} não encontrado", customer_id))?;
+        let entries = ReceivableRepository::list_by_customer(&mut *tx, customer_id).await?;
+        let balance = receivable::balance(&entries);
+        if amount > balance {
+            bail!(
+                "O valor recebido passa do saldo devedor de {} ({})",
+                customer.name,
+                balance.max(Money::ZERO)
+            );
+        }
+        ReceivableRepository::insert(
+            &mut *tx,
+            &ReceivableEntry::new(
+                customer_id,
+                None,
+                ReceivableKind::Payment,
+                -amount,
+                None,
+                Utc::now().naive_local(),
+            ),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::testing::{self, units};
+    use crate::entities::payment_method::PaymentMethod;
+    use crate::services::product_sale_service::ProductSaleService;
+
+    async fn customer(pool: &SqlitePool, name: &str, credit_limit: i64) -> i64 {
+        let customer = Customer {
+            credit_limit: Money::from_cents(credit_limit),
+            ..Customer::new(
+                0,
+                name.to_string(),
+                None,
+                None,
+                None,
+                Utc::now().naive_local(),
+            )
+        };
+        CustomerRepository::insert(pool, &customer).await.unwrap()
+    }
+
+    async fn balance(service: &SqliteReceivableService, customer_id: i64) -> Money {
+        let statement = service.statement(customer_id).await.unwrap().unwrap();
+        statement.account.balance
+    }
+
+    #[tokio::test]
+    async fn store_credit_sales_respect_the_limit_and_are_received() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let sales = testing::sale_service(&pool);
+        let service = SqliteReceivableService::new(pool.clone());
+        let maria = customer(&pool, "MARIA", 6000).await;
+        let sold = |quantity| vec![testing::sold(&rice, quantity)];
+
+        sales
+            .add_sale(sold(units(2)), Some(maria), 0, PaymentMethod::StoreCredit)
+            .await
+            .unwrap();
+        let error = sales
+            .add_sale(sold(units(1)), Some(maria), 0, PaymentMethod::StoreCredit)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Limite do fiado insuficiente: R$ 8,20 disponível para MARIA"
+        );
+        let error = sales
+            .add_sale(sold(units(1)), None, 0, PaymentMethod::StoreCredit)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Informe o cliente para vender fiado");
+
+        let accounts = service.list_open_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].balance, Money::from_cents(5180));
+        assert_eq!(accounts[0].overdue, Money::ZERO);
+        assert!(accounts[0].next_due.is_some());
+
+        service
+            .receive_payment(maria, Money::from_cents(3000))
+            .await
+            .unwrap();
+        let error = service
+            .receive_payment(maria, Money::from_cents(3000))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "O valor recebido passa do saldo devedor de MARIA (R$ 21,80)"
+        );
+        service
+            .receive_payment(maria, Money::from_cents(2180))
+            .await
+            .unwrap();
+        assert!(service.list_open_accounts().await.unwrap().is_empty());
+        let statement = service.statement(maria).await.unwrap().unwrap();
+        assert_eq!(statement.entries.len(), 3);
+        assert_eq!(statement.account.balance, Money::ZERO);
+    }
+
+    #[tokio::test]
+    async fn returns_and_voids_credit_the_account() {
+        let pool = testing::pool().await;
+        let rice = testing::rice(&pool, units(10)).await;
+        let sales = testing::sale_service(&pool);
+        let service = SqliteReceivableService::new(pool.clone());
+        let maria = customer(&pool, "MARIA", 100_000).await;
+        let id = sales
+            .add_sale(
+                vec![testing::sold(&rice, units(4))],
+                Some(maria),
+                0,
+                PaymentMethod::StoreCredit,
+            )
+            .await
+            .unwrap();
+        assert_eq!(balance(&service, maria).await, Money::from_cents(4 * 2590));
+
+        let item = sales.find_sale(id).await.unwrap().unwrap().items[0].id;
+        sales
+            .return_items(id, vec![(item, units(1))])
+            .await
+            .unwrap();
+        assert_eq!(balance(&service, maria).await, Money::from_cents(3 * 2590));
+
+        sales.void_sale(id).await.unwrap();
+        assert_eq!(balance(&service, maria).await, Money::ZERO);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::entities::user::User;
+use crate::repositories::user_repository::UserRepository;
+use anyhow::{anyhow, bail, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHasher};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+const MIN_PASSWORD_LENGTH: usize = 6;
+
+#[derive(Debug)]
+pub struct UserService {
+    pool: SqlitePool,
+}
+
+impl UserService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates an operator. The password is stored as an Argon2 hash.
+    pub async fn add_user(&self, username: &str, password: &str) -> Result<i64> {
+        let username = username.trim().to_lowercase();
+        if username.is_empty() || username.chars().any(char::is_whitespace) {
+            bail!("Nome de usuário \"{}\" inválido", username);
+        }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            bail!(
+                "A senha deve ter pelo menos {} caracteres",
+                MIN_PASSWORD_LENGTH
+            );
+        }
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Não foi possível gerar o hash da senha: {}", e))?
+            .to_string();
+        let now = Utc::now().naive_local();
+        UserRepository::insert(&self.pool, &User::new(username, password_hash, now)).await
+    }
+}